@@ -30,6 +30,7 @@ use crate::index::{
     Column,
     Line,
 };
+use crate::term::cell::Hyperlink;
 use crate::term::color::Rgb;
 
 /// Maximum time before a synchronized update is aborted.
@@ -162,11 +163,20 @@ enum Dcs {
     SyncEnd,
 }
 
+/// Size of the parser's OSC buffer, large enough to carry OSC 52 clipboard payloads.
+const OSC_RAW_BUF_SIZE: usize = 64 * 1024;
+
+/// Maximum length of a base64 OSC 52 payload.
+///
+/// Kept below [`OSC_RAW_BUF_SIZE`] so payloads truncated by the parser are rejected rather than
+/// stored partially.
+pub const MAX_CLIPBOARD_PAYLOAD_LEN: usize = OSC_RAW_BUF_SIZE - 64;
+
 /// The processor wraps a `vte::Parser` to ultimately call methods on a Handler.
 #[derive(Default)]
 pub struct Processor {
     state: ProcessorState,
-    parser: vte::Parser,
+    parser: vte::Parser<OSC_RAW_BUF_SIZE>,
 }
 
 impl Processor {
@@ -308,6 +318,9 @@ pub trait Handler {
     /// Set the cursor shape.
     fn set_cursor_shape(&mut self, _shape: CursorShape) {}
 
+    /// Set the hyperlink applied to subsequently written cells, or close it with `None`.
+    fn set_hyperlink(&mut self, _: Option<Hyperlink>) {}
+
     /// A character to be displayed.
     fn input(&mut self, _c: char) {}
 
@@ -467,6 +480,9 @@ pub trait Handler {
     /// Pop the last title from the stack.
     fn pop_title(&mut self) {}
 
+    /// Store base64 encoded `data` in the clipboard identified by `clipboard` (OSC 52).
+    fn clipboard_store(&mut self, _clipboard: u8, _data: &[u8]) {}
+
     /// Fig NewCmd Osc
     fn new_cmd(&mut self, _: &str) {}
 
@@ -1100,6 +1116,26 @@ where
                 unhandled!();
             },
 
+            // Hyperlink.
+            b"8" if params.len() >= 3 => {
+                // URIs may contain `;`, which the parser treats as a separator.
+                let uri = params[2..]
+                    .iter()
+                    .flat_map(|x| str::from_utf8(x))
+                    .collect::<Vec<&str>>()
+                    .join(";");
+                if uri.is_empty() {
+                    self.handler.set_hyperlink(None);
+                    return;
+                }
+
+                let id = params[1]
+                    .split(|&b| b == b':')
+                    .find_map(|kv| kv.strip_prefix(b"id="))
+                    .and_then(|id| str::from_utf8(id).ok());
+                self.handler.set_hyperlink(Some(Hyperlink::new(id, uri)));
+            },
+
             // Get/set Foreground, Background, Cursor colors.
             b"10" | b"11" | b"12" => {
                 if params.len() >= 2 {
@@ -1143,6 +1179,23 @@ where
                 unhandled!();
             },
 
+            // Set clipboard.
+            b"52" => {
+                if params.len() < 3 {
+                    return unhandled!();
+                }
+
+                let clipboard = params[1].first().copied().unwrap_or(b'c');
+                match params[2] {
+                    // Clipboard queries would leak host clipboard contents to the pty.
+                    b"?" => unhandled!(),
+                    data if data.len() > MAX_CLIPBOARD_PAYLOAD_LEN => {
+                        debug!("Ignoring OSC 52 payload of {} bytes", data.len());
+                    },
+                    data => self.handler.clipboard_store(clipboard, data),
+                }
+            },
+
             // Reset color index.
             b"104" => {
                 // Reset all color indexes when no parameters are given.
@@ -1686,6 +1739,8 @@ mod tests {
         index: CharsetIndex,
         charset: StandardCharset,
         attr: Option<Attr>,
+        clipboard: Option<(u8, Vec<u8>)>,
+        hyperlink: Option<Hyperlink>,
     }
 
     impl Handler for MockHandler {
@@ -1702,6 +1757,14 @@ mod tests {
             self.index = index;
         }
 
+        fn clipboard_store(&mut self, clipboard: u8, data: &[u8]) {
+            self.clipboard = Some((clipboard, data.to_vec()));
+        }
+
+        fn set_hyperlink(&mut self, hyperlink: Option<Hyperlink>) {
+            self.hyperlink = hyperlink;
+        }
+
         fn reset_state(&mut self) {
             *self = Self::default();
         }
@@ -1713,6 +1776,8 @@ mod tests {
                 index: CharsetIndex::G0,
                 charset: StandardCharset::Ascii,
                 attr: None,
+                clipboard: None,
+                hyperlink: None,
             }
         }
     }
@@ -1749,6 +1814,56 @@ mod tests {
         assert_eq!(handler.attr, Some(Attr::Foreground(Color::Spec(spec))));
     }
 
+    #[test]
+    fn parse_osc52_clipboard_store() {
+        static BYTES: &[u8] = b"\x1b]52;c;aGVsbG8=\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in BYTES {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.clipboard, Some((b'c', b"aGVsbG8=".to_vec())));
+    }
+
+    #[test]
+    fn parse_osc52_oversized_payload() {
+        let mut bytes = b"\x1b]52;c;".to_vec();
+        bytes.extend(std::iter::repeat_n(b'A', MAX_CLIPBOARD_PAYLOAD_LEN + 4));
+        bytes.push(0x07);
+
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in &bytes {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.clipboard, None);
+    }
+
+    #[test]
+    fn parse_osc8_hyperlink() {
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in b"\x1b]8;id=42;https://example.com/a;b\x1b\\" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        let hyperlink = handler.hyperlink.clone().unwrap();
+        assert_eq!(hyperlink.id(), Some("42"));
+        assert_eq!(hyperlink.uri(), "https://example.com/a;b");
+
+        for byte in b"\x1b]8;;\x1b\\" {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.hyperlink, None);
+    }
+
     /// No exactly a test; useful for debugging.
     #[test]
     fn parse_zsh_startup() {
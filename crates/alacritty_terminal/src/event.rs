@@ -1,4 +1,5 @@
 use crate::term::{
+    ClipboardType,
     CommandInfo,
     ShellState,
     SizeInfo,
@@ -20,6 +21,11 @@ pub enum Event<'a> {
     PreExec,
     ShellChanged,
     CommandInfo(&'a CommandInfo),
+    /// Text a program in the pty asked to place on the clipboard (OSC 52).
+    ClipboardStore {
+        selection: ClipboardType,
+        data: String,
+    },
 }
 
 /// Types that are interested in when the display is resized.
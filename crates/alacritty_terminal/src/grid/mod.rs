@@ -367,6 +367,22 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             self.raw[line].reset(&self.cursor.template);
         }
     }
+
+    /// Whether `line` continues the row above it, which soft-wrapped at the right margin.
+    pub fn is_wrap_continuation(&self, line: Line) -> bool {
+        line > self.topmost_line()
+            && self[line - 1i32]
+                .last()
+                .is_some_and(|cell| cell.flags().contains(ShellFlags::WRAPLINE))
+    }
+
+    /// First row of the logical line that `line` is part of.
+    pub fn logical_line_start(&self, mut line: Line) -> Line {
+        while self.is_wrap_continuation(line) {
+            line -= 1;
+        }
+        line
+    }
 }
 
 impl<T> Grid<T> {
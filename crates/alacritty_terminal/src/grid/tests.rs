@@ -350,6 +350,33 @@ fn shrink_reflow_disabled() {
     assert_eq!(grid[Line(0)][Column(1)], cell('2'));
 }
 
+#[test]
+fn reflow_long_line_round_trip() {
+    let text = "0123456789abcdef";
+    let mut grid = Grid::<Cell>::new(4, 8, 10);
+    for (i, c) in text.chars().enumerate() {
+        let line = Line((i / 8) as i32);
+        grid[line][Column(i % 8)] = if i == 7 { wrap_cell(c) } else { cell(c) };
+    }
+
+    let content = |grid: &Grid<Cell>| -> String {
+        (grid.topmost_line().0..grid.screen_lines() as i32)
+            .flat_map(|line| (0..grid.columns()).map(move |column| grid[Line(line)][Column(column)].c))
+            .filter(|c| *c != ' ')
+            .collect()
+    };
+
+    grid.resize(true, 4, 5);
+    assert_eq!(content(&grid), text);
+    assert!(!grid.is_wrap_continuation(grid.logical_line_start(Line(0))));
+    assert!(grid.is_wrap_continuation(Line(0)));
+
+    grid.resize(true, 4, 20);
+    assert_eq!(content(&grid), text);
+    let mut lines = grid.topmost_line().0..grid.screen_lines() as i32;
+    assert!(lines.all(|line| !grid.is_wrap_continuation(Line(line))));
+}
+
 // https://github.com/rust-lang/rust-clippy/pull/6375
 #[allow(clippy::all)]
 fn cell(c: char) -> Cell {
@@ -1,4 +1,5 @@
 use std::boxed::Box;
+use std::sync::Arc;
 
 use bitflags::bitflags;
 use serde::{
@@ -70,6 +71,38 @@ impl ResetDiscriminant<Color> for Cell {
 #[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
 struct CellExtra {
     zerowidth: Vec<char>,
+    #[serde(default)]
+    hyperlink: Option<Hyperlink>,
+}
+
+/// Hyperlink opened with OSC 8.
+///
+/// Cheap to clone, since every cell covered by the link holds a copy.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Hyperlink(Arc<HyperlinkInner>);
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Hash)]
+struct HyperlinkInner {
+    id: Option<String>,
+    uri: String,
+}
+
+impl Hyperlink {
+    pub fn new(id: Option<impl Into<String>>, uri: impl Into<String>) -> Self {
+        Self(Arc::new(HyperlinkInner {
+            id: id.map(Into::into),
+            uri: uri.into(),
+        }))
+    }
+
+    /// Id grouping cells of the same link that are not contiguous.
+    pub fn id(&self) -> Option<&str> {
+        self.0.id.as_deref()
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.0.uri
+    }
 }
 
 /// Content and attributes of a single cell in the terminal grid.
@@ -112,6 +145,28 @@ impl Cell {
         self.extra.get_or_insert_with(Default::default).zerowidth.push(c);
     }
 
+    /// Hyperlink covering this cell.
+    #[inline]
+    pub fn hyperlink(&self) -> Option<&Hyperlink> {
+        self.extra.as_ref()?.hyperlink.as_ref()
+    }
+
+    /// Attach or remove the hyperlink covering this cell.
+    #[inline]
+    pub fn set_hyperlink(&mut self, hyperlink: Option<Hyperlink>) {
+        match hyperlink {
+            Some(hyperlink) => self.extra.get_or_insert_with(Default::default).hyperlink = Some(hyperlink),
+            None => {
+                if let Some(extra) = &mut self.extra {
+                    extra.hyperlink = None;
+                    if extra.zerowidth.is_empty() {
+                        self.extra = None;
+                    }
+                }
+            },
+        }
+    }
+
     /// Free all dynamically allocated cell storage.
     #[inline]
     pub fn drop_extra(&mut self) {
@@ -18,6 +18,7 @@ use std::{
     str,
 };
 
+use base64::Engine;
 use bitflags::bitflags;
 use serde::{
     Deserialize,
@@ -61,6 +62,7 @@ use crate::index::{
 };
 use crate::term::cell::{
     Cell,
+    Hyperlink,
     LineLength,
     ShellFlags,
 };
@@ -245,6 +247,30 @@ pub struct TextBuffer {
     pub cursor_idx: Option<usize>,
 }
 
+/// Prompts that end the prompt of common REPLs and shells, see [`Term::get_repl_buffer`]
+pub const DEFAULT_REPL_PROMPTS: &[&str] = &[">>> ", "... ", "]: ", "> ", "$ ", "% ", "# "];
+
+/// The command line of a program that prints its own prompt, see [`Term::get_repl_buffer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplBuffer {
+    /// Text before the command line, ending with the prompt
+    pub prompt: String,
+    pub buffer: String,
+    /// Byte index of the cursor in `buffer`
+    pub cursor_idx: usize,
+    /// Column of the cursor on the screen
+    pub cursor_column: Column,
+}
+
+impl From<ReplBuffer> for TextBuffer {
+    fn from(repl: ReplBuffer) -> Self {
+        TextBuffer {
+            buffer: repl.buffer,
+            cursor_idx: Some(repl.cursor_idx),
+        }
+    }
+}
+
 pub struct Term<T> {
     /// Currently active grid.
     ///
@@ -304,11 +330,11 @@ impl<T> Term<T> {
         self.grid.scroll_display(scroll);
     }
 
+    /// Create a terminal keeping at most `max_scroll_limit` lines of scrollback.
     pub fn new(size: SizeInfo, event_proxy: T, max_scroll_limit: usize, session_id: String) -> Term<T> {
         let num_cols = size.columns;
         let num_lines = size.screen_lines;
 
-        // TODO: determine max_scroll_limit
         let grid = Grid::new(num_lines, num_cols, max_scroll_limit);
         let alt = Grid::new(num_lines, num_cols, 0);
 
@@ -342,6 +368,29 @@ impl<T> Term<T> {
         Self::new(size, event_proxy, max_scroll_limit, session_id)
     }
 
+    /// Change how many lines of scrollback are kept, evicting the oldest lines when it shrinks.
+    pub fn set_scrollback_limit(&mut self, lines: usize) {
+        self.grid.update_history(lines);
+    }
+
+    /// Plain text of the last `lines` rows of scrollback and screen, oldest first.
+    ///
+    /// Wrapped rows are joined and trailing whitespace is removed.
+    pub fn dump_scrollback(&self, lines: usize) -> String {
+        let lines = min(lines, self.total_lines());
+        if lines == 0 {
+            return String::new();
+        }
+
+        let end = Point::new(self.bottommost_line(), self.last_column());
+        let start = Point::new(end.line - (lines - 1), Column(0));
+        let text = self.bounds_to_string(start, end);
+
+        let mut text = text.lines().map(str::trim_end).collect::<Vec<_>>().join("\n");
+        text.truncate(text.trim_end().len());
+        text
+    }
+
     /// Convert range between two points to a String.
     pub fn bounds_to_string(&self, start: Point, end: Point) -> String {
         let mut res = String::new();
@@ -467,10 +516,31 @@ impl<T> Term<T> {
             self.tabs.resize(num_cols);
         }
 
+        // Reflow moves the command start along with its logical line, so remember where it sits
+        // relative to the cursor's logical line before the grid is rewrapped.
+        let cmd_cursor_anchor = self.shell_state.cmd_cursor.map(|cmd_cursor| {
+            let cursor_start = self.grid.logical_line_start(self.grid.cursor.point.line);
+            if self.grid.logical_line_start(cmd_cursor.line) == cursor_start {
+                CmdCursorAnchor::Offset((cmd_cursor.line - cursor_start).0 as usize * old_cols + cmd_cursor.column.0)
+            } else {
+                CmdCursorAnchor::Rows(cursor_start - cmd_cursor.line, cmd_cursor.column)
+            }
+        });
+
         let is_alt = self.mode.contains(TermMode::ALT_SCREEN);
         self.grid.resize(!is_alt, num_lines, num_cols);
         self.inactive_grid.resize(is_alt, num_lines, num_cols);
 
+        if let Some(anchor) = cmd_cursor_anchor {
+            let cursor_start = self.grid.logical_line_start(self.grid.cursor.point.line);
+            self.shell_state.cmd_cursor = Some(match anchor {
+                CmdCursorAnchor::Offset(offset) => {
+                    Point::new(cursor_start + (offset / num_cols) as i32, Column(offset % num_cols))
+                },
+                CmdCursorAnchor::Rows(rows, column) => Point::new(cursor_start - rows, min(column, self.last_column())),
+            });
+        }
+
         // Reset scrolling region.
         self.scroll_region = Line(0)..Line(self.screen_lines() as i32);
     }
@@ -634,6 +704,7 @@ impl<T> Term<T> {
         let bg = self.grid.cursor.template.bg;
         let shell_flags = self.grid.cursor.template.flags;
         let fig_flags = self.grid.cursor.template.fig_flags;
+        let hyperlink = self.grid.cursor.template.hyperlink().cloned();
 
         let mut cursor_cell = self.grid.cursor_cell();
 
@@ -671,6 +742,12 @@ impl<T> Term<T> {
         cursor_cell.bg = bg;
         cursor_cell.flags = shell_flags;
         cursor_cell.fig_flags = fig_flags;
+        cursor_cell.set_hyperlink(hyperlink);
+    }
+
+    /// URI of the hyperlink covering the cell at `point`.
+    pub fn hyperlink_at(&self, point: Point) -> Option<&str> {
+        self.grid.get_point(point)?.hyperlink().map(Hyperlink::uri)
     }
 
     /// Get the current [`ShellState`]
@@ -679,6 +756,14 @@ impl<T> Term<T> {
     }
 
     pub fn get_text_region(&self, rect: &Rect, start_col_offset: Column) -> Option<TextBuffer>
+    where
+        T: EventListener,
+    {
+        self.text_region(rect, start_col_offset, None)
+    }
+
+    /// Like [`Term::get_text_region`], also leaving out the rest of the row from `right_prompt` on
+    fn text_region(&self, rect: &Rect, start_col_offset: Column, right_prompt: Option<Point>) -> Option<TextBuffer>
     where
         T: EventListener,
     {
@@ -711,6 +796,8 @@ impl<T> Term<T> {
             if cell.c == '\0'
                 || cell.fig_flags.contains(FigFlags::IN_PROMPT)
                 || cell.fig_flags.contains(FigFlags::IN_SUGGESTION)
+                || right_prompt
+                    .is_some_and(|prompt| cell.point.line == prompt.line && cell.point.column >= prompt.column)
             {
                 padding = padding.saturating_add(1);
             } else if cell.c as u32 != u32::MAX {
@@ -768,7 +855,8 @@ impl<T> Term<T> {
                 if start < end {
                     let rect = Rect { start, end };
 
-                    let mut buffer = self.get_text_region(&rect, Column(*cmd_cursor.column))?;
+                    let right_prompt = self.right_prompt_start(cmd_cursor);
+                    let mut buffer = self.text_region(&rect, Column(*cmd_cursor.column), right_prompt)?;
 
                     if let Some(cursor_idx) = buffer.cursor_idx {
                         buffer.buffer = buffer.buffer.trim_end().to_string();
@@ -789,6 +877,62 @@ impl<T> Term<T> {
         }
     }
 
+    /// Where a right prompt, like zsh's `RPROMPT`, starts on the row the command starts on
+    ///
+    /// The right prompt is drawn after the command cursor is set, so it is the first prompt cell
+    /// after it. Only prompt segments are marked, anything a theme draws between them is unmarked,
+    /// so the whole rest of the row is treated as part of the right prompt.
+    fn right_prompt_start(&self, cmd_cursor: Point) -> Option<Point> {
+        let row = &self.grid[cmd_cursor.line];
+        (cmd_cursor.column.0..self.columns())
+            .map(Column)
+            .find(|column| row[*column].fig_flags.contains(FigFlags::IN_PROMPT))
+            .map(|column| Point::new(cmd_cursor.line, column))
+    }
+
+    /// Get the command line of a program without shell integration, such as a python or node REPL
+    ///
+    /// This is a heuristic. The line the cursor is on, including rows it wraps onto, is taken to
+    /// start with a prompt that ends at the first of `prompts` found on it, and the rest of the
+    /// line is the command line. Returns `None` if none of `prompts` is found before the cursor.
+    pub fn get_repl_buffer(&self, prompts: &[&str]) -> Option<ReplBuffer>
+    where
+        T: EventListener,
+    {
+        let cursor = self.grid().cursor.point;
+        let start = Point::new(self.grid().logical_line_start(cursor.line), Column(0));
+        let mut end_line = cursor.line;
+        while end_line < self.bottommost_line() && self.grid().is_wrap_continuation(end_line + 1) {
+            end_line += 1;
+        }
+        let end = Point::new(end_line, self.last_column());
+
+        let TextBuffer {
+            buffer: line,
+            cursor_idx,
+        } = self.get_text_region(&Rect { start, end }, Column(0))?;
+        let cursor_idx = cursor_idx?;
+
+        let prompt_end = prompts
+            .iter()
+            .filter_map(|prompt| line.find(prompt).map(|idx| idx + prompt.len()))
+            .filter(|prompt_end| *prompt_end <= cursor_idx)
+            .min()?;
+
+        let mut buffer = line[prompt_end..].trim_end().to_owned();
+        let cursor_idx = cursor_idx - prompt_end;
+        if buffer.len() < cursor_idx {
+            buffer.push_str(&" ".repeat(cursor_idx - buffer.len()));
+        }
+
+        Some(ReplBuffer {
+            prompt: line[..prompt_end].to_owned(),
+            buffer,
+            cursor_idx,
+            cursor_column: cursor.column,
+        })
+    }
+
     pub fn set_windows_delay_end_prompt(&mut self, delay_end_prompt: bool) {
         self.windows_delay_end_prompt = delay_end_prompt;
     }
@@ -863,11 +1007,13 @@ impl<T> Term<T> {
         self.delayed_events.len()
     }
 
+    /// Apply delayed events in the order they were received, returning the applied events.
     pub fn flush_delayed_events(&mut self) -> Vec<DelayedEvent>
     where
         T: EventListener,
     {
-        while let Some(event) = self.delayed_events.pop() {
+        let events = std::mem::take(&mut self.delayed_events);
+        for event in &events {
             match event {
                 DelayedEvent::EndPrompt => {
                     self.end_prompt_internal(true);
@@ -877,7 +1023,7 @@ impl<T> Term<T> {
                 },
             }
         }
-        self.delayed_events.clone()
+        events
     }
 }
 
@@ -1669,6 +1815,12 @@ impl<T: EventListener> Handler for Term<T> {
         self.grid.cursor.charsets[index] = charset;
     }
 
+    #[inline]
+    fn set_hyperlink(&mut self, hyperlink: Option<Hyperlink>) {
+        trace!("Setting hyperlink to {:?}", hyperlink);
+        self.grid.cursor.template.set_hyperlink(hyperlink);
+    }
+
     /// Set the indexed color value.
     #[inline]
     fn set_color(&mut self, index: usize, color: Rgb) {
@@ -1721,6 +1873,24 @@ impl<T: EventListener> Handler for Term<T> {
         }
     }
 
+    fn clipboard_store(&mut self, clipboard: u8, data: &[u8]) {
+        let selection = match clipboard {
+            b'c' => ClipboardType::Clipboard,
+            b'p' | b's' => ClipboardType::Selection,
+            _ => return,
+        };
+
+        match base64::engine::general_purpose::STANDARD.decode(data) {
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(data) => self
+                    .event_proxy
+                    .send_event(Event::ClipboardStore { selection, data }, &self.shell_state),
+                Err(err) => debug!("OSC 52 payload is not utf-8: {err}"),
+            },
+            Err(err) => debug!("OSC 52 payload is not valid base64: {err}"),
+        }
+    }
+
     #[inline]
     fn new_cmd(&mut self, session_id: &str) {
         self.new_cmd_internal(false, Some(session_id));
@@ -2042,6 +2212,15 @@ impl<'a> RenderableContent<'a> {
     }
 }
 
+/// Position of the command start, saved across a reflowing resize.
+enum CmdCursorAnchor {
+    /// Cell offset into the cursor's logical line.
+    Offset(usize),
+    /// Rows above the start of the cursor's logical line, for commands that began on an earlier
+    /// line.
+    Rows(Line, Column),
+}
+
 /// Terminal test helpers.
 pub mod test {
     use unicode_width::UnicodeWidthChar;
@@ -2335,4 +2514,218 @@ mod tests {
         assert_eq!(term.history_size(), 15);
         assert_eq!(term.grid.cursor.point, Point::new(Line(4), Column(0)));
     }
+
+    #[test]
+    fn resize_reflows_command_buffer() {
+        let mut size = SizeInfo::new(5, 10);
+        let mut term = Term::new_test(size, VoidListener, 10_000);
+
+        for c in "$ ".chars() {
+            term.input(c);
+        }
+        term.shell_state.cmd_cursor = Some(term.grid.cursor.point);
+        for c in "echo hello world".chars() {
+            term.input(c);
+        }
+        assert!(term.grid.is_wrap_continuation(Line(1)));
+        assert_eq!(term.get_current_buffer().unwrap().buffer, "echo hello world");
+
+        // Narrowing rewraps the command over more rows.
+        size.columns = 6;
+        term.resize(size);
+        assert!(term.grid.is_wrap_continuation(term.grid.cursor.point.line));
+        assert_eq!(term.get_current_buffer().unwrap().buffer, "echo hello world");
+
+        // Widening joins it back onto a single row.
+        size.columns = 30;
+        term.resize(size);
+        assert!(!term.grid.is_wrap_continuation(term.grid.cursor.point.line));
+        assert_eq!(
+            term.shell_state.cmd_cursor,
+            Some(Point::new(term.grid.cursor.point.line, Column(2)))
+        );
+        assert_eq!(term.get_current_buffer().unwrap().buffer, "echo hello world");
+    }
+
+    fn repl_term(columns: usize, lines: &[&str]) -> Term<VoidListener> {
+        let mut term = Term::new_test(SizeInfo::new(5, columns), VoidListener, 10_000);
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                term.carriage_return();
+                term.linefeed();
+            }
+            for c in line.chars() {
+                term.input(c);
+            }
+        }
+        term
+    }
+
+    #[test]
+    fn repl_buffer_after_prompt() {
+        let mut term = repl_term(30, &["Python 3.12.1", ">>> print(1)"]);
+        assert_eq!(
+            term.get_repl_buffer(DEFAULT_REPL_PROMPTS),
+            Some(ReplBuffer {
+                prompt: ">>> ".into(),
+                buffer: "print(1)".into(),
+                cursor_idx: 8,
+                cursor_column: Column(12),
+            })
+        );
+
+        // Moving the cursor back into the command line
+        term.grid.cursor.point.column = Column(6);
+        let repl = term.get_repl_buffer(DEFAULT_REPL_PROMPTS).unwrap();
+        assert_eq!(repl.buffer, "print(1)");
+        assert_eq!(repl.cursor_idx, 2);
+        assert_eq!(repl.cursor_column, Column(6));
+    }
+
+    #[test]
+    fn repl_buffer_prompt_is_first_marker() {
+        let term = repl_term(30, &["Welcome to Node.js v22", "> 1 > 2"]);
+        let repl = term.get_repl_buffer(DEFAULT_REPL_PROMPTS).unwrap();
+        assert_eq!(repl.prompt, "> ");
+        assert_eq!(repl.buffer, "1 > 2");
+
+        let term = repl_term(30, &["In [1]: x = {}"]);
+        assert_eq!(term.get_repl_buffer(DEFAULT_REPL_PROMPTS).unwrap().buffer, "x = {}");
+    }
+
+    #[test]
+    fn repl_buffer_wrapped_line() {
+        let mut term = repl_term(10, &[">>> [1, 2, 3, 4]"]);
+        assert!(term.grid.is_wrap_continuation(Line(1)));
+        assert_eq!(
+            term.get_repl_buffer(DEFAULT_REPL_PROMPTS).unwrap().buffer,
+            "[1, 2, 3, 4]"
+        );
+
+        // The cursor on the first row still sees the whole line
+        term.grid.cursor.point = Point::new(Line(0), Column(5));
+        let repl = term.get_repl_buffer(DEFAULT_REPL_PROMPTS).unwrap();
+        assert_eq!(repl.buffer, "[1, 2, 3, 4]");
+        assert_eq!(repl.cursor_idx, 1);
+    }
+
+    #[test]
+    fn repl_buffer_without_prompt() {
+        let term = repl_term(30, &["no prompt here"]);
+        assert_eq!(term.get_repl_buffer(DEFAULT_REPL_PROMPTS), None);
+    }
+
+    #[test]
+    fn osc52_sends_clipboard_event() {
+        #[derive(Default)]
+        struct ClipboardListener(std::cell::RefCell<Vec<(ClipboardType, String)>>);
+
+        impl EventListener for &ClipboardListener {
+            fn send_event(&self, event: Event<'_>, _shell_state: &ShellState) {
+                if let Event::ClipboardStore { selection, data } = event {
+                    self.0.borrow_mut().push((selection, data));
+                }
+            }
+        }
+
+        let listener = ClipboardListener::default();
+        let mut term = Term::new_test(SizeInfo::new(5, 10), &listener, 0);
+        let mut parser = ansi::Processor::new();
+        for byte in b"\x1b]52;c;aGVsbG8=\x07\x1b]52;p;!!!\x07" {
+            parser.advance(&mut term, *byte);
+        }
+
+        assert_eq!(*listener.0.borrow(), vec![(
+            ClipboardType::Clipboard,
+            "hello".to_owned()
+        )]);
+    }
+
+    #[test]
+    fn osc8_hyperlink_covers_written_cells() {
+        let mut term = Term::new_test(SizeInfo::new(5, 20), VoidListener, 0);
+        let mut parser = ansi::Processor::new();
+        for byte in b"a\x1b]8;;https://one.example\x1b\\bc\x1b]8;;https://two.example\x07d\x1b]8;;\x07e" {
+            parser.advance(&mut term, *byte);
+        }
+
+        let uri_at = |column| term.hyperlink_at(Point::new(Line(0), Column(column)));
+        assert_eq!(uri_at(0), None);
+        assert_eq!(uri_at(1), Some("https://one.example"));
+        assert_eq!(uri_at(2), Some("https://one.example"));
+        assert_eq!(uri_at(3), Some("https://two.example"));
+        assert_eq!(uri_at(4), None);
+    }
+
+    #[test]
+    fn scrollback_evicts_oldest_lines() {
+        let mut term = Term::new_test(SizeInfo::new(3, 10), VoidListener, 5);
+        let mut parser = ansi::Processor::new();
+        for i in 0..12 {
+            for byte in format!("line {i}\r\n").bytes() {
+                parser.advance(&mut term, byte);
+            }
+        }
+
+        assert_eq!(term.history_size(), 5);
+        // The cursor sits on an empty last row, so 7 lines of output remain.
+        let expected = (5..12).map(|i| format!("line {i}")).collect::<Vec<_>>().join("\n");
+        assert_eq!(term.dump_scrollback(usize::MAX), expected);
+        assert_eq!(term.dump_scrollback(3), "line 10\nline 11");
+
+        term.set_scrollback_limit(2);
+        assert_eq!(term.history_size(), 2);
+        assert!(term.dump_scrollback(usize::MAX).starts_with("line 8\n"));
+    }
+
+    #[test]
+    fn current_buffer_excludes_right_prompt() {
+        let mut term = Term::new_test(SizeInfo::new(3, 40), VoidListener, 0);
+        let mut parser = ansi::Processor::new();
+        let mut write = |term: &mut Term<VoidListener>, text: &str| {
+            for byte in text.bytes() {
+                parser.advance(term, byte);
+            }
+        };
+
+        term.start_prompt();
+        write(&mut term, "% ");
+        term.end_prompt();
+        term.new_cmd("");
+
+        // Draw a right prompt like zsh does, the separator between its segments is unmarked.
+        write(&mut term, "\x1b[28G");
+        term.start_prompt();
+        write(&mut term, "~/src");
+        term.end_prompt();
+        write(&mut term, " | ");
+        term.start_prompt();
+        write(&mut term, "10:42");
+        term.end_prompt();
+        write(&mut term, "\x1b[3G");
+
+        write(&mut term, "git status");
+        let buffer = term.get_current_buffer().unwrap();
+        assert_eq!(buffer.buffer, "git status");
+        assert_eq!(buffer.cursor_idx, Some(10));
+    }
+
+    #[test]
+    fn delayed_events_flush_in_order() {
+        let mut term = Term::new_test(SizeInfo::new(3, 10), VoidListener, 10);
+        term.set_windows_delay_end_prompt(true);
+
+        term.end_prompt();
+        term.new_cmd("");
+        assert_eq!(term.get_delayed_events_count(), 2);
+        assert!(term.shell_state.cmd_cursor.is_none());
+
+        let flushed = term.flush_delayed_events();
+        assert!(matches!(flushed.as_slice(), [
+            DelayedEvent::EndPrompt,
+            DelayedEvent::NewCmd
+        ]));
+        assert_eq!(term.get_delayed_events_count(), 0);
+        assert!(term.shell_state.cmd_cursor.is_some());
+    }
 }
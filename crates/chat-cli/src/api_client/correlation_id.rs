@@ -0,0 +1,73 @@
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::interceptors::Intercept;
+use aws_smithy_runtime_api::client::interceptors::context::BeforeTransmitInterceptorContextMut;
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_types::config_bag::ConfigBag;
+
+use crate::api_client::X_AMZN_CORRELATION_ID_HEADER;
+
+/// Sends the client generated correlation id of a chat request, so the service logs of a turn
+/// can be matched with our own logs and telemetry.
+#[derive(Debug, Clone)]
+pub struct CorrelationIdInterceptor {
+    correlation_id: Option<String>,
+}
+
+impl CorrelationIdInterceptor {
+    pub fn new(correlation_id: Option<String>) -> Self {
+        Self { correlation_id }
+    }
+}
+
+impl Intercept for CorrelationIdInterceptor {
+    fn name(&self) -> &'static str {
+        "CorrelationIdInterceptor"
+    }
+
+    fn modify_before_signing(
+        &self,
+        context: &mut BeforeTransmitInterceptorContextMut<'_>,
+        _runtime_components: &RuntimeComponents,
+        _cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        if let Some(correlation_id) = &self.correlation_id {
+            context
+                .request_mut()
+                .headers_mut()
+                .insert(X_AMZN_CORRELATION_ID_HEADER, correlation_id.clone());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amzn_consolas_client::config::RuntimeComponentsBuilder;
+    use amzn_consolas_client::config::interceptors::InterceptorContext;
+    use aws_smithy_runtime_api::client::interceptors::context::Input;
+
+    use super::*;
+
+    #[test]
+    fn test_correlation_id_interceptor() {
+        let rc = RuntimeComponentsBuilder::for_tests().build().unwrap();
+        let mut cfg = ConfigBag::base();
+
+        let mut context = InterceptorContext::new(Input::erase(()));
+        context.set_request(aws_smithy_runtime_api::http::Request::empty());
+        let mut context = BeforeTransmitInterceptorContextMut::from(&mut context);
+
+        CorrelationIdInterceptor::new(None)
+            .modify_before_signing(&mut context, &rc, &mut cfg)
+            .expect("success");
+        assert_eq!(context.request().headers().get(X_AMZN_CORRELATION_ID_HEADER), None);
+
+        CorrelationIdInterceptor::new(Some("abc-123".to_string()))
+            .modify_before_signing(&mut context, &rc, &mut cfg)
+            .expect("success");
+        assert_eq!(
+            context.request().headers().get(X_AMZN_CORRELATION_ID_HEADER),
+            Some("abc-123")
+        );
+    }
+}
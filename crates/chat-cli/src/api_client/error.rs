@@ -20,6 +20,7 @@ use thiserror::Error;
 use crate::auth::AuthError;
 use crate::aws_common::SdkErrorDisplay;
 use crate::telemetry::ReasonCode;
+use crate::util::consts::CLI_BINARY_NAME;
 
 #[derive(Debug, Error)]
 pub enum ApiClientError {
@@ -118,6 +119,25 @@ impl ApiClientError {
             Self::Credentials(_e) => None,
         }
     }
+
+    /// What the user can do about a failed request, for failures recognized by their response
+    /// status and error code: an expired login, an unsupported region, throttling, or the service
+    /// being unavailable.
+    pub fn advice(&self) -> Option<String> {
+        let class = match self {
+            Self::GenerateCompletions(e) => classify_sdk_error(e),
+            Self::GenerateRecommendations(e) => classify_sdk_error(e),
+            Self::ListAvailableCustomizations(e) => classify_sdk_error(e),
+            Self::ListAvailableServices(e) => classify_sdk_error(e),
+            Self::CodewhispererGenerateAssistantResponse(e) => classify_sdk_error(e),
+            Self::QDeveloperSendMessage(e) => classify_sdk_error(e),
+            Self::ListAvailableProfilesError(e) => classify_sdk_error(e),
+            Self::SendTelemetryEvent(e) => classify_sdk_error(e),
+            Self::CreateSubscriptionToken(e) => classify_sdk_error(e),
+            _ => None,
+        };
+        class.map(ErrorClass::advice)
+    }
 }
 
 impl ReasonCode for ApiClientError {
@@ -155,12 +175,54 @@ fn sdk_status_code<E>(e: &SdkError<E, Response>) -> Option<u16> {
     e.raw_response().map(|res| res.status().as_u16())
 }
 
+/// The kinds of failed request the user can do something about
+enum ErrorClass {
+    AuthExpired,
+    RegionUnsupported,
+    RateLimited,
+    ServiceUnavailable,
+}
+
+impl ErrorClass {
+    fn advice(self) -> String {
+        match self {
+            ErrorClass::AuthExpired => format!("Your login has expired, run `{CLI_BINARY_NAME} login` to log in again"),
+            ErrorClass::RegionUnsupported => format!(
+                "Amazon Q is not available in this region, run `{CLI_BINARY_NAME} profile` to select a profile in a supported region"
+            ),
+            ErrorClass::RateLimited => "Too many requests were sent, wait a moment and try again".to_string(),
+            ErrorClass::ServiceUnavailable => {
+                "Amazon Q is temporarily unavailable, try again in a few minutes".to_string()
+            },
+        }
+    }
+}
+
+/// The same mapping as `classify_sdk_error` in fig_api_client, which this crate doesn't depend
+/// on, keep the two in sync.
+fn classify_sdk_error<E: ProvideErrorMetadata>(e: &SdkError<E, Response>) -> Option<ErrorClass> {
+    let code = e.as_service_error().and_then(|se| se.meta().code());
+    let mentions_region = e
+        .as_service_error()
+        .and_then(|se| se.meta().message())
+        .is_some_and(|message| message.to_lowercase().contains("region"));
+
+    match (sdk_status_code(e), code) {
+        (Some(401), _) | (_, Some("ExpiredTokenException" | "UnauthorizedException")) => Some(ErrorClass::AuthExpired),
+        (Some(403), _) | (_, Some("AccessDeniedException")) if mentions_region => Some(ErrorClass::RegionUnsupported),
+        (_, Some("ThrottlingException" | "TooManyRequestsException")) => Some(ErrorClass::RateLimited),
+        (Some(502..=504), _) | (_, Some("ServiceUnavailableException")) => Some(ErrorClass::ServiceUnavailable),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::error::Error as _;
 
     use aws_smithy_runtime_api::http::Response;
     use aws_smithy_types::body::SdkBody;
+    use aws_smithy_types::error::ErrorMetadata;
     use aws_smithy_types::event_stream::Message;
 
     use super::*;
@@ -223,4 +285,48 @@ mod tests {
             println!("{error} {error:?}");
         }
     }
+
+    fn send_message_error(status: u16, code: Option<&str>, message: Option<&str>) -> ApiClientError {
+        let mut meta = ErrorMetadata::builder();
+        if let Some(code) = code {
+            meta = meta.code(code);
+        }
+        if let Some(message) = message {
+            meta = meta.message(message);
+        }
+        ApiClientError::CodewhispererGenerateAssistantResponse(SdkError::service_error(
+            GenerateAssistantResponseError::generic(meta.build()),
+            Response::new(status.try_into().unwrap(), SdkBody::empty()),
+        ))
+    }
+
+    #[test]
+    fn test_advice() {
+        let advice = |err: ApiClientError| err.advice().unwrap_or_default();
+        assert!(advice(send_message_error(401, None, None)).contains("login"));
+        assert!(advice(send_message_error(403, Some("ExpiredTokenException"), None)).contains("login"));
+        assert!(
+            advice(send_message_error(
+                403,
+                Some("AccessDeniedException"),
+                Some("Amazon Q is not available in this Region")
+            ))
+            .contains("profile")
+        );
+        assert!(advice(send_message_error(400, Some("ThrottlingException"), None)).contains("Too many requests"));
+        assert!(advice(send_message_error(503, None, None)).contains("temporarily unavailable"));
+
+        assert_eq!(
+            send_message_error(403, Some("AccessDeniedException"), None).advice(),
+            None
+        );
+        assert_eq!(
+            send_message_error(400, Some("ValidationException"), None).advice(),
+            None
+        );
+        assert_eq!(
+            ApiClientError::ContextWindowOverflow { status_code: None }.advice(),
+            None
+        );
+    }
 }
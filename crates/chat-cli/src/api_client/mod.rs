@@ -1,3 +1,4 @@
+mod correlation_id;
 mod credentials;
 pub mod customization;
 mod endpoints;
@@ -37,6 +38,7 @@ use tracing::{
     error,
 };
 
+use crate::api_client::correlation_id::CorrelationIdInterceptor;
 use crate::api_client::credentials::CredentialsChain;
 use crate::api_client::model::{
     ChatResponseStream,
@@ -63,6 +65,9 @@ use crate::os::{
 // Opt out constants
 pub const X_AMZN_CODEWHISPERER_OPT_OUT_HEADER: &str = "x-amzn-codewhisperer-optout";
 
+/// Carries [ConversationState::correlation_id] of a chat request
+pub const X_AMZN_CORRELATION_ID_HEADER: &str = "x-amzn-client-correlation-id";
+
 // TODO(bskiser): confirm timeout is updated to an appropriate value?
 const DEFAULT_TIMEOUT_DURATION: Duration = Duration::from_secs(60 * 5);
 
@@ -255,6 +260,7 @@ impl ApiClient {
             conversation_id,
             user_input_message,
             history,
+            correlation_id,
         } = conversation;
 
         let model_id_opt: Option<String> = user_input_message.model_id.clone();
@@ -280,12 +286,15 @@ impl ApiClient {
                 .generate_assistant_response()
                 .conversation_state(conversation_state)
                 .set_profile_arn(self.profile.as_ref().map(|p| p.arn.clone()))
+                .customize()
+                .interceptor(CorrelationIdInterceptor::new(correlation_id.clone()))
                 .send()
                 .await
             {
                 Ok(response) => Ok(SendMessageOutput::Codewhisperer(response)),
                 Err(err) => {
                     let status_code = err.raw_response().map(|res| res.status().as_u16());
+                    error!(?correlation_id, ?status_code, "failed to send message");
                     let is_quota_breach = status_code.is_some_and(|status| status == 429);
                     let is_context_window_overflow = err.as_service_error().is_some_and(|err| {
                         matches!(err, err if err.meta().code() == Some("ValidationException") && err.meta().message() == Some("Input is too long."))
@@ -356,12 +365,15 @@ impl ApiClient {
                 .send_message()
                 .conversation_state(conversation_state)
                 .set_source(Some(Origin::from("CLI")))
+                .customize()
+                .interceptor(CorrelationIdInterceptor::new(correlation_id.clone()))
                 .send()
                 .await
             {
                 Ok(response) => Ok(SendMessageOutput::QDeveloper(response)),
                 Err(err) => {
                     let status_code = err.raw_response().map(|res| res.status().as_u16());
+                    error!(?correlation_id, ?status_code, "failed to send message");
                     let is_quota_breach = status_code.is_some_and(|status| status == 429);
                     let is_context_window_overflow = err.as_service_error().is_some_and(|err| {
                         matches!(err, err if err.meta().code() == Some("ValidationException") && err.meta().message() == Some("Input is too long."))
@@ -578,6 +590,7 @@ mod tests {
                     model_id: Some("model".to_owned()),
                 },
                 history: None,
+                correlation_id: None,
             })
             .await
             .unwrap();
@@ -97,6 +97,10 @@ pub struct ConversationState {
     pub conversation_id: Option<String>,
     pub user_input_message: UserInputMessage,
     pub history: Option<Vec<ChatMessage>>,
+    /// Client generated id for this request, used to tie together the logs and telemetry of a
+    /// single turn. Sent to the service in the
+    /// [X_AMZN_CORRELATION_ID_HEADER](crate::api_client::X_AMZN_CORRELATION_ID_HEADER) header.
+    pub correlation_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
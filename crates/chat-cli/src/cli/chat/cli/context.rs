@@ -9,6 +9,14 @@ use crossterm::{
     execute,
     style,
 };
+use semantic_search_client::config;
+use semantic_search_client::processing::dedup::find_near_duplicates;
+use semantic_search_client::processing::text_chunker::chunk_text;
+use similar::{
+    ChangeTag,
+    TextDiff,
+};
+use tracing::warn;
 
 use crate::cli::chat::cli::hooks::{
     HookTrigger,
@@ -16,6 +24,11 @@ use crate::cli::chat::cli::hooks::{
     print_hook_section,
 };
 use crate::cli::chat::consts::CONTEXT_FILES_MAX_SIZE;
+use crate::cli::chat::context::{
+    ContextManager,
+    default_global_paths,
+};
+use crate::cli::chat::retrieval::ContextRetriever;
 use crate::cli::chat::token_counter::TokenCounter;
 use crate::cli::chat::util::drop_matched_context_files;
 use crate::cli::chat::{
@@ -23,6 +36,7 @@ use crate::cli::chat::{
     ChatSession,
     ChatState,
 };
+use crate::database::settings::Setting;
 use crate::os::Os;
 
 #[deny(missing_docs)]
@@ -73,10 +87,228 @@ pub enum ContextSubcommand {
         #[arg(short, long)]
         global: bool,
     },
+    /// Show file count, token footprint, and semantic chunk count of the active context
+    Stats,
+    /// Find rules for missing files, near-duplicate files, and files made mostly of repeated text
+    Optimize {
+        /// Remove the flagged rules instead of only listing them
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Show how the context files on disk differ from those sent with the last message
+    Diff,
     #[command(hide = true)]
     Hooks,
 }
 
+/// How a context file on disk differs from the version sent with the last message, as reported by
+/// `/context diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextFileDiff {
+    Added { lines: usize },
+    Removed { lines: usize },
+    Modified { added: usize, removed: usize },
+}
+
+/// Compares the `loaded` context files to the `current` ones, returning the files that differ
+/// sorted by name.
+pub fn diff_context_files(loaded: &[(String, String)], current: &[(String, String)]) -> Vec<(String, ContextFileDiff)> {
+    let mut diffs = Vec::new();
+    for (name, content) in current {
+        match loaded.iter().find(|(loaded_name, _)| loaded_name == name) {
+            Some((_, loaded_content)) if loaded_content == content => (),
+            Some((_, loaded_content)) => {
+                let (mut added, mut removed) = (0, 0);
+                for change in TextDiff::from_lines(loaded_content, content).iter_all_changes() {
+                    match change.tag() {
+                        ChangeTag::Insert => added += 1,
+                        ChangeTag::Delete => removed += 1,
+                        ChangeTag::Equal => (),
+                    }
+                }
+                diffs.push((name.clone(), ContextFileDiff::Modified { added, removed }));
+            },
+            None => diffs.push((name.clone(), ContextFileDiff::Added {
+                lines: content.lines().count(),
+            })),
+        }
+    }
+    for (name, content) in loaded {
+        if !current.iter().any(|(current_name, _)| current_name == name) {
+            diffs.push((name.clone(), ContextFileDiff::Removed {
+                lines: content.lines().count(),
+            }));
+        }
+    }
+
+    diffs.sort_by(|a, b| a.0.cmp(&b.0));
+    diffs
+}
+
+/// Size of the active context, as reported by `/context stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextStats {
+    pub files: usize,
+    pub tokens: usize,
+    /// Number of chunks the files split into when semantically indexed, if indexing is enabled.
+    pub chunks: Option<usize>,
+}
+
+impl ContextStats {
+    /// Computes stats for `files`, chunking each file with `(chunk_size, overlap)` when given.
+    pub fn new(files: &[(String, String)], chunking: Option<(usize, usize)>) -> Self {
+        Self {
+            files: files.len(),
+            tokens: files
+                .iter()
+                .map(|(_, content)| TokenCounter::count_tokens(content))
+                .sum(),
+            chunks: chunking.map(|(chunk_size, overlap)| {
+                files
+                    .iter()
+                    .map(|(_, content)| chunk_text(content, Some(chunk_size), Some(overlap)).len())
+                    .sum()
+            }),
+        }
+    }
+}
+
+/// Files at least this similar to an earlier file are reported as near-duplicates.
+const DUPLICATE_SIMILARITY: f32 = 0.95;
+
+/// Files below this many tokens are never reported as low value.
+const LOW_VALUE_MIN_TOKENS: usize = 500;
+
+/// Files whose share of distinct words is below this are reported as low value.
+const LOW_VALUE_DISTINCT_RATIO: f32 = 0.1;
+
+/// Redundant context found by `/context optimize`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextOptimization {
+    /// Rules that name a file or directory that doesn't exist, with whether the rule is global.
+    pub missing: Vec<(String, bool)>,
+    /// Files that nearly duplicate another context file, paired with the file they duplicate.
+    pub duplicates: Vec<(String, String)>,
+    /// Large files made mostly of repeated text.
+    pub low_value: Vec<String>,
+    /// Rules whose matched files are all duplicates or low value, with whether the rule is global.
+    pub redundant_rules: Vec<(String, bool)>,
+}
+
+impl ContextOptimization {
+    /// Analyzes the rules and matched files of `context_manager`.
+    ///
+    /// Glob rules and the default global rules are never reported as missing, since matching
+    /// nothing in the current directory is expected for them.
+    pub async fn analyze(os: &Os, context_manager: &mut ContextManager) -> Self {
+        let defaults = default_global_paths();
+        let rules = context_manager
+            .global_config
+            .paths
+            .iter()
+            .map(|path| (path, true))
+            .chain(context_manager.profile_config.paths.iter().map(|path| (path, false)));
+
+        let mut missing = Vec::new();
+        let mut rule_matches = Vec::new();
+        for (path, global) in rules {
+            match context_manager.get_context_files_by_path(os, path).await {
+                Ok(files) => rule_matches.push((path.clone(), global, files)),
+                Err(_) if is_glob(path) || (global && defaults.contains(path)) => (),
+                Err(_) => missing.push((path.clone(), global)),
+            }
+        }
+
+        let mut files = rule_matches
+            .iter()
+            .flat_map(|(_, _, files)| files.iter().cloned())
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        files.dedup_by(|a, b| a.0 == b.0);
+
+        let duplicates = match find_duplicate_files(&mut context_manager.retriever, &files).await {
+            Ok(duplicates) => duplicates,
+            Err(err) => {
+                warn!(?err, "failed to check context files for duplicates");
+                Vec::new()
+            },
+        };
+        let low_value = files
+            .iter()
+            .filter(|(_, content)| is_low_value(content))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        let redundant = duplicates
+            .iter()
+            .map(|(name, _)| name)
+            .chain(low_value.iter())
+            .collect::<HashSet<_>>();
+        let redundant_rules = rule_matches
+            .into_iter()
+            .filter(|(_, _, files)| !files.is_empty() && files.iter().all(|(name, _)| redundant.contains(name)))
+            .map(|(path, global, _)| (path, global))
+            .collect();
+
+        Self {
+            missing,
+            duplicates,
+            low_value,
+            redundant_rules,
+        }
+    }
+
+    /// Rules that `--apply` removes.
+    pub fn removable_rules(&self) -> impl Iterator<Item = &(String, bool)> {
+        self.missing.iter().chain(self.redundant_rules.iter())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.duplicates.is_empty() && self.low_value.is_empty()
+    }
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains('*') || path.contains('?') || path.contains('[')
+}
+
+fn is_low_value(content: &str) -> bool {
+    let words = content.split_whitespace().collect::<Vec<_>>();
+    if TokenCounter::count_tokens(content) < LOW_VALUE_MIN_TOKENS || words.is_empty() {
+        return false;
+    }
+
+    let distinct = words.iter().collect::<HashSet<_>>().len();
+    (distinct as f32 / words.len() as f32) < LOW_VALUE_DISTINCT_RATIO
+}
+
+/// Returns each file that nearly duplicates an earlier file in `files`, with the file it
+/// duplicates. Files are compared with the embedder of `retriever`.
+async fn find_duplicate_files(
+    retriever: &mut ContextRetriever,
+    files: &[(String, String)],
+) -> eyre::Result<Vec<(String, String)>> {
+    if files.len() < 2 {
+        return Ok(Vec::new());
+    }
+
+    let embedder = retriever.embedder().await?;
+
+    // Embedding is CPU bound, keep it off the runtime's worker threads
+    let texts = files.iter().map(|(_, content)| content.clone()).collect::<Vec<_>>();
+    let pairs =
+        tokio::task::spawn_blocking(move || find_near_duplicates(embedder.as_ref(), &texts, DUPLICATE_SIMILARITY))
+            .await?
+            .map_err(|err| eyre::eyre!("failed to compare context files: {err}"))?;
+
+    let mut seen = HashSet::new();
+    Ok(pairs
+        .into_iter()
+        .filter(|pair| seen.insert(pair.second))
+        .map(|pair| (files[pair.second].0.clone(), files[pair.first].0.clone()))
+        .collect())
+}
+
 impl ContextSubcommand {
     pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
         let Some(context_manager) = &mut session.conversation.context_manager else {
@@ -424,6 +656,247 @@ impl ContextSubcommand {
                     )?;
                 },
             },
+            Self::Stats => {
+                let files = match context_manager.get_context_files(os).await {
+                    Ok(files) => files,
+                    Err(e) => {
+                        execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Red),
+                            style::Print(format!("\nError: {}\n\n", e)),
+                            style::SetForegroundColor(Color::Reset)
+                        )?;
+                        return Ok(ChatState::PromptUser {
+                            skip_printing_tools: true,
+                        });
+                    },
+                };
+
+                let chunking = os
+                    .database
+                    .settings
+                    .get_bool(Setting::EnabledKnowledge)
+                    .unwrap_or(false)
+                    .then(|| {
+                        let config = config::try_get_config().cloned().unwrap_or_default();
+                        (config.chunk_size, config.chunk_overlap)
+                    });
+                let stats = ContextStats::new(&files, chunking);
+
+                execute!(
+                    session.stderr,
+                    style::SetAttribute(Attribute::Bold),
+                    style::SetForegroundColor(Color::Magenta),
+                    style::Print(format!("\n👤 profile ({}):\n", context_manager.current_profile)),
+                    style::SetAttribute(Attribute::Reset),
+                    style::SetForegroundColor(Color::Reset),
+                    style::Print(format!("    Files:  {}\n", stats.files)),
+                    style::Print(format!("    Tokens: ~{}\n", stats.tokens)),
+                )?;
+                match stats.chunks {
+                    Some(chunks) => execute!(session.stderr, style::Print(format!("    Chunks: {}\n\n", chunks)))?,
+                    None => execute!(
+                        session.stderr,
+                        style::Print("    Chunks: "),
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print("n/a (knowledge indexing is disabled)\n\n"),
+                        style::SetForegroundColor(Color::Reset)
+                    )?,
+                }
+            },
+            Self::Optimize { apply } => {
+                let report = ContextOptimization::analyze(os, context_manager).await;
+                if report.is_empty() {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print("\nNo redundant context found.\n\n"),
+                        style::SetForegroundColor(Color::Reset)
+                    )?;
+                    return Ok(ChatState::PromptUser {
+                        skip_printing_tools: true,
+                    });
+                }
+
+                let scope = |global: bool| if global { "🌍" } else { "👤" };
+                if !report.missing.is_empty() {
+                    execute!(
+                        session.stderr,
+                        style::SetAttribute(Attribute::Bold),
+                        style::Print("\nRules for missing files:\n"),
+                        style::SetAttribute(Attribute::Reset),
+                    )?;
+                    for (path, global) in &report.missing {
+                        execute!(
+                            session.stderr,
+                            style::Print(format!("    {} {}\n", scope(*global), path))
+                        )?;
+                    }
+                }
+                if !report.duplicates.is_empty() {
+                    execute!(
+                        session.stderr,
+                        style::SetAttribute(Attribute::Bold),
+                        style::Print("\nNear-duplicate files:\n"),
+                        style::SetAttribute(Attribute::Reset),
+                    )?;
+                    for (file, original) in &report.duplicates {
+                        execute!(
+                            session.stderr,
+                            style::Print(format!("    {} ", file)),
+                            style::SetForegroundColor(Color::DarkGrey),
+                            style::Print(format!("(duplicates {})\n", original)),
+                            style::SetForegroundColor(Color::Reset),
+                        )?;
+                    }
+                }
+                if !report.low_value.is_empty() {
+                    execute!(
+                        session.stderr,
+                        style::SetAttribute(Attribute::Bold),
+                        style::Print("\nFiles made mostly of repeated text:\n"),
+                        style::SetAttribute(Attribute::Reset),
+                    )?;
+                    for file in &report.low_value {
+                        execute!(session.stderr, style::Print(format!("    {}\n", file)))?;
+                    }
+                }
+
+                let removable = report.removable_rules().cloned().collect::<Vec<_>>();
+                if removable.is_empty() {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print(
+                            "\nThe flagged files are matched by rules that also match other files. Edit those rules to exclude them.\n\n"
+                        ),
+                        style::SetForegroundColor(Color::Reset)
+                    )?;
+                } else if apply {
+                    for global in [true, false] {
+                        let paths = removable
+                            .iter()
+                            .filter(|(_, g)| *g == global)
+                            .map(|(path, _)| path.clone())
+                            .collect::<Vec<_>>();
+                        if paths.is_empty() {
+                            continue;
+                        }
+                        if let Err(e) = context_manager.remove_paths(os, paths, global).await {
+                            execute!(
+                                session.stderr,
+                                style::SetForegroundColor(Color::Red),
+                                style::Print(format!("\nError: {}\n\n", e)),
+                                style::SetForegroundColor(Color::Reset)
+                            )?;
+                            return Ok(ChatState::PromptUser {
+                                skip_printing_tools: true,
+                            });
+                        }
+                    }
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(format!("\nRemoved {} rule(s).\n\n", removable.len())),
+                        style::SetForegroundColor(Color::Reset)
+                    )?;
+                } else {
+                    execute!(
+                        session.stderr,
+                        style::Print("\nRun "),
+                        style::SetForegroundColor(Color::Green),
+                        style::Print("/context optimize --apply"),
+                        style::SetForegroundColor(Color::Reset),
+                        style::Print(format!(" to remove {} rule(s):\n", removable.len())),
+                    )?;
+                    for (path, global) in &removable {
+                        execute!(
+                            session.stderr,
+                            style::Print(format!("    {} {}\n", scope(*global), path))
+                        )?;
+                    }
+                    execute!(session.stderr, style::Print("\n"))?;
+                }
+            },
+            Self::Diff => {
+                let Some(loaded) = &context_manager.loaded_files else {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print(
+                            "\nNo context has been sent yet, it is read from disk with your first message.\n\n"
+                        ),
+                        style::SetForegroundColor(Color::Reset)
+                    )?;
+                    return Ok(ChatState::PromptUser {
+                        skip_printing_tools: true,
+                    });
+                };
+
+                let current = match context_manager.get_context_files(os).await {
+                    Ok(files) => files,
+                    Err(e) => {
+                        execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Red),
+                            style::Print(format!("\nError: {}\n\n", e)),
+                            style::SetForegroundColor(Color::Reset)
+                        )?;
+                        return Ok(ChatState::PromptUser {
+                            skip_printing_tools: true,
+                        });
+                    },
+                };
+
+                let diffs = diff_context_files(loaded, &current);
+                if diffs.is_empty() {
+                    execute!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::Green),
+                        style::Print("\nContext files are unchanged since your last message.\n\n"),
+                        style::SetForegroundColor(Color::Reset)
+                    )?;
+                    return Ok(ChatState::PromptUser {
+                        skip_printing_tools: true,
+                    });
+                }
+
+                execute!(
+                    session.stderr,
+                    style::SetAttribute(Attribute::Bold),
+                    style::Print("\nChanged since your last message:\n"),
+                    style::SetAttribute(Attribute::Reset),
+                )?;
+                for (name, diff) in &diffs {
+                    execute!(session.stderr, style::Print(format!("    {} ", name)))?;
+                    match *diff {
+                        ContextFileDiff::Added { lines } => execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Green),
+                            style::Print(format!("added ({} lines)\n", lines)),
+                        )?,
+                        ContextFileDiff::Removed { lines } => execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Red),
+                            style::Print(format!("removed ({} lines)\n", lines)),
+                        )?,
+                        ContextFileDiff::Modified { added, removed } => execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Green),
+                            style::Print(format!("+{} ", added)),
+                            style::SetForegroundColor(Color::Red),
+                            style::Print(format!("-{}\n", removed)),
+                        )?,
+                    }
+                    execute!(session.stderr, style::SetForegroundColor(Color::Reset))?;
+                }
+                execute!(
+                    session.stderr,
+                    style::SetForegroundColor(Color::DarkGrey),
+                    style::Print("\nThe files are read again with your next message.\n\n"),
+                    style::SetForegroundColor(Color::Reset)
+                )?;
+            },
             Self::Hooks => {
                 execute!(
                     session.stderr,
@@ -443,3 +916,125 @@ impl ContextSubcommand {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use semantic_search_client::embedding::EmbeddingType;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_context_optimize() {
+        let os = Os::new().await.unwrap();
+        let mut manager = ContextManager::new(&os, None).await.unwrap();
+        manager.retriever = ContextRetriever::new(EmbeddingType::BM25);
+
+        let content = "Database migrations are applied with the migrate command before every release.";
+        os.fs.create_dir_all("docs").await.unwrap();
+        os.fs.write("docs/release.md", content).await.unwrap();
+        os.fs.write("docs/release-copy.md", content).await.unwrap();
+        os.fs
+            .write(
+                "docs/style.md",
+                "Frontend components are styled with tailwind utility classes.",
+            )
+            .await
+            .unwrap();
+        manager
+            .add_paths(
+                &os,
+                vec![
+                    "docs/release.md".to_string(),
+                    "docs/release-copy.md".to_string(),
+                    "docs/style.md".to_string(),
+                ],
+                false,
+                false,
+            )
+            .await
+            .unwrap();
+        manager
+            .add_paths(&os, vec!["docs/removed.md".to_string()], false, true)
+            .await
+            .unwrap();
+
+        let report = ContextOptimization::analyze(&os, &mut manager).await;
+        assert_eq!(report.missing, vec![("docs/removed.md".to_string(), false)]);
+        assert_eq!(report.duplicates.len(), 1);
+        assert!(report.duplicates[0].0.ends_with("release.md"));
+        assert!(report.duplicates[0].1.ends_with("release-copy.md"));
+        assert!(report.low_value.is_empty());
+        assert_eq!(report.removable_rules().cloned().collect::<Vec<_>>(), vec![
+            ("docs/removed.md".to_string(), false),
+            ("docs/release.md".to_string(), false),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_context_diff() {
+        let os = Os::new().await.unwrap();
+        let mut manager = ContextManager::new(&os, None).await.unwrap();
+
+        os.fs.create_dir_all("docs").await.unwrap();
+        os.fs.write("docs/release.md", "build\ntest\ndeploy\n").await.unwrap();
+        os.fs.write("docs/style.md", "tabs\n").await.unwrap();
+        manager
+            .add_paths(&os, vec!["docs/*.md".to_string()], false, false)
+            .await
+            .unwrap();
+        manager.collect_context_files_with_limit(&os, None).await.unwrap();
+        let loaded = manager.loaded_files.clone().unwrap();
+
+        let current = manager.get_context_files(&os).await.unwrap();
+        assert!(diff_context_files(&loaded, &current).is_empty());
+
+        os.fs
+            .write("docs/release.md", "build\nlint\ntest\nrelease\n")
+            .await
+            .unwrap();
+        os.fs.remove_file("docs/style.md").await.unwrap();
+        os.fs.write("docs/setup.md", "install\nconfigure\n").await.unwrap();
+
+        let current = manager.get_context_files(&os).await.unwrap();
+        let diffs = diff_context_files(&loaded, &current)
+            .into_iter()
+            .map(|(name, diff)| (name.rsplit('/').next().unwrap().to_string(), diff))
+            .collect::<Vec<_>>();
+        assert_eq!(diffs, vec![
+            ("release.md".to_string(), ContextFileDiff::Modified {
+                added: 2,
+                removed: 1
+            }),
+            ("setup.md".to_string(), ContextFileDiff::Added { lines: 2 }),
+            ("style.md".to_string(), ContextFileDiff::Removed { lines: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn test_is_low_value() {
+        assert!(!is_low_value("short file"));
+        assert!(is_low_value(&"lorem ipsum ".repeat(2000)));
+        let varied = (0..2000).map(|i| format!("word{i}")).collect::<Vec<_>>().join(" ");
+        assert!(!is_low_value(&varied));
+    }
+
+    #[test]
+    fn test_context_stats() {
+        let files = vec![
+            ("AGENTS.md".to_string(), "one two three four five six".to_string()),
+            ("README.md".to_string(), "alpha beta gamma".to_string()),
+        ];
+
+        let stats = ContextStats::new(&files, None);
+        assert_eq!(stats.files, 2);
+        assert_eq!(
+            stats.tokens,
+            TokenCounter::count_tokens(&files[0].1) + TokenCounter::count_tokens(&files[1].1)
+        );
+        assert_eq!(stats.chunks, None);
+
+        // 6 words with size 4 / overlap 1 -> [0..4], [3..6]; 3 words -> 1 chunk.
+        let stats = ContextStats::new(&files, Some((4, 1)));
+        assert_eq!(stats.chunks, Some(3));
+    }
+}
@@ -96,7 +96,7 @@ impl SlashCommand {
             Self::Compact(args) => args.execute(os, session).await,
             Self::Tools(args) => args.execute(session).await,
             Self::Issue(args) => {
-                if let Err(err) = args.execute(os).await {
+                if let Err(err) = args.execute(os, Some(&session.conversation.transcript)).await {
                     return Err(ChatError::Custom(err.to_string().into()));
                 }
 
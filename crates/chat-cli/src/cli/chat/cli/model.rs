@@ -40,6 +40,13 @@ pub const MODEL_OPTIONS: [ModelOption; 3] = [
     },
 ];
 
+/// Looks up a model by the name users refer to it with, e.g. `claude-4-sonnet`
+pub fn find_model(name: &str) -> Option<&'static ModelOption> {
+    let options: &'static [ModelOption] = &MODEL_OPTIONS;
+    let name = name.to_lowercase();
+    options.iter().find(|opt| opt.name == name)
+}
+
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
 pub struct ModelArgs;
@@ -88,8 +95,7 @@ pub fn select_model(session: &mut ChatSession) -> Result<Option<ChatState>, Chat
 
     if let Some(index) = selection {
         let selected = &MODEL_OPTIONS[index];
-        let model_id_str = selected.model_id.to_string();
-        session.conversation.model = Some(model_id_str);
+        session.conversation.set_session_model(selected.model_id.to_string());
 
         queue!(
             session.stderr,
@@ -6,6 +6,7 @@ use crossterm::style::{
 };
 use tracing::warn;
 
+use crate::cli::chat::context::ProfileBundle;
 use crate::cli::chat::{
     ChatError,
     ChatSession,
@@ -22,7 +23,9 @@ Notes
 • The \"global\" profile contains context files that are available in all profiles
 • The \"default\" profile is used when no profile is specified
 • You can switch between profiles to work on different projects
-• Each profile maintains its own set of context files"
+• Each profile maintains its own set of context files
+• Profiles can be shared with export and import
+• A profile can pin a model by setting \"model\" in its context.json, e.g. \"claude-3.7-sonnet\""
 )]
 pub enum ProfileSubcommand {
     /// List all available profiles
@@ -35,6 +38,24 @@ pub enum ProfileSubcommand {
     Set { name: String },
     /// Rename a profile
     Rename { old_name: String, new_name: String },
+    /// Export a profile's context rules and hooks to a file
+    Export {
+        name: String,
+        path: String,
+        /// Also export the contents of the files matched by the profile's context rules
+        #[arg(long)]
+        include_files: bool,
+        /// Overwrite the file if it already exists
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Create a profile from a file written by export
+    Import {
+        path: String,
+        /// Create the profile with this name instead of the exported one
+        #[arg(long)]
+        rename: Option<String>,
+    },
 }
 
 impl ProfileSubcommand {
@@ -56,6 +77,7 @@ impl ProfileSubcommand {
             };
         }
 
+        let mut switched = false;
         match self {
             Self::List => {
                 let profiles = match context_manager.list_profiles(os).await {
@@ -101,11 +123,11 @@ impl ProfileSubcommand {
                         style::Print(format!("\nCreated profile: {}\n\n", name)),
                         style::SetForegroundColor(Color::Reset)
                     )?;
-                    context_manager
+                    switched = context_manager
                         .switch_profile(os, &name)
                         .await
                         .map_err(|e| warn!(?e, "failed to switch to newly created profile"))
-                        .ok();
+                        .is_ok();
                 },
                 Err(e) => print_err!(e),
             },
@@ -122,12 +144,17 @@ impl ProfileSubcommand {
             },
             Self::Set { name } => match context_manager.switch_profile(os, &name).await {
                 Ok(_) => {
+                    switched = true;
                     execute!(
                         session.stderr,
                         style::SetForegroundColor(Color::Green),
-                        style::Print(format!("\nSwitched to profile: {}\n\n", name)),
+                        style::Print(format!("\nSwitched to profile: {}\n", name)),
                         style::SetForegroundColor(Color::Reset)
                     )?;
+                    if let Some(model) = &context_manager.profile_config.model {
+                        execute!(session.stderr, style::Print(format!("Using model: {}\n", model)))?;
+                    }
+                    execute!(session.stderr, style::Print("\n"))?;
                 },
                 Err(e) => print_err!(e),
             },
@@ -144,6 +171,77 @@ impl ProfileSubcommand {
                     Err(e) => print_err!(e),
                 }
             },
+            Self::Export {
+                name,
+                path,
+                include_files,
+                force,
+            } => {
+                if os.fs.exists(&path) && !force {
+                    print_err!(format!(
+                        "File at {} already exists. To overwrite, use -f or --force",
+                        path
+                    ));
+                } else {
+                    let result: eyre::Result<()> = async {
+                        let bundle = context_manager.export_profile(os, &name, include_files).await?;
+                        os.fs.write(&path, serde_json::to_string_pretty(&bundle)?).await?;
+                        Ok(())
+                    }
+                    .await;
+                    match result {
+                        Ok(()) => execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Green),
+                            style::Print(format!("\nExported profile {} to {}\n\n", name, path)),
+                            style::SetForegroundColor(Color::Reset)
+                        )?,
+                        Err(e) => print_err!(e),
+                    }
+                }
+            },
+            Self::Import { path, rename } => {
+                let result: eyre::Result<(String, usize)> = async {
+                    let contents = os
+                        .fs
+                        .read_to_string(&path)
+                        .await
+                        .map_err(|e| eyre::eyre!("Failed to read {}: {}", path, e))?;
+                    let bundle = serde_json::from_str::<ProfileBundle>(&contents)
+                        .map_err(|e| eyre::eyre!("Failed to parse profile bundle {}: {}", path, e))?;
+                    let hooks = bundle.config.hooks.len();
+                    let name = context_manager.import_profile(os, bundle, rename.as_deref()).await?;
+                    Ok((name, hooks))
+                }
+                .await;
+                match result {
+                    Ok((name, hooks)) => {
+                        execute!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::Green),
+                            style::Print(format!("\nImported profile: {}\n", name)),
+                            style::SetForegroundColor(Color::Reset)
+                        )?;
+                        if hooks > 0 {
+                            execute!(
+                                session.stderr,
+                                style::SetForegroundColor(Color::Yellow),
+                                style::Print(format!(
+                                    "The profile has {} hook(s) that run commands, review them with /hooks after switching to it\n",
+                                    hooks
+                                )),
+                                style::SetForegroundColor(Color::Reset)
+                            )?;
+                        }
+                        execute!(session.stderr, style::Print("\n"))?;
+                    },
+                    Err(e) => print_err!(e),
+                }
+            },
+        }
+
+        if switched {
+            session.conversation.apply_profile_model();
         }
 
         Ok(ChatState::PromptUser {
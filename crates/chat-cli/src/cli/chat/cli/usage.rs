@@ -183,6 +183,16 @@ impl UsageArgs {
             )),
         )?;
 
+        let last_response_tokens = session.response_tokens.response_token_count();
+        if last_response_tokens.value() > 0 {
+            queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(format!("Last response: ~{} tokens\n", last_response_tokens)),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+
         queue!(
             session.stderr,
             style::SetAttribute(Attribute::Bold),
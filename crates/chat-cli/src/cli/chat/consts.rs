@@ -18,6 +18,9 @@ pub const CONTEXT_WINDOW_SIZE: usize = 200_000;
 
 pub const CONTEXT_FILES_MAX_SIZE: usize = 150_000;
 
+/// In tokens
+pub const SYSTEM_PROMPT_PREFIX_MAX_TOKENS: usize = 4_000;
+
 pub const MAX_CHARS: usize = TokenCounter::token_to_chars(CONTEXT_WINDOW_SIZE); // Character-based warning threshold
 
 pub const DUMMY_TOOL_NAME: &str = "dummy";
@@ -1,9 +1,18 @@
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{
+    Hash,
+    Hasher,
+};
 use std::io::Write;
 use std::path::{
     Path,
     PathBuf,
 };
+use std::time::{
+    Duration,
+    SystemTime,
+};
 
 use eyre::{
     Result,
@@ -11,25 +20,42 @@ use eyre::{
 };
 use glob::glob;
 use regex::Regex;
+use semantic_search_client::processing::IgnoreRules;
 use serde::{
     Deserialize,
     Serialize,
 };
-use tracing::debug;
+use tracing::{
+    debug,
+    warn,
+};
 
 use super::cli::hooks::HookTrigger;
 use super::consts::CONTEXT_FILES_MAX_SIZE;
+use super::retrieval::{
+    ContextRetriever,
+    DEFAULT_RETRIEVAL_TOP_K,
+};
 use super::util::drop_matched_context_files;
 use crate::cli::chat::ChatError;
 use crate::cli::chat::cli::hooks::{
     Hook,
     HookExecutor,
 };
+use crate::cli::chat::cli::model::{
+    MODEL_OPTIONS,
+    find_model,
+};
+use crate::database::settings::Setting;
 use crate::os::Os;
 use crate::util::directories;
 
 pub const AMAZONQ_FILENAME: &str = "AmazonQ.md";
 
+/// How long a context file has to go without being modified before its changes are reported, so
+/// that a burst of saves is reported once.
+const CONTEXT_WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
 /// Configuration for context files, containing paths to include in the context.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
@@ -39,6 +65,24 @@ pub struct ContextConfig {
 
     /// Map of Hook Name to [`Hook`]. The hook name serves as the hook's ID.
     pub hooks: HashMap<String, Hook>,
+
+    /// Name of the model to use while this profile is active, e.g. `claude-4-sonnet`. Only read
+    /// from profile configurations, when unset the model chosen for the session is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// A profile's configuration, and optionally the files its context rules match, as written by
+/// `/profile export` and read by `/profile import`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    /// Name of the exported profile, used on import unless the profile is renamed.
+    pub name: String,
+    pub config: ContextConfig,
+    /// (filename, content) pairs of the files matched by the profile's context rules when it was
+    /// exported.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<(String, String)>,
 }
 
 /// Manager for context files and profiles.
@@ -57,6 +101,84 @@ pub struct ContextManager {
 
     #[serde(skip)]
     pub hook_executor: HookExecutor,
+
+    #[serde(skip)]
+    pub retriever: ContextRetriever,
+
+    #[serde(skip)]
+    pub watcher: ContextWatcher,
+
+    /// The context files as read for the last request, `/context diff` compares against these.
+    #[serde(skip)]
+    pub loaded_files: Option<Vec<(String, String)>>,
+}
+
+/// Remembers the context files between prompts so that edits made on disk can be reported.
+#[derive(Debug, Clone)]
+pub struct ContextWatcher {
+    debounce: Duration,
+    /// Content hash of each context file as of the last poll, `None` before the first poll.
+    seen: Option<HashMap<String, u64>>,
+}
+
+impl ContextWatcher {
+    pub fn new(debounce: Duration) -> Self {
+        Self { debounce, seen: None }
+    }
+
+    /// Records `files` and returns the names of those added, changed, or removed since the last
+    /// call. Files modified within the debounce period keep their previous state until a later
+    /// call.
+    fn update(&mut self, files: &[(String, String)]) -> Vec<String> {
+        let previous = self.seen.take();
+        let now = SystemTime::now();
+
+        let mut current = HashMap::new();
+        for (name, content) in files {
+            let settling = Path::new(name)
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age < self.debounce);
+
+            let hash = match (settling, &previous) {
+                (true, Some(previous)) => match previous.get(name) {
+                    Some(hash) => *hash,
+                    None => continue,
+                },
+                _ => {
+                    let mut hasher = DefaultHasher::new();
+                    content.hash(&mut hasher);
+                    hasher.finish()
+                },
+            };
+            current.insert(name.clone(), hash);
+        }
+
+        let changed = match &previous {
+            Some(previous) => {
+                let mut changed = current
+                    .iter()
+                    .filter(|(name, hash)| previous.get(*name) != Some(*hash))
+                    .map(|(name, _)| name.clone())
+                    .chain(previous.keys().filter(|name| !current.contains_key(*name)).cloned())
+                    .collect::<Vec<_>>();
+                changed.sort();
+                changed
+            },
+            None => Vec::new(),
+        };
+
+        self.seen = Some(current);
+        changed
+    }
+}
+
+impl Default for ContextWatcher {
+    fn default() -> Self {
+        Self::new(CONTEXT_WATCH_DEBOUNCE)
+    }
 }
 
 impl ContextManager {
@@ -91,6 +213,9 @@ impl ContextManager {
             current_profile,
             profile_config,
             hook_executor: HookExecutor::new(),
+            retriever: ContextRetriever::default(),
+            watcher: ContextWatcher::default(),
+            loaded_files: None,
         })
     }
 
@@ -399,6 +524,83 @@ impl ContextManager {
         Ok(())
     }
 
+    /// Bundle a profile's context rules and hooks so it can be recreated elsewhere.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the profile to export
+    /// * `include_files` - If true, also bundle the contents of the files the context rules match
+    pub async fn export_profile(&self, os: &Os, name: &str, include_files: bool) -> Result<ProfileBundle> {
+        validate_profile_name(name)?;
+        if name != "default" && !profile_context_path(os, name)?.exists() {
+            return Err(eyre!("Profile '{}' does not exist", name));
+        }
+
+        let config = load_profile_config(os, name).await?;
+        let mut files = Vec::new();
+        if include_files {
+            self.collect_context_files(os, &config.paths, &mut files).await?;
+            files.sort_by(|a, b| a.0.cmp(&b.0));
+            files.dedup_by(|a, b| a.0 == b.0);
+        }
+
+        Ok(ProfileBundle {
+            name: name.to_string(),
+            config,
+            files,
+        })
+    }
+
+    /// Create a profile from a [ProfileBundle].
+    ///
+    /// Bundled files are written to the new profile's directory and the profile's context rules
+    /// are replaced with the paths of those copies, so the profile has the same context it was
+    /// exported with.
+    ///
+    /// # Arguments
+    /// * `bundle` - The exported profile
+    /// * `rename` - Name to create the profile with instead of the exported name
+    ///
+    /// # Returns
+    /// The name of the created profile
+    pub async fn import_profile(&self, os: &Os, bundle: ProfileBundle, rename: Option<&str>) -> Result<String> {
+        let name = rename.unwrap_or(&bundle.name).to_string();
+        validate_profile_name(&name)?;
+
+        let profile_path = profile_context_path(os, &name)?;
+        if name == "default" || profile_path.exists() {
+            return Err(eyre!(
+                "Profile '{}' already exists. Use --rename to import it with a different name",
+                name
+            ));
+        }
+
+        let mut config = bundle.config;
+        if !bundle.files.is_empty() {
+            let files_dir = profile_dir_path(os, &name)?.join("files");
+            os.fs.create_dir_all(&files_dir).await?;
+
+            config.paths.clear();
+            for (i, (filename, content)) in bundle.files.iter().enumerate() {
+                // Only the file name is kept so a bundle can't write outside of the profile directory
+                let file_name = Path::new(filename)
+                    .file_name()
+                    .map_or_else(|| "file".into(), |n| n.to_string_lossy());
+                let path = files_dir.join(format!("{i}-{file_name}"));
+                os.fs.write(&path, content).await?;
+                config.paths.push(path.to_string_lossy().to_string());
+            }
+        }
+
+        let contents = serde_json::to_string_pretty(&config)
+            .map_err(|e| eyre!("Failed to serialize profile configuration: {}", e))?;
+        if let Some(parent) = profile_path.parent() {
+            os.fs.create_dir_all(parent).await?;
+        }
+        os.fs.write(&profile_path, contents).await?;
+
+        Ok(name)
+    }
+
     /// Switch to a different profile.
     ///
     /// # Arguments
@@ -411,30 +613,46 @@ impl ContextManager {
         self.hook_executor.profile_cache.clear();
 
         // Special handling for default profile - it always exists
-        if name == "default" {
-            // Load the default profile configuration
-            let profile_config = load_profile_config(os, name).await?;
-
-            // Update the current profile
-            self.current_profile = name.to_string();
-            self.profile_config = profile_config;
-
-            return Ok(());
+        if name != "default" {
+            // Check if profile exists
+            let profile_path = profile_context_path(os, name)?;
+            if !profile_path.exists() {
+                return Err(eyre!("Profile '{}' does not exist. Use 'create' to create it", name));
+            }
         }
 
-        // Check if profile exists
-        let profile_path = profile_context_path(os, name)?;
-        if !profile_path.exists() {
-            return Err(eyre!("Profile '{}' does not exist. Use 'create' to create it", name));
+        let profile_config = load_profile_config(os, name).await?;
+        if let Some(model) = &profile_config.model {
+            if find_model(model).is_none() {
+                let available_names: Vec<&str> = MODEL_OPTIONS.iter().map(|opt| opt.name).collect();
+                return Err(eyre!(
+                    "Profile '{}' uses model '{}' which does not exist. Available models: {}",
+                    name,
+                    model,
+                    available_names.join(", ")
+                ));
+            }
         }
 
         // Update the current profile
         self.current_profile = name.to_string();
-        self.profile_config = load_profile_config(os, name).await?;
+        self.profile_config = profile_config;
 
         Ok(())
     }
 
+    /// The model id pinned by the current profile, if any.
+    pub fn profile_model_id(&self) -> Option<&'static str> {
+        let model = self.profile_config.model.as_deref()?;
+        match find_model(model) {
+            Some(opt) => Some(opt.model_id),
+            None => {
+                warn!(?model, profile = %self.current_profile, "ignoring unknown model in profile");
+                None
+            },
+        }
+    }
+
     /// Get all context files (global + profile-specific).
     ///
     /// This method:
@@ -467,12 +685,35 @@ impl ContextManager {
     }
 
     /// Collects context files and optionally drops files if the total size exceeds the limit.
+    ///
+    /// When `chat.context.retrieval.enabled` is set and a `prompt` is given, large files are
+    /// reduced to the chunks most relevant to the prompt before the limit is applied.
     /// Returns (files_to_use, dropped_files)
     pub async fn collect_context_files_with_limit(
-        &self,
+        &mut self,
         os: &Os,
+        prompt: Option<&str>,
     ) -> Result<(Vec<(String, String)>, Vec<(String, String)>)> {
         let mut files = self.get_context_files(os).await?;
+        self.loaded_files = Some(files.clone());
+
+        let retrieval_enabled = os
+            .database
+            .settings
+            .get_bool(Setting::ChatContextRetrievalEnabled)
+            .unwrap_or(false);
+        if let Some(prompt) = prompt.filter(|_| retrieval_enabled) {
+            let top_k = os
+                .database
+                .settings
+                .get_int(Setting::ChatContextRetrievalTopK)
+                .and_then(|top_k| usize::try_from(top_k).ok())
+                .unwrap_or(DEFAULT_RETRIEVAL_TOP_K);
+            match self.retriever.retrieve(&files, prompt, top_k).await {
+                Ok(retrieved) => files = retrieved,
+                Err(err) => warn!("Context retrieval failed, using full context files: {}", err),
+            }
+        }
 
         let dropped_files = drop_matched_context_files(&mut files, self.max_context_files_size).unwrap_or_default();
 
@@ -482,6 +723,39 @@ impl ContextManager {
         Ok((files, dropped_files))
     }
 
+    /// Returns the context files that were added, changed, or removed on disk since the last call.
+    ///
+    /// Does nothing unless `chat.context.watch` is set. Context files are read again for every
+    /// prompt, so this is for telling the user about the change and for rebuilding the retrieval
+    /// index before the next prompt rather than while it is being sent. The first call only
+    /// records the files.
+    pub async fn poll_changes(&mut self, os: &Os) -> Result<Vec<String>> {
+        let watch_enabled = os
+            .database
+            .settings
+            .get_bool(Setting::ChatContextWatch)
+            .unwrap_or(false);
+        if !watch_enabled {
+            return Ok(Vec::new());
+        }
+
+        let files = self.get_context_files(os).await?;
+        let changed = self.watcher.update(&files);
+
+        let retrieval_enabled = os
+            .database
+            .settings
+            .get_bool(Setting::ChatContextRetrievalEnabled)
+            .unwrap_or(false);
+        if retrieval_enabled && !changed.is_empty() {
+            if let Err(err) = self.retriever.reindex(&files).await {
+                warn!("Failed to re-index the changed context files: {}", err);
+            }
+        }
+
+        Ok(changed)
+    }
+
     async fn collect_context_files(
         &self,
         os: &Os,
@@ -614,6 +888,15 @@ pub fn profile_context_path(os: &Os, profile_name: &str) -> Result<PathBuf> {
         .join("context.json"))
 }
 
+/// The rules in the global configuration before the user edits it.
+pub fn default_global_paths() -> Vec<String> {
+    vec![
+        ".amazonq/rules/**/*.md".to_string(),
+        "README.md".to_string(),
+        AMAZONQ_FILENAME.to_string(),
+    ]
+}
+
 /// Load the global context configuration.
 ///
 /// If the global configuration file doesn't exist, returns a default configuration.
@@ -628,12 +911,9 @@ async fn load_global_config(os: &Os) -> Result<ContextConfig> {
     } else {
         // Return default global configuration with predefined paths
         Ok(ContextConfig {
-            paths: vec![
-                ".amazonq/rules/**/*.md".to_string(),
-                "README.md".to_string(),
-                AMAZONQ_FILENAME.to_string(),
-            ],
+            paths: default_global_paths(),
             hooks: HashMap::new(),
+            model: None,
         })
     }
 }
@@ -698,6 +978,10 @@ async fn process_path(
     // Required in chroot testing scenarios so that we can use `Path::exists`.
     let full_path = os.fs.chroot_path_str(full_path);
 
+    // Files matched by globs and directories skip what the `.amazonqignore` in the current directory
+    // excludes, the same as when indexing with /knowledge. Files named directly are always added.
+    let ignore_rules = IgnoreRules::load(&os.fs.chroot_path(os.env.current_dir()?));
+
     // Check if the path contains glob patterns
     if full_path.contains('*') || full_path.contains('?') || full_path.contains('[') {
         // Expand glob pattern
@@ -708,7 +992,7 @@ async fn process_path(
                 for entry in entries {
                     match entry {
                         Ok(path) => {
-                            if path.is_file() {
+                            if path.is_file() && !ignore_rules.is_ignored(&path, false) {
                                 add_file_to_context(os, &path, context_files).await?;
                                 found_any = true;
                             }
@@ -737,7 +1021,7 @@ async fn process_path(
                 let mut read_dir = os.fs.read_dir(path).await?;
                 while let Some(entry) = read_dir.next_entry().await? {
                     let path = entry.path();
-                    if path.is_file() {
+                    if path.is_file() && !ignore_rules.is_ignored(&path, false) {
                         add_file_to_context(os, &path, context_files).await?;
                     }
                 }
@@ -871,7 +1155,7 @@ mod tests {
             .add_paths(&os, vec!["test/*.md".to_string()], false, false)
             .await?;
 
-        let (used, dropped) = manager.collect_context_files_with_limit(&os).await.unwrap();
+        let (used, dropped) = manager.collect_context_files_with_limit(&os, None).await.unwrap();
 
         assert!(used.len() + dropped.len() == 2);
         assert!(used.len() == 1);
@@ -879,6 +1163,82 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_poll_changes() -> Result<()> {
+        let mut os = Os::new().await.unwrap();
+        let mut manager = create_test_context_manager(None).await?;
+        manager.watcher = ContextWatcher::new(Duration::ZERO);
+
+        os.fs.create_dir_all("test").await?;
+        os.fs.write("test/notes.md", "use tabs").await?;
+        os.fs.write("test/other.md", "unchanged").await?;
+        manager
+            .add_paths(&os, vec!["test/*.md".to_string()], false, false)
+            .await?;
+
+        // Watching is off by default
+        os.fs.write("test/notes.md", "use spaces").await?;
+        assert!(manager.poll_changes(&os).await?.is_empty());
+
+        os.database.settings.set(Setting::ChatContextWatch, true).await?;
+        assert!(
+            manager.poll_changes(&os).await?.is_empty(),
+            "the first poll records the files"
+        );
+
+        os.fs.write("test/notes.md", "use four spaces").await?;
+        let changed = manager.poll_changes(&os).await?;
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].ends_with("notes.md"));
+        let files = manager.get_context_files(&os).await?;
+        assert!(
+            files
+                .iter()
+                .any(|(name, content)| name == &changed[0] && content == "use four spaces")
+        );
+
+        assert!(manager.poll_changes(&os).await?.is_empty());
+
+        os.fs.remove_file("test/other.md").await?;
+        let changed = manager.poll_changes(&os).await?;
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].ends_with("other.md"));
+
+        // Edits within the debounce period wait for a later poll
+        manager.watcher.debounce = Duration::from_secs(60);
+        os.fs.write("test/notes.md", "use tabs again").await?;
+        assert!(manager.poll_changes(&os).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_amazonqignore_excludes_glob_matches() -> Result<()> {
+        let os = Os::new().await.unwrap();
+        let mut manager = create_test_context_manager(None).await?;
+
+        os.fs.create_dir_all("project/keys").await?;
+        os.fs.write("project/README.md", "readme").await?;
+        os.fs.write("project/secrets.env", "API_KEY=abc").await?;
+        os.fs.write("project/keys/deploy.md", "ssh key").await?;
+        os.fs.write(".amazonqignore", "*.env\nproject/keys/\n").await?;
+        manager
+            .add_paths(&os, vec!["project/**/*".to_string()], false, false)
+            .await?;
+
+        let files = manager.get_context_files(&os).await?;
+        assert_eq!(files.len(), 1);
+        assert!(files[0].0.ends_with("README.md"));
+
+        // Naming an ignored file directly still adds it
+        manager
+            .add_paths(&os, vec!["project/secrets.env".to_string()], false, false)
+            .await?;
+        assert_eq!(manager.get_context_files(&os).await?.len(), 2);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_path_ops() -> Result<()> {
         let os = Os::new().await.unwrap();
@@ -913,4 +1273,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_profile_bundle_round_trip() -> Result<()> {
+        let os = Os::new().await.unwrap();
+        let mut manager = create_test_context_manager(None).await?;
+
+        os.fs.create_dir_all("docs").await?;
+        os.fs.write("docs/guide.md", "guide").await?;
+        os.fs.write("docs/rules.md", "rules").await?;
+
+        manager.create_profile(&os, "shared").await?;
+        let mut config = ContextConfig {
+            paths: vec!["docs/*.md".to_string()],
+            model: Some("claude-3.7-sonnet".to_string()),
+            ..Default::default()
+        };
+        config.hooks.insert(
+            "status".to_string(),
+            Hook::new_inline_hook(HookTrigger::ConversationStart, "git status".to_string()),
+        );
+        os.fs
+            .write(profile_context_path(&os, "shared")?, serde_json::to_string(&config)?)
+            .await?;
+
+        // Without files only the configuration is exported
+        let bundle = manager.export_profile(&os, "shared", false).await?;
+        assert!(bundle.files.is_empty());
+        let err = manager.import_profile(&os, bundle.clone(), None).await.unwrap_err();
+        assert!(err.to_string().contains("--rename"), "{err}");
+        let name = manager.import_profile(&os, bundle, Some("copy")).await?;
+        let imported = load_profile_config(&os, &name).await?;
+        assert_eq!(imported.paths, config.paths);
+        assert_eq!(imported.model, config.model);
+        assert_eq!(imported.hooks.len(), 1);
+
+        // With files the imported profile reads the bundled copies
+        let bundle = manager.export_profile(&os, "shared", true).await?;
+        let bundle: ProfileBundle = serde_json::from_str(&serde_json::to_string(&bundle)?)?;
+        let name = manager.import_profile(&os, bundle, Some("with-files")).await?;
+        os.fs.remove_dir_all("docs").await?;
+
+        manager.switch_profile(&os, &name).await?;
+        let files = manager.get_context_files(&os).await?;
+        let contents = files.iter().map(|(_, content)| content.as_str()).collect::<Vec<_>>();
+        assert_eq!(contents, vec!["guide", "rules"]);
+        assert!(files.iter().all(|(path, _)| path.contains("with-files")));
+        assert_eq!(manager.profile_config.hooks.len(), 1);
+
+        Ok(())
+    }
 }
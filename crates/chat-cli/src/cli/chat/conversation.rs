@@ -15,17 +15,20 @@ use serde::{
     Deserialize,
     Serialize,
 };
+use thiserror::Error;
 use tracing::{
     debug,
     error,
     warn,
 };
+use uuid::Uuid;
 
 use super::cli::compact::CompactStrategy;
 use super::consts::{
     DUMMY_TOOL_NAME,
     MAX_CHARS,
     MAX_CONVERSATION_STATE_HISTORY_LEN,
+    SYSTEM_PROMPT_PREFIX_MAX_TOKENS,
 };
 use super::context::ContextManager;
 use super::message::{
@@ -36,6 +39,7 @@ use super::message::{
 use super::token_counter::{
     CharCount,
     CharCounter,
+    TokenCounter,
 };
 use super::tool_manager::ToolManager;
 use super::tools::{
@@ -44,7 +48,10 @@ use super::tools::{
     ToolOrigin,
     ToolSpec,
 };
-use super::util::serde_value_to_document;
+use super::util::{
+    serde_value_to_document,
+    truncate_safe_in_place,
+};
 use crate::api_client::model::{
     ChatMessage,
     ConversationState as FigConversationState,
@@ -59,11 +66,13 @@ use crate::cli::chat::cli::hooks::{
     Hook,
     HookTrigger,
 };
+use crate::database::settings::Setting;
 use crate::mcp_client::Prompt;
 use crate::os::Os;
 
 const CONTEXT_ENTRY_START_HEADER: &str = "--- CONTEXT ENTRY BEGIN ---\n";
 const CONTEXT_ENTRY_END_HEADER: &str = "--- CONTEXT ENTRY END ---\n\n";
+const SYSTEM_PROMPT_PREFIX_TRUNCATED_SUFFIX: &str = "\n...organization guidelines truncated due to length";
 
 /// Tracks state related to an ongoing conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +103,13 @@ pub struct ConversationState {
     /// Model explicitly selected by the user in this conversation state via `/model`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// Model chosen for the session, used whenever the active profile doesn't pin a model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_model: Option<String>,
+    /// Randomly generated for every request sent to the backend, see
+    /// [ConversationState::correlation_id].
+    #[serde(skip)]
+    correlation_id: Option<String>,
 }
 
 impl ConversationState {
@@ -122,6 +138,8 @@ impl ConversationState {
             },
         };
 
+        let profile_model_id = context_manager.as_ref().and_then(|cm| cm.profile_model_id());
+
         Self {
             conversation_id: conversation_id.to_string(),
             next_message: None,
@@ -145,7 +163,29 @@ impl ConversationState {
             tool_manager,
             context_message_length: None,
             latest_summary: None,
-            model: current_model_id,
+            model: profile_model_id
+                .map(str::to_string)
+                .or_else(|| current_model_id.clone()),
+            session_model: current_model_id,
+            correlation_id: None,
+        }
+    }
+
+    /// Selects the model for the rest of the session, e.g. from `/model`.
+    pub fn set_session_model(&mut self, model_id: String) {
+        self.model = Some(model_id.clone());
+        self.session_model = Some(model_id);
+    }
+
+    /// Uses the model pinned by the active profile, or the session's model if it doesn't pin one.
+    /// Should be called after switching profiles.
+    pub fn apply_profile_model(&mut self) {
+        let profile_model_id = self.context_manager.as_ref().and_then(|cm| cm.profile_model_id());
+        if let Some(model_id) = profile_model_id
+            .map(str::to_string)
+            .or_else(|| self.session_model.clone())
+        {
+            self.model = Some(model_id);
         }
     }
 
@@ -275,6 +315,20 @@ impl ConversationState {
         self.history.back().and_then(|(_, msg)| msg.message_id())
     }
 
+    /// Returns the correlation id of the last request created from this conversation, if any.
+    ///
+    /// Telemetry and errors for the current turn carry this id so they can be traced back to the
+    /// request that produced them.
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.correlation_id.as_deref()
+    }
+
+    fn next_correlation_id(&mut self) -> Option<String> {
+        let correlation_id = Uuid::new_v4().to_string();
+        self.correlation_id = Some(correlation_id.clone());
+        Some(correlation_id)
+    }
+
     /// Updates the history so that, when non-empty, the following invariants are in place:
     /// 1. The history length is `<= MAX_CONVERSATION_STATE_HISTORY_LEN`. Oldest messages are
     ///    dropped.
@@ -282,9 +336,52 @@ impl ConversationState {
     ///    are dropped.
     /// 3. If the last message from the assistant contains tool results, and a next user message is
     ///    set without tool results, then the user message will have "cancelled" tool results.
+    ///
+    /// Violations that remain afterwards are logged, see [Self::validate].
     pub fn enforce_conversation_invariants(&mut self) {
         self.valid_history_range =
             enforce_conversation_invariants(&mut self.history, &mut self.next_message, &self.tools);
+        if let Err(err) = validate_history(
+            self.history
+                .range(self.valid_history_range.0..self.valid_history_range.1),
+            self.next_message.as_ref(),
+        ) {
+            warn!(%err, "conversation history is still invalid after enforcing invariants");
+        }
+    }
+
+    /// Drops the oldest user/assistant pairs so at most `max_turns` are kept.
+    ///
+    /// The history always restarts at a user prompt, so a tool use is never separated from its
+    /// results. When the newest `max_turns` pairs are all part of one tool use chain, the chain is
+    /// kept whole from the prompt that started it, even though that exceeds `max_turns`.
+    pub fn evict_oldest_turns(&mut self, max_turns: usize) {
+        let excess = self.history.len().saturating_sub(max_turns);
+        if excess == 0 {
+            return;
+        }
+
+        let is_prompt = |(user, _): &(UserMessage, AssistantMessage)| !user.has_tool_use_results();
+        let start = self
+            .history
+            .iter()
+            .skip(excess)
+            .position(is_prompt)
+            .map(|i| i + excess)
+            .or_else(|| self.history.iter().take(excess).rposition(is_prompt))
+            .unwrap_or(0);
+
+        debug!("removing the first {start} user/assistant response pairs to keep at most {max_turns} turns");
+        self.history.drain(..start);
+        self.valid_history_range = (0, self.history.len());
+    }
+
+    /// Checks the history and next user message for pairings that the backend rejects, without
+    /// modifying the conversation.
+    ///
+    /// Returns every detected violation rather than stopping at the first one.
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        validate_history(self.history.iter(), self.next_message.as_ref())
     }
 
     /// Here we also need to make sure that the tool result corresponds to one of the tools
@@ -336,6 +433,15 @@ impl ConversationState {
         self.enforce_conversation_invariants();
         self.history.drain(self.valid_history_range.1..);
         self.history.drain(..self.valid_history_range.0);
+        if let Some(max_turns) = os
+            .database
+            .settings
+            .get_int(Setting::ChatHistoryMaxTurns)
+            .and_then(|max_turns| usize::try_from(max_turns).ok())
+            .filter(|max_turns| *max_turns > 0)
+        {
+            self.evict_oldest_turns(max_turns);
+        }
 
         let context = self.backend_conversation_state(os, run_perprompt_hooks, stderr).await?;
         if !context.dropped_context_files.is_empty() {
@@ -352,9 +458,11 @@ impl ConversationState {
             .ok();
         }
 
-        Ok(context
+        let mut conversation_state = context
             .into_fig_conversation_state()
-            .expect("unable to construct conversation state"))
+            .expect("unable to construct conversation state");
+        conversation_state.correlation_id = self.next_correlation_id();
+        Ok(conversation_state)
     }
 
     pub async fn update_state(&mut self, force_update: bool) {
@@ -514,6 +622,9 @@ impl ConversationState {
         });
 
         enforce_conversation_invariants(&mut history, &mut summary_message, &tools);
+        if let Err(err) = validate_history(history.iter(), summary_message.as_ref()) {
+            warn!(%err, "summary request history is invalid after enforcing invariants");
+        }
 
         Ok(FigConversationState {
             conversation_id: Some(self.conversation_id.clone()),
@@ -521,6 +632,7 @@ impl ConversationState {
                 .unwrap_or(UserMessage::new_prompt(summary_content)) // should not happen
                 .into_user_input_message(self.model.clone(), &tools),
             history: Some(flatten_history(history.iter())),
+            correlation_id: self.next_correlation_id(),
         })
     }
 
@@ -555,6 +667,13 @@ impl ConversationState {
     ) -> (Option<Vec<(UserMessage, AssistantMessage)>>, Vec<(String, String)>) {
         let mut context_content = String::new();
         let mut dropped_context_files = Vec::new();
+        if let Some(prefix) = system_prompt_prefix(os).await {
+            context_content.push_str(CONTEXT_ENTRY_START_HEADER);
+            context_content.push_str(&prefix);
+            context_content.push('\n');
+            context_content.push_str(CONTEXT_ENTRY_END_HEADER);
+        }
+
         if let Some(summary) = &self.latest_summary {
             context_content.push_str(CONTEXT_ENTRY_START_HEADER);
             context_content.push_str("This summary contains ALL relevant information from our previous conversation including tool uses, results, code analysis, and file operations. YOU MUST reference this information when answering questions and explicitly acknowledge specific details from the summary when they're relevant to the current question.\n\n");
@@ -566,7 +685,8 @@ impl ConversationState {
 
         // Add context files if available
         if let Some(context_manager) = self.context_manager.as_mut() {
-            match context_manager.collect_context_files_with_limit(os).await {
+            let prompt = self.next_message.as_ref().and_then(|message| message.prompt());
+            match context_manager.collect_context_files_with_limit(os, prompt).await {
                 Ok((files_to_use, files_dropped)) => {
                     if !files_dropped.is_empty() {
                         dropped_context_files.extend(files_dropped);
@@ -686,6 +806,7 @@ impl
             conversation_id: Some(self.conversation_id.to_string()),
             user_input_message,
             history: Some(history),
+            correlation_id: None,
         })
     }
 
@@ -741,6 +862,30 @@ where
     })
 }
 
+/// A single way in which the conversation history is malformed. Indexes refer to positions in
+/// the history, with the next user message at index `history.len()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// The oldest user message contains tool results.
+    LeadingToolResults,
+    /// The user message at `index` contains tool results but the assistant message before it has
+    /// no tool uses.
+    OrphanToolResults { index: usize },
+    /// The assistant message at `index` contains tool uses but the following user message has no
+    /// tool results.
+    UnansweredToolUses { index: usize },
+    /// The user message at `index` contains a tool result that doesn't answer any tool use from
+    /// the previous assistant message.
+    UnknownToolResult { index: usize, tool_use_id: String },
+}
+
+/// Error returned by [ConversationState::validate] listing every detected violation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("conversation history is invalid: {violations:?}")]
+pub struct InvariantError {
+    pub violations: Vec<InvariantViolation>,
+}
+
 /// Character count warning levels for conversation size
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenWarningLevel {
@@ -758,6 +903,38 @@ impl From<InputSchema> for ToolInputSchema {
     }
 }
 
+/// Returns the user configured text to place before all other context, read from the
+/// `chat.systemPromptPrefix` setting followed by the contents of the file at
+/// `chat.systemPromptPrefixFile`.
+///
+/// The prefix is additive: it is sent alongside the built-in instructions and context rather than
+/// replacing them. It is truncated to [SYSTEM_PROMPT_PREFIX_MAX_TOKENS] so a large prefix can't
+/// crowd out the rest of the conversation.
+async fn system_prompt_prefix(os: &Os) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(prefix) = os.database.settings.get_string(Setting::ChatSystemPromptPrefix) {
+        parts.push(prefix);
+    }
+    if let Some(path) = os.database.settings.get_string(Setting::ChatSystemPromptPrefixFile) {
+        match os.fs.read_to_string(&path).await {
+            Ok(content) => parts.push(content),
+            Err(err) => warn!(?err, ?path, "failed to read the system prompt prefix file"),
+        }
+    }
+
+    let mut prefix = parts.join("\n").trim().to_string();
+    if prefix.is_empty() {
+        return None;
+    }
+
+    truncate_safe_in_place(
+        &mut prefix,
+        TokenCounter::token_to_chars(SYSTEM_PROMPT_PREFIX_MAX_TOKENS),
+        SYSTEM_PROMPT_PREFIX_TRUNCATED_SUFFIX,
+    );
+    Some(prefix)
+}
+
 /// Formats hook output to be used within context blocks (e.g., in context messages or in new user
 /// prompts).
 ///
@@ -785,6 +962,48 @@ fn format_hook_context(hook_results: &[(Hook, String)], trigger: HookTrigger) ->
     Some(context_content)
 }
 
+/// Collects every pairing in `history` and `next_message` that the backend would reject.
+fn validate_history<'a>(
+    history: impl Iterator<Item = &'a (UserMessage, AssistantMessage)>,
+    next_message: Option<&UserMessage>,
+) -> Result<(), InvariantError> {
+    let mut violations = Vec::new();
+    let mut prev_assistant: Option<&AssistantMessage> = None;
+    let mut index = 0;
+
+    let user_messages = history
+        .map(|(user, assistant)| (user, Some(assistant)))
+        .chain(next_message.map(|user| (user, None)));
+    for (user, assistant) in user_messages {
+        let prev_tool_uses = prev_assistant.and_then(|msg| msg.tool_uses());
+        match (prev_assistant, prev_tool_uses, user.tool_use_results()) {
+            (None, _, Some(_)) => violations.push(InvariantViolation::LeadingToolResults),
+            (Some(_), None, Some(_)) => violations.push(InvariantViolation::OrphanToolResults { index }),
+            (Some(_), Some(_), None) => violations.push(InvariantViolation::UnansweredToolUses { index: index - 1 }),
+            (Some(_), Some(tool_uses), Some(results)) => {
+                for result in results {
+                    if !tool_uses.iter().any(|tool_use| tool_use.id == result.tool_use_id) {
+                        violations.push(InvariantViolation::UnknownToolResult {
+                            index,
+                            tool_use_id: result.tool_use_id.clone(),
+                        });
+                    }
+                }
+            },
+            _ => (),
+        }
+
+        prev_assistant = assistant;
+        index += 1;
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(InvariantError { violations })
+    }
+}
+
 fn enforce_conversation_invariants(
     history: &mut VecDeque<(UserMessage, AssistantMessage)>,
     next_message: &mut Option<UserMessage>,
@@ -924,17 +1143,21 @@ fn enforce_tool_use_history_invariants(
 
 #[cfg(test)]
 mod tests {
+    use semantic_search_client::embedding::EmbeddingType;
+
     use super::super::context::{
         AMAZONQ_FILENAME,
         profile_context_path,
     };
     use super::super::message::AssistantToolUse;
+    use super::super::retrieval::ContextRetriever;
     use super::*;
     use crate::api_client::model::{
         AssistantResponseMessage,
         ToolResultStatus,
     };
     use crate::cli::chat::tool_manager::ToolManager;
+    use crate::database::settings::Setting;
 
     fn assert_conversation_state_invariants(state: FigConversationState, assertion_iteration: usize) {
         if let Some(Some(msg)) = state.history.as_ref().map(|h| h.first()) {
@@ -1068,6 +1291,7 @@ mod tests {
                 .await
                 .unwrap();
             assert_conversation_state_invariants(s, i);
+            conversation.validate().unwrap();
 
             conversation.push_assistant_message(
                 &mut os,
@@ -1124,6 +1348,140 @@ mod tests {
         }
     }
 
+    fn tool_use_message(id: &str) -> AssistantMessage {
+        AssistantMessage::new_tool_use(None, String::new(), vec![AssistantToolUse {
+            id: id.to_string(),
+            name: "tool name".to_string(),
+            args: serde_json::Value::Null,
+            ..Default::default()
+        }])
+    }
+
+    fn tool_results_message(id: &str) -> UserMessage {
+        UserMessage::new_tool_use_results(vec![ToolUseResult {
+            tool_use_id: id.to_string(),
+            content: vec![],
+            status: ToolResultStatus::Success,
+        }])
+    }
+
+    #[tokio::test]
+    async fn test_validate_detects_violations() {
+        let mut os = Os::new().await.unwrap();
+        let mut conversation = ConversationState::new(
+            &mut os,
+            "fake_conv_id",
+            HashMap::new(),
+            None,
+            ToolManager::default(),
+            None,
+        )
+        .await;
+        assert!(conversation.validate().is_ok());
+
+        conversation.history = VecDeque::from([
+            (
+                tool_results_message("1"),
+                AssistantMessage::new_response(None, "a".to_string()),
+            ),
+            (tool_results_message("2"), tool_use_message("3")),
+            (tool_results_message("4"), tool_use_message("5")),
+            (
+                UserMessage::new_prompt("hi".to_string()),
+                AssistantMessage::new_response(None, "b".to_string()),
+            ),
+        ]);
+        conversation.next_message = Some(UserMessage::new_prompt("next".to_string()));
+
+        assert_eq!(conversation.validate().unwrap_err().violations, vec![
+            InvariantViolation::LeadingToolResults,
+            InvariantViolation::OrphanToolResults { index: 1 },
+            InvariantViolation::UnknownToolResult {
+                index: 2,
+                tool_use_id: "4".to_string(),
+            },
+            InvariantViolation::UnansweredToolUses { index: 2 },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_enforce_invariants_fixes_unanswered_tool_uses() {
+        let mut os = Os::new().await.unwrap();
+        let mut conversation = ConversationState::new(
+            &mut os,
+            "fake_conv_id",
+            HashMap::new(),
+            None,
+            ToolManager::default(),
+            None,
+        )
+        .await;
+        conversation.set_next_user_message("start".to_string()).await;
+        conversation.push_assistant_message(&mut os, tool_use_message("1"));
+        conversation.set_next_user_message("never mind".to_string()).await;
+
+        assert_eq!(conversation.validate().unwrap_err().violations, vec![
+            InvariantViolation::UnansweredToolUses { index: 0 }
+        ]);
+
+        conversation.enforce_conversation_invariants();
+        assert!(conversation.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_max_turns_evicts_oldest_turns() {
+        let mut os = Os::new().await.unwrap();
+        os.database.settings.set(Setting::ChatHistoryMaxTurns, 3).await.unwrap();
+        let mut conversation = ConversationState::new(
+            &mut os,
+            "fake_conv_id",
+            HashMap::new(),
+            None,
+            ToolManager::default(),
+            None,
+        )
+        .await;
+
+        let prompt = |text: &str| UserMessage::new_prompt(text.to_string());
+        let response = |text: &str| AssistantMessage::new_response(None, text.to_string());
+        conversation.history = VecDeque::from([
+            (prompt("0"), response("0")),
+            (prompt("1"), tool_use_message("1")),
+            (tool_results_message("1"), tool_use_message("2")),
+            (tool_results_message("2"), response("2")),
+            (prompt("3"), response("3")),
+            (prompt("4"), response("4")),
+        ]);
+        conversation.set_next_user_message("5".to_string()).await;
+
+        conversation
+            .as_sendable_conversation_state(&os, &mut vec![], true)
+            .await
+            .unwrap();
+        // Turn 2 would orphan the tool results, so the history restarts at the next prompt
+        let prompts = conversation
+            .history
+            .iter()
+            .map(|(user, _)| user.prompt().unwrap_or_default().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(prompts, vec!["3", "4"]);
+        assert!(conversation.validate().is_ok());
+
+        // Within a tool use chain the whole chain is kept
+        conversation.history = VecDeque::from([
+            (prompt("0"), response("0")),
+            (prompt("1"), tool_use_message("1")),
+            (tool_results_message("1"), tool_use_message("2")),
+            (tool_results_message("2"), tool_use_message("3")),
+            (tool_results_message("3"), tool_use_message("4")),
+        ]);
+        conversation.evict_oldest_turns(3);
+        assert_eq!(conversation.history.len(), 4);
+        assert_eq!(conversation.history[0].0.prompt(), Some("1"));
+        conversation.next_message = Some(tool_results_message("4"));
+        assert!(conversation.validate().is_ok());
+    }
+
     #[tokio::test]
     async fn test_conversation_state_with_context_files() {
         let mut os = Os::new().await.unwrap();
@@ -1164,6 +1522,155 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_profile_pinned_model() {
+        let mut os = Os::new().await.unwrap();
+        let mut conversation = ConversationState::new(
+            &mut os,
+            "fake_conv_id",
+            HashMap::new(),
+            None,
+            ToolManager::default(),
+            Some("CLAUDE_SONNET_4_20250514_V1_0".to_string()),
+        )
+        .await;
+
+        let pinned_path = profile_context_path(&os, "pinned").unwrap();
+        os.fs.create_dir_all(pinned_path.parent().unwrap()).await.unwrap();
+        os.fs
+            .write(&pinned_path, r#"{ "paths": [], "model": "claude-3.5-sonnet" }"#)
+            .await
+            .unwrap();
+        let unknown_path = profile_context_path(&os, "unknown").unwrap();
+        os.fs.create_dir_all(unknown_path.parent().unwrap()).await.unwrap();
+        os.fs
+            .write(&unknown_path, r#"{ "paths": [], "model": "not-a-model" }"#)
+            .await
+            .unwrap();
+
+        async fn request_model_id(conversation: &ConversationState, os: &Os) -> Option<String> {
+            let mut conversation = conversation.clone();
+            conversation.set_next_user_message("hello".to_string()).await;
+            let state = conversation
+                .as_sendable_conversation_state(os, &mut vec![], false)
+                .await
+                .unwrap();
+            state.user_input_message.model_id
+        }
+
+        assert_eq!(
+            request_model_id(&conversation, &os).await.as_deref(),
+            Some("CLAUDE_SONNET_4_20250514_V1_0")
+        );
+
+        let cm = conversation.context_manager.as_mut().unwrap();
+        cm.switch_profile(&os, "pinned").await.unwrap();
+        conversation.apply_profile_model();
+        assert_eq!(
+            request_model_id(&conversation, &os).await.as_deref(),
+            Some("CLAUDE_3_5_SONNET_20241022_V2_0")
+        );
+
+        // Unknown models are rejected without leaving the current profile
+        let cm = conversation.context_manager.as_mut().unwrap();
+        let err = cm.switch_profile(&os, "unknown").await.unwrap_err();
+        assert!(err.to_string().contains("not-a-model"), "{err}");
+        assert_eq!(cm.current_profile, "pinned");
+
+        // Profiles without a model fall back to the session's model
+        let cm = conversation.context_manager.as_mut().unwrap();
+        cm.switch_profile(&os, "default").await.unwrap();
+        conversation.apply_profile_model();
+        assert_eq!(
+            request_model_id(&conversation, &os).await.as_deref(),
+            Some("CLAUDE_SONNET_4_20250514_V1_0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conversation_state_with_context_retrieval() {
+        let mut os = Os::new().await.unwrap();
+        os.database
+            .settings
+            .set(Setting::ChatContextRetrievalEnabled, true)
+            .await
+            .unwrap();
+        os.database
+            .settings
+            .set(Setting::ChatContextRetrievalTopK, 1)
+            .await
+            .unwrap();
+        let content = [
+            "Kubernetes manifests live in the deploy directory and are rendered with helm. ",
+            "Database migrations are applied with the migrate command before every release. ",
+        ]
+        .map(|sentence| sentence.repeat(60))
+        .join("\n");
+        os.fs.write(AMAZONQ_FILENAME, content).await.unwrap();
+
+        let mut tool_manager = ToolManager::default();
+        let tools = tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap();
+        let mut conversation = ConversationState::new(&mut os, "fake_conv_id", tools, None, tool_manager, None).await;
+        conversation.context_manager.as_mut().unwrap().retriever = ContextRetriever::new(EmbeddingType::BM25);
+        conversation
+            .set_next_user_message("how are database migrations applied?".to_string())
+            .await;
+
+        let s = conversation
+            .as_sendable_conversation_state(&os, &mut vec![], true)
+            .await
+            .unwrap();
+        match &s.history.as_ref().unwrap()[0] {
+            ChatMessage::UserInputMessage(user) => {
+                assert!(user.content.contains("Database migrations are applied"));
+                assert!(!user.content.contains("Kubernetes"));
+            },
+            ChatMessage::AssistantResponseMessage(_) => panic!("Expected the first message to be from the user"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conversation_state_with_system_prompt_prefix() {
+        let mut os = Os::new().await.unwrap();
+        os.database
+            .settings
+            .set(Setting::ChatSystemPromptPrefix, "Follow the org guidelines.")
+            .await
+            .unwrap();
+
+        let mut conversation = ConversationState::new(
+            &mut os,
+            "fake_conv_id",
+            HashMap::new(),
+            None,
+            ToolManager::default(),
+            None,
+        )
+        .await;
+        conversation.set_next_user_message("hello".to_string()).await;
+        let s = conversation
+            .as_sendable_conversation_state(&os, &mut vec![], true)
+            .await
+            .unwrap();
+        match &s.history.as_ref().unwrap()[0] {
+            ChatMessage::UserInputMessage(user) => {
+                assert!(user.content.contains("Follow the org guidelines."));
+            },
+            ChatMessage::AssistantResponseMessage(_) => panic!("Expected the first message to be from the user"),
+        }
+
+        // Past the budget, the prefix is truncated.
+        let max_len = TokenCounter::token_to_chars(SYSTEM_PROMPT_PREFIX_MAX_TOKENS);
+        os.database
+            .settings
+            .set(Setting::ChatSystemPromptPrefix, "a".repeat(max_len * 2))
+            .await
+            .unwrap();
+        let prefix = system_prompt_prefix(&os).await.unwrap();
+        assert_eq!(prefix.len(), max_len);
+        assert!(prefix.ends_with(SYSTEM_PROMPT_PREFIX_TRUNCATED_SUFFIX));
+    }
+
     #[tokio::test]
     async fn test_conversation_state_additional_context() {
         let mut os = Os::new().await.unwrap();
@@ -1,7 +1,10 @@
 use eyre::Result;
 use rustyline::error::ReadlineError;
 
-use super::prompt::rl;
+use super::prompt::{
+    CommandValues,
+    rl,
+};
 #[cfg(unix)]
 use super::skim_integration::SkimCommandSelector;
 use crate::os::Os;
@@ -66,6 +69,15 @@ impl InputSource {
         }
     }
 
+    /// Replaces the session values completed as command arguments.
+    pub fn set_command_values(&mut self, values: CommandValues) {
+        if let inner::Inner::Readline(rl) = &mut self.0 {
+            if let Some(helper) = rl.helper_mut() {
+                helper.set_command_values(values);
+            }
+        }
+    }
+
     #[allow(dead_code)]
     pub fn new_mock(lines: Vec<String>) -> Self {
         Self(inner::Inner::Mock { index: 0, lines })
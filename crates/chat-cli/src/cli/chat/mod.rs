@@ -5,15 +5,19 @@ mod conversation;
 mod error_formatter;
 mod input_source;
 mod message;
+mod pager;
 mod parse;
 use std::path::MAIN_SEPARATOR;
 mod parser;
 mod prompt;
 mod prompt_parser;
+mod retrieval;
 mod server_messenger;
 #[cfg(unix)]
 mod skim_integration;
+mod tips;
 mod token_counter;
+mod tool_audit;
 pub mod tool_manager;
 pub mod tools;
 pub mod util;
@@ -21,7 +25,6 @@ pub mod util;
 use std::borrow::Cow;
 use std::collections::{
     HashMap,
-    HashSet,
     VecDeque,
 };
 use std::io::{
@@ -29,8 +32,15 @@ use std::io::{
     Read,
     Write,
 };
+use std::path::{
+    Path,
+    PathBuf,
+};
 use std::process::ExitCode;
-use std::time::Duration;
+use std::time::{
+    Duration,
+    Instant,
+};
 
 use amzn_codewhisperer_client::types::SubscriptionStatus;
 use clap::{
@@ -43,6 +53,10 @@ use cli::model::select_model;
 use context::ContextManager;
 pub use conversation::ConversationState;
 use conversation::TokenWarningLevel;
+use convert_case::{
+    Case,
+    Casing,
+};
 use crossterm::style::{
     Attribute,
     Color,
@@ -68,14 +82,20 @@ use message::{
     ToolUseResult,
     ToolUseResultBlock,
 };
+use pager::{
+    Pager,
+    exceeds_screen,
+    render_response,
+};
 use parse::{
+    MarkdownStream,
     ParseState,
-    interpret_markdown,
 };
 use parser::{
     RecvErrorKind,
     ResponseParser,
 };
+use prompt::CommandValues;
 use regex::Regex;
 use spinners::{
     Spinner,
@@ -83,8 +103,12 @@ use spinners::{
 };
 use thiserror::Error;
 use time::OffsetDateTime;
-use token_counter::TokenCounter;
+use token_counter::{
+    RunningTokenCount,
+    TokenCounter,
+};
 use tokio::signal::ctrl_c;
+use tool_audit::ToolAudit;
 use tool_manager::{
     McpServerConfig,
     ToolManager,
@@ -106,13 +130,14 @@ use tracing::{
     warn,
 };
 use util::images::RichImageBlock;
+use util::panic_hook::SpinnerGuard;
 use util::ui::draw_box;
 use util::{
+    NotifyPolicy,
     animate_output,
+    panic_hook,
     play_notification_bell,
 };
-use winnow::Partial;
-use winnow::stream::Offset;
 
 use crate::api_client::ApiClientError;
 use crate::api_client::model::{
@@ -131,6 +156,7 @@ use crate::cli::chat::cli::prompts::{
     GetPromptError,
     PromptsSubcommand,
 };
+use crate::cli::chat::consts::DUMMY_TOOL_NAME;
 use crate::database::settings::Setting;
 use crate::mcp_client::Prompt;
 use crate::os::Os;
@@ -140,6 +166,10 @@ use crate::telemetry::{
     TelemetryResult,
     get_error_reason,
 };
+use crate::util::color::{
+    self,
+    ColorWriter,
+};
 
 const LIMIT_REACHED_TEXT: &str = color_print::cstr! { "You've used all your free requests for this month. You have two options:
 1. Upgrade to a paid subscription for increased limits. See our Pricing page for what's included> <blue!>https://aws.amazon.com/q/developer/pricing/</blue!>
@@ -174,13 +204,29 @@ pub struct ChatArgs {
     /// Allows the model to use any tool to run commands without asking for confirmation.
     #[arg(short = 'a', long)]
     pub trust_all_tools: bool,
-    /// Trust only this set of tools. Example: trust some tools:
-    /// '--trust-tools=fs_read,fs_write', trust no tools: '--trust-tools='
+    /// Trust only this set of tools, names may be glob patterns. Example: trust some tools:
+    /// '--trust-tools=fs_read,fs_write', trust every tool from an MCP server:
+    /// '--trust-tools=server___*', trust no tools: '--trust-tools='
     #[arg(long, value_delimiter = ',', value_name = "TOOL_NAMES")]
     pub trust_tools: Option<Vec<String>>,
+    /// Trust the tools matching the names or glob patterns in this file, one per line. Lines
+    /// starting with `#` are ignored.
+    #[arg(long, value_name = "PATH")]
+    pub trust_tools_from: Option<PathBuf>,
     /// Whether the command should run without expecting user input
     #[arg(long, alias = "non-interactive")]
     pub no_interactive: bool,
+    /// Suppress the spinner, welcome banner, tips, and shortcuts. Enabled automatically when
+    /// output is not a terminal.
+    #[arg(long)]
+    pub quiet: bool,
+    /// Run each prompt from this file in order without expecting user input. Prompts are
+    /// separated by lines containing only `---`, or one per line otherwise. Use `-` for stdin.
+    #[arg(long, value_name = "PATH", conflicts_with = "input")]
+    pub input_file: Option<PathBuf>,
+    /// Keep running the remaining prompts from --input-file after one fails
+    #[arg(long, requires = "input_file")]
+    pub continue_on_error: bool,
     /// The first question to ask
     pub input: Option<String>,
 }
@@ -189,7 +235,13 @@ impl ChatArgs {
     pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
         let mut input = self.input;
 
-        if self.no_interactive && input.is_none() {
+        let batch_prompts = match &self.input_file {
+            Some(path) => Some(read_batch_prompts(os, path).await?),
+            None => None,
+        };
+        let interactive = !self.no_interactive && batch_prompts.is_none();
+
+        if self.no_interactive && input.is_none() && batch_prompts.is_none() {
             if !std::io::stdin().is_terminal() {
                 let mut buffer = String::new();
                 match std::io::stdin().read_to_string(&mut buffer) {
@@ -270,6 +322,8 @@ impl ChatArgs {
             None
         };
 
+        let quiet = self.quiet || !std::io::stdout().is_terminal() || !std::io::stderr().is_terminal();
+
         let conversation_id = uuid::Uuid::new_v4().to_string();
         info!(?conversation_id, "Generated new conversation id");
         let (prompt_request_sender, prompt_request_receiver) = std::sync::mpsc::channel::<Option<String>>();
@@ -279,36 +333,30 @@ impl ChatArgs {
             .prompt_list_sender(prompt_response_sender)
             .prompt_list_receiver(prompt_request_receiver)
             .conversation_id(&conversation_id)
-            .build(os, Box::new(std::io::stderr()), !self.no_interactive)
+            .build(os, Box::new(std::io::stderr()), interactive)
             .await?;
         let tool_config = tool_manager.load_tools(os, &mut stderr).await?;
         let mut tool_permissions = ToolPermissions::new(tool_config.len());
 
+        let mut trust_tools = self.trust_tools;
+        if let Some(path) = &self.trust_tools_from {
+            trust_tools
+                .get_or_insert_default()
+                .extend(read_trust_patterns(os, path).await?);
+        }
+
         if self.trust_all_tools {
             tool_permissions.trust_all = true;
             for tool in tool_config.values() {
                 tool_permissions.trust_tool(&tool.name);
             }
-        } else if let Some(trusted) = self.trust_tools.map(|vec| vec.into_iter().collect::<HashSet<_>>()) {
+        } else if let Some(trusted) = trust_tools {
             // --trust-all-tools takes precedence over --trust-tools=...
-            for tool_name in &trusted {
-                if !tool_name.is_empty() {
-                    // Store the original trust settings for later use with MCP tools
-                    tool_permissions.add_pending_trust_tool(tool_name.clone());
-                }
-            }
-
-            // Apply to currently known tools
-            for tool in tool_config.values() {
-                if trusted.contains(&tool.name) {
-                    tool_permissions.trust_tool(&tool.name);
-                } else {
-                    tool_permissions.untrust_tool(&tool.name);
-                }
-            }
+            tool_permissions.apply_trust_patterns(&trusted, tool_config.values().map(|tool| tool.name.as_str()));
         }
 
-        ChatSession::new(
+        panic_hook::install_panic_hook();
+        let mut session = ChatSession::new(
             os,
             stdout,
             stderr,
@@ -322,15 +370,75 @@ impl ChatArgs {
             model_id,
             tool_config,
             tool_permissions,
-            !self.no_interactive,
+            interactive,
+            quiet,
         )
-        .await?
-        .spawn(os)
-        .await
+        .await?;
+
+        match batch_prompts {
+            Some(prompts) => session.spawn_batch(os, prompts, self.continue_on_error).await,
+            None => session.spawn(os).await,
+        }
         .map(|_| ExitCode::SUCCESS)
     }
 }
 
+/// Reads the prompts for `--input-file`, where `-` reads from stdin.
+async fn read_batch_prompts(os: &Os, path: &Path) -> Result<Vec<String>> {
+    let content = if path == Path::new("-") {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        os.fs
+            .read_to_string(path)
+            .await
+            .map_err(|err| eyre!("Failed to read {}: {err}", path.display()))?
+    };
+
+    let prompts = split_batch_prompts(&content);
+    if prompts.is_empty() {
+        bail!("No prompts found in {}", path.display());
+    }
+
+    Ok(prompts)
+}
+
+/// Reads the tool names and patterns for `--trust-tools-from`, skipping blank lines and comments.
+async fn read_trust_patterns(os: &Os, path: &Path) -> Result<Vec<String>> {
+    let content = os
+        .fs
+        .read_to_string(path)
+        .await
+        .map_err(|err| eyre!("Failed to read {}: {err}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Splits batch input on [BATCH_SEPARATOR] lines if there are any, otherwise on every line.
+fn split_batch_prompts(content: &str) -> Vec<String> {
+    let lines = content.lines().collect::<Vec<_>>();
+    let prompts = if lines.iter().any(|line| line.trim() == BATCH_SEPARATOR) {
+        lines
+            .split(|line| line.trim() == BATCH_SEPARATOR)
+            .map(|chunk| chunk.join("\n"))
+            .collect::<Vec<_>>()
+    } else {
+        lines.into_iter().map(str::to_owned).collect()
+    };
+
+    prompts
+        .into_iter()
+        .map(|prompt| prompt.trim().to_owned())
+        .filter(|prompt| !prompt.is_empty())
+        .collect()
+}
+
 const WELCOME_TEXT: &str = color_print::cstr! {"<cyan!>
     ⢠⣶⣶⣦⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⠀⢀⣤⣶⣿⣿⣿⣶⣦⡀⠀
  ⠀⠀⠀⣾⡿⢻⣿⡆⠀⠀⠀⢀⣄⡄⢀⣠⣤⣤⡀⢀⣠⣤⣤⡀⠀⠀⢀⣠⣤⣤⣤⣄⠀⠀⢀⣤⣤⣤⣤⣤⣤⡀⠀⠀⣀⣤⣤⣤⣀⠀⠀⠀⢠⣤⡀⣀⣤⣤⣄⡀⠀⠀⠀⠀⠀⠀⢠⣿⣿⠋⠀⠀⠀⠙⣿⣿⡆
@@ -342,41 +450,11 @@ const WELCOME_TEXT: &str = color_print::cstr! {"<cyan!>
 
 const SMALL_SCREEN_WELCOME_TEXT: &str = color_print::cstr! {"<em>Welcome to <cyan!>Amazon Q</cyan!>!</em>"};
 const RESUME_TEXT: &str = color_print::cstr! {"<em>Picking up where we left off...</em>"};
+const MINIMAL_WELCOME_TEXT: &str =
+    color_print::cstr! {"<em><cyan!>Amazon Q</cyan!></em>  <black!><green!>/help</green!> all commands</black!>"};
 
-// Only show the model-related tip for now to make users aware of this feature.
-const ROTATING_TIPS: [&str; 16] = [
-    color_print::cstr! {"You can resume the last conversation from your current directory by launching with
-    <green!>q chat --resume</green!>"},
-    color_print::cstr! {"Get notified whenever Q CLI finishes responding.
-    Just run <green!>q settings chat.enableNotifications true</green!>"},
-    color_print::cstr! {"You can use
-    <green!>/editor</green!> to edit your prompt with a vim-like experience"},
-    color_print::cstr! {"<green!>/usage</green!> shows you a visual breakdown of your current context window usage"},
-    color_print::cstr! {"Get notified whenever Q CLI finishes responding. Just run <green!>q settings
-    chat.enableNotifications true</green!>"},
-    color_print::cstr! {"You can execute bash commands by typing
-    <green!>!</green!> followed by the command"},
-    color_print::cstr! {"Q can use tools without asking for
-    confirmation every time. Give <green!>/tools trust</green!> a try"},
-    color_print::cstr! {"You can
-    programmatically inject context to your prompts by using hooks. Check out <green!>/context hooks
-    help</green!>"},
-    color_print::cstr! {"You can use <green!>/compact</green!> to replace the conversation
-    history with its summary to free up the context space"},
-    color_print::cstr! {"If you want to file an issue
-    to the Q CLI team, just tell me, or run <green!>q issue</green!>"},
-    color_print::cstr! {"You can enable
-    custom tools with <green!>MCP servers</green!>. Learn more with /help"},
-    color_print::cstr! {"You can
-    specify wait time (in ms) for mcp server loading with <green!>q settings mcp.initTimeout {timeout in
-    int}</green!>. Servers that takes longer than the specified time will continue to load in the background. Use
-    /tools to see pending servers."},
-    color_print::cstr! {"You can see the server load status as well as any
-    warnings or errors associated with <green!>/mcp</green!>"},
-    color_print::cstr! {"Use <green!>/model</green!> to select the model to use for this conversation"},
-    color_print::cstr! {"Set a default model by running <green!>q settings chat.defaultModel MODEL</green!>. Run <green!>/model</green!> to learn more."},
-    color_print::cstr! {"Run <green!>/prompts</green!> to learn how to build & run repeatable workflows"},
-];
+/// Launches that show the full greeting when `chat.greeting.mode` is `firstRunOnly`
+const DEFAULT_GREETING_FULL_LAUNCHES: i64 = 5;
 
 const GREETING_BREAK_POINT: usize = 80;
 
@@ -391,6 +469,9 @@ const TRUST_ALL_TEXT: &str = color_print::cstr! {"<green!>All tools are now trus
 \nAgents can sometimes do unexpected things so understand the risks.</green!>
 \nLearn more at https://docs.aws.amazon.com/amazonq/latest/qdeveloper-ug/command-line-chat-security.html#command-line-chat-trustall-safety"};
 
+/// Separates prompts in an `--input-file`, and their responses in the output.
+const BATCH_SEPARATOR: &str = "---";
+
 const TOOL_BULLET: &str = " ● ";
 const CONTINUATION_LINE: &str = " ⋮ ";
 const PURPOSE_ARROW: &str = " ↳ ";
@@ -408,12 +489,19 @@ enum ToolUseStatus {
 
 #[derive(Debug, Error)]
 pub enum ChatError {
-    #[error("{0}")]
-    Client(Box<crate::api_client::ApiClientError>),
+    #[error("{source}{}", display_correlation_id(.correlation_id))]
+    Client {
+        source: Box<crate::api_client::ApiClientError>,
+        /// Set once the error is handled, see [ChatError::with_correlation_id]
+        correlation_id: Option<String>,
+    },
     #[error("{0}")]
     Auth(#[from] AuthError),
-    #[error("{0}")]
-    ResponseStream(Box<parser::RecvError>),
+    #[error("{source}{}", display_correlation_id(.correlation_id))]
+    ResponseStream {
+        source: Box<parser::RecvError>,
+        correlation_id: Option<String>,
+    },
     #[error("{0}")]
     Std(#[from] std::io::Error),
     #[error("{0}")]
@@ -435,9 +523,9 @@ pub enum ChatError {
 impl ChatError {
     fn status_code(&self) -> Option<u16> {
         match self {
-            ChatError::Client(e) => e.status_code(),
+            ChatError::Client { source, .. } => source.status_code(),
             ChatError::Auth(_) => None,
-            ChatError::ResponseStream(_) => None,
+            ChatError::ResponseStream { .. } => None,
             ChatError::Std(_) => None,
             ChatError::Readline(_) => None,
             ChatError::Custom(_) => None,
@@ -447,13 +535,29 @@ impl ChatError {
             ChatError::CompactHistoryFailure => None,
         }
     }
+
+    /// Attaches the correlation id of the request, if the error came from sending that request or
+    /// reading its response.
+    fn with_correlation_id(mut self, id: Option<&str>) -> Self {
+        if let ChatError::Client { correlation_id, .. } | ChatError::ResponseStream { correlation_id, .. } = &mut self {
+            *correlation_id = id.map(str::to_owned);
+        }
+        self
+    }
+}
+
+fn display_correlation_id(correlation_id: &Option<String>) -> String {
+    match correlation_id {
+        Some(correlation_id) => format!(" (correlation_id: {correlation_id})"),
+        None => String::new(),
+    }
 }
 
 impl ReasonCode for ChatError {
     fn reason_code(&self) -> String {
         match self {
-            ChatError::Client(e) => e.reason_code(),
-            ChatError::ResponseStream(e) => e.reason_code(),
+            ChatError::Client { source, .. } => source.reason_code(),
+            ChatError::ResponseStream { source, .. } => source.reason_code(),
             ChatError::Std(_) => "StdIoError".to_string(),
             ChatError::Readline(_) => "ReadlineError".to_string(),
             ChatError::Custom(_) => "GenericError".to_string(),
@@ -468,28 +572,36 @@ impl ReasonCode for ChatError {
 
 impl From<ApiClientError> for ChatError {
     fn from(value: ApiClientError) -> Self {
-        Self::Client(Box::new(value))
+        Self::Client {
+            source: Box::new(value),
+            correlation_id: None,
+        }
     }
 }
 
 impl From<parser::RecvError> for ChatError {
     fn from(value: parser::RecvError) -> Self {
-        Self::ResponseStream(Box::new(value))
+        Self::ResponseStream {
+            source: Box::new(value),
+            correlation_id: None,
+        }
     }
 }
 
 pub struct ChatSession {
     /// For output read by humans and machine
-    pub stdout: std::io::Stdout,
+    pub stdout: ColorWriter<std::io::Stdout>,
     /// For display output, only read by humans
-    pub stderr: std::io::Stderr,
+    pub stderr: ColorWriter<std::io::Stderr>,
     initial_input: Option<String>,
     /// Whether we're starting a new conversation or continuing an old one.
     existing_conversation: bool,
     input_source: InputSource,
     /// Width of the terminal, required for [ParseState].
     terminal_width_provider: fn() -> Option<usize>,
-    spinner: Option<Spinner>,
+    spinner: Option<SpinnerGuard>,
+    /// Style of the spinner shown while waiting on a response.
+    spinner_style: Spinners,
     /// [ConversationState].
     conversation: ConversationState,
     tool_uses: Vec<QueuedTool>,
@@ -505,6 +617,12 @@ pub struct ChatSession {
     /// Pending prompts to be sent
     pending_prompts: VecDeque<Prompt>,
     interactive: bool,
+    /// Suppresses decorative output (spinner, greeting, tips) while still streaming responses.
+    quiet: bool,
+    /// How long the last response took to stream, for [NotifyPolicy].
+    response_time: Duration,
+    /// Tokens in the conversation plus those received so far in the response being streamed.
+    response_tokens: RunningTokenCount,
     inner: Option<ChatState>,
 }
 
@@ -525,6 +643,7 @@ impl ChatSession {
         tool_config: HashMap<String, ToolSpec>,
         tool_permissions: ToolPermissions,
         interactive: bool,
+        quiet: bool,
     ) -> Result<Self> {
         let valid_model_id = match model_id {
             Some(id) => id,
@@ -584,14 +703,25 @@ impl ChatSession {
             },
         };
 
+        let spinner_style = os
+            .database
+            .settings
+            .get_string(Setting::ChatSpinnerStyle)
+            .and_then(|style| style.to_case(Case::Pascal).parse::<Spinners>().ok())
+            .unwrap_or(Spinners::Dots);
+
+        let stdout_color = color::color_enabled(&stdout);
+        let stderr_color = color::color_enabled(&stderr);
+
         Ok(Self {
-            stdout,
-            stderr,
+            stdout: ColorWriter::new(stdout, stdout_color),
+            stderr: ColorWriter::new(stderr, stderr_color),
             initial_input: input,
             existing_conversation,
             input_source,
             terminal_width_provider,
             spinner: None,
+            spinner_style,
             tool_permissions,
             conversation,
             tool_uses: vec![],
@@ -601,6 +731,9 @@ impl ChatSession {
             failed_request_ids: Vec::new(),
             pending_prompts: VecDeque::new(),
             interactive,
+            quiet,
+            response_time: Duration::ZERO,
+            response_tokens: RunningTokenCount::default(),
             inner: Some(ChatState::default()),
         })
     }
@@ -679,6 +812,7 @@ impl ChatSession {
         };
 
         // We encountered an error. Handle it.
+        let err = err.with_correlation_id(self.conversation.correlation_id());
         error!(?err, "An error occurred processing the current state");
         let (reason, reason_desc) = get_error_reason(&err);
         self.send_error_telemetry(os, reason, Some(reason_desc), err.status_code())
@@ -740,7 +874,10 @@ impl ChatSession {
                 )?;
                 ("Unable to compact the conversation history", eyre!(err), true)
             },
-            ChatError::Client(err) => match *err {
+            ChatError::Client {
+                source: err,
+                correlation_id,
+            } => match *err {
                 // Errors from attempting to send too large of a conversation history. In
                 // this case, attempt to automatically compact the history for the user.
                 ApiClientError::ContextWindowOverflow { .. } => {
@@ -781,7 +918,14 @@ impl ChatSession {
                         return Ok(());
                     }
                 },
-                ApiClientError::QuotaBreach { message, .. } => (message, Report::from(err), true),
+                ApiClientError::QuotaBreach { message, .. } => (
+                    message,
+                    Report::from(ChatError::Client {
+                        source: err,
+                        correlation_id,
+                    }),
+                    true,
+                ),
                 ApiClientError::ModelOverloadedError { request_id, .. } => {
                     if self.interactive {
                         execute!(
@@ -882,11 +1026,21 @@ impl ChatSession {
 
                     return Ok(());
                 },
-                _ => (
-                    "Amazon Q is having trouble responding right now",
-                    Report::from(err),
-                    true,
-                ),
+                _ => {
+                    let advice = err.advice();
+                    let report = Report::from(ChatError::Client {
+                        source: err,
+                        correlation_id,
+                    });
+                    (
+                        "Amazon Q is having trouble responding right now",
+                        match advice {
+                            Some(advice) => report.wrap_err(advice),
+                            None => report,
+                        },
+                        true,
+                    )
+                },
             },
             _ => (
                 "Amazon Q is having trouble responding right now",
@@ -993,57 +1147,7 @@ impl Default for ChatState {
 impl ChatSession {
     async fn spawn(&mut self, os: &mut Os) -> Result<()> {
         let is_small_screen = self.terminal_width() < GREETING_BREAK_POINT;
-        if os
-            .database
-            .settings
-            .get_bool(Setting::ChatGreetingEnabled)
-            .unwrap_or(true)
-        {
-            let welcome_text = match self.existing_conversation {
-                true => RESUME_TEXT,
-                false => match is_small_screen {
-                    true => SMALL_SCREEN_WELCOME_TEXT,
-                    false => WELCOME_TEXT,
-                },
-            };
-
-            execute!(self.stderr, style::Print(welcome_text), style::Print("\n\n"),)?;
-
-            let tip = ROTATING_TIPS[usize::try_from(rand::random::<u32>()).unwrap_or(0) % ROTATING_TIPS.len()];
-            if is_small_screen {
-                // If the screen is small, print the tip in a single line
-                execute!(
-                    self.stderr,
-                    style::Print("💡 ".to_string()),
-                    style::Print(tip),
-                    style::Print("\n")
-                )?;
-            } else {
-                draw_box(
-                    &mut self.stderr,
-                    "Did you know?",
-                    tip,
-                    GREETING_BREAK_POINT,
-                    Color::DarkGrey,
-                )?;
-            }
-
-            execute!(
-                self.stderr,
-                style::Print("\n"),
-                style::Print(match is_small_screen {
-                    true => SMALL_SCREEN_POPULAR_SHORTCUTS,
-                    false => POPULAR_SHORTCUTS,
-                }),
-                style::Print("\n"),
-                style::Print(
-                    "━"
-                        .repeat(if is_small_screen { 0 } else { GREETING_BREAK_POINT })
-                        .dark_grey()
-                )
-            )?;
-            execute!(self.stderr, style::Print("\n"), style::SetForegroundColor(Color::Reset))?;
-        }
+        self.print_greeting(os, is_small_screen).await?;
 
         if self.all_tools_trusted() {
             queue!(
@@ -1056,15 +1160,17 @@ impl ChatSession {
         }
         self.stderr.flush()?;
 
-        if let Some(ref id) = self.conversation.model {
-            if let Some(model_option) = MODEL_OPTIONS.iter().find(|option| option.model_id == *id) {
-                execute!(
-                    self.stderr,
-                    style::SetForegroundColor(Color::Cyan),
-                    style::Print(format!("🤖 You are chatting with {}\n", model_option.name)),
-                    style::SetForegroundColor(Color::Reset),
-                    style::Print("\n")
-                )?;
+        if !self.quiet {
+            if let Some(ref id) = self.conversation.model {
+                if let Some(model_option) = MODEL_OPTIONS.iter().find(|option| option.model_id == *id) {
+                    execute!(
+                        self.stderr,
+                        style::SetForegroundColor(Color::Cyan),
+                        style::Print(format!("🤖 You are chatting with {}\n", model_option.name)),
+                        style::SetForegroundColor(Color::Reset),
+                        style::Print("\n")
+                    )?;
+                }
             }
         }
 
@@ -1079,6 +1185,134 @@ impl ChatSession {
         Ok(())
     }
 
+    /// Prints a notice for each context file edited on disk since the last prompt.
+    async fn print_context_changes(&mut self, os: &Os) -> Result<(), ChatError> {
+        let Some(context_manager) = self.conversation.context_manager.as_mut() else {
+            return Ok(());
+        };
+
+        let changed = match context_manager.poll_changes(os).await {
+            Ok(changed) => changed,
+            Err(err) => {
+                warn!("Failed to check the context files for changes: {}", err);
+                return Ok(());
+            },
+        };
+
+        for file in changed {
+            queue!(
+                self.stderr,
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(format!("Context file changed, using the new version: {file}\n")),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+        self.stderr.flush()?;
+
+        Ok(())
+    }
+
+    /// Prints the welcome banner, a tip, and the popular shortcuts, or a one line header once the
+    /// greeting has collapsed.
+    async fn print_greeting(&mut self, os: &mut Os, is_small_screen: bool) -> Result<()> {
+        if !self.should_show_greeting(os) {
+            return Ok(());
+        }
+
+        if self.should_collapse_greeting(os) {
+            let header = match self.existing_conversation {
+                true => RESUME_TEXT,
+                false => MINIMAL_WELCOME_TEXT,
+            };
+            execute!(self.stderr, style::Print(header), style::Print("\n\n"))?;
+            return Ok(());
+        }
+
+        let welcome_text = match self.existing_conversation {
+            true => RESUME_TEXT,
+            false => match is_small_screen {
+                true => SMALL_SCREEN_WELCOME_TEXT,
+                false => WELCOME_TEXT,
+            },
+        };
+
+        execute!(self.stderr, style::Print(welcome_text), style::Print("\n\n"),)?;
+
+        let tips = tips::load_tips(os).await;
+        let tip = tips::next_tip(&mut os.database, &tips);
+        if is_small_screen {
+            // If the screen is small, print the tip in a single line
+            execute!(
+                self.stderr,
+                style::Print("💡 ".to_string()),
+                style::Print(tip),
+                style::Print("\n")
+            )?;
+        } else {
+            draw_box(
+                &mut self.stderr,
+                "Did you know?",
+                tip,
+                GREETING_BREAK_POINT,
+                Color::DarkGrey,
+            )?;
+        }
+
+        execute!(
+            self.stderr,
+            style::Print("\n"),
+            style::Print(match is_small_screen {
+                true => SMALL_SCREEN_POPULAR_SHORTCUTS,
+                false => POPULAR_SHORTCUTS,
+            }),
+            style::Print("\n"),
+            style::Print(
+                "━"
+                    .repeat(if is_small_screen { 0 } else { GREETING_BREAK_POINT })
+                    .dark_grey()
+            )
+        )?;
+        execute!(self.stderr, style::Print("\n"), style::SetForegroundColor(Color::Reset))?;
+
+        Ok(())
+    }
+
+    /// Runs each prompt to completion in order without user input, printing [BATCH_SEPARATOR]
+    /// between the responses.
+    ///
+    /// Stops at the first prompt that fails with an error unless `continue_on_error` is set, in
+    /// which case the error is reported and the next prompt is run.
+    async fn spawn_batch(&mut self, os: &mut Os, prompts: Vec<String>, continue_on_error: bool) -> Result<()> {
+        for (i, prompt) in prompts.into_iter().enumerate() {
+            if i > 0 {
+                execute!(self.stdout, style::Print(format!("\n{BATCH_SEPARATOR}\n\n")))?;
+            }
+
+            self.inner = Some(ChatState::HandleInput { input: prompt });
+            while !matches!(self.inner, Some(ChatState::Exit)) {
+                if let Err(err) = self.next(os).await {
+                    if !continue_on_error {
+                        return Err(err.into());
+                    }
+
+                    error!(?err, "Batch prompt failed, continuing with the next prompt");
+                    execute!(
+                        self.stderr,
+                        style::SetForegroundColor(Color::Red),
+                        style::Print(format!("Prompt {} failed: {err}\n", i + 1)),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                    self.conversation.reset_next_user_message();
+                    self.tool_uses.clear();
+                    self.pending_tool_index = None;
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Compacts the conversation history using the strategy specified by [CompactStrategy],
     /// replacing the history with a summary generated by the model.
     ///
@@ -1131,9 +1365,7 @@ impl ChatSession {
 
         execute!(self.stderr, cursor::Hide, style::Print("\n"))?;
 
-        if self.interactive {
-            self.spinner = Some(Spinner::new(Spinners::Dots, "Creating summary...".to_string()));
-        }
+        self.start_spinner("Creating summary...");
 
         let response = os.client.send_message(summary_state).await;
 
@@ -1340,6 +1572,8 @@ impl ChatSession {
             if let Err(err) = self.display_char_warnings(os).await {
                 warn!("Failed to display character limit warnings: {}", err);
             }
+
+            self.print_context_changes(os).await?;
         }
 
         let show_tool_use_confirmation_dialog = !skip_printing_tools && self.pending_tool_index.is_some();
@@ -1368,6 +1602,24 @@ impl ChatSession {
             )?;
         }
 
+        let mut command_values = CommandValues {
+            tool_names: self
+                .conversation
+                .tool_manager
+                .schema
+                .keys()
+                .filter(|name| *name != DUMMY_TOOL_NAME)
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        command_values.tool_names.sort();
+        if let Some(context_manager) = &self.conversation.context_manager {
+            command_values.global_context_paths = context_manager.global_config.paths.clone();
+            command_values.profile_context_paths = context_manager.profile_config.paths.clone();
+        }
+        self.input_source.set_command_values(command_values);
+
         // Do this here so that the skim integration sees an updated view of the context *during the current
         // q session*. (e.g., if I add files to context, that won't show up for skim for the current
         // q session unless we do this in prompt_user... unless you can find a better way)
@@ -1375,8 +1627,6 @@ impl ChatSession {
         if let Some(ref context_manager) = self.conversation.context_manager {
             use std::sync::Arc;
 
-            use crate::cli::chat::consts::DUMMY_TOOL_NAME;
-
             let tool_names = self
                 .conversation
                 .tool_manager
@@ -1583,9 +1833,7 @@ impl ChatSession {
             queue!(self.stderr, style::SetForegroundColor(Color::Reset))?;
             queue!(self.stderr, cursor::Hide)?;
 
-            if self.interactive {
-                self.spinner = Some(Spinner::new(Spinners::Dots, "Thinking...".to_owned()));
-            }
+            self.start_spinner("Thinking...");
 
             Ok(ChatState::HandleResponseStream(
                 os.client.send_message(conv_state).await?,
@@ -1608,12 +1856,7 @@ impl ChatSession {
                 || self.tool_permissions.is_trusted(&tool.name)
                 || (!self.tool_permissions.has(&tool.name) && !tool.tool.requires_acceptance(os));
 
-            if os
-                .database
-                .settings
-                .get_bool(Setting::ChatEnableNotifications)
-                .unwrap_or(false)
-            {
+            if NotifyPolicy::from_settings(&os.database.settings).should_notify(self.response_time) {
                 play_notification_bell(!allowed);
             }
 
@@ -1638,7 +1881,29 @@ impl ChatSession {
         let mut tool_results = vec![];
         let mut image_blocks: Vec<RichImageBlock> = Vec::new();
 
+        // When enabled, the first failing tool short-circuits the rest so its error reaches the model
+        // before anything else runs.
+        let stop_on_error = os
+            .database
+            .settings
+            .get_bool(Setting::ChatStopOnToolError)
+            .unwrap_or(false);
+        let mut failed = false;
+        let tool_audit = ToolAudit::from_settings(os);
+
         for tool in &self.tool_uses {
+            if failed {
+                // Every tool use still requires a matching result in the conversation.
+                tool_results.push(ToolUseResult {
+                    tool_use_id: tool.id.clone(),
+                    content: vec![ToolUseResultBlock::Text(
+                        "Skipped because a previous tool in this request failed.".to_string(),
+                    )],
+                    status: ToolResultStatus::Error,
+                });
+                continue;
+            }
+
             let mut tool_telemetry = self.tool_use_telemetry_events.entry(tool.id.clone());
             tool_telemetry = tool_telemetry.and_modify(|ev| ev.is_accepted = true);
 
@@ -1668,6 +1933,9 @@ impl ChatSession {
             execute!(self.stdout, style::Print("\n"))?;
 
             let tool_time = std::time::Instant::now().duration_since(tool_start);
+            if let Some(tool_audit) = &tool_audit {
+                tool_audit.record(tool, invoke_result.is_ok(), tool_time);
+            }
             if let Tool::Custom(ct) = &tool.tool {
                 tool_telemetry = tool_telemetry.and_modify(|ev| {
                     ev.custom_tool_call_latency = Some(tool_time.as_secs() as usize);
@@ -1745,6 +2013,7 @@ impl ChatSession {
                                 .map_or("No utterance id found".to_string(), |v| v.to_string()),
                         );
                     }
+                    failed = stop_on_error;
                 },
             }
         }
@@ -1764,9 +2033,7 @@ impl ChatSession {
 
         execute!(self.stderr, cursor::Hide)?;
         execute!(self.stderr, style::Print("\n"), style::SetAttribute(Attribute::Reset))?;
-        if self.interactive {
-            self.spinner = Some(Spinner::new(Spinners::Dots, "Thinking...".to_string()));
-        }
+        self.start_spinner("Thinking...");
 
         self.send_tool_use_telemetry(os).await;
         return Ok(ChatState::HandleResponseStream(
@@ -1781,13 +2048,21 @@ impl ChatSession {
     }
 
     async fn handle_response(&mut self, os: &mut Os, response: SendMessageOutput) -> Result<ChatState, ChatError> {
+        let response_start = Instant::now();
+        self.response_tokens = RunningTokenCount::new(self.conversation.calculate_char_count(os).await?);
         let request_id = response.request_id().map(|s| s.to_string());
-        let mut buf = String::new();
-        let mut offset = 0;
+        let mut markdown = MarkdownStream::new();
         let mut ended = false;
         let mut parser = ResponseParser::new(response);
         let mut state = ParseState::new(Some(self.terminal_width()));
+        state.syntax_highlighting = os
+            .database
+            .settings
+            .get_bool(Setting::ChatSyntaxHighlighting)
+            .unwrap_or(true)
+            && tools::supports_truecolor(os);
         let mut response_prefix_printed = false;
+        let mut response_text = String::new();
 
         let mut tool_uses = Vec::new();
         let mut tool_name_being_recvd: Option<String> = None;
@@ -1811,7 +2086,7 @@ impl ChatSession {
                         parser::ResponseEvent::ToolUseStart { name } => {
                             // We need to flush the buffer here, otherwise text will not be
                             // printed while we are receiving tool use events.
-                            buf.push('\n');
+                            markdown.push('\n');
                             tool_name_being_recvd = Some(name);
                         },
                         parser::ResponseEvent::AssistantText(text) => {
@@ -1819,10 +2094,14 @@ impl ChatSession {
                             // This must be markdown - using a code tick, which is printed
                             // as green.
                             if !response_prefix_printed && !text.trim().is_empty() {
-                                buf.push_str("`>` ");
+                                markdown.push_str("`>` ");
+                                response_text.push_str("`>` ");
                                 response_prefix_printed = true;
                             }
-                            buf.push_str(&text);
+                            self.response_tokens.push(&text);
+                            markdown.push_str(&text);
+                            response_text.push_str(&text);
+                            self.warn_on_context_window_overflow()?;
                         },
                         parser::ResponseEvent::ToolUse(tool_use) => {
                             if self.spinner.is_some() {
@@ -1834,6 +2113,8 @@ impl ChatSession {
                                     cursor::Show
                                 )?;
                             }
+                            self.response_tokens.push_value(&tool_use.args);
+                            self.warn_on_context_window_overflow()?;
                             tool_uses.push(tool_use);
                             tool_name_being_recvd = None;
                         },
@@ -1874,7 +2155,7 @@ impl ChatSession {
                             );
 
                             execute!(self.stderr, cursor::Hide)?;
-                            self.spinner = Some(Spinner::new(Spinners::Dots, "Dividing up the work...".to_string()));
+                            self.start_spinner("Dividing up the work...");
 
                             // For stream timeouts, we'll tell the model to try and split its response into
                             // smaller chunks.
@@ -1938,10 +2219,16 @@ impl ChatSession {
             // this is a hack since otherwise the parser might report Incomplete with useful data
             // still left in the buffer. I'm not sure how this is intended to be handled.
             if ended {
-                buf.push('\n');
+                markdown.push('\n');
+
+                // Tables are only rendered once a line that isn't part of the table is seen, so
+                // make sure a table at the very end of the response still gets terminated.
+                if markdown.pending().starts_with('|') {
+                    markdown.push('\n');
+                }
             }
 
-            if tool_name_being_recvd.is_none() && !buf.is_empty() && self.spinner.is_some() {
+            if tool_name_being_recvd.is_none() && !markdown.is_empty() && self.spinner.is_some() {
                 drop(self.spinner.take());
                 queue!(
                     self.stderr,
@@ -1953,18 +2240,10 @@ impl ChatSession {
 
             // Print the response for normal cases
             loop {
-                let input = Partial::new(&buf[offset..]);
-                match interpret_markdown(input, &mut self.stdout, &mut state) {
-                    Ok(parsed) => {
-                        offset += parsed.offset_from(&input);
-                        self.stdout.flush()?;
-                        state.newline = state.set_newline;
-                        state.set_newline = false;
-                    },
-                    Err(err) => match err.into_inner() {
-                        Some(err) => return Err(ChatError::Custom(err.to_string().into())),
-                        None => break, // Data was incomplete
-                    },
+                match markdown.render_next(&mut self.stdout, &mut state) {
+                    Ok(true) => self.stdout.flush()?,
+                    Ok(false) => break, // Data was incomplete
+                    Err(err) => return Err(ChatError::Custom(err.to_string().into())),
                 }
 
                 // TODO: We should buffer output based on how much we have to parse, not as a constant
@@ -1975,21 +2254,21 @@ impl ChatSession {
             // Set spinner after showing all of the assistant text content so far.
             if tool_name_being_recvd.is_some() {
                 queue!(self.stderr, cursor::Hide)?;
-                if self.interactive {
-                    self.spinner = Some(Spinner::new(Spinners::Dots, "Thinking...".to_string()));
-                }
+                self.start_spinner("Thinking...");
             }
 
             if ended {
                 self.send_chat_telemetry(os, request_id, TelemetryResult::Succeeded, None, None, None)
                     .await;
 
-                if os
-                    .database
-                    .settings
-                    .get_bool(Setting::ChatEnableNotifications)
-                    .unwrap_or(false)
-                {
+                self.response_time = response_start.elapsed();
+                debug!(
+                    tokens = %self.response_tokens.response_token_count(),
+                    total_tokens = %self.response_tokens.token_count(),
+                    elapsed = ?self.response_time,
+                    "Response finished streaming"
+                );
+                if NotifyPolicy::from_settings(&os.database.settings).should_notify(self.response_time) {
                     // For final responses (no tools suggested), always play the bell
                     play_notification_bell(tool_uses.is_empty());
                 }
@@ -2009,6 +2288,12 @@ impl ChatSession {
                     )?;
                 }
 
+                if self.interactive {
+                    self.stdout.flush()?;
+                    self.page_long_response(os, &response_text, state.syntax_highlighting)
+                        .await;
+                }
+
                 break;
             }
         }
@@ -2034,11 +2319,13 @@ impl ChatSession {
         for tool_use in tool_uses {
             let tool_use_id = tool_use.id.clone();
             let tool_use_name = tool_use.name.clone();
+            let tool_use_args = tool_use.args.clone();
             let mut tool_telemetry =
                 ToolUseEventBuilder::new(conv_id.clone(), tool_use.id.clone(), self.conversation.model.clone())
                     .set_tool_use_id(tool_use_id.clone())
                     .set_tool_name(tool_use.name.clone())
-                    .utterance_id(self.conversation.message_id().map(|s| s.to_string()));
+                    .utterance_id(self.conversation.message_id().map(|s| s.to_string()))
+                    .correlation_id(self.conversation.correlation_id().map(|s| s.to_string()));
             match self.conversation.tool_manager.get_tool_from_tool_use(tool_use) {
                 Ok(mut tool) => {
                     // Apply non-Q-generated context to tools
@@ -2052,6 +2339,7 @@ impl ChatSession {
                                 name: tool_use_name,
                                 tool,
                                 accepted: false,
+                                args: tool_use_args,
                             });
                         },
                         Err(err) => {
@@ -2149,9 +2437,7 @@ impl ChatSession {
             .as_sendable_conversation_state(os, &mut self.stderr, true)
             .await?;
 
-        if self.interactive {
-            self.spinner = Some(Spinner::new(Spinners::Dots, "Thinking...".to_owned()));
-        }
+        self.start_spinner("Thinking...");
 
         Ok(ChatState::HandleResponseStream(
             os.client.send_message(conv_state).await?,
@@ -2164,15 +2450,17 @@ impl ChatSession {
     // TODO: Is there a better way?
     fn contextualize_tool(&self, tool: &mut Tool) {
         if let Tool::GhIssue(gh_issue) = tool {
-            gh_issue.set_context(GhIssueContext {
+            gh_issue.set_context(
                 // Ideally we avoid cloning, but this function is not called very often.
                 // Using references with lifetimes requires a large refactor, and Arc<Mutex<T>>
                 // seems like overkill and may incur some performance cost anyway.
-                context_manager: self.conversation.context_manager.clone(),
-                transcript: self.conversation.transcript.clone(),
-                failed_request_ids: self.failed_request_ids.clone(),
-                tool_permissions: self.tool_permissions.permissions.clone(),
-            });
+                GhIssueContext::builder()
+                    .context_manager(self.conversation.context_manager.clone())
+                    .transcript(self.conversation.transcript.clone())
+                    .failed_request_ids(self.failed_request_ids.clone())
+                    .tool_permissions(self.tool_permissions.permissions.clone())
+                    .build(),
+            );
         }
     }
 
@@ -2267,16 +2555,98 @@ impl ChatSession {
         }
     }
 
+    /// Show a completed response once more through the configured [Pager] when it didn't fit on
+    /// the screen
+    async fn page_long_response(&self, os: &Os, text: &str, syntax_highlighting: bool) {
+        let Some(pager) = Pager::from_settings(&os.database.settings, os.env.get("PAGER").ok()) else {
+            return;
+        };
+        let Ok((_, terminal_height)) = terminal::size() else {
+            return;
+        };
+
+        let terminal_width = self.terminal_width();
+        let output = match render_response(text, terminal_width, syntax_highlighting) {
+            Ok(output) => output,
+            Err(err) => {
+                warn!(?err, "Failed to render the response for the pager");
+                return;
+            },
+        };
+        if !exceeds_screen(
+            &String::from_utf8_lossy(&output),
+            terminal_width,
+            terminal_height.into(),
+        ) {
+            return;
+        }
+
+        if let Err(err) = pager.page(&output).await {
+            warn!(?err, "Failed to show the response in the pager");
+        }
+    }
+
     fn terminal_width(&self) -> usize {
         (self.terminal_width_provider)().unwrap_or(80)
     }
 
+    /// Starts the spinner with the given message, unless the session is non-interactive or quiet.
+    fn start_spinner(&mut self, message: &str) {
+        if self.interactive && !self.quiet {
+            self.spinner = Some(SpinnerGuard::start(self.spinner_style.clone(), message.to_owned()));
+        }
+    }
+
+    /// Whether the welcome banner, tips, and shortcuts should be displayed on startup.
+    fn should_show_greeting(&self, os: &Os) -> bool {
+        !self.quiet
+            && os
+                .database
+                .settings
+                .get_bool(Setting::ChatGreetingEnabled)
+                .unwrap_or(true)
+    }
+
+    /// Whether the greeting should be reduced to a single header line, which happens in the
+    /// `firstRunOnly` greeting mode once the full greeting has been shown enough times.
+    fn should_collapse_greeting(&self, os: &Os) -> bool {
+        let mode = os.database.settings.get_string(Setting::ChatGreetingMode);
+        if mode.as_deref() != Some("firstRunOnly") {
+            return false;
+        }
+
+        let full_launches = os
+            .database
+            .settings
+            .get_int(Setting::ChatGreetingFullLaunches)
+            .unwrap_or(DEFAULT_GREETING_FULL_LAUNCHES);
+        let launches = os.database.get_rotating_tip().ok().flatten().unwrap_or(0);
+        i64::try_from(launches).unwrap_or(i64::MAX) >= full_launches
+    }
+
     fn all_tools_trusted(&mut self) -> bool {
         self.conversation.tools.values().flatten().all(|t| match t {
             FigTool::ToolSpecification(t) => self.tool_permissions.is_trusted(&t.name),
         })
     }
 
+    /// Warns once per response when the conversation plus the response streamed in so far no
+    /// longer fits in the context window, rather than after the turn has finished.
+    fn warn_on_context_window_overflow(&mut self) -> Result<(), ChatError> {
+        if !self.response_tokens.take_overflow() {
+            return Ok(());
+        }
+
+        warn!(tokens = %self.response_tokens.token_count(), "Response is overflowing the context window");
+        queue!(
+            self.stderr,
+            style::SetForegroundColor(Color::Yellow),
+            style::Print("\n⚠️ This response is filling up the context window, use /compact once it finishes.\n"),
+            style::SetForegroundColor(Color::Reset)
+        )?;
+        Ok(())
+    }
+
     /// Display character limit warnings based on current conversation size
     async fn display_char_warnings(&mut self, os: &Os) -> Result<(), ChatError> {
         let warning_level = self.conversation.get_token_warning_level(os).await?;
@@ -2320,6 +2690,7 @@ impl ChatSession {
                 self.conversation.conversation_id().to_owned(),
                 self.conversation.message_id().map(|s| s.to_owned()),
                 request_id,
+                self.conversation.correlation_id().map(|s| s.to_owned()),
                 self.conversation.context_message_length(),
                 result,
                 reason,
@@ -2455,6 +2826,7 @@ fn does_input_reference_file(input: &str) -> Option<ChatState> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::chat::consts::MAX_CHARS;
 
     #[tokio::test]
     async fn test_flow() {
@@ -2499,6 +2871,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            false,
         )
         .await
         .unwrap()
@@ -2509,6 +2882,101 @@ mod tests {
         assert_eq!(os.fs.read_to_string("/file.txt").await.unwrap(), "Hello, world!\n");
     }
 
+    #[tokio::test]
+    async fn test_correlation_id_flows_to_telemetry() {
+        let mut os = Os::new().await.unwrap();
+        let (telemetry, mut events) = crate::telemetry::TelemetryThread::new_capturing();
+        os.telemetry = telemetry;
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Sure, I'll create a file for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file.txt",
+                    }
+                }
+            ],
+            [
+                "Hope that looks good to you!",
+            ],
+        ]));
+
+        let tool_manager = ToolManager::default();
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        let mut session = ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            None,
+            InputSource::new_mock(vec![
+                "create a new file".to_string(),
+                "y".to_string(),
+                "exit".to_string(),
+            ]),
+            false,
+            || Some(80),
+            tool_manager,
+            None,
+            None,
+            tool_config,
+            ToolPermissions::new(0),
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+        session.spawn(&mut os).await.unwrap();
+
+        let mut added_message_ids = Vec::new();
+        let mut tool_use_ids = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event.ty {
+                crate::telemetry::EventType::ChatAddedMessage { correlation_id, .. } => {
+                    added_message_ids.push(correlation_id.unwrap());
+                },
+                crate::telemetry::EventType::ToolUseSuggested { correlation_id, .. } => {
+                    tool_use_ids.push(correlation_id.unwrap());
+                },
+                _ => (),
+            }
+        }
+
+        // One id per request, and the tool use is tied to the request that suggested it.
+        assert_eq!(added_message_ids.len(), 2);
+        assert_ne!(added_message_ids[0], added_message_ids[1]);
+        assert_eq!(tool_use_ids, vec![added_message_ids[0].clone()]);
+        assert_eq!(
+            session.conversation.correlation_id(),
+            Some(added_message_ids[1].as_str())
+        );
+    }
+
+    #[test]
+    fn test_chat_error_displays_correlation_id() {
+        let err = || {
+            ChatError::from(ApiClientError::QuotaBreach {
+                message: "quota has reached its limit",
+                status_code: Some(429),
+            })
+        };
+        assert!(!err().to_string().contains("correlation_id"));
+        assert!(
+            err()
+                .with_correlation_id(Some("abc-123"))
+                .to_string()
+                .ends_with("(correlation_id: abc-123)")
+        );
+        // Only errors from a request are tied to it
+        let err = ChatError::Custom("oops".into()).with_correlation_id(Some("abc-123"));
+        assert_eq!(err.to_string(), "oops");
+    }
+
     #[tokio::test]
     async fn test_flow_tool_permissions() {
         let mut os = Os::new().await.unwrap();
@@ -2640,6 +3108,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            false,
         )
         .await
         .unwrap()
@@ -2735,6 +3204,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            false,
         )
         .await
         .unwrap()
@@ -2809,6 +3279,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            false,
         )
         .await
         .unwrap()
@@ -2820,6 +3291,88 @@ mod tests {
         assert!(!os.fs.exists("/file2.txt"));
     }
 
+    async fn run_failing_then_succeeding_tools(stop_on_error: bool) -> Os {
+        let mut os = Os::new().await.unwrap();
+        os.fs.write("/file1.txt", "Hello, world!").await.unwrap();
+        os.database
+            .settings
+            .set(Setting::ChatStopOnToolError, stop_on_error)
+            .await
+            .unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Sure, I'll update both files",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "str_replace",
+                        "old_str": "Not in the file",
+                        "new_str": "Goodbye",
+                        "path": "/file1.txt",
+                    }
+                },
+                {
+                    "tool_use_id": "2",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file2.txt",
+                    }
+                }
+            ],
+            [
+                "Done",
+            ],
+        ]));
+
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            None,
+            InputSource::new_mock(vec![
+                "/tools trust-all".to_string(),
+                "update both files".to_string(),
+                "exit".to_string(),
+            ]),
+            false,
+            || Some(80),
+            ToolManager::default(),
+            None,
+            None,
+            tool_config,
+            ToolPermissions::new(0),
+            true,
+            false,
+        )
+        .await
+        .unwrap()
+        .spawn(&mut os)
+        .await
+        .unwrap();
+
+        os
+    }
+
+    #[tokio::test]
+    async fn test_flow_tool_error_continues_by_default() {
+        let os = run_failing_then_succeeding_tools(false).await;
+        assert_eq!(os.fs.read_to_string("/file1.txt").await.unwrap(), "Hello, world!");
+        assert_eq!(os.fs.read_to_string("/file2.txt").await.unwrap(), "Hello, world!\n");
+    }
+
+    #[tokio::test]
+    async fn test_flow_tool_error_stops_remaining_tools() {
+        let os = run_failing_then_succeeding_tools(true).await;
+        assert_eq!(os.fs.read_to_string("/file1.txt").await.unwrap(), "Hello, world!");
+        assert!(!os.fs.exists("/file2.txt"));
+    }
+
     #[test]
     fn test_editor_content_processing() {
         // Since we no longer have template replacement, this test is simplified
@@ -2857,6 +3410,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            false,
         )
         .await
         .unwrap()
@@ -2865,6 +3419,201 @@ mod tests {
         .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_quiet_session_hides_greeting_and_spinner() {
+        let mut os = Os::new().await.unwrap();
+        os.database
+            .settings
+            .set(Setting::ChatSpinnerStyle, "bouncing_bar")
+            .await
+            .unwrap();
+
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        let mut session = ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            None,
+            InputSource::new_mock(vec![]),
+            false,
+            || Some(80),
+            ToolManager::default(),
+            None,
+            None,
+            tool_config,
+            ToolPermissions::new(0),
+            true,
+            true,
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(session.spinner_style, Spinners::BouncingBar));
+        assert!(!session.should_show_greeting(&os));
+        session.start_spinner("Thinking...");
+        assert!(session.spinner.is_none());
+
+        session.quiet = false;
+        assert!(session.should_show_greeting(&os));
+    }
+
+    #[tokio::test]
+    async fn test_greeting_collapses_after_first_runs() {
+        let mut os = Os::new().await.unwrap();
+        let mut session = batch_session(&mut os).await;
+        session.quiet = false;
+
+        // The full greeting is always shown without the firstRunOnly mode
+        for _ in 0..3 {
+            assert!(!session.should_collapse_greeting(&os));
+            session.print_greeting(&mut os, false).await.unwrap();
+        }
+
+        os.database
+            .settings
+            .set(Setting::ChatGreetingMode, "firstRunOnly")
+            .await
+            .unwrap();
+        os.database
+            .settings
+            .set(Setting::ChatGreetingFullLaunches, 5)
+            .await
+            .unwrap();
+        for _ in 3..5 {
+            assert!(!session.should_collapse_greeting(&os));
+            session.print_greeting(&mut os, false).await.unwrap();
+        }
+        assert!(session.should_collapse_greeting(&os));
+        assert!(session.should_show_greeting(&os));
+
+        // Collapsed launches don't show a tip, so the count stays put
+        session.print_greeting(&mut os, false).await.unwrap();
+        assert_eq!(os.database.get_rotating_tip().unwrap(), Some(5));
+    }
+
+    async fn batch_session(os: &mut Os) -> ChatSession {
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        ChatSession::new(
+            os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            None,
+            InputSource::new_mock(vec![]),
+            false,
+            || Some(80),
+            ToolManager::default(),
+            None,
+            None,
+            tool_config,
+            ToolPermissions::new(0),
+            false,
+            true,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_prompts_from_file() {
+        let mut os = Os::new().await.unwrap();
+        os.client
+            .set_mock_output(serde_json::json!([["Hello!"], ["Goodbye!"],]));
+        os.fs
+            .write("/prompts.txt", "say hello\nacross lines\n---\nsay goodbye\n")
+            .await
+            .unwrap();
+
+        let prompts = read_batch_prompts(&os, Path::new("/prompts.txt")).await.unwrap();
+        assert_eq!(prompts, vec!["say hello\nacross lines", "say goodbye"]);
+
+        let mut session = batch_session(&mut os).await;
+        session.spawn_batch(&mut os, prompts, false).await.unwrap();
+        assert_eq!(session.conversation.history().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_response_tokens_checked_against_context_window() {
+        // Each response fits in the context window alone, but not on top of the first one
+        let long_response = "All work and no play.\n".repeat(MAX_CHARS / 22 * 3 / 5);
+        let mut os = Os::new().await.unwrap();
+        os.client
+            .set_mock_output(serde_json::json!([[&long_response], ["Sure, ", &long_response]]));
+
+        let mut session = batch_session(&mut os).await;
+        session
+            .spawn_batch(&mut os, vec!["say something long".to_string()], false)
+            .await
+            .unwrap();
+        assert!(!session.response_tokens.exceeds_context_window());
+
+        session
+            .spawn_batch(&mut os, vec!["say it again".to_string()], false)
+            .await
+            .unwrap();
+        assert!(session.response_tokens.exceeds_context_window());
+        // Already reported while the response was streaming
+        assert!(!session.response_tokens.take_overflow());
+    }
+
+    #[tokio::test]
+    async fn test_batch_stops_on_error() {
+        let tool_use_response = serde_json::json!([
+            [
+                "Sure, I'll create a file for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file.txt",
+                    }
+                }
+            ],
+            ["Hello!"],
+        ]);
+        let prompts = vec!["create a file".to_string(), "say hello".to_string()];
+
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(tool_use_response.clone());
+        let mut session = batch_session(&mut os).await;
+        assert!(session.spawn_batch(&mut os, prompts.clone(), false).await.is_err());
+        assert_eq!(session.conversation.history().len(), 1);
+
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(tool_use_response);
+        let mut session = batch_session(&mut os).await;
+        session.spawn_batch(&mut os, prompts, true).await.unwrap();
+        assert_eq!(session.conversation.history().len(), 2);
+        assert!(!os.fs.exists("/file.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_read_trust_patterns() {
+        let os = Os::new().await.unwrap();
+        os.fs
+            .write("trusted_tools", "# MCP servers\nfs___*\n\n  execute_bash  \n")
+            .await
+            .unwrap();
+        assert_eq!(
+            read_trust_patterns(&os, Path::new("trusted_tools")).await.unwrap(),
+            vec!["fs___*", "execute_bash"]
+        );
+    }
+
+    #[test]
+    fn test_split_batch_prompts() {
+        assert_eq!(split_batch_prompts("one\n\ntwo\n"), vec!["one", "two"]);
+        assert_eq!(split_batch_prompts("one\ntwo\n---\n\n---\nthree"), vec![
+            "one\ntwo", "three"
+        ]);
+        assert!(split_batch_prompts("\n---\n").is_empty());
+    }
+
     #[test]
     fn test_does_input_reference_file() {
         let tests = &[
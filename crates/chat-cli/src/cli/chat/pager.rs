@@ -0,0 +1,155 @@
+//! Paging of long chat responses
+//!
+//! Responses always stream to the terminal as they arrive. When `chat.pager` is set and a
+//! completed response is taller than the terminal, it is shown once more through a pager so it can
+//! be scrolled from the top. `chat.pager` is either `true`, to use `$PAGER` or `less -R`, or the
+//! pager command itself.
+
+use std::io::ErrorKind;
+use std::process::Stdio;
+
+use eyre::Result;
+use serde_json::Value;
+use strip_ansi_escapes::strip_str;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use unicode_width::UnicodeWidthStr;
+
+use super::parse::{
+    MarkdownStream,
+    ParseState,
+};
+use crate::database::settings::{
+    Setting,
+    Settings,
+};
+
+const DEFAULT_PAGER: &str = "less -R";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pager {
+    command: String,
+}
+
+impl Pager {
+    /// The pager configured in the settings, `None` when paging is off
+    ///
+    /// `env_pager` is the value of `$PAGER`, used when the setting is `true`.
+    pub fn from_settings(settings: &Settings, env_pager: Option<String>) -> Option<Self> {
+        let command = match settings.get(Setting::ChatPager)? {
+            Value::Bool(true) => env_pager
+                .filter(|pager| !pager.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_PAGER.to_string()),
+            Value::String(command) if !command.trim().is_empty() => command.clone(),
+            _ => return None,
+        };
+
+        Some(Self { command })
+    }
+
+    /// Show `output` in the pager, waiting for it to exit
+    ///
+    /// The pager exiting before reading all of `output`, as when quitting `less` early, is not an
+    /// error.
+    pub async fn page(&self, output: &[u8]) -> Result<()> {
+        let args = shlex::split(&self.command).unwrap_or_default();
+        let Some((program, args)) = args.split_first() else {
+            eyre::bail!("invalid pager command: {:?}", self.command);
+        };
+
+        let mut child = Command::new(program).args(args).stdin(Stdio::piped()).spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(output).await {
+                Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(err.into()),
+                _ => (),
+            }
+        }
+        child.wait().await?;
+
+        Ok(())
+    }
+}
+
+/// Whether `output` takes more rows than the terminal has, counting lines wrapped at
+/// `terminal_width`
+pub fn exceeds_screen(output: &str, terminal_width: usize, terminal_height: usize) -> bool {
+    let terminal_width = terminal_width.max(1);
+    let mut rows = 0;
+    for line in output.lines() {
+        rows += strip_str(line).width().div_ceil(terminal_width).max(1);
+        if rows > terminal_height {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Render the markdown of a completed response, the same way it was streamed
+pub fn render_response(text: &str, terminal_width: usize, syntax_highlighting: bool) -> Result<Vec<u8>> {
+    let mut markdown = MarkdownStream::new();
+    markdown.push_str(text);
+    markdown.push('\n');
+    if markdown.pending().starts_with('|') {
+        markdown.push('\n');
+    }
+
+    let mut state = ParseState::new(Some(terminal_width));
+    state.syntax_highlighting = syntax_highlighting;
+    let mut output = Vec::new();
+    while markdown.render_next(&mut output, &mut state)? {}
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exceeds_screen() {
+        let ten_lines = "line\n".repeat(10);
+        assert!(!exceeds_screen(&ten_lines, 80, 10));
+        assert!(exceeds_screen(&ten_lines, 80, 9));
+
+        // Long lines wrap onto more rows.
+        let wrapped = format!("{}\n", "a".repeat(200));
+        assert!(!exceeds_screen(&wrapped, 100, 2));
+        assert!(exceeds_screen(&wrapped, 80, 2));
+
+        // Colors don't take up any columns.
+        let colored = format!("\x1b[32m{}\x1b[0m\n", "a".repeat(80));
+        assert!(!exceeds_screen(&colored, 80, 1));
+
+        assert!(!exceeds_screen("", 80, 0));
+    }
+
+    #[tokio::test]
+    async fn test_pager_from_settings() {
+        let mut settings = Settings::default();
+        assert_eq!(Pager::from_settings(&settings, Some("more".into())), None);
+
+        settings.set(Setting::ChatPager, true).await.unwrap();
+        assert_eq!(
+            Pager::from_settings(&settings, Some("more".into())),
+            Some(Pager { command: "more".into() })
+        );
+        assert_eq!(
+            Pager::from_settings(&settings, None),
+            Some(Pager {
+                command: DEFAULT_PAGER.into()
+            })
+        );
+
+        settings.set(Setting::ChatPager, "bat -p").await.unwrap();
+        assert_eq!(
+            Pager::from_settings(&settings, Some("more".into())),
+            Some(Pager {
+                command: "bat -p".into()
+            })
+        );
+
+        settings.set(Setting::ChatPager, false).await.unwrap();
+        assert_eq!(Pager::from_settings(&settings, None), None);
+    }
+}
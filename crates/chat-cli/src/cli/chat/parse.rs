@@ -9,6 +9,7 @@ use crossterm::{
     Command,
     style,
 };
+use syntect::easy::HighlightLines;
 use unicode_width::{
     UnicodeWidthChar,
     UnicodeWidthStr,
@@ -24,6 +25,7 @@ use winnow::ascii::{
 use winnow::combinator::{
     alt,
     delimited,
+    peek,
     preceded,
     repeat,
     terminated,
@@ -36,6 +38,7 @@ use winnow::error::{
 use winnow::prelude::*;
 use winnow::stream::{
     AsChar,
+    Offset,
     Stream,
 };
 use winnow::token::{
@@ -45,6 +48,11 @@ use winnow::token::{
     take_while,
 };
 
+use super::tools::{
+    SYNTAX_SET,
+    THEME_SET,
+};
+
 const CODE_COLOR: Color = Color::Green;
 const HEADING_COLOR: Color = Color::Magenta;
 const BLOCKQUOTE_COLOR: Color = Color::DarkGrey;
@@ -53,6 +61,11 @@ const URL_LINK_COLOR: Color = Color::DarkGrey;
 
 const DEFAULT_RULE_WIDTH: usize = 40;
 
+const SYNTAX_THEME: &str = "base16-ocean.dark";
+
+/// Width taken up by the borders and padding of a table, excluding the cell contents.
+const TABLE_COLUMN_OVERHEAD: usize = 3;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error<'a> {
     #[error(transparent)]
@@ -90,6 +103,11 @@ pub struct ParseState {
     pub set_newline: bool,
     pub newline: bool,
     pub citations: Vec<(String, String)>,
+    /// Whether code blocks with a known language should be syntax highlighted.
+    pub syntax_highlighting: bool,
+    /// Highlighter for the code block currently being parsed, if any. Kept across calls so that
+    /// the highlighting state carries over as lines of the code block are streamed in.
+    highlighter: Option<CodeHighlighter>,
 }
 
 impl ParseState {
@@ -104,10 +122,77 @@ impl ParseState {
             set_newline: false,
             newline: true,
             citations: vec![],
+            syntax_highlighting: false,
+            highlighter: None,
         }
     }
 }
 
+/// Renders a response as it streams in, writing each markdown element only once it's complete
+///
+/// Elements are rendered into a scratch buffer first, so one that turns out to be incomplete,
+/// such as a code fence that hasn't been closed, writes nothing and is rendered from its start
+/// once more of the response arrives. Output that was written is never written again.
+#[derive(Debug, Default)]
+pub struct MarkdownStream {
+    buf: String,
+    /// Length of the prefix of `buf` that has been rendered and written
+    committed: usize,
+    scratch: Vec<u8>,
+}
+
+impl MarkdownStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.buf.push(c);
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.buf.push_str(text);
+    }
+
+    /// Whether nothing has been received yet
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// The received text that hasn't been rendered yet
+    pub fn pending(&self) -> &str {
+        &self.buf[self.committed..]
+    }
+
+    /// Renders the next complete element to `o`, returning `false` without writing anything once
+    /// the pending text is incomplete
+    pub fn render_next(&mut self, mut o: impl Write, state: &mut ParseState) -> eyre::Result<bool> {
+        self.scratch.clear();
+        let input = Partial::new(&self.buf[self.committed..]);
+        match interpret_markdown(input, &mut self.scratch, state) {
+            Ok(parsed) => {
+                self.committed += parsed.offset_from(&input);
+                state.newline = state.set_newline;
+                state.set_newline = false;
+                o.write_all(&self.scratch)?;
+                Ok(true)
+            },
+            Err(err) => match err.into_inner() {
+                Some(err) => Err(eyre::eyre!("{err}")),
+                None => Ok(false), // Data was incomplete
+            },
+        }
+    }
+}
+
+struct CodeHighlighter(HighlightLines<'static>);
+
+impl std::fmt::Debug for CodeHighlighter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodeHighlighter").finish_non_exhaustive()
+    }
+}
+
 pub fn interpret_markdown<'a, 'b>(
     mut i: Partial<&'a str>,
     mut o: impl Write + 'b,
@@ -143,6 +228,7 @@ pub fn interpret_markdown<'a, 'b>(
                 text,
                 // multiline patterns
                 blockquote,
+                table,
                 // linted_codeblock,
                 codeblock_begin,
                 // single line patterns
@@ -169,6 +255,7 @@ pub fn interpret_markdown<'a, 'b>(
         },
         true => {
             stateful_alt!(
+                codeblock_highlighted_line,
                 codeblock_less_than,
                 codeblock_greater_than,
                 codeblock_ampersand,
@@ -274,6 +361,154 @@ fn horizontal_rule<'a, 'b>(
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl TableAlignment {
+    /// Parses a cell from the header separator row, e.g. `:---`, `:---:`, or `---:`.
+    fn from_separator(cell: &str) -> Option<Self> {
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+            return None;
+        }
+
+        match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Some(Self::Center),
+            (false, true) => Some(Self::Right),
+            _ => Some(Self::Left),
+        }
+    }
+}
+
+fn table<'a, 'b>(
+    mut o: impl Write + 'b,
+    state: &'b mut ParseState,
+) -> impl FnMut(&mut Partial<&'a str>) -> PResult<(), Error<'a>> + 'b {
+    move |i| {
+        if !state.newline {
+            return Err(ErrMode::from_error_kind(i, ErrorKind::Fail));
+        }
+
+        let header = table_row.parse_next(i)?;
+        let alignments = table_row
+            .parse_next(i)?
+            .into_iter()
+            .map(TableAlignment::from_separator)
+            .collect::<Option<Vec<_>>>()
+            .filter(|alignments| alignments.len() == header.len())
+            .ok_or_else(|| ErrMode::from_error_kind(i, ErrorKind::Fail))?;
+
+        // Tables can only be rendered once every row is known, so keep consuming rows until the
+        // next line is not part of the table.
+        let mut rows = vec![header];
+        while peek(any).parse_next(i)? == '|' {
+            rows.push(table_row.parse_next(i)?);
+        }
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let mut cells = row
+                    .into_iter()
+                    .map(|cell| cell.replace("&amp;", "&").replace("&gt;", ">").replace("&lt;", "<"))
+                    .collect::<Vec<_>>();
+                cells.resize(alignments.len(), String::new());
+                cells
+            })
+            .collect::<Vec<_>>();
+
+        let widths = table_column_widths(&rows, state.terminal_width);
+
+        state.column = 0;
+        state.set_newline = true;
+
+        let border = |left: &str, middle: &str, right: &str| {
+            let segments = widths.iter().map(|w| "─".repeat(w + 2)).collect::<Vec<_>>();
+            format!("{left}{}{right}\n", segments.join(middle))
+        };
+
+        queue(&mut o, style::Print(border("┌", "┬", "┐")))?;
+        for (row_index, row) in rows.iter().enumerate() {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .zip(&alignments)
+                .map(|((cell, width), alignment)| format_table_cell(cell, *width, *alignment))
+                .collect::<Vec<_>>();
+            queue(&mut o, style::Print(format!("│ {} │\n", cells.join(" │ "))))?;
+
+            if row_index == 0 {
+                queue(&mut o, style::Print(border("├", "┼", "┤")))?;
+            }
+        }
+        queue(&mut o, style::Print(border("└", "┴", "┘")))
+    }
+}
+
+/// Parses a single `| a | b |` table row into its trimmed cells.
+fn table_row<'a>(i: &mut Partial<&'a str>) -> PResult<Vec<&'a str>, Error<'a>> {
+    let line = delimited("|", till_line_ending, ascii::line_ending).parse_next(i)?;
+    let line = line.trim_end();
+    let line = line.strip_suffix('|').unwrap_or(line);
+    Ok(line.split('|').map(str::trim).collect())
+}
+
+/// Calculates the width of each column, shrinking the widest columns until the table fits within
+/// the terminal width.
+fn table_column_widths(rows: &[Vec<String>], terminal_width: Option<usize>) -> Vec<usize> {
+    let column_count = rows.first().map_or(0, Vec::len);
+    let mut widths = (0..column_count)
+        .map(|col| rows.iter().map(|row| row[col].width()).max().unwrap_or(0).max(1))
+        .collect::<Vec<_>>();
+
+    if let Some(terminal_width) = terminal_width {
+        let overhead = column_count * TABLE_COLUMN_OVERHEAD + 1;
+        let max_content_width = terminal_width.saturating_sub(overhead);
+        while widths.iter().sum::<usize>() > max_content_width {
+            match widths.iter_mut().max() {
+                Some(widest) if *widest > 1 => *widest -= 1,
+                _ => break,
+            }
+        }
+    }
+
+    widths
+}
+
+/// Pads `cell` to exactly `width` columns, truncating with an ellipsis if it is too wide.
+fn format_table_cell(cell: &str, width: usize, alignment: TableAlignment) -> String {
+    let cell = if cell.width() > width {
+        let mut truncated = String::new();
+        let mut truncated_width = 0;
+        for c in cell.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if truncated_width + char_width + 1 > width {
+                break;
+            }
+            truncated.push(c);
+            truncated_width += char_width;
+        }
+        truncated.push('…');
+        truncated
+    } else {
+        cell.to_owned()
+    };
+
+    let padding = width.saturating_sub(cell.width());
+    match alignment {
+        TableAlignment::Left => format!("{cell}{}", " ".repeat(padding)),
+        TableAlignment::Right => format!("{}{cell}", " ".repeat(padding)),
+        TableAlignment::Center => {
+            let left = padding / 2;
+            format!("{}{cell}{}", " ".repeat(left), " ".repeat(padding - left))
+        },
+    }
+}
+
 fn code<'a, 'b>(
     mut o: impl Write + 'b,
     state: &'b mut ParseState,
@@ -560,6 +795,12 @@ fn codeblock_begin<'a, 'b>(
             queue(&mut o, style::Print(format!("{}\n", language).bold()))?;
         }
 
+        if state.syntax_highlighting {
+            state.highlighter = SYNTAX_SET
+                .find_syntax_by_token(language.trim())
+                .map(|syntax| CodeHighlighter(HighlightLines::new(syntax, &THEME_SET.themes[SYNTAX_THEME])));
+        }
+
         queue(&mut o, style::SetForegroundColor(CODE_COLOR))?;
 
         Ok(())
@@ -573,10 +814,54 @@ fn codeblock_end<'a, 'b>(
     move |i| {
         "```".parse_next(i)?;
         state.in_codeblock = false;
+        state.highlighter = None;
         queue(&mut o, style::ResetColor)
     }
 }
 
+fn codeblock_highlighted_line<'a, 'b>(
+    mut o: impl Write + 'b,
+    state: &'b mut ParseState,
+) -> impl FnMut(&mut Partial<&'a str>) -> PResult<(), Error<'a>> + 'b {
+    move |i| {
+        let Some(CodeHighlighter(highlighter)) = state.highlighter.as_mut() else {
+            return Err(ErrMode::from_error_kind(i, ErrorKind::Fail));
+        };
+
+        // Highlighting works on whole lines, so wait until the full line has been received. Lines
+        // containing the closing fence are left to the other codeblock parsers.
+        let (line, ending) = (till_line_ending, ascii::line_ending).parse_next(i)?;
+        if line.contains("```") {
+            return Err(ErrMode::from_error_kind(i, ErrorKind::Fail));
+        }
+
+        let line = format!(
+            "{}{ending}",
+            line.replace("&amp;", "&")
+                .replace("&gt;", ">")
+                .replace("&lt;", "<")
+                .replace("&quot;", "\"")
+        );
+        let ranges = highlighter
+            .highlight_line(&line, &SYNTAX_SET)
+            .map_err(|_err| ErrMode::from_error_kind(i, ErrorKind::Fail))?;
+
+        for (highlight, text) in ranges {
+            queue(
+                &mut o,
+                style::SetForegroundColor(Color::Rgb {
+                    r: highlight.foreground.r,
+                    g: highlight.foreground.g,
+                    b: highlight.foreground.b,
+                }),
+            )?;
+            queue(&mut o, style::Print(text))?;
+        }
+
+        Ok(())
+    }
+}
+
 fn codeblock_less_than<'a, 'b>(
     mut o: impl Write + 'b,
     _state: &'b mut ParseState,
@@ -641,8 +926,6 @@ fn codeblock_fallback<'a, 'b>(
 mod tests {
     use std::io::Write;
 
-    use winnow::stream::Offset;
-
     use super::*;
 
     macro_rules! validate {
@@ -688,6 +971,29 @@ mod tests {
         };
     }
 
+    /// Renders all of `input` using `state`, returning the raw terminal output.
+    fn render(input: &str, state: &mut ParseState) -> String {
+        let mut output = vec![];
+        let mut offset = 0;
+
+        loop {
+            let partial = Partial::new(&input[offset..]);
+            match interpret_markdown(partial, &mut output, state) {
+                Ok(parsed) => {
+                    offset += parsed.offset_from(&partial);
+                    state.newline = state.set_newline;
+                    state.set_newline = false;
+                },
+                Err(err) => match err.into_inner() {
+                    Some(err) => panic!("{err}"),
+                    None => break, // Data was incomplete
+                },
+            }
+        }
+
+        String::from_utf8(output).unwrap()
+    }
+
     validate!(text_1, "hello world!", [style::Print("hello world!")]);
     validate!(linted_codeblock_1, "```java\nhello world!```", [
         style::SetAttribute(Attribute::Bold),
@@ -759,4 +1065,111 @@ mod tests {
     validate!(square_bracket_url_like_2, "[text](without url part", [style::Print(
         "[text](without url part"
     )]);
+    validate!(table_1, "| a | bb |\n|---|--:|\n| ccc | d |\ndone", [style::Print(
+        concat!(
+            "┌─────┬────┐\n",
+            "│ a   │ bb │\n",
+            "├─────┼────┤\n",
+            "│ ccc │  d │\n",
+            "└─────┴────┘\n",
+            "done",
+        )
+    )]);
+    validate!(
+        table_alignment_1,
+        "| left | center | right |\n|:--|:-:|--:|\n| a | b | c |\ndone",
+        [style::Print(concat!(
+            "┌──────┬────────┬───────┐\n",
+            "│ left │ center │ right │\n",
+            "├──────┼────────┼───────┤\n",
+            "│ a    │   b    │     c │\n",
+            "└──────┴────────┴───────┘\n",
+            "done",
+        ))]
+    );
+
+    #[test]
+    fn test_codeblock_syntax_highlighting() {
+        let mut state = ParseState::new(Some(80));
+        state.syntax_highlighting = true;
+
+        let output = render("```rust\nfn main() {}\n```\n  ", &mut state);
+        insta::assert_snapshot!(output.trim().replace('\x1b', "\\e"), @r"
+        \e[1mrust
+        \e[0m\e[38;5;10m\e[38;2;180;142;173mfn\e[38;2;192;197;206m \e[38;2;143;161;179mmain\e[38;2;192;197;206m(\e[38;2;192;197;206m)\e[38;2;192;197;206m \e[38;2;192;197;206m{\e[38;2;192;197;206m}\e[38;2;192;197;206m
+        \e[0m\e[0m\e[0m
+        ");
+        assert_eq!(strip_ansi_escapes::strip_str(&output).trim(), "rust\nfn main() {}");
+    }
+
+    #[test]
+    fn test_codeblock_unknown_language_is_not_highlighted() {
+        let input = "```notalanguage\nhello\n```\n  ";
+        let mut state = ParseState::new(Some(80));
+        state.syntax_highlighting = true;
+
+        assert_eq!(render(input, &mut state), render(input, &mut ParseState::new(Some(80))));
+    }
+
+    #[test]
+    fn test_no_color_output_has_no_escapes() {
+        use crate::util::color::{
+            ColorWriter,
+            colors_allowed,
+        };
+
+        let mut state = ParseState::new(Some(80));
+        state.syntax_highlighting = true;
+        let input = "# Title\n**bold** *italic* `code` [link](example.com)\n```rust\nfn main() {}\n```\n  ";
+
+        let mut output = ColorWriter::new(vec![], colors_allowed(Some("1".into()), None, true));
+        output.write_all(render(input, &mut state).as_bytes()).unwrap();
+        let output = String::from_utf8(output.into_inner()).unwrap();
+
+        assert!(!output.contains('\x1b'), "{output:?}");
+        assert!(output.contains("fn main() {}"));
+    }
+
+    #[test]
+    fn test_markdown_stream_writes_each_element_once() {
+        let response = "Some **bold** text\n```\nfn main() {}\n```\ndone  ";
+        let chunks = ["Some **bo", "ld** te", "xt\n``", "`\nfn ma", "in() {}\n`", "``\ndone  "];
+        assert_eq!(chunks.concat(), response);
+
+        let mut stream = MarkdownStream::new();
+        let mut state = ParseState::new(Some(80));
+        let mut output = vec![];
+        for chunk in chunks {
+            stream.push_str(chunk);
+            while stream.render_next(&mut output, &mut state).unwrap() {}
+
+            // An unclosed fence isn't rendered until the rest of it arrives
+            if chunk.ends_with("``") {
+                assert_eq!(stream.pending(), "``");
+            }
+        }
+
+        // Anything written twice would make the output longer than rendering it in one go
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, render(response, &mut ParseState::new(Some(80))));
+        assert_eq!(stream.pending(), "");
+    }
+
+    #[test]
+    fn test_table_truncates_to_terminal_width() {
+        let rows = vec![vec![
+            "header".to_owned(),
+            "a very long cell that will not fit".to_owned(),
+        ]];
+        let widths = table_column_widths(&rows, Some(30));
+        assert_eq!(
+            widths.iter().sum::<usize>() + widths.len() * TABLE_COLUMN_OVERHEAD + 1,
+            30
+        );
+        assert_eq!(
+            format_table_cell(&rows[0][1], widths[1], TableAlignment::Left).width(),
+            widths[1]
+        );
+        assert!(format_table_cell(&rows[0][1], widths[1], TableAlignment::Left).ends_with('…'));
+    }
 }
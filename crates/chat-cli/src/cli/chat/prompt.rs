@@ -60,6 +60,8 @@ pub const COMMANDS: &[&str] = &[
     "/profile delete",
     "/profile rename",
     "/profile set",
+    "/profile export",
+    "/profile import",
     "/prompts",
     "/context",
     "/context help",
@@ -71,6 +73,9 @@ pub const COMMANDS: &[&str] = &[
     "/context rm --global",
     "/context clear",
     "/context clear --global",
+    "/context stats",
+    "/context optimize",
+    "/context diff",
     "/hooks",
     "/hooks help",
     "/hooks add",
@@ -162,9 +167,44 @@ impl PromptCompleter {
     }
 }
 
+/// Session values completed as command arguments, refreshed before every prompt.
+#[derive(Debug, Clone, Default)]
+pub struct CommandValues {
+    /// Paths in the global context, completed for `/context rm --global`.
+    pub global_context_paths: Vec<String>,
+    /// Paths in the current profile's context, completed for `/context rm`.
+    pub profile_context_paths: Vec<String>,
+    /// Names in the tool manager's schema, including MCP tools, completed for `/tools trust`.
+    pub tool_names: Vec<String>,
+}
+
+impl CommandValues {
+    /// Completes the argument at `pos` of a command that takes session values.
+    fn complete(&self, line: &str, pos: usize) -> Option<(usize, Vec<String>)> {
+        let commands: [(&str, &[String]); 4] = [
+            ("/context rm --global ", &self.global_context_paths),
+            ("/context rm ", &self.profile_context_paths),
+            ("/tools trust ", &self.tool_names),
+            ("/tools untrust ", &self.tool_names),
+        ];
+        let (_, values) = commands
+            .into_iter()
+            .find(|(command, _)| line[..pos].starts_with(command))?;
+
+        let (start, word) = extract_word(line, pos, None, |c| c.is_space());
+        let candidates = values
+            .iter()
+            .filter(|value| value.starts_with(word))
+            .cloned()
+            .collect::<Vec<_>>();
+        (!candidates.is_empty()).then_some((start, candidates))
+    }
+}
+
 pub struct ChatCompleter {
     path_completer: PathCompleter,
     prompt_completer: PromptCompleter,
+    command_values: CommandValues,
 }
 
 impl ChatCompleter {
@@ -172,6 +212,7 @@ impl ChatCompleter {
         Self {
             path_completer: PathCompleter::new(),
             prompt_completer: PromptCompleter::new(sender, receiver),
+            command_values: CommandValues::default(),
         }
     }
 }
@@ -185,6 +226,11 @@ impl Completer for ChatCompleter {
         pos: usize,
         _os: &Context<'_>,
     ) -> Result<(usize, Vec<Self::Candidate>), ReadlineError> {
+        // Handle arguments taken from the session, before paths starting with `/` look like commands
+        if let Some(completions) = self.command_values.complete(line, pos) {
+            return Ok(completions);
+        }
+
         let (start, word) = extract_word(line, pos, None, |c| c.is_space());
 
         // Handle command completion
@@ -317,6 +363,11 @@ impl ChatHelper {
 
         self.hinter.update_history(command);
     }
+
+    /// Replaces the session values completed as command arguments
+    pub fn set_command_values(&mut self, values: CommandValues) {
+        self.completer.command_values = values;
+    }
 }
 
 impl Validator for ChatHelper {
@@ -465,6 +516,38 @@ mod tests {
         assert!(completions.is_empty());
     }
 
+    #[test]
+    fn test_chat_completer_command_values() {
+        let (prompt_request_sender, _) = std::sync::mpsc::channel::<Option<String>>();
+        let (_, prompt_response_receiver) = std::sync::mpsc::channel::<Vec<String>>();
+        let mut completer = ChatCompleter::new(prompt_request_sender, prompt_response_receiver);
+        completer.command_values = CommandValues {
+            global_context_paths: vec!["~/.aws/amazonq/rules/**/*.md".to_string()],
+            profile_context_paths: vec!["README.md".to_string(), "/etc/hosts".to_string()],
+            tool_names: vec!["fs_read".to_string(), "git___status".to_string()],
+        };
+        let empty_history = DefaultHistory::new();
+        let os = Context::new(&empty_history);
+
+        let line = "/context rm /e";
+        assert_eq!(
+            completer.complete(line, line.len(), &os).unwrap(),
+            (12, vec!["/etc/hosts".to_string()])
+        );
+
+        let line = "/context rm --global ~";
+        assert_eq!(
+            completer.complete(line, line.len(), &os).unwrap(),
+            (21, vec!["~/.aws/amazonq/rules/**/*.md".to_string()])
+        );
+
+        let line = "/tools trust fs_read gi";
+        assert_eq!(
+            completer.complete(line, line.len(), &os).unwrap(),
+            (21, vec!["git___status".to_string()])
+        );
+    }
+
     #[test]
     fn test_highlight_prompt_basic() {
         let (prompt_request_sender, _) = std::sync::mpsc::channel::<Option<String>>();
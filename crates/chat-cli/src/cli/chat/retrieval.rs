@@ -0,0 +1,307 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{
+    BTreeMap,
+    HashSet,
+};
+use std::hash::{
+    Hash,
+    Hasher,
+};
+use std::sync::Arc;
+
+use eyre::{
+    Result,
+    eyre,
+};
+use semantic_search_client::client::embedder_factory::create_embedder;
+use semantic_search_client::config;
+use semantic_search_client::embedding::{
+    EmbeddingType,
+    TextEmbedderTrait,
+};
+use semantic_search_client::index::VectorIndex;
+use semantic_search_client::processing::text_chunker::chunk_text;
+
+use super::token_counter::TokenCounter;
+
+/// Context files at or below this many tokens are always injected in full.
+pub const RETRIEVAL_MIN_FILE_TOKENS: usize = 1000;
+
+/// Default number of chunks retrieved per prompt when `chat.context.retrieval.topK` is unset.
+pub const DEFAULT_RETRIEVAL_TOP_K: usize = 5;
+
+/// Separator placed between non-adjacent chunks of the same file.
+const CHUNK_SEPARATOR: &str = "\n...\n";
+
+/// Selects the parts of large context files that are relevant to the current prompt.
+///
+/// Small files pass through untouched. Large files are split into chunks, embedded, and indexed
+/// once; every prompt then only injects the `top_k` chunks closest to it. The index is rebuilt
+/// whenever the set of large files or their contents change.
+#[derive(Clone)]
+pub struct ContextRetriever {
+    embedding_type: EmbeddingType,
+    embedder: Option<Arc<dyn TextEmbedderTrait>>,
+    index: Option<Arc<ChunkIndex>>,
+}
+
+impl Default for ContextRetriever {
+    fn default() -> Self {
+        Self::new(EmbeddingType::default())
+    }
+}
+
+impl std::fmt::Debug for ContextRetriever {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextRetriever")
+            .field("embedding_type", &self.embedding_type)
+            .field("embedder", &self.embedder.is_some())
+            .field("indexed_chunks", &self.index.as_ref().map(|index| index.chunks.len()))
+            .finish()
+    }
+}
+
+struct Chunk {
+    file: String,
+    position: usize,
+    text: String,
+}
+
+struct ChunkIndex {
+    fingerprint: u64,
+    chunks: Vec<Chunk>,
+    vectors: VectorIndex,
+}
+
+impl ContextRetriever {
+    /// A retriever that creates an embedder of `embedding_type` the first time it's needed.
+    pub fn new(embedding_type: EmbeddingType) -> Self {
+        Self {
+            embedding_type,
+            embedder: None,
+            index: None,
+        }
+    }
+
+    /// A retriever that uses `embedder` rather than creating its own.
+    pub fn with_embedder(embedder: Arc<dyn TextEmbedderTrait>) -> Self {
+        Self {
+            embedder: Some(embedder),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `files` with every large file replaced by its `top_k` most relevant chunks for
+    /// `query`, kept in document order. Large files without any relevant chunk are reduced to
+    /// their first chunk, so the model still knows they exist.
+    pub async fn retrieve(
+        &mut self,
+        files: &[(String, String)],
+        query: &str,
+        top_k: usize,
+    ) -> Result<Vec<(String, String)>> {
+        let large_files = large_files(files);
+        if large_files.is_empty() || query.trim().is_empty() || top_k == 0 {
+            return Ok(files.to_vec());
+        }
+
+        let (embedder, index) = self.index_for(&large_files).await?;
+        let prompt = query.to_owned();
+        let query = tokio::task::spawn_blocking(move || embedder.embed(&prompt))
+            .await?
+            .map_err(|err| eyre!("failed to embed prompt: {err}"))?;
+        let mut selected: BTreeMap<&str, Vec<&Chunk>> = BTreeMap::new();
+        for (id, _) in index.vectors.search(&query, top_k, top_k.max(100)) {
+            if let Some(chunk) = index.chunks.get(id) {
+                selected.entry(chunk.file.as_str()).or_default().push(chunk);
+            }
+        }
+
+        let large_names = large_files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<HashSet<_>>();
+        Ok(files
+            .iter()
+            .filter_map(|(name, content)| {
+                if !large_names.contains(name.as_str()) {
+                    return Some((name.clone(), content.clone()));
+                }
+
+                let mut chunks = selected.remove(name.as_str()).unwrap_or_else(|| {
+                    index
+                        .chunks
+                        .iter()
+                        .filter(|chunk| chunk.file == *name && chunk.position == 0)
+                        .collect()
+                });
+                if chunks.is_empty() {
+                    return None;
+                }
+                chunks.sort_by_key(|chunk| chunk.position);
+                let content = chunks
+                    .iter()
+                    .map(|chunk| chunk.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(CHUNK_SEPARATOR);
+                Some((name.clone(), content))
+            })
+            .collect())
+    }
+
+    /// Builds the index for the large files among `files` now rather than on the next prompt.
+    pub async fn reindex(&mut self, files: &[(String, String)]) -> Result<()> {
+        let large_files = large_files(files);
+        if !large_files.is_empty() {
+            self.index_for(&large_files).await?;
+        }
+        Ok(())
+    }
+
+    /// The index of `large_files`, reusing the current one if they haven't changed.
+    async fn index_for(
+        &mut self,
+        large_files: &[&(String, String)],
+    ) -> Result<(Arc<dyn TextEmbedderTrait>, Arc<ChunkIndex>)> {
+        let embedder = self.embedder().await?;
+        let fingerprint = fingerprint(large_files);
+        let index = match &self.index {
+            Some(index) if index.fingerprint == fingerprint => index.clone(),
+            _ => {
+                // Embedding is CPU bound, keep it off the runtime's worker threads
+                let files = large_files.iter().map(|file| (*file).clone()).collect::<Vec<_>>();
+                let build_embedder = embedder.clone();
+                let index = tokio::task::spawn_blocking(move || {
+                    ChunkIndex::build(build_embedder.as_ref(), &files, fingerprint)
+                })
+                .await??;
+                let index = Arc::new(index);
+                self.index = Some(index.clone());
+                index
+            },
+        };
+        Ok((embedder, index))
+    }
+
+    /// The embedder used for the index, created on first use.
+    pub async fn embedder(&mut self) -> Result<Arc<dyn TextEmbedderTrait>> {
+        if let Some(embedder) = &self.embedder {
+            return Ok(embedder.clone());
+        }
+
+        let embedding_type = self.embedding_type;
+        let embedder: Arc<dyn TextEmbedderTrait> = tokio::task::spawn_blocking(move || create_embedder(embedding_type))
+            .await?
+            .map_err(|err| eyre!("failed to create embedder: {err}"))?
+            .into();
+
+        self.embedder = Some(embedder.clone());
+        Ok(embedder)
+    }
+}
+
+impl ChunkIndex {
+    fn build(embedder: &dyn TextEmbedderTrait, files: &[(String, String)], fingerprint: u64) -> Result<Self> {
+        let config = config::try_get_config().cloned().unwrap_or_default();
+        let chunks = files
+            .iter()
+            .flat_map(|(name, content)| {
+                chunk_text(content, Some(config.chunk_size), Some(config.chunk_overlap))
+                    .into_iter()
+                    .enumerate()
+                    .map(|(position, text)| Chunk {
+                        file: name.clone(),
+                        position,
+                        text,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let texts = chunks.iter().map(|chunk| chunk.text.clone()).collect::<Vec<_>>();
+        let embeddings = embedder
+            .embed_batch(&texts)
+            .map_err(|err| eyre!("failed to embed context chunks: {err}"))?;
+
+        let vectors = VectorIndex::new(chunks.len());
+        for (id, embedding) in embeddings.iter().enumerate() {
+            vectors.insert(embedding, id);
+        }
+
+        Ok(Self {
+            fingerprint,
+            chunks,
+            vectors,
+        })
+    }
+}
+
+fn large_files(files: &[(String, String)]) -> Vec<&(String, String)> {
+    files
+        .iter()
+        .filter(|(_, content)| TokenCounter::count_tokens(content) > RETRIEVAL_MIN_FILE_TOKENS)
+        .collect()
+}
+
+fn fingerprint(files: &[&(String, String)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn large_file() -> String {
+        [
+            "Kubernetes manifests live in the deploy directory and are rendered with helm. ",
+            "Database migrations are applied with the migrate command before every release. ",
+            "Frontend components are styled with tailwind utility classes in the web package. ",
+        ]
+        .iter()
+        .map(|sentence| sentence.repeat(60))
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_keeps_small_files_and_selects_relevant_chunks() {
+        let files = vec![
+            ("big.md".to_string(), large_file()),
+            ("small.md".to_string(), "short note".to_string()),
+        ];
+        let mut retriever = ContextRetriever::new(EmbeddingType::BM25);
+        let retrieved = retriever
+            .retrieve(&files, "how are database migrations applied", 1)
+            .await
+            .unwrap();
+
+        assert_eq!(retrieved.len(), 2);
+        assert_eq!(retrieved[1], files[1]);
+        let (name, content) = &retrieved[0];
+        assert_eq!(name, "big.md");
+        assert!(content.contains("Database migrations"));
+        assert!(content.len() < files[0].1.len());
+        assert!(!content.contains("Kubernetes"));
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_keeps_head_of_files_without_relevant_chunks() {
+        let other = "Release notes are drafted in the changelog and reviewed by the team. ".repeat(200);
+        let files = vec![
+            ("big.md".to_string(), large_file()),
+            ("other.md".to_string(), other.clone()),
+        ];
+        let mut retriever = ContextRetriever::new(EmbeddingType::BM25);
+        let retrieved = retriever
+            .retrieve(&files, "how are database migrations applied", 1)
+            .await
+            .unwrap();
+
+        assert_eq!(retrieved.len(), 2);
+        let (name, content) = &retrieved[1];
+        assert_eq!(name, "other.md");
+        assert!(other.starts_with(content.as_str()));
+        assert!(content.len() < other.len());
+    }
+}
@@ -35,6 +35,9 @@ pub enum UpdateEventMessage {
     InitStart {
         server_name: String,
     },
+    Reconnected {
+        server_name: String,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -127,6 +130,16 @@ impl Messenger for ServerMessenger {
             .map_err(|e| MessengerError::Custom(e.to_string()))?)
     }
 
+    async fn send_reconnect_msg(&self) -> Result<(), MessengerError> {
+        Ok(self
+            .update_event_sender
+            .send(UpdateEventMessage::Reconnected {
+                server_name: self.server_name.clone(),
+            })
+            .await
+            .map_err(|e| MessengerError::Custom(e.to_string()))?)
+    }
+
     fn duplicate(&self) -> Box<dyn Messenger> {
         Box::new(self.clone())
     }
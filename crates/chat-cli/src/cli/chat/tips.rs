@@ -0,0 +1,131 @@
+//! Tips shown in the greeting box when a chat session starts
+//!
+//! Users can replace the built-in tips with their own by writing one tip per line to
+//! [chat_tips_path]. Blank lines and lines starting with `#` are ignored.
+
+use std::borrow::Cow;
+
+use tracing::warn;
+
+use crate::database::Database;
+use crate::os::Os;
+use crate::util::directories::chat_tips_path;
+
+/// Longest tip, in characters, that fits in the greeting box without crowding it. Longer tips from
+/// the tips file are skipped.
+pub const MAX_TIP_LENGTH: usize = 300;
+
+/// Built-in tips, shown when the user has no tips file.
+const ROTATING_TIPS: [&str; 16] = [
+    color_print::cstr! {"You can resume the last conversation from your current directory by launching with
+    <green!>q chat --resume</green!>"},
+    color_print::cstr! {"Get notified whenever Q CLI finishes responding.
+    Just run <green!>q settings chat.enableNotifications true</green!>"},
+    color_print::cstr! {"You can use
+    <green!>/editor</green!> to edit your prompt with a vim-like experience"},
+    color_print::cstr! {"<green!>/usage</green!> shows you a visual breakdown of your current context window usage"},
+    color_print::cstr! {"Get notified whenever Q CLI finishes responding. Just run <green!>q settings
+    chat.enableNotifications true</green!>"},
+    color_print::cstr! {"You can execute bash commands by typing
+    <green!>!</green!> followed by the command"},
+    color_print::cstr! {"Q can use tools without asking for
+    confirmation every time. Give <green!>/tools trust</green!> a try"},
+    color_print::cstr! {"You can
+    programmatically inject context to your prompts by using hooks. Check out <green!>/context hooks
+    help</green!>"},
+    color_print::cstr! {"You can use <green!>/compact</green!> to replace the conversation
+    history with its summary to free up the context space"},
+    color_print::cstr! {"If you want to file an issue
+    to the Q CLI team, just tell me, or run <green!>q issue</green!>"},
+    color_print::cstr! {"You can enable
+    custom tools with <green!>MCP servers</green!>. Learn more with /help"},
+    color_print::cstr! {"You can
+    specify wait time (in ms) for mcp server loading with <green!>q settings mcp.initTimeout {timeout in
+    int}</green!>. Servers that takes longer than the specified time will continue to load in the background. Use
+    /tools to see pending servers."},
+    color_print::cstr! {"You can see the server load status as well as any
+    warnings or errors associated with <green!>/mcp</green!>"},
+    color_print::cstr! {"Use <green!>/model</green!> to select the model to use for this conversation"},
+    color_print::cstr! {"Set a default model by running <green!>q settings chat.defaultModel MODEL</green!>. Run <green!>/model</green!> to learn more."},
+    color_print::cstr! {"Run <green!>/prompts</green!> to learn how to build & run repeatable workflows"},
+];
+
+/// Load the tips to rotate through, the user's tips file if it has any valid tips and the built-in
+/// tips otherwise.
+pub async fn load_tips(os: &Os) -> Vec<Cow<'static, str>> {
+    let user_tips = match chat_tips_path(os) {
+        Ok(path) if os.fs.exists(&path) => match os.fs.read_to_string(&path).await {
+            Ok(contents) => parse_tips(&contents),
+            Err(err) => {
+                warn!(?err, ?path, "failed to read the tips file");
+                vec![]
+            },
+        },
+        _ => vec![],
+    };
+
+    if user_tips.is_empty() {
+        ROTATING_TIPS.iter().map(|tip| Cow::Borrowed(*tip)).collect()
+    } else {
+        user_tips.into_iter().map(Cow::Owned).collect()
+    }
+}
+
+/// Returns the next tip to show and advances the stored index so the following session shows the
+/// one after it.
+///
+/// The stored index keeps counting past the number of tips, `chat.greeting.mode` uses it as the
+/// number of launches that showed the full greeting.
+pub fn next_tip<'a>(database: &mut Database, tips: &'a [Cow<'static, str>]) -> &'a str {
+    if tips.is_empty() {
+        return "";
+    }
+
+    let count = database.get_rotating_tip().ok().flatten().unwrap_or(0);
+    if let Err(err) = database.set_rotating_tip(count.saturating_add(1)) {
+        warn!(?err, "failed to save the rotating tip index");
+    }
+    &tips[count % tips.len()]
+}
+
+fn parse_tips(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|tip| {
+            let fits = tip.chars().count() <= MAX_TIP_LENGTH;
+            if !fits {
+                warn!(tip, "skipping tip longer than {MAX_TIP_LENGTH} characters");
+            }
+            fits
+        })
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_user_tips_override_builtins_and_rotate() {
+        let mut os = Os::new().await.unwrap();
+        assert_eq!(load_tips(&os).await.len(), ROTATING_TIPS.len());
+
+        let path = chat_tips_path(&os).unwrap();
+        os.fs.create_dir_all(path.parent().unwrap()).await.unwrap();
+        let contents = format!(
+            "# my tips\nfirst tip\n\n  second tip  \n{}\n",
+            "a".repeat(MAX_TIP_LENGTH + 1)
+        );
+        os.fs.write(&path, contents).await.unwrap();
+
+        let tips = load_tips(&os).await;
+        assert_eq!(tips, vec!["first tip", "second tip"]);
+
+        assert_eq!(next_tip(&mut os.database, &tips), "first tip");
+        assert_eq!(next_tip(&mut os.database, &tips), "second tip");
+        assert_eq!(next_tip(&mut os.database, &tips), "first tip");
+    }
+}
@@ -7,6 +7,7 @@ use super::message::{
     UserMessage,
     UserMessageContent,
 };
+use crate::cli::chat::consts::MAX_CHARS;
 use crate::cli::chat::conversation::{
     BackendConversationState,
     ConversationSize,
@@ -72,6 +73,67 @@ impl std::fmt::Display for TokenCount {
     }
 }
 
+/// A token estimate of the conversation kept up to date while a response streams in
+///
+/// Only the number of characters seen so far is kept, so the estimate always matches
+/// [TokenCounter::count_tokens] over everything pushed without recounting it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunningTokenCount {
+    /// Characters in the conversation sent with the request
+    conversation: usize,
+    /// Characters of the response received so far
+    response: usize,
+    overflow_reported: bool,
+}
+
+impl RunningTokenCount {
+    /// Starts counting a response to a conversation of the given size
+    pub fn new(conversation: CharCount) -> Self {
+        Self {
+            conversation: conversation.value(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, chunk: &str) {
+        self.response += chunk.len();
+    }
+
+    /// Counts a JSON value, such as tool use arguments, the same way the conversation history does
+    pub fn push_value(&mut self, value: &serde_json::Value) {
+        self.response += calculate_value_char_count(value);
+    }
+
+    /// The conversation plus the response so far
+    pub fn char_count(&self) -> CharCount {
+        (self.conversation + self.response).into()
+    }
+
+    /// The conversation plus the response so far
+    pub fn token_count(&self) -> TokenCount {
+        self.char_count().into()
+    }
+
+    /// Only the response so far
+    pub fn response_token_count(&self) -> TokenCount {
+        CharCount::from(self.response).into()
+    }
+
+    pub fn exceeds_context_window(&self) -> bool {
+        self.char_count().value() >= MAX_CHARS
+    }
+
+    /// Whether the total has grown past the context window, only true the first time it's
+    /// checked after that happens so it's reported once per response.
+    pub fn take_overflow(&mut self) -> bool {
+        if self.overflow_reported || !self.exceeds_context_window() {
+            return false;
+        }
+        self.overflow_reported = true;
+        true
+    }
+}
+
 pub struct TokenCounter;
 
 impl TokenCounter {
@@ -81,6 +143,9 @@ impl TokenCounter {
     /// Currently uses a simple heuristic: content length / TOKEN_TO_CHAR_RATIO
     ///
     /// Rounds up to the nearest multiple of 10 to avoid giving users a false sense of precision.
+    ///
+    /// Only the byte length is read, so counting is constant time and not worth caching: hashing
+    /// the content to look up a cached count would cost more than the count itself.
     pub fn count_tokens(content: &str) -> usize {
         Self::count_tokens_char_count(content.len())
     }
@@ -190,6 +255,49 @@ mod tests {
         assert_eq!(count, (text.len() / 3 + 5) / 10 * 10);
     }
 
+    #[test]
+    fn test_running_token_count() {
+        let chunks = [
+            "Sure, ",
+            "here is ",
+            "the updated function",
+            ":\n\n```rust\n",
+            "fn main() {}\n```",
+            "",
+        ];
+        let mut running = RunningTokenCount::default();
+        let mut full = String::new();
+        for chunk in chunks.iter().cycle().take(chunks.len() * 20) {
+            running.push(chunk);
+            full.push_str(chunk);
+            assert_eq!(running.char_count().value(), full.len());
+            assert_eq!(running.token_count().value(), TokenCounter::count_tokens(&full));
+        }
+
+        // Tool arguments count their values, not the serialized JSON
+        running.push_value(&serde_json::json!({ "path": "src/main.rs", "lines": [1, 2] }));
+        assert_eq!(running.char_count().value(), full.len() + 13);
+    }
+
+    #[test]
+    fn test_running_token_count_includes_conversation() {
+        let mut running = RunningTokenCount::new(CharCount::from(MAX_CHARS - 100));
+        running.push(&"a".repeat(60));
+        assert_eq!(running.char_count().value(), MAX_CHARS - 40);
+        assert_eq!(
+            running.response_token_count().value(),
+            TokenCounter::count_tokens(&"a".repeat(60))
+        );
+        assert!(!running.take_overflow());
+
+        running.push(&"a".repeat(40));
+        assert!(running.exceeds_context_window());
+        assert!(running.take_overflow());
+        // Reported once per response
+        running.push("more");
+        assert!(!running.take_overflow());
+    }
+
     #[test]
     fn test_calculate_value_char_count() {
         // Test simple types
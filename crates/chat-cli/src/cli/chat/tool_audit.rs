@@ -0,0 +1,259 @@
+//! An audit log of the tools executed in chat
+//!
+//! Each tool invocation appends one JSON line to `tool_audit.log` in the logs directory, kept
+//! apart from the chat transcript and the application log so it can be collected on its own. The
+//! log is off unless `chat.toolAudit.enabled` is set, and tool arguments are redacted unless
+//! `chat.toolAudit.redactArgs` is set to false. A full log is renamed to `tool_audit.log.1`,
+//! shifting older logs up by one, entries are never deleted.
+
+use std::fs::File;
+use std::io::{
+    ErrorKind,
+    Write,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::Duration;
+
+use serde::Serialize;
+use sha2::{
+    Digest,
+    Sha256,
+};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::warn;
+
+use super::tools::QueuedTool;
+use crate::database::settings::Setting;
+use crate::os::Os;
+use crate::util::directories::logs_dir;
+use crate::util::redact::{
+    RedactionPolicy,
+    redact,
+};
+
+const TOOL_AUDIT_LOG_FILE: &str = "tool_audit.log";
+const MAX_TOOL_AUDIT_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// One executed tool
+#[derive(Debug, Serialize)]
+pub struct ToolAuditEntry {
+    /// RFC 3339 time the tool finished at
+    pub timestamp: String,
+    pub tool_name: String,
+    pub tool_use_id: String,
+    /// SHA-256 of the arguments as logged, so calls can be correlated without reading the
+    /// arguments themselves
+    pub args_digest: String,
+    pub args: String,
+    pub success: bool,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolAudit {
+    path: PathBuf,
+    redact_args: bool,
+}
+
+impl ToolAudit {
+    /// The audit log configured in the settings, `None` when it is disabled
+    pub fn from_settings(os: &Os) -> Option<Self> {
+        let settings = &os.database.settings;
+        if !settings.get_bool(Setting::ChatToolAuditEnabled).unwrap_or(false) {
+            return None;
+        }
+
+        let path = match logs_dir() {
+            Ok(dir) => dir.join(TOOL_AUDIT_LOG_FILE),
+            Err(err) => {
+                warn!(
+                    ?err,
+                    "Unable to find the logs directory, the tool audit log is disabled"
+                );
+                return None;
+            },
+        };
+
+        Some(Self {
+            path,
+            redact_args: settings.get_bool(Setting::ChatToolAuditRedactArgs).unwrap_or(true),
+        })
+    }
+
+    pub fn new(path: impl Into<PathBuf>, redact_args: bool) -> Self {
+        Self {
+            path: path.into(),
+            redact_args,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entry(&self, tool: &QueuedTool, success: bool, duration: Duration) -> ToolAuditEntry {
+        let args = match self.redact_args {
+            true => redact(&tool.args.to_string(), RedactionPolicy::ALL),
+            false => tool.args.to_string(),
+        };
+        let args_digest = hex::encode(Sha256::digest(args.as_bytes()));
+
+        ToolAuditEntry {
+            timestamp: OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default(),
+            tool_name: tool.name.clone(),
+            tool_use_id: tool.id.clone(),
+            args_digest,
+            args,
+            success,
+            duration_ms: duration.as_millis(),
+        }
+    }
+
+    /// Append an entry for `tool`, failing to write is logged rather than interrupting the chat
+    pub fn record(&self, tool: &QueuedTool, success: bool, duration: Duration) {
+        if let Err(err) = self.write(&self.entry(tool, success, duration)) {
+            warn!(?err, path = ?self.path, "Failed to write to the tool audit log");
+        }
+    }
+
+    fn write(&self, entry: &ToolAuditEntry) -> eyre::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        rotate_if_full(&self.path, MAX_TOOL_AUDIT_FILE_SIZE)?;
+        let mut file = open_audit_file(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Open the log for appending, on unix only the owner can access it
+fn open_audit_file(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = File::options().append(true).create(true).open(path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600)).ok();
+    }
+
+    Ok(file)
+}
+
+/// Once the log at `path` reaches `max_size`, rename it to `<path>.1`, first renaming each older
+/// `<path>.<n>` to `<path>.<n + 1>`
+fn rotate_if_full(path: &Path, max_size: u64) -> std::io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.len() >= max_size => (),
+        Ok(_) => return Ok(()),
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    }
+
+    let rotated = |n: usize| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    };
+
+    let mut oldest = 0;
+    while rotated(oldest + 1).exists() {
+        oldest += 1;
+    }
+    for n in (1..=oldest).rev() {
+        std::fs::rename(rotated(n), rotated(n + 1))?;
+    }
+    std::fs::rename(path, rotated(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::cli::chat::tools::Tool;
+    use crate::cli::chat::tools::fs_read::FsRead;
+
+    fn queued_tool(id: &str, args: serde_json::Value) -> QueuedTool {
+        QueuedTool {
+            id: id.to_string(),
+            name: "fs_read".to_string(),
+            accepted: true,
+            tool: Tool::FsRead(serde_json::from_value::<FsRead>(args.clone()).unwrap()),
+            args,
+        }
+    }
+
+    #[test]
+    fn test_entry_per_tool_call() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = ToolAudit::new(dir.path().join(TOOL_AUDIT_LOG_FILE), true);
+
+        let first = queued_tool("tool_1", json!({ "mode": "Line", "path": "/tmp/token=abc123" }));
+        let second = queued_tool("tool_2", json!({ "mode": "Directory", "path": "/tmp" }));
+        audit.record(&first, true, Duration::from_millis(12));
+        audit.record(&second, false, Duration::from_millis(3));
+
+        let log = std::fs::read_to_string(audit.path()).unwrap();
+        let entries = log
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0]["tool_name"], "fs_read");
+        assert_eq!(entries[0]["tool_use_id"], "tool_1");
+        assert_eq!(entries[0]["success"], true);
+        assert_eq!(entries[0]["duration_ms"], 12);
+        let logged_args = entries[0]["args"].as_str().unwrap();
+        assert!(!logged_args.contains("abc123"));
+        assert_eq!(
+            entries[0]["args_digest"],
+            hex::encode(Sha256::digest(logged_args.as_bytes()))
+        );
+        assert!(OffsetDateTime::parse(entries[0]["timestamp"].as_str().unwrap(), &Rfc3339).is_ok());
+
+        assert_eq!(entries[1]["tool_use_id"], "tool_2");
+        assert_eq!(entries[1]["success"], false);
+    }
+
+    #[test]
+    fn test_unredacted_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = ToolAudit::new(dir.path().join(TOOL_AUDIT_LOG_FILE), false);
+        let tool = queued_tool("tool_1", json!({ "mode": "Line", "path": "/tmp/token=abc123" }));
+
+        let entry = audit.entry(&tool, true, Duration::ZERO);
+        assert_eq!(entry.args, tool.args.to_string());
+        assert_eq!(entry.args_digest, hex::encode(Sha256::digest(entry.args.as_bytes())));
+    }
+
+    #[test]
+    fn test_rotate_keeps_every_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TOOL_AUDIT_LOG_FILE);
+        let rotated = |n: usize| dir.path().join(format!("{TOOL_AUDIT_LOG_FILE}.{n}"));
+
+        // Nothing to rotate yet
+        rotate_if_full(&path, 4).unwrap();
+        std::fs::write(&path, "one").unwrap();
+        rotate_if_full(&path, 4).unwrap();
+        assert!(path.exists());
+
+        std::fs::write(&path, "first").unwrap();
+        rotate_if_full(&path, 4).unwrap();
+        std::fs::write(&path, "second").unwrap();
+        rotate_if_full(&path, 4).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(rotated(1)).unwrap(), "second");
+        assert_eq!(std::fs::read_to_string(rotated(2)).unwrap(), "first");
+    }
+}
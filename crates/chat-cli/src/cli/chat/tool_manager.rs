@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::{
     HashMap,
     HashSet,
@@ -278,12 +279,14 @@ impl ToolManagerBuilder {
             })
             .collect();
 
+        let log_payloads = os.database.settings.get_bool(Setting::McpLogPayloads).unwrap_or(false);
         let pre_initialized = enabled_servers
             .into_iter()
             .map(|(server_name, server_config)| {
                 let snaked_cased_name = server_name.to_case(convert_case::Case::Snake);
                 let sanitized_server_name = sanitize_name(snaked_cased_name, &regex, &mut hasher);
-                let custom_tool_client = CustomToolClient::from_config(sanitized_server_name.clone(), server_config);
+                let custom_tool_client =
+                    CustomToolClient::from_config(sanitized_server_name.clone(), server_config, log_payloads);
                 (sanitized_server_name, custom_tool_client)
             })
             .collect::<Vec<(String, _)>>();
@@ -576,6 +579,11 @@ impl ToolManagerBuilder {
                         pending_clone.write().await.insert(server_name.clone());
                         loading_servers.insert(server_name, std::time::Instant::now());
                     },
+                    UpdateEventMessage::Reconnected { server_name } => {
+                        // The tools list that follows replaces the tools of the exited process
+                        warn!("Server {server_name} exited and was restarted");
+                        loading_servers.insert(server_name, std::time::Instant::now());
+                    },
                 }
             }
         });
@@ -729,6 +737,7 @@ enum OutOfSpecName {
     TooLong(String),
     IllegalChar(String),
     EmptyDescription(String),
+    InvalidSchema(String, String),
 }
 
 type NewToolSpecs = Arc<Mutex<HashMap<String, (HashMap<String, String>, Vec<ToolSpec>)>>>;
@@ -862,6 +871,12 @@ impl ToolManager {
                 });
             }
 
+            for spec in tool_specs.values() {
+                for warning in spec.validate()? {
+                    warn!(tool = %spec.name, "{warning}");
+                }
+            }
+
             tool_specs
         };
         let load_tools = self
@@ -1314,6 +1329,17 @@ async fn process_tool_specs(
             out_of_spec_tool_names.push(OutOfSpecName::EmptyDescription(spec.name.clone()));
             continue;
         }
+        match spec.input_schema.validate() {
+            Ok(warnings) => {
+                for warning in warnings {
+                    warn!(server = %server_name, tool = %spec.name, "unexpected input schema: {warning}");
+                }
+            },
+            Err(reason) => {
+                out_of_spec_tool_names.push(OutOfSpecName::InvalidSchema(spec.name.clone(), reason));
+                continue;
+            },
+        }
         if sn != spec.name {
             tn_map.insert(
                 full_name.clone(),
@@ -1342,18 +1368,22 @@ async fn process_tool_specs(
                 "The following tools are out of spec. They will be excluded from the list of available tools:\n",
             ),
             |mut acc, name| {
-                let (tool_name, msg) = match name {
+                let (tool_name, msg): (&str, Cow<'_, str>) = match name {
                     OutOfSpecName::TooLong(tool_name) => (
                         tool_name.as_str(),
-                        "tool name exceeds max length of 64 when combined with server name",
+                        "tool name exceeds max length of 64 when combined with server name".into(),
                     ),
                     OutOfSpecName::IllegalChar(tool_name) => (
                         tool_name.as_str(),
-                        "tool name must be compliant with ^[a-zA-Z][a-zA-Z0-9_]*$",
+                        "tool name must be compliant with ^[a-zA-Z][a-zA-Z0-9_]*$".into(),
                     ),
                     OutOfSpecName::EmptyDescription(tool_name) => {
-                        (tool_name.as_str(), "tool schema contains empty description")
+                        (tool_name.as_str(), "tool schema contains empty description".into())
                     },
+                    OutOfSpecName::InvalidSchema(tool_name, reason) => (
+                        tool_name.as_str(),
+                        Cow::Owned(format!("tool input schema is invalid: {reason}")),
+                    ),
                 };
                 acc.push_str(format!(" - {} ({})\n", tool_name, msg).as_str());
                 acc
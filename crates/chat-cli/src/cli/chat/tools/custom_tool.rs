@@ -26,6 +26,7 @@ use crate::mcp_client::{
     MessageContent,
     Messenger,
     PromptGet,
+    RestartPolicy,
     ServerCapabilities,
     StdioTransport,
     ToolCallResult,
@@ -44,6 +45,9 @@ pub struct CustomToolConfig {
     pub timeout: u64,
     #[serde(default)]
     pub disabled: bool,
+    /// Whether and how the server is relaunched if its process exits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RestartPolicy>,
 }
 
 pub fn default_timeout() -> u64 {
@@ -61,13 +65,14 @@ pub enum CustomToolClient {
 
 impl CustomToolClient {
     // TODO: add support for http transport
-    pub fn from_config(server_name: String, config: CustomToolConfig) -> Result<Self> {
+    pub fn from_config(server_name: String, config: CustomToolConfig, log_payloads: bool) -> Result<Self> {
         let CustomToolConfig {
             command,
             args,
             env,
             timeout,
             disabled: _,
+            restart,
         } = config;
         let mcp_client_config = McpClientConfig {
             server_name: server_name.clone(),
@@ -79,6 +84,8 @@ impl CustomToolClient {
                "version": "1.0.0"
             }),
             env,
+            log_payloads,
+            restart: restart.unwrap_or_default(),
         };
         let client = McpClient::<JsonRpcStdioTransport>::from_config(mcp_client_config)?;
         Ok(CustomToolClient::Stdio {
@@ -101,6 +108,7 @@ impl CustomToolClient {
                 // We'll need to first initialize. This is the handshake every client and server
                 // needs to do before proceeding to anything else
                 let cap = client.init().await?;
+                client.supervise();
                 // We'll be scrapping this for background server load: https://github.com/aws/amazon-q-developer-cli/issues/1466
                 // So don't worry about the tidiness for now
                 server_capabilities.write().await.replace(cap);
@@ -1,6 +1,9 @@
 use std::io::Write;
-use std::path::Path;
-use std::sync::LazyLock;
+use std::path::{
+    Component,
+    Path,
+    PathBuf,
+};
 
 use crossterm::queue;
 use crossterm::style::{
@@ -14,10 +17,9 @@ use eyre::{
     eyre,
 };
 use serde::Deserialize;
+use serde_json::Value;
 use similar::DiffableStr;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
 use syntect::util::{
     LinesWithEndings,
     as_24_bit_terminal_escaped,
@@ -29,15 +31,15 @@ use tracing::{
 
 use super::{
     InvokeOutput,
+    SYNTAX_SET,
+    THEME_SET,
     format_path,
     sanitize_path_tool_arg,
     supports_truecolor,
 };
+use crate::database::settings::Setting;
 use crate::os::Os;
 
-static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
-static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
-
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "command")]
 pub enum FsWrite {
@@ -284,7 +286,16 @@ impl FsWrite {
             },
         }
 
-        Ok(())
+        check_allowed_roots(os, self.path())
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            FsWrite::Create { path, .. }
+            | FsWrite::StrReplace { path, .. }
+            | FsWrite::Insert { path, .. }
+            | FsWrite::Append { path, .. } => path,
+        }
     }
 
     fn print_relative_path(&self, os: &Os, output: &mut impl Write) -> Result<()> {
@@ -635,6 +646,68 @@ fn syntect_to_crossterm_color(syntect: syntect::highlighting::Color) -> style::C
     }
 }
 
+/// Checks that `path` is inside one of the directories in [Setting::ChatToolsFsAllowedRoots], or
+/// inside the current directory when the setting isn't set.
+fn check_allowed_roots(os: &Os, path: &str) -> Result<()> {
+    let cwd = sanitize_path_tool_arg(os, os.env.current_dir()?);
+    let resolve = |path: &str| resolve_write_path(&cwd.join(sanitize_path_tool_arg(os, path)));
+
+    let roots = match os.database.settings.get(Setting::ChatToolsFsAllowedRoots) {
+        Some(Value::Array(roots)) => roots.iter().filter_map(Value::as_str).map(resolve).collect::<Vec<_>>(),
+        Some(Value::String(root)) => vec![resolve(root)],
+        _ => vec![resolve_write_path(&cwd)],
+    };
+
+    if roots.iter().any(|root| resolve(path).starts_with(root)) {
+        return Ok(());
+    }
+
+    bail!(
+        "Writing to {path} is not allowed, files can only be written inside of: {}. \
+         The user can change this with the {} setting",
+        roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        Setting::ChatToolsFsAllowedRoots,
+    )
+}
+
+/// Resolves `.` and `..` components, and symlinks in the part of `path` that exists, so a path
+/// can't leave an allowed root through either.
+fn resolve_write_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            Component::CurDir => (),
+            component => normalized.push(component),
+        }
+    }
+
+    // The path has already been through [sanitize_path_tool_arg], so it can be used directly
+    let mut existing = normalized.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            },
+            _ => break,
+        }
+    }
+
+    let resolved = existing.canonicalize().ok().map(|mut resolved| {
+        resolved.extend(missing.iter().rev());
+        resolved
+    });
+    resolved.unwrap_or(normalized)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -722,6 +795,35 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_fs_write_allowed_roots() {
+        let mut os = setup_test_directory().await;
+        let create = |path: &str| {
+            serde_json::from_value::<FsWrite>(serde_json::json!({
+                "path": path,
+                "command": "create",
+                "file_text": "hello",
+            }))
+            .unwrap()
+        };
+
+        // Defaults to the current directory
+        create("/aaaa1/new-file").validate(&os).await.unwrap();
+
+        os.database
+            .settings
+            .set(Setting::ChatToolsFsAllowedRoots, serde_json::json!(["/aaaa1"]))
+            .await
+            .unwrap();
+        create("/aaaa1/new-file").validate(&os).await.unwrap();
+        create("/aaaa1/bbbb1/new-dir/new-file").validate(&os).await.unwrap();
+
+        let err = create("/aaaa2/new-file").validate(&os).await.unwrap_err();
+        assert!(err.to_string().contains("chat.tools.fs.allowedRoots"), "{err}");
+        create("/aaaa1/../aaaa2/new-file").validate(&os).await.unwrap_err();
+        create(TEST_FILE_PATH).validate(&os).await.unwrap_err();
+    }
+
     #[tokio::test]
     async fn test_fs_write_tool_create() {
         let os = setup_test_directory().await;
@@ -24,6 +24,10 @@ use super::{
 };
 use crate::cli::chat::token_counter::TokenCounter;
 use crate::os::Os;
+use crate::util::redact::{
+    RedactionPolicy,
+    redact,
+};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct GhIssue {
@@ -36,112 +40,122 @@ pub struct GhIssue {
     pub context: Option<GhIssueContext>,
 }
 
+/// Details of the chat session that can be attached to an issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueDetail {
+    /// Which tools are trusted
+    ChatSettings,
+    /// Ids of the requests that failed
+    FailedRequestIds,
+    /// The profile and context file paths with their sizes, not their contents
+    Context,
+}
+
+/// The details attached to issues by default, anything not listed here is left out
+pub const ATTACHED_DETAILS: &[IssueDetail] = &[
+    IssueDetail::ChatSettings,
+    IssueDetail::FailedRequestIds,
+    IssueDetail::Context,
+];
+
+/// Chat session state attached to an issue, built with [GhIssueContext::builder] so every field
+/// that ends up in the issue is redacted
 #[derive(Debug, Clone)]
 pub struct GhIssueContext {
-    pub context_manager: Option<ContextManager>,
-    pub transcript: VecDeque<String>,
-    pub failed_request_ids: Vec<String>,
-    pub tool_permissions: HashMap<String, ToolPermission>,
+    context_manager: Option<ContextManager>,
+    transcript: VecDeque<String>,
+    failed_request_ids: Vec<String>,
+    tool_permissions: HashMap<String, ToolPermission>,
+    details: Vec<IssueDetail>,
 }
 
-/// Max amount of characters to include in the transcript.
-const MAX_TRANSCRIPT_CHAR_LEN: usize = 3_000;
+#[derive(Debug, Default)]
+pub struct GhIssueContextBuilder {
+    context_manager: Option<ContextManager>,
+    transcript: VecDeque<String>,
+    failed_request_ids: Vec<String>,
+    tool_permissions: HashMap<String, ToolPermission>,
+    details: Option<Vec<IssueDetail>>,
+}
 
-impl GhIssue {
-    pub async fn invoke(&self, os: &Os, _updates: impl Write) -> Result<InvokeOutput> {
-        let Some(context) = self.context.as_ref() else {
-            return Err(eyre!(
-                "report_issue: Required tool context (GhIssueContext) not set by the program."
-            ));
-        };
+impl GhIssueContextBuilder {
+    pub fn context_manager(mut self, context_manager: Option<ContextManager>) -> Self {
+        self.context_manager = context_manager;
+        self
+    }
 
-        // Prepare additional details from the chat session
-        let additional_environment = [
-            Self::get_chat_settings(context),
-            Self::get_request_ids(context),
-            Self::get_context(os, context).await,
-        ]
-        .join("\n\n");
-
-        // Add chat history to the actual behavior text.
-        let actual_behavior = self.actual_behavior.as_ref().map_or_else(
-            || Self::get_transcript(context),
-            |behavior| format!("{behavior}\n\n{}\n", Self::get_transcript(context)),
-        );
+    pub fn transcript(mut self, transcript: VecDeque<String>) -> Self {
+        self.transcript = transcript;
+        self
+    }
 
-        let _ = IssueCreator {
-            title: Some(self.title.clone()),
-            expected_behavior: self.expected_behavior.clone(),
-            actual_behavior: Some(actual_behavior),
-            steps_to_reproduce: self.steps_to_reproduce.clone(),
-            additional_environment: Some(additional_environment),
-        }
-        .create_url(os)
-        .await
-        .wrap_err("failed to invoke gh issue tool");
+    pub fn failed_request_ids(mut self, failed_request_ids: Vec<String>) -> Self {
+        self.failed_request_ids = failed_request_ids;
+        self
+    }
 
-        Ok(Default::default())
+    pub fn tool_permissions(mut self, tool_permissions: HashMap<String, ToolPermission>) -> Self {
+        self.tool_permissions = tool_permissions;
+        self
     }
 
-    pub fn set_context(&mut self, context: GhIssueContext) {
-        self.context = Some(context);
+    /// Attach only these details instead of [ATTACHED_DETAILS]
+    pub fn details(mut self, details: &[IssueDetail]) -> Self {
+        self.details.replace(details.to_vec());
+        self
     }
 
-    fn get_transcript(context: &GhIssueContext) -> String {
-        let mut transcript_str = String::from("```\n[chat-transcript]\n");
-        let mut is_truncated = false;
-        let transcript: Vec<String> = context.transcript
-            .iter()
-            .rev() // To take last N items
-            .scan(0, |user_msg_char_count, line| {
-                if *user_msg_char_count >= MAX_TRANSCRIPT_CHAR_LEN {
-                        is_truncated = true;
-                    return None;
-                }
-                let remaining_chars = MAX_TRANSCRIPT_CHAR_LEN - *user_msg_char_count;
-                let trimmed_line = if line.len() > remaining_chars {
-                    &line[..remaining_chars]
-                } else {
-                    line
-                };
-                *user_msg_char_count += trimmed_line.len();
-
-                // backticks will mess up the markdown
-                let text = trimmed_line.replace("```", r"\```");
-                Some(text)
-            })
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev() // Now return items to the proper order
-            .collect();
-
-        if !transcript.is_empty() {
-            transcript_str.push_str(&transcript.join("\n\n"));
-        } else {
-            transcript_str.push_str("No chat history found.");
+    pub fn build(self) -> GhIssueContext {
+        GhIssueContext {
+            context_manager: self.context_manager,
+            transcript: self
+                .transcript
+                .iter()
+                .map(|line| redact(line, RedactionPolicy::ALL))
+                .collect(),
+            failed_request_ids: self
+                .failed_request_ids
+                .iter()
+                .map(|id| redact(id, RedactionPolicy::ALL))
+                .collect(),
+            tool_permissions: self.tool_permissions,
+            details: self.details.unwrap_or_else(|| ATTACHED_DETAILS.to_vec()),
         }
+    }
+}
 
-        if is_truncated {
-            transcript_str.push_str("\n\n(...truncated)");
+impl GhIssueContext {
+    pub fn builder() -> GhIssueContextBuilder {
+        GhIssueContextBuilder::default()
+    }
+
+    /// The allowed details as sections of the issue's environment, redacted
+    pub async fn environment(&self, os: &Os) -> String {
+        let mut sections = Vec::new();
+        for detail in &self.details {
+            sections.push(match detail {
+                IssueDetail::ChatSettings => self.chat_settings(),
+                IssueDetail::FailedRequestIds => self.request_ids(),
+                IssueDetail::Context => self.context_files(os).await,
+            });
         }
-        transcript_str.push_str("\n```");
-        transcript_str
+        redact(&sections.join("\n\n"), RedactionPolicy::ALL)
     }
 
-    fn get_request_ids(context: &GhIssueContext) -> String {
+    fn request_ids(&self) -> String {
         format!(
             "[chat-failed_request_ids]\n{}",
-            if context.failed_request_ids.is_empty() {
+            if self.failed_request_ids.is_empty() {
                 "none".to_string()
             } else {
-                context.failed_request_ids.join("\n")
+                self.failed_request_ids.join("\n")
             }
         )
     }
 
-    async fn get_context(os: &Os, context: &GhIssueContext) -> String {
+    async fn context_files(&self, os: &Os) -> String {
         let mut os_str = "[chat-context]\n".to_string();
-        let Some(os_manager) = &context.context_manager else {
+        let Some(os_manager) = &self.context_manager else {
             os_str.push_str("No context available.");
             return os_str;
         };
@@ -193,15 +207,47 @@ impl GhIssue {
         os_str
     }
 
-    fn get_chat_settings(context: &GhIssueContext) -> String {
+    fn chat_settings(&self) -> String {
         let mut result_str = "[chat-settings]\n".to_string();
         result_str.push_str("\n\n[chat-trusted_tools]");
-        for (tool, permission) in context.tool_permissions.iter() {
+        for (tool, permission) in self.tool_permissions.iter() {
             result_str.push_str(&format!("\n{tool}={}", permission.trusted));
         }
 
         result_str
     }
+}
+
+impl GhIssue {
+    pub async fn invoke(&self, os: &Os, _updates: impl Write) -> Result<InvokeOutput> {
+        let Some(context) = self.context.as_ref() else {
+            return Err(eyre!(
+                "report_issue: Required tool context (GhIssueContext) not set by the program."
+            ));
+        };
+
+        // Prepare additional details from the chat session
+        let additional_environment = context.environment(os).await;
+
+        let _ = IssueCreator {
+            title: Some(self.title.clone()),
+            expected_behavior: self.expected_behavior.clone(),
+            actual_behavior: self.actual_behavior.clone(),
+            steps_to_reproduce: self.steps_to_reproduce.clone(),
+            additional_environment: Some(additional_environment),
+            // Accepting the tool use agrees to sharing the chat history
+            transcript: Some(context.transcript.clone()),
+        }
+        .create_url(os)
+        .await
+        .wrap_err("failed to invoke gh issue tool");
+
+        Ok(Default::default())
+    }
+
+    pub fn set_context(&mut self, context: GhIssueContext) {
+        self.context = Some(context);
+    }
 
     pub fn queue_description(&self, output: &mut impl Write) -> Result<()> {
         Ok(queue!(
@@ -217,3 +263,47 @@ impl GhIssue {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::chat::util::test::create_test_context_manager;
+
+    #[tokio::test]
+    async fn test_context_is_redacted() {
+        let os = Os::new().await.unwrap();
+        // The real home directory and username are redacted, so the expected values are computed
+        // with the same inputs, the replacements themselves are tested in util::redact
+        let home = dirs::home_dir().unwrap().to_string_lossy().into_owned();
+        let username = whoami::username();
+        let global_path = format!("{home}/notes/*.md");
+        let transcript_line = format!("> AWS_SECRET_ACCESS_KEY=abc123 cat /Users/{username}/.aws/config");
+
+        let mut context_manager = create_test_context_manager(None).await.unwrap();
+        context_manager.global_config.paths.push(global_path.clone());
+        let context = GhIssueContext::builder()
+            .context_manager(Some(context_manager))
+            .transcript(VecDeque::from([transcript_line.clone()]))
+            .build();
+
+        let environment = context.environment(&os).await;
+        assert!(environment.contains(&redact(
+            &format!("global_context=\n{global_path}"),
+            RedactionPolicy::ALL
+        )));
+        assert_eq!(context.transcript, [redact(&transcript_line, RedactionPolicy::ALL)]);
+        assert!(!context.transcript[0].contains("abc123"));
+    }
+
+    #[tokio::test]
+    async fn test_only_allowed_details_are_attached() {
+        let os = Os::new().await.unwrap();
+        let context = GhIssueContext::builder()
+            .failed_request_ids(vec!["request-1".to_string()])
+            .details(&[IssueDetail::FailedRequestIds])
+            .build();
+
+        let environment = context.environment(&os).await;
+        assert_eq!(environment, "[chat-failed_request_ids]\nrequest-1");
+    }
+}
@@ -16,6 +16,7 @@ use std::path::{
     Path,
     PathBuf,
 };
+use std::sync::LazyLock;
 
 use crossterm::queue;
 use crossterm::style::{
@@ -29,11 +30,14 @@ use eyre::Result;
 use fs_read::FsRead;
 use fs_write::FsWrite;
 use gh_issue::GhIssue;
+use globset::Glob;
 use knowledge::Knowledge;
 use serde::{
     Deserialize,
     Serialize,
 };
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
 use thinking::Thinking;
 use use_aws::UseAws;
 
@@ -41,6 +45,9 @@ use super::consts::MAX_TOOL_RESPONSE_SIZE;
 use super::util::images::RichImageBlocks;
 use crate::os::Os;
 
+pub static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+pub static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
 /// Represents an executable tool use.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
@@ -140,6 +147,12 @@ impl Tool {
     }
 }
 
+/// Whether `tool_name` matches a `--trust-tools` entry, either exactly or as a glob such as
+/// `fs___*`
+pub fn matches_trust_pattern(pattern: &str, tool_name: &str) -> bool {
+    pattern == tool_name || Glob::new(pattern).is_ok_and(|glob| glob.compile_matcher().is_match(tool_name))
+}
+
 #[derive(Debug, Clone)]
 pub struct ToolPermission {
     pub trusted: bool,
@@ -168,7 +181,7 @@ impl ToolPermissions {
 
     pub fn is_trusted(&mut self, tool_name: &str) -> bool {
         // Check if we should trust from pending patterns first
-        if self.should_trust_from_pending(tool_name) {
+        if !self.permissions.contains_key(tool_name) && self.should_trust_from_pending(tool_name) {
             self.trust_tool(tool_name);
             self.pending_trusted_tools.remove(tool_name);
         }
@@ -219,13 +232,34 @@ impl ToolPermissions {
 
     /// Check if a tool should be trusted based on preceding trust declarations
     pub fn should_trust_from_pending(&self, tool_name: &str) -> bool {
-        // Check for exact match
-        self.pending_trusted_tools.contains(tool_name)
+        self.pending_trusted_tools
+            .iter()
+            .any(|pattern| matches_trust_pattern(pattern, tool_name))
+    }
+
+    /// Trusts the tools in `tool_names` that match one of `patterns` and untrusts the rest.
+    ///
+    /// The patterns are also kept as pending so that MCP tools loaded later are trusted when they
+    /// match.
+    pub fn apply_trust_patterns<'a>(&mut self, patterns: &[String], tool_names: impl IntoIterator<Item = &'a str>) {
+        for pattern in patterns {
+            if !pattern.is_empty() {
+                self.add_pending_trust_tool(pattern.clone());
+            }
+        }
+
+        for tool_name in tool_names {
+            if patterns.iter().any(|pattern| matches_trust_pattern(pattern, tool_name)) {
+                self.trust_tool(tool_name);
+            } else {
+                self.untrust_tool(tool_name);
+            }
+        }
     }
 
     pub fn has(&mut self, tool_name: &str) -> bool {
         // Check if we should trust from pending tools first
-        if self.should_trust_from_pending(tool_name) {
+        if !self.permissions.contains_key(tool_name) && self.should_trust_from_pending(tool_name) {
             self.trust_tool(tool_name);
             self.pending_trusted_tools.remove(tool_name);
         }
@@ -318,12 +352,179 @@ pub struct QueuedTool {
     pub name: String,
     pub accepted: bool,
     pub tool: Tool,
+    /// The arguments as the model sent them
+    pub args: serde_json::Value,
+}
+
+impl ToolSpec {
+    /// Checks that the input schema is one the model can be given, see [InputSchema::validate].
+    pub fn validate(&self) -> Result<Vec<String>> {
+        self.input_schema
+            .validate()
+            .map_err(|reason| eyre::eyre!("Tool '{}' has an invalid input schema: {}", self.name, reason))
+    }
 }
 
 /// The schema specification describing a tool's fields.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputSchema(pub serde_json::Value);
 
+const JSON_SCHEMA_TYPES: [&str; 7] = ["object", "array", "string", "number", "integer", "boolean", "null"];
+
+/// Keywords defined by JSON Schema (draft 4 through 2020-12), anything else in a schema is most
+/// likely a typo.
+const JSON_SCHEMA_KEYWORDS: [&str; 61] = [
+    "$schema",
+    "$id",
+    "id",
+    "$ref",
+    "$defs",
+    "definitions",
+    "$comment",
+    "$anchor",
+    "$dynamicRef",
+    "$dynamicAnchor",
+    "$vocabulary",
+    "type",
+    "enum",
+    "const",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "format",
+    "properties",
+    "required",
+    "additionalProperties",
+    "patternProperties",
+    "propertyNames",
+    "minProperties",
+    "maxProperties",
+    "dependencies",
+    "dependentRequired",
+    "dependentSchemas",
+    "unevaluatedProperties",
+    "items",
+    "prefixItems",
+    "additionalItems",
+    "unevaluatedItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+];
+
+impl InputSchema {
+    /// Checks the structure of the schema: the root must be an object schema, every `type` must be
+    /// a JSON Schema type, and every name in `required` must be one of the `properties`.
+    ///
+    /// Keywords that aren't JSON Schema keywords are returned as warnings, since the model ignores
+    /// them. Keywords that aren't checked here (e.g. `anyOf`, `$ref`) are passed through as is.
+    pub fn validate(&self) -> Result<Vec<String>, String> {
+        let mut warnings = Vec::new();
+        match self.0.get("type") {
+            Some(serde_json::Value::String(ty)) if ty == "object" => (),
+            Some(ty) => return Err(format!("input_schema.type must be \"object\", found {ty}")),
+            None => return Err("input_schema.type must be \"object\"".to_string()),
+        }
+        validate_schema(&self.0, "input_schema", &mut warnings)?;
+        Ok(warnings)
+    }
+}
+
+fn validate_schema(schema: &serde_json::Value, path: &str, warnings: &mut Vec<String>) -> Result<(), String> {
+    use serde_json::Value;
+
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        // `true` and `false` are valid schemas
+        Value::Bool(_) => return Ok(()),
+        other => return Err(format!("{path} must be an object, found {other}")),
+    };
+
+    for keyword in schema.keys() {
+        if !JSON_SCHEMA_KEYWORDS.contains(&keyword.as_str()) {
+            warnings.push(format!("{path}.{keyword} is not a JSON Schema keyword"));
+        }
+    }
+
+    match schema.get("type") {
+        None => (),
+        Some(Value::String(ty)) if JSON_SCHEMA_TYPES.contains(&ty.as_str()) => (),
+        Some(Value::String(ty)) => return Err(format!("{path}.type is not a JSON Schema type: \"{ty}\"")),
+        Some(Value::Array(types)) => {
+            for ty in types {
+                match ty.as_str() {
+                    Some(ty) if JSON_SCHEMA_TYPES.contains(&ty) => (),
+                    Some(ty) => return Err(format!("{path}.type is not a JSON Schema type: \"{ty}\"")),
+                    None => return Err(format!("{path}.type must only contain strings, found {ty}")),
+                }
+            }
+        },
+        Some(ty) => return Err(format!("{path}.type must be a string or an array, found {ty}")),
+    }
+
+    let properties = match schema.get("properties") {
+        None => None,
+        Some(Value::Object(properties)) => Some(properties),
+        Some(other) => return Err(format!("{path}.properties must be an object, found {other}")),
+    };
+    for (name, property) in properties.into_iter().flatten() {
+        validate_schema(property, &format!("{path}.properties.{name}"), warnings)?;
+    }
+
+    match schema.get("required") {
+        None => (),
+        Some(Value::Array(required)) => {
+            for name in required {
+                let Some(name) = name.as_str() else {
+                    return Err(format!("{path}.required must only contain strings, found {name}"));
+                };
+                if !properties.is_some_and(|properties| properties.contains_key(name)) {
+                    return Err(format!("{path}.required lists \"{name}\" which is not in properties"));
+                }
+            }
+        },
+        Some(other) => return Err(format!("{path}.required must be an array, found {other}")),
+    }
+
+    if let Some(items) = schema.get("items") {
+        match items {
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    validate_schema(item, &format!("{path}.items[{i}]"), warnings)?;
+                }
+            },
+            item => validate_schema(item, &format!("{path}.items"), warnings)?,
+        }
+    }
+
+    Ok(())
+}
+
 /// The output received from invoking a [Tool].
 #[derive(Debug, Default)]
 pub struct InvokeOutput {
@@ -423,7 +624,7 @@ fn format_path(cwd: impl AsRef<Path>, path: impl AsRef<Path>) -> String {
         .unwrap_or(path.as_ref().to_string_lossy().to_string())
 }
 
-fn supports_truecolor(os: &Os) -> bool {
+pub fn supports_truecolor(os: &Os) -> bool {
     // Simple override to disable truecolor since shell_color doesn't use Context.
     !os.env.get("Q_DISABLE_TRUECOLOR").is_ok_and(|s| !s.is_empty())
         && shell_color::get_color_support().contains(shell_color::ColorSupport::TERM24BIT)
@@ -454,6 +655,26 @@ mod tests {
     use super::*;
     use crate::os::ACTIVE_USER_HOME;
 
+    #[test]
+    fn test_trust_tool_patterns() {
+        let mut permissions = ToolPermissions::new(0);
+        let patterns = ["fs___*".to_string(), "execute_bash".to_string()];
+        permissions.apply_trust_patterns(&patterns, ["fs___read", "git___status", "execute_bash", "fs_read"]);
+
+        assert!(permissions.is_trusted("fs___read"));
+        assert!(permissions.is_trusted("execute_bash"));
+        assert!(!permissions.is_trusted("git___status"));
+        assert!(!permissions.is_trusted("fs_read"));
+
+        // MCP tools that are loaded later
+        assert!(permissions.is_trusted("fs___write"));
+        assert!(!permissions.is_trusted("git___log"));
+
+        // Untrusting a tool overrides the pattern that matched it
+        permissions.untrust_tool("fs___write");
+        assert!(!permissions.is_trusted("fs___write"));
+    }
+
     #[tokio::test]
     async fn test_tilde_path_expansion() {
         let os = Os::new().await.unwrap();
@@ -520,4 +741,85 @@ mod tests {
         )
         .await;
     }
+
+    #[test]
+    fn test_native_tool_specs_are_valid() {
+        let specs = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tool_index.json")).unwrap();
+        for spec in specs.values() {
+            assert_eq!(spec.validate().unwrap(), Vec::<String>::new(), "{}", spec.name);
+        }
+    }
+
+    #[test]
+    fn test_invalid_tool_spec() {
+        let spec = |input_schema: serde_json::Value| ToolSpec {
+            name: "bad_tool".to_string(),
+            description: "A tool with a broken schema".to_string(),
+            input_schema: InputSchema(input_schema),
+            tool_origin: ToolOrigin::McpServer("server".to_string()),
+        };
+
+        let warnings = spec(serde_json::json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "descripton": "A typo" } },
+            "required": ["path"]
+        }))
+        .validate()
+        .unwrap();
+        assert_eq!(warnings, vec![
+            "input_schema.properties.path.descripton is not a JSON Schema keyword".to_string()
+        ]);
+
+        let err = spec(serde_json::json!({
+            "type": "object",
+            "properties": { "path": { "type": "string" } },
+            "required": ["path", "mode"]
+        }))
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tool 'bad_tool' has an invalid input schema: input_schema.required lists \"mode\" which is not in properties"
+        );
+
+        let err = spec(serde_json::json!({
+            "type": "object",
+            "properties": { "paths": { "type": "array", "items": { "type": "str" } } }
+        }))
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tool 'bad_tool' has an invalid input schema: input_schema.properties.paths.items.type is not a JSON Schema type: \"str\""
+        );
+
+        let err = spec(serde_json::json!({ "type": "string" })).validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tool 'bad_tool' has an invalid input schema: input_schema.type must be \"object\", found \"string\""
+        );
+
+        let err = spec(serde_json::json!({ "properties": {} })).validate().unwrap_err();
+        assert!(err.to_string().contains("input_schema.type must be \"object\""));
+
+        let err = spec(serde_json::json!({
+            "type": "object",
+            "properties": ["path"]
+        }))
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tool 'bad_tool' has an invalid input schema: input_schema.properties must be an object, found [\"path\"]"
+        );
+
+        let err = spec(serde_json::json!({
+            "type": "object",
+            "properties": { "path": { "type": "string" } },
+            "required": "path"
+        }))
+        .validate()
+        .unwrap_err();
+        assert!(err.to_string().contains("input_schema.required must be an array"));
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use anstream::{
     eprintln,
     println,
@@ -8,10 +10,17 @@ use eyre::Result;
 use crate::os::Os;
 use crate::os::diagnostics::Diagnostics;
 use crate::util::GITHUB_REPO_NAME;
+use crate::util::redact::{
+    RedactionPolicy,
+    redact,
+};
 use crate::util::system_info::is_remote;
 
 const TEMPLATE_NAME: &str = "1_bug_report_template.yml";
 
+/// Max amount of characters to include in the transcript.
+const MAX_TRANSCRIPT_CHAR_LEN: usize = 3_000;
+
 pub struct IssueCreator {
     /// Issue title
     pub title: Option<String>,
@@ -23,18 +32,17 @@ pub struct IssueCreator {
     pub steps_to_reproduce: Option<String>,
     /// Issue description
     pub additional_environment: Option<String>,
+    /// Chat history appended to the actual behavior, only set once the user agreed to share it
+    pub transcript: Option<VecDeque<String>>,
 }
 
 impl IssueCreator {
     pub async fn create_url(&self, os: &Os) -> Result<url::Url> {
         println!("Heading over to GitHub...");
 
-        let warning = |text: &String| {
-            format!("<This will be visible to anyone. Do not include personal or sensitive information>\n\n{text}")
-        };
         let diagnostics = Diagnostics::new(&os.env).await;
 
-        let os = match &diagnostics.system_info.os {
+        let os_name = match &diagnostics.system_info.os {
             Some(os) => os.to_string(),
             None => "None".to_owned(),
         };
@@ -47,9 +55,34 @@ impl IssueCreator {
             },
         };
 
+        let url = url::Url::parse_with_params(
+            &format!("https://github.com/{GITHUB_REPO_NAME}/issues/new"),
+            self.params(os_name, &diagnostic_info).iter(),
+        )?;
+
+        if is_remote() || crate::util::open::open_url_async(url.as_str()).await.is_err() {
+            println!("Issue Url: {}", url.as_str().underlined());
+        }
+
+        Ok(url)
+    }
+
+    /// The fields of the issue form, the diagnostics and transcript are redacted
+    fn params(&self, os: String, diagnostics: &str) -> Vec<(&'static str, String)> {
+        let warning = |text: &String| {
+            format!("<This will be visible to anyone. Do not include personal or sensitive information>\n\n{text}")
+        };
+
+        let diagnostics = redact(diagnostics, RedactionPolicy::ALL);
         let environment = match &self.additional_environment {
-            Some(os) => format!("{diagnostic_info}\n{os}"),
-            None => diagnostic_info,
+            Some(env) => format!("{diagnostics}\n{env}"),
+            None => diagnostics,
+        };
+
+        let actual_behavior = match (&self.actual_behavior, &self.transcript) {
+            (Some(behavior), Some(transcript)) => Some(format!("{behavior}\n\n{}\n", format_transcript(transcript))),
+            (None, Some(transcript)) => Some(format_transcript(transcript)),
+            (behavior, None) => behavior.clone(),
         };
 
         let mut params = Vec::new();
@@ -63,22 +96,113 @@ impl IssueCreator {
         if let Some(t) = self.expected_behavior.as_ref() {
             params.push(("expected", warning(t)));
         }
-        if let Some(t) = self.actual_behavior.as_ref() {
+        if let Some(t) = actual_behavior.as_ref() {
             params.push(("actual", warning(t)));
         }
         if let Some(t) = self.steps_to_reproduce.as_ref() {
             params.push(("reproduce", warning(t)));
         }
 
-        let url = url::Url::parse_with_params(
-            &format!("https://github.com/{GITHUB_REPO_NAME}/issues/new"),
-            params.iter(),
-        )?;
+        params
+    }
+}
 
-        if is_remote() || crate::util::open::open_url_async(url.as_str()).await.is_err() {
-            println!("Issue Url: {}", url.as_str().underlined());
+/// The last [MAX_TRANSCRIPT_CHAR_LEN] characters of the transcript as a redacted code block
+fn format_transcript(transcript: &VecDeque<String>) -> String {
+    let mut transcript_str = String::from("```\n[chat-transcript]\n");
+    let mut is_truncated = false;
+    let transcript: Vec<String> = transcript
+        .iter()
+        .rev() // To take last N items
+        .scan(0, |user_msg_char_count, line| {
+            if *user_msg_char_count >= MAX_TRANSCRIPT_CHAR_LEN {
+                is_truncated = true;
+                return None;
+            }
+            // Redact before truncating so a cut off secret can't slip past the patterns
+            let line = redact(line, RedactionPolicy::ALL);
+            let remaining_chars = MAX_TRANSCRIPT_CHAR_LEN - *user_msg_char_count;
+            let trimmed_line = super::truncate_safe(&line, remaining_chars);
+            *user_msg_char_count += trimmed_line.len();
+
+            // backticks will mess up the markdown
+            let text = trimmed_line.replace("```", r"\```");
+            Some(text)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev() // Now return items to the proper order
+        .collect();
+
+    if !transcript.is_empty() {
+        transcript_str.push_str(&transcript.join("\n\n"));
+    } else {
+        transcript_str.push_str("No chat history found.");
+    }
+
+    if is_truncated {
+        transcript_str.push_str("\n\n(...truncated)");
+    }
+    transcript_str.push_str("\n```");
+    transcript_str
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIAGNOSTICS: &str = concat!(
+        "[q-details]\nversion = \"1.0.0\"\n\n",
+        "[environment-variables]\nAWS_SECRET_ACCESS_KEY = \"hunter2\"",
+    );
+
+    fn issue(transcript: Option<VecDeque<String>>) -> IssueCreator {
+        IssueCreator {
+            title: Some("title".into()),
+            expected_behavior: None,
+            actual_behavior: Some("it broke".into()),
+            steps_to_reproduce: None,
+            additional_environment: None,
+            transcript,
         }
+    }
 
-        Ok(url)
+    fn param<'a>(params: &'a [(&'static str, String)], name: &str) -> Option<&'a str> {
+        params
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn test_params_with_context() {
+        let transcript = VecDeque::from(["> my token=abc123".to_string(), "ok\n[Tool uses: none]".to_string()]);
+        let creator = issue(Some(transcript));
+        let params = creator.params("Linux".into(), DIAGNOSTICS);
+
+        let environment = param(&params, "environment").unwrap();
+        assert!(environment.contains("version = \"1.0.0\""));
+        assert!(environment.contains("<token>"));
+        assert!(!environment.contains("hunter2"));
+
+        let actual = param(&params, "actual").unwrap();
+        assert!(actual.contains("it broke"));
+        assert!(actual.contains("[chat-transcript]"));
+        assert!(actual.contains("> my token=<token>"));
+        assert!(!actual.contains("abc123"));
+    }
+
+    #[test]
+    fn test_params_without_transcript() {
+        let creator = issue(None);
+        let params = creator.params("Linux".into(), DIAGNOSTICS);
+
+        assert_eq!(param(&params, "os"), Some("Linux"));
+        let environment = param(&params, "environment").unwrap();
+        assert!(environment.contains("version = \"1.0.0\""));
+        assert!(!environment.contains("hunter2"));
+        let actual = param(&params, "actual").unwrap();
+        assert!(actual.ends_with("it broke"));
+        assert!(!actual.contains("[chat-transcript]"));
     }
 }
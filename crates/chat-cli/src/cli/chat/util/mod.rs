@@ -1,5 +1,6 @@
 pub mod images;
 pub mod issue;
+pub mod panic_hook;
 #[cfg(test)]
 pub mod test;
 pub mod ui;
@@ -15,6 +16,10 @@ use eyre::Result;
 
 use super::ChatError;
 use super::token_counter::TokenCounter;
+use crate::database::settings::{
+    Setting,
+    Settings,
+};
 
 pub fn truncate_safe(s: &str, max_bytes: usize) -> &str {
     if s.len() <= max_bytes {
@@ -59,6 +64,66 @@ pub fn animate_output(output: &mut impl Write, bytes: &[u8]) -> Result<(), ChatE
     Ok(())
 }
 
+/// How long a response has to take before [NotifyMode::OnLongResponse] notifies
+const DEFAULT_LONG_RESPONSE: Duration = Duration::from_secs(30);
+
+/// When to play the notification bell, set with `chat.notify.mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyMode {
+    Off,
+    Bell,
+    /// Only after responses that took longer than the threshold
+    OnLongResponse,
+}
+
+impl NotifyMode {
+    fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "off" => Some(Self::Off),
+            "bell" => Some(Self::Bell),
+            "onLongResponse" => Some(Self::OnLongResponse),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotifyPolicy {
+    pub mode: NotifyMode,
+    /// Threshold for [NotifyMode::OnLongResponse], set with `chat.notify.longResponseSeconds`
+    pub long_response: Duration,
+}
+
+impl NotifyPolicy {
+    /// Without `chat.notify.mode` the older `chat.enableNotifications` toggles the bell
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mode = match settings.get_string(Setting::ChatNotifyMode) {
+            Some(mode) => NotifyMode::parse(&mode).unwrap_or_else(|| {
+                tracing::warn!(mode, "Unknown chat.notify.mode, notifications are off");
+                NotifyMode::Off
+            }),
+            None if settings.get_bool(Setting::ChatEnableNotifications).unwrap_or(false) => NotifyMode::Bell,
+            None => NotifyMode::Off,
+        };
+
+        let long_response = settings
+            .get_int(Setting::ChatNotifyLongResponseSeconds)
+            .and_then(|secs| u64::try_from(secs).ok())
+            .map_or(DEFAULT_LONG_RESPONSE, Duration::from_secs);
+
+        Self { mode, long_response }
+    }
+
+    /// Whether to notify after a response that took `elapsed` to stream
+    pub fn should_notify(&self, elapsed: Duration) -> bool {
+        match self.mode {
+            NotifyMode::Off => false,
+            NotifyMode::Bell => true,
+            NotifyMode::OnLongResponse => elapsed >= self.long_response,
+        }
+    }
+}
+
 /// Play the terminal bell notification sound
 pub fn play_notification_bell(requires_confirmation: bool) {
     // Don't play bell for tools that don't require confirmation
@@ -193,6 +258,34 @@ pub fn document_to_serde_value(value: Document) -> serde_json::Value {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_notify_policy() {
+        let mut settings = Settings::default();
+        assert_eq!(NotifyPolicy::from_settings(&settings).mode, NotifyMode::Off);
+
+        settings.set(Setting::ChatEnableNotifications, true).await.unwrap();
+        assert_eq!(NotifyPolicy::from_settings(&settings).mode, NotifyMode::Bell);
+
+        settings.set(Setting::ChatNotifyMode, "off").await.unwrap();
+        let policy = NotifyPolicy::from_settings(&settings);
+        assert!(!policy.should_notify(Duration::from_secs(600)));
+
+        settings.set(Setting::ChatNotifyMode, "bell").await.unwrap();
+        let policy = NotifyPolicy::from_settings(&settings);
+        assert!(policy.should_notify(Duration::ZERO));
+
+        settings.set(Setting::ChatNotifyMode, "onLongResponse").await.unwrap();
+        let policy = NotifyPolicy::from_settings(&settings);
+        assert_eq!(policy.long_response, DEFAULT_LONG_RESPONSE);
+        assert!(!policy.should_notify(Duration::from_secs(29)));
+        assert!(policy.should_notify(Duration::from_secs(30)));
+
+        settings.set(Setting::ChatNotifyLongResponseSeconds, 5).await.unwrap();
+        let policy = NotifyPolicy::from_settings(&settings);
+        assert!(!policy.should_notify(Duration::from_secs(4)));
+        assert!(policy.should_notify(Duration::from_secs(5)));
+    }
+
     #[test]
     fn test_truncate_safe() {
         assert_eq!(truncate_safe("Hello World", 5), "Hello");
@@ -0,0 +1,148 @@
+//! The panic hook that puts the terminal back into a usable state
+//!
+//! [ChatSession](crate::cli::chat::ChatSession) stops its spinner and resets the terminal when it's
+//! dropped, but a panic message is printed before unwinding gets that far, while the spinner is
+//! still drawing, the cursor may be hidden and text may still be styled. The hook stops every
+//! [SpinnerGuard] and resets the terminal before the message is printed.
+
+use std::io::{
+    self,
+    Write,
+};
+use std::sync::atomic::{
+    AtomicU64,
+    Ordering,
+};
+use std::sync::{
+    Mutex,
+    MutexGuard,
+    Once,
+    PoisonError,
+    TryLockError,
+};
+
+use crossterm::style::{
+    self,
+    Attribute,
+};
+use crossterm::{
+    cursor,
+    queue,
+};
+use spinners::{
+    Spinner,
+    Spinners,
+};
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+/// Spinners started through [SpinnerGuard] that haven't been stopped yet
+static RUNNING_SPINNERS: Mutex<Vec<(u64, Spinner)>> = Mutex::new(Vec::new());
+static NEXT_SPINNER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Install the panic hook, the hook that was installed before runs after the terminal is reset
+pub fn install_panic_hook() {
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            stop_running_spinners();
+            restore_terminal(&mut io::stderr()).ok();
+            previous(info);
+        }));
+    });
+}
+
+/// A running spinner, stopped when the guard is dropped or by the panic hook
+#[derive(Debug)]
+pub struct SpinnerGuard {
+    id: u64,
+}
+
+impl SpinnerGuard {
+    pub fn start(spinner: Spinners, message: String) -> Self {
+        let id = NEXT_SPINNER_ID.fetch_add(1, Ordering::Relaxed);
+        running_spinners().push((id, Spinner::new(spinner, message)));
+        Self { id }
+    }
+
+    /// Stops the spinner, does nothing if the panic hook already stopped it
+    pub fn stop(&mut self) {
+        let spinner = {
+            let mut spinners = running_spinners();
+            spinners
+                .iter()
+                .position(|(id, _)| *id == self.id)
+                .map(|i| spinners.remove(i).1)
+        };
+        if let Some(mut spinner) = spinner {
+            spinner.stop();
+        }
+    }
+}
+
+impl Drop for SpinnerGuard {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn running_spinners() -> MutexGuard<'static, Vec<(u64, Spinner)>> {
+    RUNNING_SPINNERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Doesn't wait on the lock, the panic may have happened while it was held
+fn stop_running_spinners() {
+    let spinners = match RUNNING_SPINNERS.try_lock() {
+        Ok(mut spinners) => std::mem::take(&mut *spinners),
+        Err(TryLockError::Poisoned(err)) => std::mem::take(&mut *err.into_inner()),
+        Err(TryLockError::WouldBlock) => return,
+    };
+    for (_, mut spinner) in spinners {
+        spinner.stop();
+    }
+}
+
+/// Undo the state a turn may leave the terminal in, starting a new line so the message isn't
+/// printed over a spinner or a partially streamed response
+fn restore_terminal(output: &mut impl Write) -> io::Result<()> {
+    queue!(
+        output,
+        style::SetAttribute(Attribute::Reset),
+        style::ResetColor,
+        cursor::Show,
+        style::Print("\n"),
+    )?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_terminal() {
+        let mut output = Vec::new();
+        restore_terminal(&mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("\x1b[0m"), "attributes should be reset: {output:?}");
+        assert!(output.contains("\x1b[?25h"), "cursor should be shown: {output:?}");
+        assert!(output.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_panic_stops_running_spinner() {
+        let is_running = |guard: &SpinnerGuard| running_spinners().iter().any(|(id, _)| *id == guard.id);
+
+        install_panic_hook();
+        let mut guard = SpinnerGuard::start(Spinners::Dots, "Thinking...".to_string());
+        assert!(is_running(&guard));
+
+        let result = std::panic::catch_unwind(|| panic!("panic during a turn"));
+        assert!(result.is_err());
+        assert!(!is_running(&guard));
+
+        // Stopping the guard afterwards is a no-op
+        guard.stop();
+    }
+}
@@ -15,6 +15,10 @@ use crossterm::{
 };
 use eyre::Result;
 use strip_ansi_escapes::strip_str;
+use unicode_width::{
+    UnicodeWidthChar,
+    UnicodeWidthStr,
+};
 
 pub fn draw_box(
     output: &mut impl Write,
@@ -24,47 +28,16 @@ pub fn draw_box(
     border_color: Color,
 ) -> Result<()> {
     let inner_width = box_width - 4; // account for │ and padding
+    let wrapped_lines = wrap_words(content, inner_width);
 
-    // wrap the single line into multiple lines respecting inner width
-    // Manually wrap the text by splitting at word boundaries
-    let mut wrapped_lines = Vec::new();
-    let mut line = String::new();
-
-    for word in content.split_whitespace() {
-        if line.len() + word.len() < inner_width {
-            if !line.is_empty() {
-                line.push(' ');
-            }
-            line.push_str(word);
-        } else {
-            // Here we need to account for words that are too long as well
-            if word.len() >= inner_width {
-                let mut start = 0_usize;
-                for (i, _) in word.chars().enumerate() {
-                    if i - start >= inner_width {
-                        wrapped_lines.push(word[start..i].to_string());
-                        start = i;
-                    }
-                }
-                wrapped_lines.push(word[start..].to_string());
-                line = String::new();
-            } else {
-                wrapped_lines.push(line);
-                line = word.to_string();
-            }
-        }
-    }
-
-    if !line.is_empty() {
-        wrapped_lines.push(line);
-    }
-
-    let side_len = (box_width.saturating_sub(title.len())) / 2;
+    let title_width = display_width(title);
+    let side_len = (box_width.saturating_sub(title_width)) / 2;
+    let right_len = box_width.saturating_sub(side_len + title_width + 2);
     let top_border = format!(
         "{} {} {}",
         style::style(format!("╭{}", "─".repeat(side_len - 2))).with(border_color),
         title,
-        style::style(format!("{}╮", "─".repeat(box_width - side_len - title.len() - 2))).with(border_color)
+        style::style(format!("{}╮", "─".repeat(right_len))).with(border_color)
     );
 
     execute!(
@@ -83,7 +56,7 @@ pub fn draw_box(
 
     // Centered wrapped content
     for line in wrapped_lines {
-        let visible_line_len = strip_str(&line).len();
+        let visible_line_len = display_width(&line);
         let left_pad = box_width.saturating_sub(4).saturating_sub(visible_line_len) / 2;
 
         let content = format!(
@@ -114,6 +87,72 @@ pub fn draw_box(
     Ok(())
 }
 
+/// Width of `s` as displayed in a terminal, ignoring ANSI escape sequences
+fn display_width(s: &str) -> usize {
+    strip_str(s).width()
+}
+
+/// Wrap `content` at word boundaries so no line is wider than `width` columns, words wider than
+/// a whole line are split between characters
+fn wrap_words(content: &str, width: usize) -> Vec<String> {
+    let mut wrapped_lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in content.split_whitespace() {
+        let word_width = display_width(word);
+        if line.is_empty() && word_width <= width {
+            line.push_str(word);
+            line_width = word_width;
+        } else if !line.is_empty() && line_width + 1 + word_width <= width {
+            line.push(' ');
+            line.push_str(word);
+            line_width += 1 + word_width;
+        } else {
+            if !line.is_empty() {
+                wrapped_lines.push(std::mem::take(&mut line));
+            }
+
+            if word_width > width {
+                // Split between characters, escape sequences take no space and are never split
+                let mut chunk = String::new();
+                let mut chunk_width = 0;
+                let mut in_escape = false;
+                for c in word.chars() {
+                    let char_width = match c {
+                        '\x1b' => {
+                            in_escape = true;
+                            0
+                        },
+                        _ if in_escape => {
+                            in_escape = !(c.is_ascii_alphabetic() || c == '~');
+                            0
+                        },
+                        _ => c.width().unwrap_or(0),
+                    };
+                    if !chunk.is_empty() && chunk_width + char_width > width {
+                        wrapped_lines.push(std::mem::take(&mut chunk));
+                        chunk_width = 0;
+                    }
+                    chunk.push(c);
+                    chunk_width += char_width;
+                }
+                line = chunk;
+                line_width = chunk_width;
+            } else {
+                line = word.to_string();
+                line_width = word_width;
+            }
+        }
+    }
+
+    if !line.is_empty() {
+        wrapped_lines.push(line);
+    }
+
+    wrapped_lines
+}
+
 #[cfg(test)]
 mod tests {
     use bstr::ByteSlice;
@@ -199,4 +238,22 @@ mod tests {
             assert!(output_str.contains(part), "Output should contain parts of the long tip");
         }
     }
+
+    #[test]
+    fn test_draw_box_wide_characters() {
+        let mut output = vec![];
+        let tip = format!(
+            "使用 {} 命令可以查看上下文 🎉 {}",
+            "/context".green(),
+            "这是一个没有空格的很长的中文句子用来测试按字符换行是否正确处理宽字符的显示宽度"
+        );
+        draw_box(&mut output, "你知道吗?", &tip, GREETING_BREAK_POINT, Color::DarkGrey).unwrap();
+
+        let rendered = strip_str(output.to_str_lossy());
+        let lines = rendered.lines().collect::<Vec<_>>();
+        assert!(lines.len() > 5, "tip should wrap: {rendered}");
+        for line in lines {
+            assert_eq!(line.width(), GREETING_BREAK_POINT, "misaligned line: {line:?}");
+        }
+    }
 }
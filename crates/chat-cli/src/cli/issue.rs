@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::process::ExitCode;
 
 use clap::Args;
@@ -10,12 +11,15 @@ pub struct IssueArgs {
     /// Force issue creation
     #[arg(long, short = 'f')]
     force: bool,
+    /// Attach the redacted chat transcript when run from chat
+    #[arg(long)]
+    include_context: bool,
     /// Issue description
     description: Vec<String>,
 }
 
 impl IssueArgs {
-    pub async fn execute(&self, os: &Os) -> Result<ExitCode> {
+    pub async fn execute(&self, os: &Os, transcript: Option<&VecDeque<String>>) -> Result<ExitCode> {
         let joined_description = self.description.join(" ").trim().to_owned();
 
         let issue_title = match joined_description.len() {
@@ -25,12 +29,20 @@ impl IssueArgs {
             _ => joined_description,
         };
 
+        let include_transcript = self.include_context
+            && transcript.is_some()
+            && dialoguer::Confirm::with_theme(&crate::util::dialoguer_theme())
+                .with_prompt("Attach the chat transcript? The issue will be public")
+                .default(false)
+                .interact()?;
+
         let _ = crate::cli::chat::util::issue::IssueCreator {
             title: Some(issue_title),
             expected_behavior: None,
             actual_behavior: None,
             steps_to_reproduce: None,
             additional_environment: None,
+            transcript: transcript.filter(|_| include_transcript).cloned(),
         }
         .create_url(os)
         .await;
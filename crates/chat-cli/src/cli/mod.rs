@@ -50,6 +50,7 @@ use crate::util::directories::logs_dir;
 use crate::util::{
     CLI_BINARY_NAME,
     GOV_REGIONS,
+    color,
 };
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
@@ -150,7 +151,7 @@ impl RootSubcommand {
             Self::Whoami(args) => args.execute(os).await,
             Self::Profile => user::profile(os).await,
             Self::Settings(settings_args) => settings_args.execute(os).await,
-            Self::Issue(args) => args.execute(os).await,
+            Self::Issue(args) => args.execute(os, None).await,
             Self::Version { changelog } => Cli::print_version(changelog),
             Self::Chat(args) => args.execute(os).await,
             Self::Mcp(args) => args.execute(os, &mut std::io::stderr()).await,
@@ -191,6 +192,9 @@ pub struct Cli {
     /// Increase logging verbosity
     #[arg(long, short = 'v', action = ArgAction::Count, global = true)]
     pub verbose: u8,
+    /// Disable colored output. Also disabled by setting `NO_COLOR` or `CLICOLOR=0`
+    #[arg(long, global = true)]
+    pub no_color: bool,
     /// Print help for all subcommands
     #[arg(long)]
     help_all: bool,
@@ -200,6 +204,10 @@ impl Cli {
     pub async fn execute(self) -> Result<ExitCode> {
         let subcommand = self.subcommand.unwrap_or_default();
 
+        if self.no_color {
+            color::disable();
+        }
+
         // Initialize our logger and keep around the guard so logging can perform as expected.
         let _log_guard = initialize_logging(LogArgs {
             log_level: match self.verbose > 0 {
@@ -335,18 +343,21 @@ mod test {
         assert_eq!(Cli::parse_from([CHAT_BINARY_NAME, "-v"]), Cli {
             subcommand: None,
             verbose: 1,
+            no_color: false,
             help_all: false,
         });
 
         assert_eq!(Cli::parse_from([CHAT_BINARY_NAME, "-vvv"]), Cli {
             subcommand: None,
             verbose: 3,
+            no_color: false,
             help_all: false,
         });
 
         assert_eq!(Cli::parse_from([CHAT_BINARY_NAME, "--help-all"]), Cli {
             subcommand: None,
             verbose: 0,
+            no_color: false,
             help_all: true,
         });
 
@@ -358,9 +369,14 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })),
             verbose: 2,
+            no_color: false,
             help_all: false,
         });
     }
@@ -397,7 +413,11 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
     }
@@ -413,7 +433,11 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
     }
@@ -429,7 +453,11 @@ mod test {
                 model: None,
                 trust_all_tools: true,
                 trust_tools: None,
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
     }
@@ -445,7 +473,11 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: true
+                trust_tools_from: None,
+                no_interactive: true,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
         assert_parse!(
@@ -457,7 +489,11 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: true
+                trust_tools_from: None,
+                no_interactive: true,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
     }
@@ -473,7 +509,11 @@ mod test {
                 model: None,
                 trust_all_tools: true,
                 trust_tools: None,
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
     }
@@ -489,7 +529,51 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: Some(vec!["".to_string()]),
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_with_quiet() {
+        assert_parse!(
+            ["chat", "--no-interactive", "--quiet"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: false,
+                input: None,
+                profile: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                trust_tools_from: None,
+                no_interactive: true,
+                quiet: true,
+                input_file: None,
+                continue_on_error: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_with_input_file() {
+        assert_parse!(
+            ["chat", "--input-file", "prompts.txt", "--continue-on-error"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: false,
+                input: None,
+                profile: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: Some("prompts.txt".into()),
+                continue_on_error: true,
             })
         );
     }
@@ -505,7 +589,31 @@ mod test {
                 model: None,
                 trust_all_tools: false,
                 trust_tools: Some(vec!["fs_read".to_string(), "fs_write".to_string()]),
-                no_interactive: false
+                trust_tools_from: None,
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_with_tool_trust_patterns() {
+        assert_parse!(
+            ["chat", "--trust-tools=fs___*", "--trust-tools-from", "trusted_tools"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: false,
+                input: None,
+                profile: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: Some(vec!["fs___*".to_string()]),
+                trust_tools_from: Some("trusted_tools".into()),
+                no_interactive: false,
+                quiet: false,
+                input_file: None,
+                continue_on_error: false,
             })
         );
     }
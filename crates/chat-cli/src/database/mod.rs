@@ -58,6 +58,7 @@ const CLIENT_ID_KEY: &str = "telemetryClientId";
 const CODEWHISPERER_PROFILE_KEY: &str = "api.codewhisperer.profile";
 const START_URL_KEY: &str = "auth.idc.start-url";
 const IDC_REGION_KEY: &str = "auth.idc.region";
+const ROTATING_TIP_KEY: &str = "chat.greeting.rotatingTip";
 // We include this key to remove for backwards compatibility
 const CUSTOMIZATION_STATE_KEY: &str = "api.selectedCustomization";
 
@@ -300,6 +301,18 @@ impl Database {
         self.set_json_entry(Table::State, IDC_REGION_KEY, region)
     }
 
+    /// Get the index of the next tip to show in the chat greeting.
+    ///
+    /// This is also the number of times the full greeting has been shown.
+    pub fn get_rotating_tip(&self) -> Result<Option<usize>, DatabaseError> {
+        self.get_json_entry(Table::State, ROTATING_TIP_KEY)
+    }
+
+    /// Set the index of the next tip to show in the chat greeting.
+    pub fn set_rotating_tip(&mut self, index: usize) -> Result<usize, DatabaseError> {
+        self.set_json_entry(Table::State, ROTATING_TIP_KEY, index)
+    }
+
     // /// Get the model id used for last conversation state.
     // pub fn get_last_used_model_id(&self) -> Result<Option<String>, DatabaseError> {
     //     self.get_json_entry::<String>(Table::State, LAST_USED_MODEL_ID)
@@ -32,9 +32,27 @@ pub enum Setting {
     McpInitTimeout,
     McpNoInteractiveTimeout,
     McpLoadedBefore,
+    McpLogPayloads,
     ChatDefaultModel,
     ChatDisableAutoCompaction,
     ChatEnableHistoryHints,
+    ChatSyntaxHighlighting,
+    ChatSpinnerStyle,
+    ChatStopOnToolError,
+    ChatContextRetrievalEnabled,
+    ChatContextRetrievalTopK,
+    ChatSystemPromptPrefix,
+    ChatSystemPromptPrefixFile,
+    ChatHistoryMaxTurns,
+    ChatToolsFsAllowedRoots,
+    ChatToolAuditEnabled,
+    ChatToolAuditRedactArgs,
+    ChatNotifyMode,
+    ChatNotifyLongResponseSeconds,
+    ChatGreetingMode,
+    ChatGreetingFullLaunches,
+    ChatContextWatch,
+    ChatPager,
 }
 
 impl AsRef<str> for Setting {
@@ -55,9 +73,27 @@ impl AsRef<str> for Setting {
             Self::McpInitTimeout => "mcp.initTimeout",
             Self::McpNoInteractiveTimeout => "mcp.noInteractiveTimeout",
             Self::McpLoadedBefore => "mcp.loadedBefore",
+            Self::McpLogPayloads => "mcp.logPayloads",
             Self::ChatDefaultModel => "chat.defaultModel",
             Self::ChatDisableAutoCompaction => "chat.disableAutoCompaction",
             Self::ChatEnableHistoryHints => "chat.enableHistoryHints",
+            Self::ChatSyntaxHighlighting => "chat.syntaxHighlighting",
+            Self::ChatSpinnerStyle => "chat.spinnerStyle",
+            Self::ChatStopOnToolError => "chat.stopOnToolError",
+            Self::ChatContextRetrievalEnabled => "chat.context.retrieval.enabled",
+            Self::ChatContextRetrievalTopK => "chat.context.retrieval.topK",
+            Self::ChatSystemPromptPrefix => "chat.systemPromptPrefix",
+            Self::ChatSystemPromptPrefixFile => "chat.systemPromptPrefixFile",
+            Self::ChatHistoryMaxTurns => "chat.history.maxTurns",
+            Self::ChatToolsFsAllowedRoots => "chat.tools.fs.allowedRoots",
+            Self::ChatToolAuditEnabled => "chat.toolAudit.enabled",
+            Self::ChatToolAuditRedactArgs => "chat.toolAudit.redactArgs",
+            Self::ChatNotifyMode => "chat.notify.mode",
+            Self::ChatNotifyLongResponseSeconds => "chat.notify.longResponseSeconds",
+            Self::ChatGreetingMode => "chat.greeting.mode",
+            Self::ChatGreetingFullLaunches => "chat.greeting.fullLaunches",
+            Self::ChatContextWatch => "chat.context.watch",
+            Self::ChatPager => "chat.pager",
         }
     }
 }
@@ -88,9 +124,27 @@ impl TryFrom<&str> for Setting {
             "mcp.initTimeout" => Ok(Self::McpInitTimeout),
             "mcp.noInteractiveTimeout" => Ok(Self::McpNoInteractiveTimeout),
             "mcp.loadedBefore" => Ok(Self::McpLoadedBefore),
+            "mcp.logPayloads" => Ok(Self::McpLogPayloads),
             "chat.defaultModel" => Ok(Self::ChatDefaultModel),
             "chat.disableAutoCompaction" => Ok(Self::ChatDisableAutoCompaction),
             "chat.enableHistoryHints" => Ok(Self::ChatEnableHistoryHints),
+            "chat.syntaxHighlighting" => Ok(Self::ChatSyntaxHighlighting),
+            "chat.spinnerStyle" => Ok(Self::ChatSpinnerStyle),
+            "chat.stopOnToolError" => Ok(Self::ChatStopOnToolError),
+            "chat.context.retrieval.enabled" => Ok(Self::ChatContextRetrievalEnabled),
+            "chat.context.retrieval.topK" => Ok(Self::ChatContextRetrievalTopK),
+            "chat.systemPromptPrefix" => Ok(Self::ChatSystemPromptPrefix),
+            "chat.systemPromptPrefixFile" => Ok(Self::ChatSystemPromptPrefixFile),
+            "chat.history.maxTurns" => Ok(Self::ChatHistoryMaxTurns),
+            "chat.tools.fs.allowedRoots" => Ok(Self::ChatToolsFsAllowedRoots),
+            "chat.toolAudit.enabled" => Ok(Self::ChatToolAuditEnabled),
+            "chat.toolAudit.redactArgs" => Ok(Self::ChatToolAuditRedactArgs),
+            "chat.notify.mode" => Ok(Self::ChatNotifyMode),
+            "chat.notify.longResponseSeconds" => Ok(Self::ChatNotifyLongResponseSeconds),
+            "chat.greeting.mode" => Ok(Self::ChatGreetingMode),
+            "chat.greeting.fullLaunches" => Ok(Self::ChatGreetingFullLaunches),
+            "chat.context.watch" => Ok(Self::ChatContextWatch),
+            "chat.pager" => Ok(Self::ChatPager),
             _ => Err(DatabaseError::InvalidSetting(value.to_string())),
         }
     }
@@ -74,40 +74,13 @@ pub fn initialize_logging<T: AsRef<Path>>(args: LogArgs<T>) -> Result<LogGuard,
         Some(log_file_path) => {
             let log_path = log_file_path.as_ref();
 
-            // Make the log path parent directory if it doesn't exist.
             if let Some(parent) = log_path.parent() {
                 if log_path.ends_with("qchat.log") {
                     mcp_path = Some(parent.to_path_buf());
                 }
-                std::fs::create_dir_all(parent)?;
-            }
-
-            // We delete the old log file when requested each time the logger is initialized, otherwise we only
-            // delete the file when it has grown too large.
-            if args.delete_old_log_file {
-                std::fs::remove_file(log_path).ok();
-            } else if log_path.exists() && std::fs::metadata(log_path)?.len() > MAX_FILE_SIZE {
-                std::fs::remove_file(log_path)?;
-            }
-
-            // Create the new log file or append to the existing one.
-            let file = if args.delete_old_log_file {
-                File::create(log_path)?
-            } else {
-                File::options().append(true).create(true).open(log_path)?
-            };
-
-            // On posix-like systems, we modify permissions so that only the owner has access.
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                if let Ok(metadata) = file.metadata() {
-                    let mut permissions = metadata.permissions();
-                    permissions.set_mode(0o600);
-                    file.set_permissions(permissions).ok();
-                }
             }
 
+            let file = open_log_file(log_path, args.delete_old_log_file)?;
             let (non_blocking, guard) = tracing_appender::non_blocking(file);
             let file_layer = fmt::layer().with_line_number(true).with_writer(non_blocking);
 
@@ -128,25 +101,7 @@ pub fn initialize_logging<T: AsRef<Path>>(args: LogArgs<T>) -> Result<LogGuard,
     // Set up for mcp servers layer if we are in chat
     let (mcp_server_layer, _mcp_file_guard) = if let Some(parent) = mcp_path {
         let mcp_path = parent.join("mcp.log");
-        if args.delete_old_log_file {
-            std::fs::remove_file(&mcp_path).ok();
-        } else if mcp_path.exists() && std::fs::metadata(&mcp_path)?.len() > MAX_FILE_SIZE {
-            std::fs::remove_file(&mcp_path)?;
-        }
-        let file = if args.delete_old_log_file {
-            File::create(&mcp_path)?
-        } else {
-            File::options().append(true).create(true).open(&mcp_path)?
-        };
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            if let Ok(metadata) = file.metadata() {
-                let mut permissions = metadata.permissions();
-                permissions.set_mode(0o600);
-                file.set_permissions(permissions).ok();
-            }
-        }
+        let file = open_log_file(&mcp_path, args.delete_old_log_file)?;
         let (non_blocking, guard) = tracing_appender::non_blocking(file);
         let file_layer = fmt::layer()
             .with_line_number(true)
@@ -185,6 +140,44 @@ pub fn initialize_logging<T: AsRef<Path>>(args: LogArgs<T>) -> Result<LogGuard,
     })
 }
 
+/// Open a log file for appending, or truncate it when `delete_old` is set
+///
+/// A file that has grown past [MAX_FILE_SIZE] is started over, and on unix only the owner can
+/// access it.
+fn open_log_file(path: &Path, delete_old: bool) -> Result<File, Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // We delete the old log file when requested each time the logger is initialized, otherwise we only
+    // delete the file when it has grown too large.
+    if delete_old {
+        std::fs::remove_file(path).ok();
+    } else if path.exists() && std::fs::metadata(path)?.len() > MAX_FILE_SIZE {
+        std::fs::remove_file(path)?;
+    }
+
+    // Create the new log file or append to the existing one.
+    let file = if delete_old {
+        File::create(path)?
+    } else {
+        File::options().append(true).create(true).open(path)?
+    };
+
+    // On posix-like systems, we modify permissions so that only the owner has access.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = file.metadata() {
+            let mut permissions = metadata.permissions();
+            permissions.set_mode(0o600);
+            file.set_permissions(permissions).ok();
+        }
+    }
+
+    Ok(file)
+}
+
 /// Get the current log level by first seeing if it is set in application, then environment, then
 /// otherwise using the default
 ///
@@ -2,22 +2,35 @@ use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::atomic::{
     AtomicBool,
+    AtomicU32,
     AtomicU64,
     Ordering,
 };
 use std::sync::{
     Arc,
+    OnceLock,
     RwLock as SyncRwLock,
 };
-use std::time::Duration;
+use std::time::{
+    Duration,
+    Instant,
+};
 
 use serde::{
     Deserialize,
     Serialize,
 };
 use thiserror::Error;
+use tokio::process::Child;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::AbortHandle;
 use tokio::time;
 use tokio::time::error::Elapsed;
+use tracing::Instrument as _;
+use tracing::field::{
+    Empty,
+    display,
+};
 
 use super::transport::base_protocol::{
     JsonRpcMessage,
@@ -74,6 +87,58 @@ impl From<ClientInfo> for ClientCapabilities {
     }
 }
 
+/// Why a tool advertised by a server is hidden from the tools list.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ToolFilterReason {
+    #[error("requires the {0} client capability, which was not negotiated")]
+    MissingCapability(String),
+}
+
+/// A tool advertised by a server that is left out of the tools list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilteredTool {
+    pub name: String,
+    pub reason: ToolFilterReason,
+}
+
+/// Key in a tool's `_meta` under which servers can list the client capabilities the tool relies
+/// on, e.g. `"_meta": { "com.amazon.q/requiredCapabilities": ["sampling"] }`.
+///
+/// This is an Amazon Q extension, the MCP spec has no field for it. `_meta` keys with a
+/// reverse-DNS prefix are reserved for vendors, so other clients ignore it.
+pub const REQUIRED_CAPABILITIES_META_KEY: &str = "com.amazon.q/requiredCapabilities";
+
+/// Splits `tools` into the tools the client supports and the ones it does not.
+///
+/// A tool is supported when every capability listed under [REQUIRED_CAPABILITIES_META_KEY] in
+/// its `_meta` is in `negotiated`, the capabilities sent during initialization. Tools without
+/// the key are always supported.
+pub fn filter_tools_by_capabilities(
+    tools: Vec<serde_json::Value>,
+    negotiated: &HashMap<String, serde_json::Value>,
+) -> (Vec<serde_json::Value>, Vec<FilteredTool>) {
+    let mut supported = Vec::new();
+    let mut filtered = Vec::new();
+    for tool in tools {
+        let missing = tool
+            .get("_meta")
+            .and_then(|meta| meta.get(REQUIRED_CAPABILITIES_META_KEY))
+            .and_then(|required| required.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|capability| capability.as_str())
+            .find(|capability| !negotiated.contains_key(*capability));
+        match missing {
+            Some(capability) => filtered.push(FilteredTool {
+                name: tool.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_owned(),
+                reason: ToolFilterReason::MissingCapability(capability.to_owned()),
+            }),
+            None => supported.push(tool),
+        }
+    }
+    (supported, filtered)
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ClientConfig {
     pub server_name: String,
@@ -82,6 +147,87 @@ pub struct ClientConfig {
     pub timeout: u64,
     pub client_info: serde_json::Value,
     pub env: Option<HashMap<String, String>>,
+    /// Whether full request and response payloads are written to the `mcp` log target
+    #[serde(default)]
+    pub log_payloads: bool,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+}
+
+/// How a server process that exits unexpectedly is relaunched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RestartPolicy {
+    /// Whether the server is relaunched at all
+    pub enabled: bool,
+    /// Maximum number of relaunches over the lifetime of the client
+    pub max_restarts: u32,
+    /// Delay before the first relaunch in milliseconds, doubled for every relaunch after it
+    pub backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_restarts: 3,
+            backoff_ms: 500,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before the relaunch following `restarts` earlier ones.
+    pub fn backoff(&self, restarts: u32) -> Duration {
+        Duration::from_millis(self.backoff_ms.saturating_mul(1 << restarts.min(16)))
+    }
+}
+
+/// The command used to launch, and relaunch, a stdio server.
+#[derive(Debug)]
+struct ServerCommand {
+    bin_path: String,
+    args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+}
+
+impl ServerCommand {
+    fn spawn(&self) -> Result<Child, ClientError> {
+        let expanded_bin_path = shellexpand::tilde(&self.bin_path);
+
+        // On Windows, we need to use cmd.exe to run the binary with arguments because Tokio
+        // always assumes that the program has an .exe extension, which is not the case for
+        // helpers like `uvx` or `npx`.
+        let mut command = if cfg!(windows) {
+            let mut cmd = tokio::process::Command::new("cmd.exe");
+            cmd.args([
+                "/C",
+                &Client::<StdioTransport>::build_windows_command(&expanded_bin_path, self.args.clone()),
+            ]);
+            cmd
+        } else {
+            let mut cmd = tokio::process::Command::new(expanded_bin_path.to_string());
+            cmd.args(&self.args);
+            cmd
+        };
+
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .envs(std::env::vars());
+
+        #[cfg(not(windows))]
+        command.process_group(0);
+
+        if let Some(env) = &self.env {
+            for (env_name, env_value) in env {
+                command.env(env_name, env_value);
+            }
+        }
+
+        Ok(command.spawn()?)
+    }
 }
 
 #[allow(dead_code)]
@@ -124,13 +270,19 @@ pub struct Client<T: Transport> {
     server_name: String,
     transport: Arc<T>,
     timeout: u64,
-    server_process_id: Option<Pid>,
+    // Shared with the supervisor so that a relaunched process is still terminated on drop
+    server_process_id: Option<Arc<AtomicU32>>,
+    command: Option<Arc<ServerCommand>>,
+    restart: RestartPolicy,
+    supervisor: OnceLock<AbortHandle>,
     client_info: serde_json::Value,
     current_id: Arc<AtomicU64>,
+    log_payloads: bool,
     pub messenger: Option<Box<dyn Messenger>>,
     // TODO: move this to tool manager that way all the assets are treated equally
     pub prompt_gets: Arc<SyncRwLock<HashMap<String, PromptGet>>>,
     pub is_prompts_out_of_date: Arc<AtomicBool>,
+    filtered_tools: Arc<SyncRwLock<Vec<FilteredTool>>>,
 }
 
 impl<T: Transport> Clone for Client<T> {
@@ -142,11 +294,16 @@ impl<T: Transport> Clone for Client<T> {
             // Note that we cannot have an id for the clone because we would kill the original
             // process when we drop the clone
             server_process_id: None,
+            command: self.command.clone(),
+            restart: self.restart,
+            supervisor: OnceLock::new(),
             client_info: self.client_info.clone(),
             current_id: self.current_id.clone(),
+            log_payloads: self.log_payloads,
             messenger: None,
             prompt_gets: self.prompt_gets.clone(),
             is_prompts_out_of_date: self.is_prompts_out_of_date.clone(),
+            filtered_tools: self.filtered_tools.clone(),
         }
     }
 }
@@ -160,43 +317,14 @@ impl Client<StdioTransport> {
             timeout,
             client_info,
             env,
+            log_payloads,
+            restart,
         } = config;
-        let child = {
-            let expanded_bin_path = shellexpand::tilde(&bin_path);
-
-            // On Windows, we need to use cmd.exe to run the binary with arguments because Tokio
-            // always assumes that the program has an .exe extension, which is not the case for
-            // helpers like `uvx` or `npx`.
-            let mut command = if cfg!(windows) {
-                let mut cmd = tokio::process::Command::new("cmd.exe");
-                cmd.args(["/C", &Self::build_windows_command(&expanded_bin_path, args)]);
-                cmd
-            } else {
-                let mut cmd = tokio::process::Command::new(expanded_bin_path.to_string());
-                cmd.args(args);
-                cmd
-            };
-
-            command
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .envs(std::env::vars());
-
-            #[cfg(not(windows))]
-            command.process_group(0);
-
-            if let Some(env) = env {
-                for (env_name, env_value) in env {
-                    command.env(env_name, env_value);
-                }
-            }
-
-            command.spawn()?
-        };
+        let command = ServerCommand { bin_path, args, env };
+        let child = command.spawn()?;
 
         let server_process_id = child.id().ok_or(ClientError::MissingProcessId)?;
-        let server_process_id = Some(Pid::from_u32(server_process_id));
+        let server_process_id = Some(Arc::new(AtomicU32::new(server_process_id)));
 
         let transport = Arc::new(transport::stdio::JsonRpcStdioTransport::client(child)?);
         Ok(Self {
@@ -204,14 +332,108 @@ impl Client<StdioTransport> {
             transport,
             timeout,
             server_process_id,
+            command: Some(Arc::new(command)),
+            restart,
+            supervisor: OnceLock::new(),
             client_info,
             current_id: Arc::new(AtomicU64::new(0)),
+            log_payloads,
             messenger: None,
             prompt_gets: Arc::new(SyncRwLock::new(HashMap::new())),
             is_prompts_out_of_date: Arc::new(AtomicBool::new(false)),
+            filtered_tools: Arc::new(SyncRwLock::new(Vec::new())),
         })
     }
 
+    /// Relaunches the server whenever its process exits, as allowed by the client's
+    /// [RestartPolicy].
+    ///
+    /// After a relaunch the initialization handshake is run again, the tools and prompts are
+    /// fetched again, and the messenger is sent a reconnection message. Requests that were
+    /// pending when the process exited fail with [TransportError::ServerExited].
+    ///
+    /// This does nothing when restarts are disabled or the client is already supervised. The
+    /// supervisor stops when this client is dropped.
+    pub fn supervise(&self) {
+        if !self.restart.enabled || self.supervisor.get().is_some() {
+            return;
+        }
+
+        let client = self.clone();
+        let messenger = self.messenger.as_ref().map(|m| m.duplicate());
+        let server_process_id = self.server_process_id.clone();
+        let handle = tokio::spawn(async move {
+            let mut restarts = 0;
+            let mut listener = client.transport.get_listener();
+            loop {
+                match listener.recv().await {
+                    Err(TransportError::ServerExited) => {},
+                    Err(TransportError::RecvError(RecvError::Closed)) => break,
+                    _ => continue,
+                }
+                tracing::warn!(target: "mcp", "Server {} exited", client.server_name);
+
+                loop {
+                    if restarts >= client.restart.max_restarts {
+                        tracing::error!(
+                            target: "mcp",
+                            "Server {} exited and has already been restarted {} times, giving up",
+                            client.server_name,
+                            restarts
+                        );
+                        return;
+                    }
+                    time::sleep(client.restart.backoff(restarts)).await;
+                    restarts += 1;
+
+                    match client.relaunch(server_process_id.as_deref()).await {
+                        Ok(cap) => {
+                            tracing::info!(
+                                target: "mcp",
+                                "Server {} restarted (attempt {})",
+                                client.server_name,
+                                restarts
+                            );
+                            if let Some(messenger) = &messenger {
+                                let _ = messenger
+                                    .send_reconnect_msg()
+                                    .await
+                                    .map_err(|e| tracing::error!("Failed to send reconnect message {:?}", e));
+                            }
+                            if cap.prompts.is_some() {
+                                fetch_prompts_and_notify_with_messenger(&client, messenger.as_ref()).await;
+                            }
+                            if cap.tools.is_some() {
+                                fetch_tools_and_notify_with_messenger(&client, messenger.as_ref()).await;
+                            }
+                            break;
+                        },
+                        Err(e) => {
+                            tracing::error!(target: "mcp", "Failed to restart server {}: {}", client.server_name, e);
+                        },
+                    }
+                }
+
+                // Exits reported by failed attempts were already handled above.
+                listener = client.transport.get_listener();
+            }
+        });
+        let _ = self.supervisor.set(handle.abort_handle());
+    }
+
+    /// Launches a new server process on the existing transport and runs the initialization
+    /// handshake with it.
+    async fn relaunch(&self, server_process_id: Option<&AtomicU32>) -> Result<ServerCapabilities, ClientError> {
+        let command = self.command.as_ref().ok_or(ClientError::InvalidPath)?;
+        let child = command.spawn()?;
+        let pid = child.id().ok_or(ClientError::MissingProcessId)?;
+        if let Some(server_process_id) = server_process_id {
+            server_process_id.store(pid, Ordering::Release);
+        }
+        self.transport.reattach(child).await?;
+        self.handshake().await
+    }
+
     fn build_windows_command(bin_path: &str, args: Vec<String>) -> String {
         let mut parts = Vec::new();
 
@@ -273,8 +495,11 @@ where
     // IF the servers are implemented well, they will shutdown once the pipe closes.
     // This drop trait is here as a fail safe to ensure we don't leave behind any orphans.
     fn drop(&mut self) {
-        if let Some(process_id) = self.server_process_id {
-            let _ = terminate_process(process_id);
+        if let Some(supervisor) = self.supervisor.get() {
+            supervisor.abort();
+        }
+        if let Some(process_id) = &self.server_process_id {
+            let _ = terminate_process(Pid::from_u32(process_id.load(Ordering::Acquire)));
         }
     }
 }
@@ -312,32 +537,7 @@ where
             }
         });
 
-        let init_params = Some({
-            let client_cap = ClientCapabilities::from(self.client_info.clone());
-            serde_json::json!(client_cap)
-        });
-        let init_resp = self.request("initialize", init_params).await?;
-        if let Err(e) = examine_server_capabilities(&init_resp) {
-            return Err(ClientError::NegotiationError(format!(
-                "Client {} has failed to negotiate server capabilities with server: {:?}",
-                self.server_name, e
-            )));
-        }
-        let cap = {
-            let result = init_resp.result.ok_or(ClientError::NegotiationError(format!(
-                "Server {} init resp is missing result",
-                self.server_name
-            )))?;
-            let cap = result
-                .get("capabilities")
-                .ok_or(ClientError::NegotiationError(format!(
-                    "Server {} init resp result is missing capabilities",
-                    self.server_name
-                )))?
-                .clone();
-            serde_json::from_value::<ServerCapabilities>(cap)?
-        };
-        self.notify("initialized", None).await?;
+        let cap = self.handshake().await?;
 
         // TODO: group this into examine_server_capabilities
         // Prefetch prompts in the background. We should only do this after the server has been
@@ -448,12 +648,90 @@ where
         Ok(cap)
     }
 
+    /// Tools from the last tools list that were hidden, with the reason for each.
+    pub fn filtered_tools(&self) -> Vec<FilteredTool> {
+        self.filtered_tools
+            .read()
+            .map(|tools| tools.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sends `initialize`, validates the server's reply, and acknowledges it with `initialized`.
+    async fn handshake(&self) -> Result<ServerCapabilities, ClientError> {
+        let init_params = Some({
+            let client_cap = ClientCapabilities::from(self.client_info.clone());
+            serde_json::json!(client_cap)
+        });
+        let init_resp = self.request("initialize", init_params).await?;
+        if let Err(e) = examine_server_capabilities(&init_resp) {
+            return Err(ClientError::NegotiationError(format!(
+                "Client {} has failed to negotiate server capabilities with server: {:?}",
+                self.server_name, e
+            )));
+        }
+        let cap = {
+            let result = init_resp.result.ok_or(ClientError::NegotiationError(format!(
+                "Server {} init resp is missing result",
+                self.server_name
+            )))?;
+            let cap = result
+                .get("capabilities")
+                .ok_or(ClientError::NegotiationError(format!(
+                    "Server {} init resp result is missing capabilities",
+                    self.server_name
+                )))?
+                .clone();
+            serde_json::from_value::<ServerCapabilities>(cap)?
+        };
+        self.notify("initialized", None).await?;
+        Ok(cap)
+    }
+
     /// Sends a request to the server associated.
     /// This call will yield until a response is received.
+    ///
+    /// Each call is recorded as an `mcp_request` span on the `mcp` target with the method, the
+    /// size of the params and the response, the duration, and any error. Payloads themselves are
+    /// only logged when `log_payloads` is set.
     pub async fn request(
         &self,
         method: &str,
         params: Option<serde_json::Value>,
+    ) -> Result<JsonRpcResponse, ClientError> {
+        let params_bytes = params.as_ref().map_or(0, |params| params.to_string().len());
+        let span = tracing::info_span!(
+            target: "mcp",
+            "mcp_request",
+            server = %self.server_name,
+            method,
+            params_bytes,
+            response_bytes = Empty,
+            duration_ms = Empty,
+            error = Empty,
+        );
+        let start = Instant::now();
+        let result = self.send_request(method, params).instrument(span.clone()).await;
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+
+        let _guard = span.enter();
+        match &result {
+            Ok(resp) => {
+                let response_bytes = resp.result.as_ref().map_or(0, |result| result.to_string().len());
+                span.record("response_bytes", response_bytes);
+                tracing::info!(target: "mcp", "request completed");
+            },
+            Err(err) => {
+                span.record("error", display(err));
+                tracing::warn!(target: "mcp", "request failed");
+            },
+        }
+        result
+    }
+
+    async fn send_request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
     ) -> Result<JsonRpcResponse, ClientError> {
         let send_map_err = |e: Elapsed| (e, method.to_string());
         let recv_map_err = |e: Elapsed| (e, format!("recv for {method}"));
@@ -464,7 +742,9 @@ where
             method: method.to_owned(),
             params,
         };
-        tracing::trace!(target: "mcp", "To {}:\n{:#?}", self.server_name, request);
+        if self.log_payloads {
+            tracing::trace!(target: "mcp", "To {}:\n{:#?}", self.server_name, request);
+        }
         let msg = JsonRpcMessage::Request(request);
         time::timeout(Duration::from_millis(self.timeout), self.transport.send(&msg))
             .await
@@ -477,10 +757,15 @@ where
             // not deserialize into a valid JsonRpcMessage (they are not supposed to do this but
             // too many people complained about this so we are adding this safeguard in)
             loop {
-                if let Ok(JsonRpcMessage::Response(resp)) = listener.recv().await {
-                    if resp.id == id {
+                match listener.recv().await {
+                    Ok(JsonRpcMessage::Response(resp)) if resp.id == id => {
                         break Ok::<JsonRpcResponse, TransportError>(resp);
-                    }
+                    },
+                    // The server is gone, so the response is never coming
+                    Err(e @ (TransportError::ServerExited | TransportError::RecvError(RecvError::Closed))) => {
+                        break Err(e);
+                    },
+                    _ => {},
                 }
             }
         })
@@ -544,10 +829,16 @@ where
                         .map_err(send_map_err)??;
                     let resp = time::timeout(Duration::from_millis(self.timeout), async {
                         loop {
-                            if let Ok(JsonRpcMessage::Response(resp)) = listener.recv().await {
-                                if resp.id == id {
+                            match listener.recv().await {
+                                Ok(JsonRpcMessage::Response(resp)) if resp.id == id => {
                                     break Ok::<JsonRpcResponse, TransportError>(resp);
-                                }
+                                },
+                                Err(
+                                    e @ (TransportError::ServerExited | TransportError::RecvError(RecvError::Closed)),
+                                ) => {
+                                    break Err(e);
+                                },
+                                _ => {},
                             }
                         }
                     })
@@ -563,7 +854,9 @@ where
                 });
             }
         }
-        tracing::trace!(target: "mcp", "From {}:\n{:#?}", self.server_name, resp);
+        if self.log_payloads {
+            tracing::trace!(target: "mcp", "From {}:\n{:#?}", self.server_name, resp);
+        }
         Ok(resp)
     }
 
@@ -665,13 +958,23 @@ where
             let msg = format!("Tool list response from {} is missing result", client.server_name);
             break 'tool_list_result Err(eyre::eyre!(msg));
         };
-        let tool_list_result = match serde_json::from_value::<ToolsListResult>(result) {
+        let mut tool_list_result = match serde_json::from_value::<ToolsListResult>(result) {
             Ok(result) => result,
             Err(e) => {
                 let msg = format!("Failed to deserialize tool result from {}: {:?}", client.server_name, e);
                 break 'tool_list_result Err(eyre::eyre!(msg));
             },
         };
+        // Hide tools that would only fail once invoked
+        let negotiated = ClientCapabilities::from(client.client_info.clone()).capabilities;
+        let (tools, filtered) = filter_tools_by_capabilities(std::mem::take(&mut tool_list_result.tools), &negotiated);
+        for tool in &filtered {
+            tracing::warn!(target: "mcp", "Hiding tool {} from {}: {}", tool.name, client.server_name, tool.reason);
+        }
+        if let Ok(mut lock) = client.filtered_tools.write() {
+            *lock = filtered;
+        }
+        tool_list_result.tools = tools;
         Ok::<ToolsListResult, eyre::Report>(tool_list_result)
     };
     if let Some(messenger) = messenger {
@@ -738,6 +1041,8 @@ mod tests {
                 map.insert("ENV_TWO".to_owned(), "2".to_owned());
                 Some(map)
             },
+            log_payloads: false,
+            restart: RestartPolicy::default(),
         };
         let client_info_two = serde_json::json!({
           "name": "TestClientTwo",
@@ -755,6 +1060,8 @@ mod tests {
                 map.insert("ENV_TWO".to_owned(), "2".to_owned());
                 Some(map)
             },
+            log_payloads: false,
+            restart: RestartPolicy::default(),
         };
         let mut client_one = Client::<StdioTransport>::from_config(client_config_one).expect("Failed to create client");
         let mut client_two = Client::<StdioTransport>::from_config(client_config_two).expect("Failed to create client");
@@ -777,6 +1084,123 @@ mod tests {
         assert!(res_two.is_ok());
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    // Builds and runs the test server like `test_client_stdio`, so it is ignored for the same
+    // reason.
+    #[ignore]
+    async fn test_client_restarts_killed_server() {
+        std::process::Command::new("cargo")
+            .args(["build", "--bin", TEST_SERVER_NAME])
+            .status()
+            .expect("Failed to build binary");
+        let bin_path = get_workspace_root().join(TEST_BIN_OUT_DIR).join(TEST_SERVER_NAME);
+        let client = Client::<StdioTransport>::from_config(ClientConfig {
+            server_name: "test_tool".to_owned(),
+            bin_path: bin_path.to_str().unwrap().to_string(),
+            args: vec![],
+            timeout: 5 * 1000,
+            client_info: serde_json::json!({ "name": "TestClient", "version": "1.0.0" }),
+            env: None,
+            log_payloads: false,
+            restart: RestartPolicy {
+                enabled: true,
+                max_restarts: 1,
+                backoff_ms: 100,
+            },
+        })
+        .expect("Failed to create client");
+        client.init().await.expect("Client init failed");
+        client.supervise();
+
+        let first_pid = client.server_process_id.as_ref().unwrap().load(Ordering::Acquire);
+        terminate_process(Pid::from_u32(first_pid)).expect("Failed to kill server");
+
+        // Requests fail until the relaunched server has completed the handshake again.
+        let ack = time::timeout(Duration::from_secs(10), async {
+            loop {
+                let pid = client.server_process_id.as_ref().unwrap().load(Ordering::Acquire);
+                if pid != first_pid {
+                    if let Ok(resp) = client.request("verify_init_ack_sent", None).await {
+                        if resp.result.as_ref().is_some_and(|v| v.to_string() == "true") {
+                            break resp;
+                        }
+                    }
+                }
+                time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await;
+        assert!(ack.is_ok(), "Server was not restarted");
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_pending_request_fails_when_server_exits() {
+        // Exits shortly after reading the request, without responding to it.
+        let client = Client::<StdioTransport>::from_config(ClientConfig {
+            server_name: "exits".to_owned(),
+            bin_path: "sh".to_owned(),
+            args: vec!["-c".to_owned(), "read line; sleep 0.2".to_owned()],
+            timeout: 5 * 1000,
+            client_info: serde_json::json!({}),
+            env: None,
+            log_payloads: false,
+            restart: RestartPolicy::default(),
+        })
+        .expect("Failed to create client");
+
+        let err = client.request("tools/list", None).await.unwrap_err();
+        assert!(
+            matches!(err, ClientError::TransportError(TransportError::ServerExited)),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_requiring_unnegotiated_capabilities_are_filtered() {
+        let tools = vec![
+            create_fake_tool_spec("get_weather"),
+            serde_json::json!({
+                "name": "summarize_with_sampling",
+                "description": "Asks the client's model to summarize",
+                "inputSchema": { "type": "object" },
+                "_meta": { REQUIRED_CAPABILITIES_META_KEY: ["sampling"] }
+            }),
+            // The spec's annotations are hints about behavior, not requirements
+            serde_json::json!({
+                "name": "annotated",
+                "description": "Uses a similarly named annotation",
+                "inputSchema": { "type": "object" },
+                "annotations": { "requiredCapabilities": ["sampling"] }
+            }),
+        ];
+        let client = echo_client_with_tools(false, tools.clone());
+        fetch_tools_and_notify_with_messenger(&client, None).await;
+        assert_eq!(client.filtered_tools(), vec![FilteredTool {
+            name: "summarize_with_sampling".to_owned(),
+            reason: ToolFilterReason::MissingCapability("sampling".to_owned()),
+        }]);
+
+        let (supported, _) = filter_tools_by_capabilities(tools.clone(), &HashMap::new());
+        assert_eq!(supported, vec![tools[0].clone(), tools[2].clone()]);
+
+        let negotiated = HashMap::from([("sampling".to_owned(), serde_json::json!({}))]);
+        let (supported, filtered) = filter_tools_by_capabilities(tools.clone(), &negotiated);
+        assert_eq!(supported, tools);
+        assert!(filtered.is_empty());
+    }
+
+    #[test]
+    fn test_restart_backoff() {
+        let policy = RestartPolicy {
+            enabled: true,
+            max_restarts: 3,
+            backoff_ms: 100,
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+    }
+
     #[allow(clippy::await_holding_lock)]
     async fn test_client_routine<T: Transport>(
         client: &mut Client<T>,
@@ -979,6 +1403,155 @@ mod tests {
         })
     }
 
+    /// Transport that answers `tools/list` with its tools, and every other request with its own
+    /// params, once the client is listening for the reply.
+    #[derive(Debug)]
+    struct EchoTransport {
+        tx: tokio::sync::broadcast::Sender<Result<JsonRpcMessage, TransportError>>,
+        log_tx: tokio::sync::broadcast::Sender<String>,
+        tools: Vec<Value>,
+        /// Notified whenever a listener subscribes
+        subscribed: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for EchoTransport {
+        async fn send(&self, msg: &JsonRpcMessage) -> Result<(), TransportError> {
+            if let JsonRpcMessage::Request(request) = msg {
+                let resp = JsonRpcResponse {
+                    jsonrpc: JsonRpcVersion::default(),
+                    id: request.id,
+                    result: Some(match request.method.as_str() {
+                        "tools/list" => serde_json::json!({ "tools": self.tools }),
+                        _ => serde_json::json!({ "echo": request.params }),
+                    }),
+                    error: None,
+                };
+                let tx = self.tx.clone();
+                let subscribed = Arc::clone(&self.subscribed);
+                let listeners = tx.receiver_count();
+                // The client subscribes only after sending, so wait for its listener to reply.
+                tokio::spawn(async move {
+                    while tx.receiver_count() <= listeners {
+                        subscribed.notified().await;
+                    }
+                    let _ = tx.send(Ok(JsonRpcMessage::Response(resp)));
+                });
+            }
+            Ok(())
+        }
+
+        fn get_listener(&self) -> impl crate::mcp_client::Listener {
+            let receiver = self.tx.subscribe();
+            self.subscribed.notify_one();
+            transport::StdioListener { receiver }
+        }
+
+        async fn shutdown(&self) -> Result<(), TransportError> {
+            Ok(())
+        }
+
+        fn get_log_listener(&self) -> impl LogListener {
+            transport::StdioLogListener {
+                receiver: self.log_tx.subscribe(),
+            }
+        }
+    }
+
+    fn echo_client(log_payloads: bool) -> Client<EchoTransport> {
+        echo_client_with_tools(log_payloads, vec![])
+    }
+
+    fn echo_client_with_tools(log_payloads: bool, tools: Vec<Value>) -> Client<EchoTransport> {
+        let (tx, _) = tokio::sync::broadcast::channel(16);
+        let (log_tx, _) = tokio::sync::broadcast::channel(16);
+        Client {
+            server_name: "echo".to_owned(),
+            transport: Arc::new(EchoTransport {
+                tx,
+                log_tx,
+                tools,
+                subscribed: Arc::default(),
+            }),
+            timeout: 5000,
+            server_process_id: None,
+            command: None,
+            restart: RestartPolicy::default(),
+            supervisor: OnceLock::new(),
+            client_info: serde_json::json!({}),
+            current_id: Arc::new(AtomicU64::new(0)),
+            log_payloads,
+            messenger: None,
+            prompt_gets: Arc::new(SyncRwLock::new(HashMap::new())),
+            is_prompts_out_of_date: Arc::new(AtomicBool::new(false)),
+            filtered_tools: Arc::new(SyncRwLock::new(Vec::new())),
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl CapturedLogs {
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for CapturedLogs {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    async fn logged_request(log_payloads: bool) -> String {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(logs.clone())
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let params = serde_json::json!({ "name": "secret_tool" });
+        echo_client(log_payloads)
+            .request("tools/call", Some(params))
+            .await
+            .expect("echo request failed");
+        logs.contents()
+    }
+
+    #[tokio::test]
+    async fn test_request_emits_span() {
+        let logs = logged_request(false).await;
+        assert!(logs.contains("mcp_request{"), "{logs}");
+        assert!(logs.contains("server=echo"), "{logs}");
+        assert!(logs.contains("method=\"tools/call\""), "{logs}");
+        assert!(logs.contains("params_bytes=22"), "{logs}");
+        assert!(logs.contains("duration_ms="), "{logs}");
+        assert!(logs.contains("request completed"), "{logs}");
+        assert!(!logs.contains("secret_tool"), "{logs}");
+    }
+
+    #[tokio::test]
+    async fn test_request_logs_payloads_when_enabled() {
+        let logs = logged_request(true).await;
+        assert!(logs.contains("mcp_request{"), "{logs}");
+        assert!(logs.contains("secret_tool"), "{logs}");
+    }
+
     #[cfg(windows)]
     mod windows_command_tests {
         use super::*;
@@ -37,6 +37,9 @@ pub trait Messenger: std::fmt::Debug + Send + Sync + 'static {
     /// Signals to the orchestrator that a server has started initializing
     async fn send_init_msg(&self) -> Result<(), MessengerError>;
 
+    /// Signals to the orchestrator that a server was relaunched after exiting
+    async fn send_reconnect_msg(&self) -> Result<(), MessengerError>;
+
     /// Creates a duplicate of the messenger object
     /// This function is used to create a new instance of the messenger with the same configuration
     fn duplicate(&self) -> Box<dyn Messenger>;
@@ -79,6 +82,10 @@ impl Messenger for NullMessenger {
         Ok(())
     }
 
+    async fn send_reconnect_msg(&self) -> Result<(), MessengerError> {
+        Ok(())
+    }
+
     fn duplicate(&self) -> Box<dyn Messenger> {
         Box::new(NullMessenger)
     }
@@ -15,6 +15,8 @@ pub enum TransportError {
     Stdio(String),
     #[error("{0}")]
     Custom(String),
+    #[error("Server process exited")]
+    ServerExited,
     #[error(transparent)]
     RecvError(#[from] tokio::sync::broadcast::error::RecvError),
 }
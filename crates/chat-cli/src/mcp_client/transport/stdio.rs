@@ -10,7 +10,9 @@ use tokio::io::{
 };
 use tokio::process::{
     Child,
+    ChildStderr,
     ChildStdin,
+    ChildStdout,
 };
 use tokio::sync::{
     Mutex,
@@ -29,6 +31,10 @@ use super::{
 pub enum JsonRpcStdioTransport {
     Client {
         stdin: Arc<Mutex<ChildStdin>>,
+        // The senders are kept so that the channels outlive the server process and can be fed by
+        // a relaunched one.
+        tx: broadcast::Sender<Result<JsonRpcMessage, TransportError>>,
+        log_tx: broadcast::Sender<String>,
         receiver: broadcast::Receiver<Result<JsonRpcMessage, TransportError>>,
         log_receiver: broadcast::Receiver<String>,
     },
@@ -39,9 +45,14 @@ pub enum JsonRpcStdioTransport {
 }
 
 impl JsonRpcStdioTransport {
+    /// Forwards messages read from `reader` to `tx`.
+    ///
+    /// When `notify_exit` is set, reaching the end of `reader` is reported as
+    /// [TransportError::ServerExited], since a server closing its stdout means it has exited.
     fn spawn_reader<R: AsyncRead + Unpin + Send + 'static>(
         reader: R,
         tx: broadcast::Sender<Result<JsonRpcMessage, TransportError>>,
+        notify_exit: bool,
     ) {
         tokio::spawn(async move {
             let mut buffer = Vec::<u8>::new();
@@ -51,7 +62,12 @@ impl JsonRpcStdioTransport {
                 // Messages are delimited by newlines and assumed to contain no embedded newlines
                 // See https://spec.modelcontextprotocol.io/specification/2024-11-05/basic/transports/#stdio
                 match buf_reader.read_until(b'\n', &mut buffer).await {
-                    Ok(0) => break,
+                    Ok(0) => {
+                        if notify_exit {
+                            let _ = tx.send(Err(TransportError::ServerExited));
+                        }
+                        break;
+                    },
                     Ok(_) => match serde_json::from_slice::<JsonRpcMessage>(buffer.as_slice()) {
                         Ok(msg) => {
                             let _ = tx.send(Ok(msg));
@@ -68,8 +84,17 @@ impl JsonRpcStdioTransport {
         });
     }
 
-    pub fn client(child_process: Child) -> Result<Self, TransportError> {
-        let (tx, receiver) = broadcast::channel::<Result<JsonRpcMessage, TransportError>>(100);
+    fn spawn_log_reader(stderr: ChildStderr, log_tx: broadcast::Sender<String>) {
+        tokio::task::spawn(async move {
+            let stderr = tokio::io::BufReader::new(stderr);
+            let mut lines = stderr.lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = log_tx.send(line);
+            }
+        });
+    }
+
+    fn take_pipes(child_process: Child) -> Result<(ChildStdin, ChildStdout, ChildStderr), TransportError> {
         let Some(stdout) = child_process.stdout else {
             return Err(TransportError::Custom("No stdout found on child process".to_owned()));
         };
@@ -79,26 +104,50 @@ impl JsonRpcStdioTransport {
         let Some(stderr) = child_process.stderr else {
             return Err(TransportError::Custom("No stderr found on child process".to_owned()));
         };
+        Ok((stdin, stdout, stderr))
+    }
+
+    pub fn client(child_process: Child) -> Result<Self, TransportError> {
+        let (tx, receiver) = broadcast::channel::<Result<JsonRpcMessage, TransportError>>(100);
+        let (stdin, stdout, stderr) = Self::take_pipes(child_process)?;
         let (log_tx, log_receiver) = broadcast::channel::<String>(100);
-        tokio::task::spawn(async move {
-            let stderr = tokio::io::BufReader::new(stderr);
-            let mut lines = stderr.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
-                let _ = log_tx.send(line);
-            }
-        });
+        Self::spawn_log_reader(stderr, log_tx.clone());
         let stdin = Arc::new(Mutex::new(stdin));
-        Self::spawn_reader(stdout, tx);
+        Self::spawn_reader(stdout, tx.clone(), true);
         Ok(JsonRpcStdioTransport::Client {
             stdin,
+            tx,
+            log_tx,
             receiver,
             log_receiver,
         })
     }
 
+    /// Connects a client transport to a relaunched server process.
+    ///
+    /// Existing listeners keep working and receive messages from the new process.
+    pub async fn reattach(&self, child_process: Child) -> Result<(), TransportError> {
+        let JsonRpcStdioTransport::Client {
+            stdin: current_stdin,
+            tx,
+            log_tx,
+            ..
+        } = self
+        else {
+            return Err(TransportError::Custom(
+                "Only client transports can be reattached".to_owned(),
+            ));
+        };
+        let (stdin, stdout, stderr) = Self::take_pipes(child_process)?;
+        *current_stdin.lock().await = stdin;
+        Self::spawn_log_reader(stderr, log_tx.clone());
+        Self::spawn_reader(stdout, tx.clone(), true);
+        Ok(())
+    }
+
     pub fn server(stdin: Stdin, stdout: Stdout) -> Result<Self, TransportError> {
         let (tx, receiver) = broadcast::channel::<Result<JsonRpcMessage, TransportError>>(100);
-        Self::spawn_reader(stdin, tx);
+        Self::spawn_reader(stdin, tx, false);
         let stdout = Arc::new(Mutex::new(stdout));
         Ok(JsonRpcStdioTransport::Server { stdout, receiver })
     }
@@ -255,6 +304,41 @@ mod tests {
         assert!(are_json_values_equal(&echo_value, &message_value));
     }
 
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_client_transport_reattach() {
+        use super::TransportError;
+
+        let spawn = |program: &str, args: &[&str]| {
+            Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .expect("Failed to spawn command")
+        };
+
+        // The first process exits on its own shortly after starting, which should be reported to
+        // listeners.
+        let transport = JsonRpcStdioTransport::client(spawn("sh", &["-c", "sleep 0.2"]))
+            .expect("Failed to create client transport");
+        let mut listener = transport.get_listener();
+        assert!(matches!(listener.recv().await, Err(TransportError::ServerExited)));
+
+        transport
+            .reattach(spawn("cat", &[]))
+            .await
+            .expect("Failed to reattach transport");
+        let message = create_test_message();
+        transport.send(&message).await.expect("Failed to send message");
+        let echo = listener.recv().await.expect("Failed to receive message");
+        assert!(are_json_values_equal(
+            &serde_json::to_value(&echo).unwrap(),
+            &serde_json::to_value(&message).unwrap()
+        ));
+    }
+
     fn are_json_values_equal(a: &Value, b: &Value) -> bool {
         match (a, b) {
             (Value::Null, Value::Null) => true,
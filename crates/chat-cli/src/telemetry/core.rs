@@ -137,6 +137,7 @@ impl Event {
                 context_file_length,
                 message_id,
                 request_id,
+                correlation_id,
                 result,
                 reason,
                 reason_desc,
@@ -149,6 +150,7 @@ impl Event {
                     value: None,
                     amazonq_conversation_id: Some(conversation_id.into()),
                     request_id: request_id.map(Into::into),
+                    codewhispererterminal_correlation_id: correlation_id.map(Into::into),
                     codewhispererterminal_utterance_id: message_id.map(Into::into),
                     credential_start_url: self.credential_start_url.map(Into::into),
                     sso_region: self.sso_region.map(Into::into),
@@ -179,6 +181,7 @@ impl Event {
                 model,
                 aws_service_name,
                 aws_operation_name,
+                correlation_id,
             } => Some(
                 CodewhispererterminalToolUseSuggested {
                     create_time: self.created_time,
@@ -203,6 +206,7 @@ impl Event {
                     codewhispererterminal_client_application: self.client_application.map(Into::into),
                     codewhispererterminal_aws_service_name: aws_service_name.map(Into::into),
                     codewhispererterminal_aws_operation_name: aws_operation_name.map(Into::into),
+                    codewhispererterminal_correlation_id: correlation_id.map(Into::into),
                 }
                 .into_metric_datum(),
             ),
@@ -314,6 +318,7 @@ pub enum EventType {
         conversation_id: String,
         message_id: Option<String>,
         request_id: Option<String>,
+        correlation_id: Option<String>,
         context_file_length: Option<usize>,
         result: TelemetryResult,
         reason: Option<String>,
@@ -337,6 +342,7 @@ pub enum EventType {
         model: Option<String>,
         aws_service_name: Option<String>,
         aws_operation_name: Option<String>,
+        correlation_id: Option<String>,
     },
     McpServerInit {
         conversation_id: String,
@@ -383,6 +389,7 @@ pub struct ToolUseEventBuilder {
     pub model: Option<String>,
     pub aws_service_name: Option<String>,
     pub aws_operation_name: Option<String>,
+    pub correlation_id: Option<String>,
 }
 
 impl ToolUseEventBuilder {
@@ -403,6 +410,7 @@ impl ToolUseEventBuilder {
             model,
             aws_service_name: None,
             aws_operation_name: None,
+            correlation_id: None,
         }
     }
 
@@ -411,6 +419,11 @@ impl ToolUseEventBuilder {
         self
     }
 
+    pub fn correlation_id(mut self, id: Option<String>) -> Self {
+        self.correlation_id = id;
+        self
+    }
+
     pub fn set_tool_use_id(mut self, id: String) -> Self {
         self.tool_use_id.replace(id);
         self
@@ -205,6 +205,20 @@ impl TelemetryThread {
         })
     }
 
+    /// Only meant for testing. Events are sent to the returned receiver instead of being
+    /// published.
+    #[cfg(test)]
+    pub fn new_capturing() -> (Self, mpsc::UnboundedReceiver<Event>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (
+            Self {
+                handle: None,
+                tx: TelemetrySender::Strong(tx),
+            },
+            rx,
+        )
+    }
+
     pub async fn finish(self) -> Result<(), TelemetryError> {
         drop(self.tx);
         if let Some(handle) = self.handle {
@@ -247,6 +261,7 @@ impl TelemetryThread {
         conversation_id: String,
         message_id: Option<String>,
         request_id: Option<String>,
+        correlation_id: Option<String>,
         context_file_length: Option<usize>,
         result: TelemetryResult,
         reason: Option<String>,
@@ -258,6 +273,7 @@ impl TelemetryThread {
             conversation_id,
             message_id,
             request_id,
+            correlation_id,
             context_file_length,
             result,
             reason,
@@ -291,6 +307,7 @@ impl TelemetryThread {
             model: event.model,
             aws_service_name: event.aws_service_name,
             aws_operation_name: event.aws_operation_name,
+            correlation_id: event.correlation_id,
         });
         set_event_metadata(database, &mut telemetry_event).await;
 
@@ -662,6 +679,7 @@ mod test {
                 "conv_id".to_owned(),
                 Some("message_id".to_owned()),
                 Some("req_id".to_owned()),
+                Some("correlation_id".to_owned()),
                 Some(123),
                 TelemetryResult::Succeeded,
                 None,
@@ -0,0 +1,171 @@
+//! Whether styled output should be written
+//!
+//! Color is disabled by `--no-color`, a non-empty `NO_COLOR`, `CLICOLOR=0`, or when the stream
+//! being written to is not a terminal. Output written through a [ColorWriter] has its colors and
+//! text attributes removed when color is disabled, other escape sequences such as cursor movement
+//! are kept.
+
+use std::ffi::OsString;
+use std::io::{
+    self,
+    IsTerminal,
+    Write,
+};
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+
+const ESC: u8 = 0x1b;
+
+static NO_COLOR_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Disable color for the rest of the process, used for `--no-color`
+pub fn disable() {
+    NO_COLOR_FLAG.store(true, Ordering::Relaxed);
+    crossterm::style::force_color_output(false);
+    anstream::ColorChoice::Never.write_global();
+}
+
+/// Whether styled output should be written to `stream`
+pub fn color_enabled(stream: &impl IsTerminal) -> bool {
+    !NO_COLOR_FLAG.load(Ordering::Relaxed)
+        && colors_allowed(
+            std::env::var_os("NO_COLOR"),
+            std::env::var_os("CLICOLOR"),
+            stream.is_terminal(),
+        )
+}
+
+/// Apply the `NO_COLOR` and `CLICOLOR` conventions to a stream
+pub fn colors_allowed(no_color: Option<OsString>, clicolor: Option<OsString>, is_terminal: bool) -> bool {
+    if no_color.is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if clicolor.is_some_and(|v| v == "0") {
+        return false;
+    }
+    is_terminal
+}
+
+/// Writer that drops SGR escape sequences, colors and text attributes, when color is disabled
+///
+/// Sequences may be split across writes, an incomplete sequence is held until the rest of it is
+/// written.
+#[derive(Debug)]
+pub struct ColorWriter<W> {
+    inner: W,
+    enabled: bool,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> ColorWriter<W> {
+    pub fn new(inner: W, enabled: bool) -> Self {
+        Self {
+            inner,
+            enabled,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for ColorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.enabled {
+            return self.inner.write(buf);
+        }
+
+        let mut out = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            if self.pending.is_empty() {
+                match byte {
+                    ESC => self.pending.push(byte),
+                    _ => out.push(byte),
+                }
+                continue;
+            }
+
+            self.pending.push(byte);
+            match self.pending.as_slice() {
+                [ESC, b'['] => (),
+                // Not a control sequence, pass it through
+                [ESC, _] => out.append(&mut self.pending),
+                [ESC, b'[', .., last] if (0x40..=0x7e).contains(last) => {
+                    if *last == b'm' {
+                        self.pending.clear();
+                    } else {
+                        out.append(&mut self.pending);
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{
+        Attribute,
+        Color,
+    };
+    use crossterm::{
+        cursor,
+        queue,
+        style,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_colors_allowed() {
+        assert!(colors_allowed(None, None, true));
+        assert!(!colors_allowed(None, None, false));
+        assert!(!colors_allowed(Some("1".into()), None, true));
+        assert!(colors_allowed(Some("".into()), None, true));
+        assert!(!colors_allowed(None, Some("0".into()), true));
+        assert!(colors_allowed(None, Some("1".into()), true));
+    }
+
+    #[test]
+    fn test_strips_styles_and_keeps_cursor_movement() {
+        let mut writer = ColorWriter::new(Vec::new(), false);
+        queue!(
+            writer,
+            style::SetForegroundColor(Color::Red),
+            style::SetAttribute(Attribute::Bold),
+            style::Print("error"),
+            style::ResetColor,
+            cursor::MoveToColumn(0),
+        )
+        .unwrap();
+        assert_eq!(writer.into_inner(), b"error\x1b[1G");
+    }
+
+    #[test]
+    fn test_sequence_split_across_writes() {
+        let mut writer = ColorWriter::new(Vec::new(), false);
+        for chunk in [&b"a\x1b"[..], b"[3", b"8;5;1", b"mb\x1b[", b"0m"] {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.into_inner(), b"ab");
+    }
+
+    #[test]
+    fn test_enabled_passes_through() {
+        let mut writer = ColorWriter::new(Vec::new(), true);
+        writer.write_all(b"\x1b[31mx\x1b[0m").unwrap();
+        assert_eq!(writer.into_inner(), b"\x1b[31mx\x1b[0m");
+    }
+}
@@ -1,8 +1,12 @@
+pub mod color;
 pub mod consts;
 pub mod directories;
 pub mod knowledge_store;
 pub mod open;
 pub mod process;
+/// A copy of `fig_util::redact`, since this crate doesn't depend on `fig_util`. Edit both, the
+/// copies are checked to be identical by a test.
+pub mod redact;
 pub mod spinner;
 pub mod system_info;
 #[cfg(test)]
@@ -97,3 +101,14 @@ pub fn dialoguer_theme() -> ColorfulTheme {
         ..ColorfulTheme::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_redact_matches_fig_util() {
+        assert!(
+            include_str!("redact.rs") == include_str!("../../../fig_util/src/redact.rs"),
+            "chat-cli's util/redact.rs must be kept identical to fig_util's src/redact.rs"
+        );
+    }
+}
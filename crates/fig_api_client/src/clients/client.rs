@@ -169,9 +169,13 @@ impl Client {
 
         match &self.inner {
             inner::Inner::Codewhisperer(client) => {
-                Ok(codewhisperer_generate_recommendation(client, input, self.profile_arn.clone()).await?)
+                codewhisperer_generate_recommendation(client, input, self.profile_arn.clone())
+                    .await
+                    .map_err(|e| Error::from(e).classify())
             },
-            inner::Inner::Consolas(client) => Ok(consolas_generate_recommendation(client, input).await?),
+            inner::Inner::Consolas(client) => consolas_generate_recommendation(client, input)
+                .await
+                .map_err(|e| Error::from(e).classify()),
             inner::Inner::Mock => Ok(RecommendationsOutput {
                 recommendations: vec![Recommendation {
                     content: "Hello, world!".to_string(),
@@ -172,12 +172,20 @@ impl StreamingClient {
                                 && err.meta().message() == Some("Input is too long."))
                         });
 
-                        if is_quota_breach {
-                            Err(Error::QuotaBreach("quota has reached its limit"))
-                        } else if is_context_window_overflow {
-                            Err(Error::ContextWindowOverflow)
-                        } else {
-                            Err(e.into())
+                        if is_context_window_overflow {
+                            return Err(Error::ContextWindowOverflow);
+                        }
+                        let err = Error::from(e);
+                        // Checked before classifying, which would treat it as a retryable 429
+                        if err.is_quota_exceeded() {
+                            return Err(Error::QuotaBreach("quota has reached its limit"));
+                        }
+                        match err.classify() {
+                            // A 429 that isn't throttling means the quota was used up
+                            Error::CodewhispererGenerateAssistantResponse(_) if is_quota_breach => {
+                                Err(Error::QuotaBreach("quota has reached its limit"))
+                            },
+                            err => Err(err),
                         }
                     },
                 }
@@ -200,7 +208,11 @@ impl StreamingClient {
                         .send_message()
                         .conversation_state(conversation_state_builder.build().expect("fix me"))
                         .send()
-                        .await?,
+                        .await
+                        .map_err(|e| match Error::from(e) {
+                            err if err.is_quota_exceeded() => Error::QuotaBreach("quota has reached its limit"),
+                            err => err.classify(),
+                        })?,
                 ))
             },
             inner::Inner::Mock(events) => {
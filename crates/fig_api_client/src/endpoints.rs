@@ -13,6 +13,11 @@ use crate::consts::{
     PROD_Q_ENDPOINT_URL,
 };
 
+/// Setting that selects one of the known endpoints by URL
+const API_ENDPOINT_KEY: &str = "api.endpoint";
+/// Setting that selects one of the known endpoints by region
+const API_REGION_KEY: &str = "api.region";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Endpoint {
     pub url: Cow<'static, str>,
@@ -33,7 +38,15 @@ impl Endpoint {
         region: PROD_Q_ENDPOINT_REGION,
     };
 
+    /// The CodeWhisperer endpoint, the first of these that is set wins:
+    ///
+    /// 1. `api.codewhisperer.service`, any endpoint and region, meant for development
+    /// 2. `api.endpoint` and `api.region`, which must select one of [Self::CODEWHISPERER_ENDPOINTS]
+    ///    in the region of the selected profile, if there is one
+    /// 3. The region of the selected profile
+    /// 4. [Self::DEFAULT_ENDPOINT]
     pub fn load_codewhisperer() -> Self {
+        let profile_region = Self::profile_region();
         let (endpoint, region) =
             if let Ok(Some(Value::Object(o))) = fig_settings::settings::get_value("api.codewhisperer.service") {
                 // The following branch is evaluated in case the user has set their own endpoint.
@@ -41,23 +54,22 @@ impl Endpoint {
                     o.get("endpoint").and_then(|v| v.as_str()).map(|v| v.to_owned()),
                     o.get("region").and_then(|v| v.as_str()).map(|v| v.to_owned()),
                 )
-            } else if let Ok(Some(Value::Object(o))) = fig_settings::state::get_value("api.codewhisperer.profile") {
+            } else if let Some(endpoint) = Self::load_override(profile_region.as_deref()) {
+                (
+                    Some(endpoint.url().to_owned()),
+                    Some(endpoint.region().as_ref().to_owned()),
+                )
+            } else if let Some(region) = profile_region {
                 // The following branch is evaluated in the case of user profile being set.
-                match o.get("arn").and_then(|v| v.as_str()).map(|v| v.to_owned()) {
-                    Some(arn) => {
-                        let region = arn.split(':').nth(3).unwrap_or_default().to_owned();
-                        match Self::CODEWHISPERER_ENDPOINTS
-                            .iter()
-                            .find(|e| e.region().as_ref() == region)
-                        {
-                            Some(endpoint) => (Some(endpoint.url().to_owned()), Some(region)),
-                            None => {
-                                error!("Failed to find endpoint for region: {region}");
-                                (None, None)
-                            },
-                        }
+                match Self::CODEWHISPERER_ENDPOINTS
+                    .iter()
+                    .find(|e| e.region().as_ref() == region)
+                {
+                    Some(endpoint) => (Some(endpoint.url().to_owned()), Some(region)),
+                    None => {
+                        error!("Failed to find endpoint for region: {region}");
+                        (None, None)
                     },
-                    None => (None, None),
                 }
             } else {
                 (None, None)
@@ -72,6 +84,66 @@ impl Endpoint {
         }
     }
 
+    /// The region of the selected profile, taken from its ARN
+    fn profile_region() -> Option<String> {
+        let Ok(Some(Value::Object(o))) = fig_settings::state::get_value("api.codewhisperer.profile") else {
+            return None;
+        };
+        let arn = o.get("arn").and_then(|v| v.as_str())?;
+        Some(arn.split(':').nth(3).unwrap_or_default().to_owned())
+    }
+
+    /// The endpoint selected with the `api.endpoint` and `api.region` settings, invalid values are
+    /// logged and ignored
+    fn load_override(profile_region: Option<&str>) -> Option<Self> {
+        let endpoint = fig_settings::settings::get_string_opt(API_ENDPOINT_KEY);
+        let region = fig_settings::settings::get_string_opt(API_REGION_KEY);
+        match Self::resolve_override(endpoint.as_deref(), region.as_deref(), profile_region) {
+            Ok(endpoint) => endpoint,
+            Err(err) => {
+                error!("Ignoring {API_ENDPOINT_KEY} and {API_REGION_KEY}: {err}");
+                None
+            },
+        }
+    }
+
+    /// Find the known endpoint matching the overrides, both must agree when both are set
+    ///
+    /// Only the known endpoints can be selected, so an override can't point the client at a
+    /// region that `region_check` would reject. A profile can only be used in its own region, so
+    /// the endpoint must also be in `profile_region` when a profile is selected.
+    fn resolve_override(
+        endpoint: Option<&str>,
+        region: Option<&str>,
+        profile_region: Option<&str>,
+    ) -> Result<Option<Self>, String> {
+        if endpoint.is_none() && region.is_none() {
+            return Ok(None);
+        }
+
+        let same_url = |a: &str, b: &str| a.trim_end_matches('/') == b.trim_end_matches('/');
+        let resolved = Self::CODEWHISPERER_ENDPOINTS
+            .into_iter()
+            .find(|known| {
+                endpoint.is_none_or(|endpoint| same_url(known.url(), endpoint))
+                    && region.is_none_or(|region| known.region().as_ref() == region)
+            })
+            .map(Some)
+            .ok_or_else(|| match (endpoint, region) {
+                (Some(endpoint), Some(region)) => format!("{endpoint} in {region} is not a known endpoint"),
+                (Some(endpoint), None) => format!("{endpoint} is not a known endpoint"),
+                (None, region) => format!("{} is not a supported region", region.unwrap_or_default()),
+            })?;
+
+        match (&resolved, profile_region) {
+            (Some(resolved), Some(profile_region)) if resolved.region().as_ref() != profile_region => Err(format!(
+                "{} is not the region of the selected profile, {profile_region}",
+                resolved.region()
+            )),
+            _ => Ok(resolved),
+        }
+    }
+
     pub fn load_q() -> Self {
         match fig_settings::settings::get_value("api.q.service") {
             Ok(Some(Value::Object(o))) => {
@@ -121,4 +193,38 @@ mod tests {
         Url::parse(custom.url()).unwrap();
         assert_eq!(custom.region(), &Region::new("us-west-2"));
     }
+
+    #[test]
+    fn test_resolve_override() {
+        assert_eq!(Endpoint::resolve_override(None, None, None), Ok(None));
+
+        let fra = Endpoint::resolve_override(None, Some("eu-central-1"), None)
+            .unwrap()
+            .unwrap();
+        assert_ne!(fra, Endpoint::DEFAULT_ENDPOINT);
+        assert_eq!(fra.url(), PROD_CODEWHISPERER_FRA_ENDPOINT_URL);
+        assert_eq!(fra.region(), &PROD_CODEWHISPERER_FRA_ENDPOINT_REGION);
+
+        // Trailing slashes don't matter
+        assert_eq!(
+            Endpoint::resolve_override(Some("https://q.eu-central-1.amazonaws.com"), None, None),
+            Ok(Some(fra.clone()))
+        );
+        assert_eq!(
+            Endpoint::resolve_override(Some(PROD_CODEWHISPERER_ENDPOINT_URL), Some("us-east-1"), None),
+            Ok(Some(Endpoint::DEFAULT_ENDPOINT))
+        );
+
+        assert!(Endpoint::resolve_override(None, Some("us-gov-west-1"), None).is_err());
+        assert!(Endpoint::resolve_override(Some("https://example.com"), None, None).is_err());
+        assert!(Endpoint::resolve_override(Some(PROD_CODEWHISPERER_ENDPOINT_URL), Some("eu-central-1"), None).is_err());
+
+        // The override must be in the region of the selected profile
+        assert_eq!(
+            Endpoint::resolve_override(None, Some("eu-central-1"), Some("eu-central-1")),
+            Ok(Some(fra))
+        );
+        assert!(Endpoint::resolve_override(None, Some("eu-central-1"), Some("us-east-1")).is_err());
+        assert!(Endpoint::resolve_override(Some(PROD_CODEWHISPERER_ENDPOINT_URL), None, Some("eu-central-1")).is_err());
+    }
 }
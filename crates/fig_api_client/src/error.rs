@@ -1,15 +1,20 @@
+use std::time::Duration;
+
 use amzn_codewhisperer_client::operation::generate_completions::GenerateCompletionsError;
 use amzn_codewhisperer_client::operation::list_available_customizations::ListAvailableCustomizationsError;
 use amzn_codewhisperer_client::operation::list_available_profiles::ListAvailableProfilesError;
 pub use amzn_codewhisperer_streaming_client::operation::generate_assistant_response::GenerateAssistantResponseError;
+use amzn_codewhisperer_streaming_client::types::ThrottlingExceptionReason as CodewhispererThrottlingExceptionReason;
 use amzn_codewhisperer_streaming_client::types::error::ChatResponseStreamError as CodewhispererChatResponseStreamError;
 use amzn_consolas_client::operation::generate_recommendations::GenerateRecommendationsError;
 use amzn_consolas_client::operation::list_customizations::ListCustomizationsError;
 use amzn_qdeveloper_streaming_client::operation::send_message::SendMessageError as QDeveloperSendMessageError;
+use amzn_qdeveloper_streaming_client::types::ThrottlingExceptionReason as QDeveloperThrottlingExceptionReason;
 use amzn_qdeveloper_streaming_client::types::error::ChatResponseStreamError as QDeveloperChatResponseStreamError;
 use aws_credential_types::provider::error::CredentialsError;
 use aws_smithy_runtime_api::client::orchestrator::HttpResponse;
 pub use aws_smithy_runtime_api::client::result::SdkError;
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
 use aws_smithy_types::event_stream::RawMessage;
 use fig_aws_common::SdkErrorDisplay;
 use thiserror::Error;
@@ -55,6 +60,35 @@ pub enum Error {
     #[error("the context window has overflowed")]
     ContextWindowOverflow,
 
+    /// The credentials or token used for the request have expired, the user has to log in again.
+    #[error("authentication has expired")]
+    AuthExpired {
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// The service isn't available in the region the request was sent to.
+    #[error("the service is not available in this region")]
+    RegionUnsupported {
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// The request was throttled, `retry_after` is how long the service asked to wait if it said.
+    #[error("the request was rate limited")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        #[source]
+        source: Box<Error>,
+    },
+
+    /// The service is temporarily unavailable, the request can be retried later.
+    #[error("the service is unavailable")]
+    ServiceUnavailable {
+        #[source]
+        source: Box<Error>,
+    },
+
     #[error(transparent)]
     SmithyBuild(#[from] aws_smithy_types::error::operation::BuildError),
 
@@ -83,7 +117,11 @@ impl Error {
             | Error::SmithyBuild(_)
             | Error::UnsupportedConsolas(_)
             | Error::ContextWindowOverflow
+            | Error::AuthExpired { .. }
+            | Error::RegionUnsupported { .. }
+            | Error::ServiceUnavailable { .. }
             | Error::QuotaBreach(_) => false,
+            Error::RateLimited { .. } => true,
         }
     }
 
@@ -96,7 +134,11 @@ impl Error {
             Error::ListAvailableServices(e) => e.as_service_error().is_some(),
             Error::CodewhispererGenerateAssistantResponse(e) => e.as_service_error().is_some(),
             Error::QDeveloperSendMessage(e) => e.as_service_error().is_some(),
-            Error::ContextWindowOverflow => true,
+            Error::ContextWindowOverflow
+            | Error::AuthExpired { .. }
+            | Error::RegionUnsupported { .. }
+            | Error::RateLimited { .. }
+            | Error::ServiceUnavailable { .. } => true,
             Error::ListAvailableProfilesError(e) => e.as_service_error().is_some(),
             Error::CodewhispererChatResponseStream(_)
             | Error::QDeveloperChatResponseStream(_)
@@ -105,6 +147,106 @@ impl Error {
             | Error::QuotaBreach(_) => false,
         }
     }
+
+    /// How long the service asked to wait before retrying a [Error::RateLimited] request
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Whether a send message request was rejected because the daily or monthly request quota
+    /// was used up, which is also returned as a 429 throttling error but can't be retried
+    pub(crate) fn is_quota_exceeded(&self) -> bool {
+        match self {
+            Error::CodewhispererGenerateAssistantResponse(e) => match e.as_service_error() {
+                Some(GenerateAssistantResponseError::ThrottlingError(e)) => matches!(
+                    e.reason(),
+                    Some(
+                        CodewhispererThrottlingExceptionReason::DailyRequestCount
+                            | CodewhispererThrottlingExceptionReason::MonthlyRequestCount
+                    )
+                ),
+                Some(GenerateAssistantResponseError::ServiceQuotaExceededError(_)) => true,
+                _ => false,
+            },
+            Error::QDeveloperSendMessage(e) => match e.as_service_error() {
+                Some(QDeveloperSendMessageError::ThrottlingError(e)) => matches!(
+                    e.reason(),
+                    Some(
+                        QDeveloperThrottlingExceptionReason::DailyRequestCount
+                            | QDeveloperThrottlingExceptionReason::MonthlyRequestCount
+                    )
+                ),
+                Some(QDeveloperSendMessageError::ServiceQuotaExceededError(_)) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Map a failed request to one of the classified variants, so callers can give specific
+    /// advice or decide whether to retry. The original error is kept as the source of the
+    /// classified one, errors that don't match a class are returned unchanged.
+    pub fn classify(self) -> Self {
+        let class = match &self {
+            Error::GenerateCompletions(e) => classify_sdk_error(e),
+            Error::GenerateRecommendations(e) => classify_sdk_error(e),
+            Error::ListAvailableCustomizations(e) => classify_sdk_error(e),
+            Error::ListAvailableServices(e) => classify_sdk_error(e),
+            Error::CodewhispererGenerateAssistantResponse(e) => classify_sdk_error(e),
+            Error::QDeveloperSendMessage(e) => classify_sdk_error(e),
+            Error::ListAvailableProfilesError(e) => classify_sdk_error(e),
+            _ => None,
+        };
+
+        let source = Box::new(self);
+        match class {
+            Some(ErrorClass::AuthExpired) => Error::AuthExpired { source },
+            Some(ErrorClass::RegionUnsupported) => Error::RegionUnsupported { source },
+            Some(ErrorClass::RateLimited { retry_after }) => Error::RateLimited { retry_after, source },
+            Some(ErrorClass::ServiceUnavailable) => Error::ServiceUnavailable { source },
+            None => *source,
+        }
+    }
+}
+
+/// The classified variants of [Error], without the source
+enum ErrorClass {
+    AuthExpired,
+    RegionUnsupported,
+    RateLimited { retry_after: Option<Duration> },
+    ServiceUnavailable,
+}
+
+fn classify_sdk_error<E: ProvideErrorMetadata>(err: &SdkError<E, HttpResponse>) -> Option<ErrorClass> {
+    let response = err.raw_response();
+    let status = response.map(|response| response.status().as_u16());
+    let code = err.as_service_error().and_then(|e| e.code());
+    let mentions_region = err
+        .as_service_error()
+        .and_then(|e| e.message())
+        .is_some_and(|message| message.to_lowercase().contains("region"));
+
+    match (status, code) {
+        (Some(401), _) | (_, Some("ExpiredTokenException" | "UnauthorizedException")) => Some(ErrorClass::AuthExpired),
+        (Some(403), _) | (_, Some("AccessDeniedException")) if mentions_region => Some(ErrorClass::RegionUnsupported),
+        (_, Some("ThrottlingException" | "TooManyRequestsException")) => Some(ErrorClass::RateLimited {
+            retry_after: response.and_then(retry_after),
+        }),
+        (Some(502..=504), _) | (_, Some("ServiceUnavailableException")) => Some(ErrorClass::ServiceUnavailable),
+        _ => None,
+    }
+}
+
+/// The delay in seconds from the `Retry-After` header
+fn retry_after(response: &HttpResponse) -> Option<Duration> {
+    response
+        .headers()
+        .get("retry-after")
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
 }
 
 #[cfg(test)]
@@ -113,6 +255,7 @@ mod tests {
 
     use aws_smithy_runtime_api::http::Response;
     use aws_smithy_types::body::SdkBody;
+    use aws_smithy_types::error::ErrorMetadata;
     use aws_smithy_types::event_stream::Message;
 
     use super::*;
@@ -162,9 +305,117 @@ mod tests {
             )),
             Error::SmithyBuild(aws_smithy_types::error::operation::BuildError::other("<other>")),
             Error::UnsupportedConsolas("test"),
+            Error::AuthExpired {
+                source: Box::new(Error::UnsupportedConsolas("test")),
+            },
+            Error::RegionUnsupported {
+                source: Box::new(Error::UnsupportedConsolas("test")),
+            },
+            Error::RateLimited {
+                retry_after: None,
+                source: Box::new(Error::UnsupportedConsolas("test")),
+            },
+            Error::ServiceUnavailable {
+                source: Box::new(Error::UnsupportedConsolas("test")),
+            },
         ]
     }
 
+    fn send_message_error(status: u16, code: Option<&str>, message: Option<&str>) -> Error {
+        let mut meta = ErrorMetadata::builder();
+        if let Some(code) = code {
+            meta = meta.code(code);
+        }
+        if let Some(message) = message {
+            meta = meta.message(message);
+        }
+        let mut response = Response::new(status.try_into().unwrap(), SdkBody::empty());
+        response.headers_mut().insert("retry-after", "7");
+        Error::CodewhispererGenerateAssistantResponse(SdkError::service_error(
+            GenerateAssistantResponseError::generic(meta.build()),
+            response,
+        ))
+    }
+
+    #[test]
+    fn test_classify() {
+        let err = send_message_error(403, Some("ExpiredTokenException"), None).classify();
+        assert!(matches!(err, Error::AuthExpired { .. }));
+        assert!(matches!(
+            err.source().and_then(|source| source.downcast_ref::<Error>()),
+            Some(Error::CodewhispererGenerateAssistantResponse(_))
+        ));
+        assert!(matches!(
+            send_message_error(401, None, None).classify(),
+            Error::AuthExpired { .. }
+        ));
+        assert!(matches!(
+            send_message_error(
+                403,
+                Some("AccessDeniedException"),
+                Some("Amazon Q is not available in this Region")
+            )
+            .classify(),
+            Error::RegionUnsupported { .. }
+        ));
+        assert!(matches!(
+            send_message_error(429, Some("ThrottlingException"), None).classify(),
+            Error::RateLimited {
+                retry_after: Some(retry_after),
+                ..
+            } if retry_after == Duration::from_secs(7)
+        ));
+        assert_eq!(
+            send_message_error(429, Some("ThrottlingException"), None)
+                .classify()
+                .retry_after(),
+            Some(Duration::from_secs(7))
+        );
+        assert!(matches!(
+            send_message_error(503, None, None).classify(),
+            Error::ServiceUnavailable { .. }
+        ));
+
+        // Anything else is left as is
+        assert!(matches!(
+            send_message_error(403, Some("AccessDeniedException"), Some("Not allowed")).classify(),
+            Error::CodewhispererGenerateAssistantResponse(_)
+        ));
+        assert!(matches!(
+            send_message_error(400, Some("ValidationException"), None).classify(),
+            Error::CodewhispererGenerateAssistantResponse(_)
+        ));
+        assert!(matches!(
+            Error::UnsupportedConsolas("test").classify(),
+            Error::UnsupportedConsolas(_)
+        ));
+    }
+
+    #[test]
+    fn test_quota_exceeded() {
+        let throttled = |reason: Option<CodewhispererThrottlingExceptionReason>| {
+            let error = amzn_codewhisperer_streaming_client::types::error::ThrottlingError::builder()
+                .message("throttled")
+                .set_reason(reason)
+                .meta(ErrorMetadata::builder().code("ThrottlingException").build())
+                .build()
+                .unwrap();
+            Error::CodewhispererGenerateAssistantResponse(SdkError::service_error(
+                GenerateAssistantResponseError::ThrottlingError(error),
+                Response::new(429.try_into().unwrap(), SdkBody::empty()),
+            ))
+        };
+
+        assert!(throttled(Some(CodewhispererThrottlingExceptionReason::MonthlyRequestCount)).is_quota_exceeded());
+        assert!(throttled(Some(CodewhispererThrottlingExceptionReason::DailyRequestCount)).is_quota_exceeded());
+        assert!(
+            !throttled(Some(CodewhispererThrottlingExceptionReason::InsufficientModelCapacity)).is_quota_exceeded()
+        );
+        assert!(!throttled(None).is_quota_exceeded());
+        assert!(matches!(throttled(None).classify(), Error::RateLimited { .. }));
+        assert!(!send_message_error(429, Some("ThrottlingException"), None).is_quota_exceeded());
+    }
+
     #[test]
     fn test_errors() {
         for error in all_errors() {
@@ -1,12 +1,21 @@
+use std::future::Future;
+use std::time::Duration;
+
+use fig_auth::consts::PROFILES_CACHE_STATE_KEY;
+use fig_settings::State;
 use serde::{
     Deserialize,
     Serialize,
 };
+use tracing::error;
 
 use crate::Client;
 use crate::endpoints::Endpoint;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// How long a fetched profile list is reused for
+const PROFILES_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Profile {
     pub arn: String,
     pub profile_name: String,
@@ -21,7 +30,45 @@ impl From<amzn_codewhisperer_client::types::Profile> for Profile {
     }
 }
 
+/// The profiles available to the signed in user, reusing the list fetched in the last few minutes
 pub async fn list_available_profiles() -> Vec<Profile> {
+    cached_profiles(&State::new(), false, fetch_available_profiles).await
+}
+
+/// Like [list_available_profiles], but always fetches the list and replaces the cached one
+pub async fn refresh_available_profiles() -> Vec<Profile> {
+    cached_profiles(&State::new(), true, fetch_available_profiles).await
+}
+
+async fn cached_profiles<F, Fut>(state: &State, force_refresh: bool, fetch: F) -> Vec<Profile>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Vec<Profile>>,
+{
+    if !force_refresh {
+        match state.get::<Vec<Profile>>(PROFILES_CACHE_STATE_KEY) {
+            Ok(Some(profiles)) => return profiles,
+            Ok(None) => {},
+            Err(err) => error!(?err, "Failed to load the cached profiles"),
+        }
+    }
+
+    let profiles = fetch().await;
+
+    // An empty list is most likely a failed request, so it isn't worth holding on to
+    if !profiles.is_empty() {
+        let cached = serde_json::to_value(&profiles)
+            .map_err(fig_settings::Error::from)
+            .and_then(|value| state.set_with_ttl(PROFILES_CACHE_STATE_KEY, value, PROFILES_CACHE_TTL));
+        if let Err(err) = cached {
+            error!(?err, "Failed to cache the profiles");
+        }
+    }
+
+    profiles
+}
+
+async fn fetch_available_profiles() -> Vec<Profile> {
     let mut profiles = vec![];
     for endpoint in Endpoint::CODEWHISPERER_ENDPOINTS {
         let client = Client::new_codewhisperer_client(&endpoint).await;
@@ -33,3 +80,44 @@ pub async fn list_available_profiles() -> Vec<Profile> {
 
     profiles
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cached_profiles() {
+        let state = State::new_fake();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Client::mock().list_available_profiles().await.unwrap()
+        };
+
+        let first = cached_profiles(&state, false, fetch).await;
+        let second = cached_profiles(&state, false, fetch).await;
+        assert_eq!(first.len(), 2);
+        assert_eq!(first, second);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        cached_profiles(&state, true, fetch).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        // Signing in or out clears the cache
+        fig_auth::clear_profiles_cache(&state);
+        cached_profiles(&state, false, fetch).await;
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_empty_profiles_are_not_cached() {
+        let state = State::new_fake();
+        cached_profiles(&state, false, || async { vec![] }).await;
+        assert_eq!(state.get_value(PROFILES_CACHE_STATE_KEY).unwrap(), None);
+    }
+}
@@ -21,6 +21,8 @@
 //!          - Calls [Client::create_token]
 //!        - RETURNS: [BuilderIdToken]
 
+use std::time::Instant;
+
 use aws_sdk_ssooidc::client::Client;
 use aws_sdk_ssooidc::config::retry::RetryConfig;
 use aws_sdk_ssooidc::config::{
@@ -44,6 +46,7 @@ use aws_types::region::Region;
 use aws_types::request_id::RequestId;
 use aws_types::sdk_config::StalledStreamProtectionConfig;
 use fig_aws_common::app_name;
+use fig_settings::State;
 use fig_telemetry_core::{
     Event,
     EventType,
@@ -59,6 +62,7 @@ use tracing::{
 };
 
 use crate::consts::*;
+use crate::refresh_backoff::refresh_backoff;
 use crate::scope::is_scopes;
 use crate::secret_store::{
     Secret,
@@ -379,6 +383,21 @@ impl BuilderIdToken {
             },
         };
 
+        let backing_off = {
+            let backoff = refresh_backoff();
+            backoff
+                .remaining(Instant::now())
+                .map(|retry_in| (backoff.consecutive_failures(), retry_in))
+        };
+        if let Some((failures, retry_in)) = backing_off {
+            debug!(
+                failures,
+                ?retry_in,
+                "Skipping access token refresh after repeated failures"
+            );
+            return Err(Error::RefreshBackoff { failures, retry_in });
+        }
+
         debug!("Refreshing access token");
         match client
             .create_token()
@@ -390,6 +409,7 @@ impl BuilderIdToken {
             .await
         {
             Ok(output) => {
+                refresh_backoff().record_success();
                 fig_telemetry_core::send_event(
                     Event::new(EventType::RefreshCredentials {
                         request_id: output.request_id().unwrap_or_default().into(),
@@ -418,20 +438,26 @@ impl BuilderIdToken {
             },
             Err(err) => {
                 let display_err = DisplayErrorContext(&err);
-                error!("Failed to refresh builder id access token: {}", display_err);
+                let backoff = {
+                    let mut backoff = refresh_backoff();
+                    backoff.record_failure(Instant::now());
+                    backoff.to_string()
+                };
+                error!("Failed to refresh builder id access token ({backoff}): {}", display_err);
+
+                fig_telemetry_core::send_event(
+                    Event::new(EventType::RefreshCredentials {
+                        request_id: err.request_id().unwrap_or_default().into(),
+                        result: TelemetryResult::Failed,
+                        reason: Some(format!("{display_err} ({backoff})")),
+                        oauth_flow: registration.oauth_flow.to_string(),
+                    })
+                    .with_credential_start_url(self.start_url.clone().unwrap_or_else(|| START_URL.to_owned())),
+                )
+                .await;
 
                 // if the error is the client's fault, clear the token
                 if let SdkError::ServiceError(service_err) = &err {
-                    fig_telemetry_core::send_event(
-                        Event::new(EventType::RefreshCredentials {
-                            request_id: err.request_id().unwrap_or_default().into(),
-                            result: TelemetryResult::Failed,
-                            reason: Some(display_err.to_string()),
-                            oauth_flow: registration.oauth_flow.to_string(),
-                        })
-                        .with_credential_start_url(self.start_url.clone().unwrap_or_else(|| START_URL.to_owned())),
-                    )
-                    .await;
                     if !service_err.err().is_slow_down_exception() {
                         if let Err(err) = self.delete(secret_store).await {
                             error!(?err, "Failed to delete builder id token");
@@ -544,6 +570,7 @@ pub async fn poll_create_token(
             if let Err(err) = token.save(secret_store).await {
                 error!(?err, "Failed to store builder id token");
             };
+            clear_profiles_cache(&State::new());
 
             PollCreateToken::Complete(token)
         },
@@ -596,6 +623,7 @@ pub async fn logout() -> Result<()> {
     );
 
     let profile_res = fig_settings::state::remove_value("api.codewhisperer.profile");
+    clear_profiles_cache(&State::new());
 
     builder_res?;
     device_res?;
@@ -604,6 +632,14 @@ pub async fn logout() -> Result<()> {
     Ok(())
 }
 
+/// The profiles available depend on who is signed in, so the cached list is dropped on sign in and
+/// sign out
+pub fn clear_profiles_cache(state: &State) {
+    if let Err(err) = state.remove_value(PROFILES_CACHE_STATE_KEY) {
+        error!(?err, "Failed to clear the cached profiles");
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BearerResolver;
 
@@ -24,5 +24,8 @@ pub const START_URL: &str = "https://view.awsapps.com/start";
 // The start URL for internal amzn users
 pub const AMZN_START_URL: &str = "https://amzn.awsapps.com/start";
 
+/// State key of the cached profile list, cleared whenever the signed in user changes
+pub const PROFILES_CACHE_STATE_KEY: &str = "api.codewhisperer.profilesCache";
+
 pub(crate) const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
 pub(crate) const REFRESH_GRANT_TYPE: &str = "refresh_token";
@@ -48,6 +48,11 @@ pub enum Error {
     OAuthMissingCode,
     #[error("OAuth error: {0}")]
     OAuthCustomError(String),
+    #[error("Token refresh skipped after {failures} consecutive failures, retry in {}s", .retry_in.as_secs())]
+    RefreshBackoff {
+        failures: u32,
+        retry_in: std::time::Duration,
+    },
 }
 
 impl Error {
@@ -2,11 +2,13 @@ pub mod builder_id;
 pub mod consts;
 mod error;
 pub mod pkce;
+mod refresh_backoff;
 mod scope;
 pub mod secret_store;
 
 pub use builder_id::{
     builder_id_token,
+    clear_profiles_cache,
     is_amzn_user,
     is_logged_in,
     logout,
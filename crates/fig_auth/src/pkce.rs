@@ -273,6 +273,7 @@ impl PkceRegistration {
         if let Err(err) = token.save(secret_store).await {
             error!(?err, "Failed to store builder id token");
         };
+        clear_profiles_cache(&fig_settings::State::new());
 
         Ok(())
     }
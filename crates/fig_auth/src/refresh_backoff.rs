@@ -0,0 +1,131 @@
+//! Backoff for access token refreshes
+//!
+//! When a refresh keeps failing, for example because the token endpoint is unreachable, every
+//! caller that finds an expired token would otherwise try again immediately. After each
+//! consecutive failure the next attempt is delayed twice as long, up to [MAX_DELAY], and a
+//! successful refresh resets the backoff.
+
+use std::fmt;
+use std::sync::{
+    Mutex,
+    MutexGuard,
+    PoisonError,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/// Delay after the first failure
+const BASE_DELAY: Duration = Duration::from_secs(1);
+/// Longest delay between attempts
+const MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+static REFRESH_BACKOFF: Mutex<RefreshBackoff> = Mutex::new(RefreshBackoff::new(BASE_DELAY, MAX_DELAY));
+
+/// The process wide backoff shared by all token refreshes
+pub(crate) fn refresh_backoff() -> MutexGuard<'static, RefreshBackoff> {
+    REFRESH_BACKOFF.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug)]
+pub(crate) struct RefreshBackoff {
+    base_delay: Duration,
+    max_delay: Duration,
+    consecutive_failures: u32,
+    /// The last delay handed out, `None` when the last attempt succeeded
+    delay: Option<Duration>,
+    retry_at: Option<Instant>,
+}
+
+impl RefreshBackoff {
+    pub(crate) const fn new(base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            base_delay,
+            max_delay,
+            consecutive_failures: 0,
+            delay: None,
+            retry_at: None,
+        }
+    }
+
+    /// Time left before another refresh may be attempted, `None` if one may be attempted now
+    pub(crate) fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.retry_at
+            .and_then(|retry_at| retry_at.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Record a failed refresh and return how long to wait before the next attempt
+    pub(crate) fn record_failure(&mut self, now: Instant) -> Duration {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let exponent = (self.consecutive_failures - 1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exponent).min(self.max_delay);
+        self.delay = Some(delay);
+        self.retry_at = Some(now + delay);
+        delay
+    }
+
+    pub(crate) fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.delay = None;
+        self.retry_at = None;
+    }
+
+    pub(crate) fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+}
+
+impl fmt::Display for RefreshBackoff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.delay {
+            Some(delay) => write!(
+                f,
+                "{} consecutive failures, next attempt in {}s",
+                self.consecutive_failures,
+                delay.as_secs()
+            ),
+            None => write!(f, "no recent failures"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consecutive_failures_increase_delay() {
+        let mut backoff = RefreshBackoff::new(Duration::from_secs(1), Duration::from_secs(10));
+        let now = Instant::now();
+        assert_eq!(backoff.remaining(now), None);
+
+        let delays = (0..6)
+            .map(|_| backoff.record_failure(now).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+        assert_eq!(backoff.consecutive_failures(), 6);
+        assert_eq!(backoff.to_string(), "6 consecutive failures, next attempt in 10s");
+    }
+
+    #[test]
+    fn test_remaining_and_reset() {
+        let mut backoff = RefreshBackoff::new(Duration::from_secs(2), Duration::from_secs(60));
+        let now = Instant::now();
+
+        backoff.record_failure(now);
+        backoff.record_failure(now);
+        assert_eq!(backoff.remaining(now), Some(Duration::from_secs(4)));
+        assert_eq!(
+            backoff.remaining(now + Duration::from_secs(3)),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(backoff.remaining(now + Duration::from_secs(4)), None);
+
+        backoff.record_success();
+        assert_eq!(backoff.remaining(now), None);
+        assert_eq!(backoff.consecutive_failures(), 0);
+        assert_eq!(backoff.record_failure(now), Duration::from_secs(2));
+    }
+}
@@ -21,6 +21,8 @@ use std::sync::{
     Arc,
     RwLock,
 };
+#[cfg(unix)]
+use std::time::Duration;
 
 use clap::Parser;
 use event::Event;
@@ -70,6 +72,10 @@ use webview::{
     dashboard,
 };
 
+/// How long to wait on an already running instance to open a window before giving up
+#[cfg(unix)]
+const OPEN_UI_ELEMENT_TIMEOUT: Duration = Duration::from_secs(5);
+
 // #[global_allocator]
 // static GLOBAL: Jemalloc = Jemalloc;
 
@@ -275,8 +281,12 @@ async fn allow_multiple_running_check(
                     },
                 };
 
-                if let Err(err) =
-                    fig_ipc::local::open_ui_element(fig_proto::local::UiElement::MissionControl, page).await
+                if let Err(err) = fig_ipc::local::open_ui_element_with_timeout(
+                    fig_proto::local::UiElement::MissionControl,
+                    page,
+                    OPEN_UI_ELEMENT_TIMEOUT,
+                )
+                .await
                 {
                     eprintln!("Failed to open Fig: {err}");
                 }
@@ -337,8 +347,12 @@ async fn allow_multiple_running_check(
                         },
                     };
 
-                    if let Err(err) =
-                        fig_ipc::local::open_ui_element(fig_proto::local::UiElement::MissionControl, page).await
+                    if let Err(err) = fig_ipc::local::open_ui_element_with_timeout(
+                        fig_proto::local::UiElement::MissionControl,
+                        page,
+                        OPEN_UI_ELEMENT_TIMEOUT,
+                    )
+                    .await
                     {
                         eprintln!("Failed to open Fig: {err}");
                     }
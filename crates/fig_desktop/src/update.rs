@@ -68,7 +68,7 @@ pub async fn check_for_update(show_webview: bool, relaunch_dashboard: bool) -> b
                         ..
                     } => *control_flow = ControlFlow::Exit,
                     Event::UserEvent(event) => match event {
-                        UpdateStatus::Percent(p) => {
+                        UpdateStatus::Percent(p) | UpdateStatus::Progress { percent: p, .. } => {
                             webview
                                 .evaluate_script(&format!("updateProgress({});", p as i32))
                                 .unwrap();
@@ -1,8 +1,11 @@
 #![allow(clippy::ref_option_ref)]
 use std::collections::BTreeMap;
+use std::path::Path;
 
 use fig_os_shim::{
     Context,
+    Env,
+    Fs,
     Os,
     PlatformProvider,
 };
@@ -14,8 +17,12 @@ use fig_util::system_info::{
     os_version,
 };
 use fig_util::{
+    RedactionPolicy,
     Shell,
     Terminal,
+    directories,
+    redact,
+    shell_integration_version,
 };
 use serde::Serialize;
 use sysinfo::{
@@ -129,44 +136,133 @@ pub struct EnvVarDiagnostic {
     pub env_vars: BTreeMap<String, String>,
 }
 
+/// Env vars captured in addition to [`fig_util::env_var::ALL`]
+const BUILT_IN_ENV_VARS: &[&str] = &[
+    // General env vars
+    "SHELL",
+    "DISPLAY",
+    "PATH",
+    "TERM",
+    "ZDOTDIR",
+    // Linux vars
+    "XDG_CURRENT_DESKTOP",
+    "XDG_SESSION_DESKTOP",
+    "XDG_SESSION_TYPE",
+    "GLFW_IM_MODULE",
+    "GTK_IM_MODULE",
+    "QT_IM_MODULE",
+    "XMODIFIERS",
+    // Macos vars
+    "__CFBundleIdentifier",
+];
+
 impl EnvVarDiagnostic {
     fn new() -> EnvVarDiagnostic {
-        let env_vars = std::env::vars()
-            .filter(|(key, _)| {
-                let fig_var = fig_util::env_var::ALL.contains(&key.as_str());
-                let other_var = [
-                    // General env vars
-                    "SHELL",
-                    "DISPLAY",
-                    "PATH",
-                    "TERM",
-                    "ZDOTDIR",
-                    // Linux vars
-                    "XDG_CURRENT_DESKTOP",
-                    "XDG_SESSION_DESKTOP",
-                    "XDG_SESSION_TYPE",
-                    "GLFW_IM_MODULE",
-                    "GTK_IM_MODULE",
-                    "QT_IM_MODULE",
-                    "XMODIFIERS",
-                    // Macos vars
-                    "__CFBundleIdentifier",
-                ]
-                .contains(&key.as_str());
-
-                fig_var || other_var
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Keeps only the vars on the allow-list, which is the built-in list plus any names listed in
+    /// [`Q_DIAGNOSTIC_EXTRA_VARS`](fig_util::env_var::Q_DIAGNOSTIC_EXTRA_VARS)
+    fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> EnvVarDiagnostic {
+        let vars = vars.into_iter().collect::<Vec<_>>();
+        let extra_vars = vars
+            .iter()
+            .find(|(key, _)| key == fig_util::env_var::Q_DIAGNOSTIC_EXTRA_VARS)
+            .map(|(_, value)| {
+                value
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>()
             })
-            .map(|(key, value)| {
-                // sanitize username from values
-                let username = format!("/{}", whoami::username());
-                (key, value.replace(&username, "/USER"))
+            .unwrap_or_default();
+
+        let env_vars = vars
+            .into_iter()
+            .filter(|(key, _)| {
+                fig_util::env_var::ALL.contains(&key.as_str())
+                    || BUILT_IN_ENV_VARS.contains(&key.as_str())
+                    || extra_vars.contains(key)
             })
+            .map(|(key, value)| (key, redact(&value, RedactionPolicy::ALL)))
             .collect();
 
         EnvVarDiagnostic { env_vars }
     }
 }
 
+/// Details about the SSH session, without the client or server addresses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SshDiagnostic {
+    /// Whether `SSH_CONNECTION` or `SSH_CLIENT` is set
+    pub client_ip_present: bool,
+    /// The value of `SSH_TTY`, if the session has a tty
+    pub tty: Option<String>,
+}
+
+impl SshDiagnostic {
+    /// Returns [None] if `env` is not an SSH session
+    fn new(env: &Env) -> Option<SshDiagnostic> {
+        if !env.in_ssh() {
+            return None;
+        }
+
+        Some(SshDiagnostic {
+            client_ip_present: env.get("SSH_CONNECTION").is_ok() || env.get("SSH_CLIENT").is_ok(),
+            tty: env.get("SSH_TTY").ok(),
+        })
+    }
+}
+
+/// Version recorded for shell integration scripts written before versions were recorded
+const UNKNOWN_INTEGRATION_VERSION: &str = "unknown";
+
+/// The versions of the CLI that wrote the installed shell integration scripts
+///
+/// Scripts left behind by another version of the CLI explain much of the autocomplete breaking
+/// after an update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShellIntegrationDiagnostic {
+    /// The version recorded in each installed script, by file name
+    pub script_versions: BTreeMap<String, String>,
+    /// Whether any script was written by a version other than the running CLI
+    pub version_mismatch: bool,
+}
+
+impl ShellIntegrationDiagnostic {
+    /// Returns [None] if there are no shell integration scripts in `shell_dir`
+    async fn new(fs: &Fs, shell_dir: &Path, cli_version: &str) -> Option<ShellIntegrationDiagnostic> {
+        let mut entries = fs.read_dir(shell_dir).await.ok()?;
+        let mut script_versions = BTreeMap::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            // Scripts are named after the dotfile sourcing them, e.g. `zshrc.pre.zsh`
+            if !name.contains(".pre.") && !name.contains(".post.") {
+                continue;
+            }
+            let Ok(contents) = fs.read_to_string(entry.path()).await else {
+                continue;
+            };
+            let version = shell_integration_version(&contents).unwrap_or(UNKNOWN_INTEGRATION_VERSION);
+            script_versions.insert(name, version.to_owned());
+        }
+
+        if script_versions.is_empty() {
+            return None;
+        }
+
+        let version_mismatch = script_versions.values().any(|version| version != cli_version);
+        Some(ShellIntegrationDiagnostic {
+            script_versions,
+            version_mismatch,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct CurrentEnvironment {
@@ -183,6 +279,8 @@ pub struct CurrentEnvironment {
     pub in_cloudshell: bool,
     #[serde(skip_serializing_if = "is_false")]
     pub in_ssh: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<SshDiagnostic>,
     #[serde(skip_serializing_if = "is_false")]
     pub in_ci: bool,
     #[serde(skip_serializing_if = "is_false")]
@@ -199,25 +297,23 @@ impl CurrentEnvironment {
         };
         let ctx = Context::new();
 
-        let username = format!("/{}", whoami::username());
-
         let shell_path = Pid::current()
             .parent()
             .and_then(|pid| pid.exe())
-            .map(|p| p.to_string_lossy().replace(&username, "/USER"));
+            .map(|p| redact(&p.to_string_lossy(), RedactionPolicy::ALL));
         let shell_version = Shell::current_shell_version().await.map(|(_, v)| v).ok();
 
         let cwd = ctx
             .env()
             .current_dir()
             .ok()
-            .map(|path| path.to_string_lossy().replace(&username, "/USER"));
+            .map(|path| redact(&path.to_string_lossy(), RedactionPolicy::ALL));
 
         let cli_path = ctx
             .env()
             .current_dir()
             .ok()
-            .map(|path| path.to_string_lossy().replace(&username, "/USER"));
+            .map(|path| redact(&path.to_string_lossy(), RedactionPolicy::ALL));
 
         let os = ctx.platform().os();
         let terminal = Terminal::parent_terminal(&ctx);
@@ -225,6 +321,7 @@ impl CurrentEnvironment {
 
         let in_cloudshell = fig_util::system_info::in_cloudshell();
         let in_ssh = fig_util::system_info::in_ssh();
+        let ssh = SshDiagnostic::new(ctx.env());
         let in_ci = fig_util::system_info::in_ci();
         let in_wsl = fig_util::system_info::in_wsl();
         let in_codespaces = fig_util::system_info::in_codespaces();
@@ -239,6 +336,7 @@ impl CurrentEnvironment {
             install_method,
             in_cloudshell,
             in_ssh,
+            ssh,
             in_ci,
             in_wsl,
             in_codespaces,
@@ -253,16 +351,27 @@ pub struct Diagnostics {
     pub build_details: BuildDetails,
     pub system_info: SystemInfo,
     pub environment: CurrentEnvironment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell_integration: Option<ShellIntegrationDiagnostic>,
     #[serde(flatten)]
     pub environment_variables: EnvVarDiagnostic,
 }
 
 impl Diagnostics {
     pub async fn new() -> Diagnostics {
+        let shell_integration = match directories::fig_data_dir() {
+            Ok(data_dir) => {
+                let ctx = Context::new();
+                ShellIntegrationDiagnostic::new(ctx.fs(), &data_dir.join("shell"), env!("CARGO_PKG_VERSION")).await
+            },
+            Err(_) => None,
+        };
+
         Diagnostics {
             build_details: BuildDetails::new(),
             system_info: SystemInfo::new(),
             environment: CurrentEnvironment::new().await,
+            shell_integration,
             environment_variables: EnvVarDiagnostic::new(),
         }
     }
@@ -282,4 +391,106 @@ mod tests {
         let toml = diagnostics.user_readable().unwrap();
         assert!(!toml.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_shell_integration_diagnostic() {
+        let fs = Fs::new_chroot();
+        let shell_dir = Path::new("/shell");
+        assert_eq!(ShellIntegrationDiagnostic::new(&fs, shell_dir, "1.17.1").await, None);
+
+        fs.create_dir_all(shell_dir).await.unwrap();
+        let script = |version: &str| {
+            format!(
+                "{}{version}\neval \"$(q init zsh pre)\"\n",
+                fig_util::SHELL_INTEGRATION_VERSION_PREFIX
+            )
+        };
+        fs.write(shell_dir.join("zshrc.pre.zsh"), script("1.17.1"))
+            .await
+            .unwrap();
+        fs.write(shell_dir.join("zshrc.post.zsh"), script("1.17.1"))
+            .await
+            .unwrap();
+        fs.write(shell_dir.join("README"), "not a script").await.unwrap();
+
+        let diagnostic = ShellIntegrationDiagnostic::new(&fs, shell_dir, "1.17.1").await.unwrap();
+        assert_eq!(diagnostic, ShellIntegrationDiagnostic {
+            script_versions: BTreeMap::from([
+                ("zshrc.post.zsh".into(), "1.17.1".into()),
+                ("zshrc.pre.zsh".into(), "1.17.1".into()),
+            ]),
+            version_mismatch: false,
+        });
+
+        // Left behind by an older CLI
+        fs.write(shell_dir.join("zshrc.post.zsh"), script("1.16.0"))
+            .await
+            .unwrap();
+        let diagnostic = ShellIntegrationDiagnostic::new(&fs, shell_dir, "1.17.1").await.unwrap();
+        assert!(diagnostic.version_mismatch);
+        assert_eq!(diagnostic.script_versions["zshrc.post.zsh"], "1.16.0");
+
+        // Written before versions were recorded
+        fs.write(shell_dir.join("bashrc.pre.bash"), "eval \"$(q init bash pre)\"\n")
+            .await
+            .unwrap();
+        fs.write(shell_dir.join("zshrc.post.zsh"), script("1.17.1"))
+            .await
+            .unwrap();
+        let diagnostic = ShellIntegrationDiagnostic::new(&fs, shell_dir, "1.17.1").await.unwrap();
+        assert!(diagnostic.version_mismatch);
+        assert_eq!(
+            diagnostic.script_versions["bashrc.pre.bash"],
+            UNKNOWN_INTEGRATION_VERSION
+        );
+
+        let toml = toml::to_string(&diagnostic).unwrap();
+        assert!(toml.contains("version-mismatch = true"));
+    }
+
+    #[test]
+    fn test_ssh_diagnostic() {
+        assert_eq!(SshDiagnostic::new(&Env::from_slice(&[("TERM", "xterm")])), None);
+
+        let env = Env::from_slice(&[
+            ("SSH_CONNECTION", "203.0.113.7 51234 198.51.100.2 22"),
+            ("SSH_CLIENT", "203.0.113.7 51234 22"),
+            ("SSH_TTY", "/dev/pts/3"),
+        ]);
+        let ssh = SshDiagnostic::new(&env).unwrap();
+        assert_eq!(ssh, SshDiagnostic {
+            client_ip_present: true,
+            tty: Some("/dev/pts/3".into()),
+        });
+
+        let toml = toml::to_string(&ssh).unwrap();
+        assert!(toml.contains("client-ip-present = true"));
+        assert!(!toml.contains("203.0.113.7"));
+        assert!(!toml.contains("198.51.100.2"));
+    }
+
+    #[test]
+    fn test_env_var_extra_vars() {
+        let vars = [
+            ("Q_DIAGNOSTIC_EXTRA_VARS", "TERMINFO, HTTPS_PROXY"),
+            ("TERM", "xterm-256color"),
+            ("TERMINFO", "/usr/share/terminfo"),
+            ("HTTPS_PROXY", "http://proxy.internal:8080?token=hunter2"),
+            ("AWS_SECRET_ACCESS_KEY", "not-listed"),
+        ]
+        .map(|(key, value)| (key.to_owned(), value.to_owned()));
+
+        let env_vars = EnvVarDiagnostic::from_vars(vars).env_vars;
+        assert_eq!(env_vars.get("TERM").unwrap(), "xterm-256color");
+        assert_eq!(env_vars.get("TERMINFO").unwrap(), "/usr/share/terminfo");
+        assert_eq!(
+            env_vars.get("HTTPS_PROXY").unwrap(),
+            "http://proxy.internal:8080?token=<token>"
+        );
+        assert!(!env_vars.contains_key("AWS_SECRET_ACCESS_KEY"));
+
+        let env_vars =
+            EnvVarDiagnostic::from_vars([("TERMINFO".to_owned(), "/usr/share/terminfo".to_owned())]).env_vars;
+        assert!(env_vars.is_empty());
+    }
 }
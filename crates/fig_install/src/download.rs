@@ -1,37 +1,292 @@
 use std::borrow::BorrowMut as _;
-use std::path::Path;
-use std::time::Duration;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+};
 
+use fig_os_shim::FsProvider;
 use hex::encode;
-use reqwest::IntoUrl;
-use tokio::io::AsyncWriteExt as _;
+use reqwest::header::{
+    CONTENT_RANGE,
+    RANGE,
+};
+use reqwest::{
+    Client,
+    IntoUrl,
+    StatusCode,
+    Url,
+};
+use tokio::io::{
+    AsyncReadExt as _,
+    AsyncWriteExt as _,
+};
 use tokio::sync::mpsc::Sender;
+use tracing::{
+    debug,
+    error,
+    warn,
+};
 
 use crate::{
     Error,
     UpdateStatus,
 };
 
+/// Number of times a download is attempted before giving up
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry, doubled for each retry after
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Cached packages older than this are removed
+const CACHE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// The oldest cached packages are removed until the cache is at most this many bytes
+const CACHE_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Update packages that were already downloaded, keyed by their sha256
+///
+/// An install that fails after the download, or is retried, reuses the package instead of
+/// downloading it again.
+#[derive(Debug, Clone)]
+pub(crate) struct DownloadCache {
+    dir: PathBuf,
+    max_age: Duration,
+    max_size: u64,
+}
+
+impl DownloadCache {
+    pub(crate) fn new(fs: &impl FsProvider) -> Result<Self, Error> {
+        let dir = fig_util::directories::downloads_cache_dir()?;
+        Ok(Self::with_dir(fs.fs().chroot_path(dir)))
+    }
+
+    fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_age: CACHE_MAX_AGE,
+            max_size: CACHE_MAX_SIZE,
+        }
+    }
+
+    /// The path a package is cached at, `None` if `sha256` isn't a valid hash
+    fn entry(&self, sha256: &str) -> Option<PathBuf> {
+        (sha256.len() == 64 && sha256.bytes().all(|b| b.is_ascii_hexdigit()))
+            .then(|| self.dir.join(sha256.to_ascii_lowercase()))
+    }
+
+    /// Copy the cached package with `sha256` to `dst`, returns whether it was cached
+    ///
+    /// A cached file whose contents no longer match the hash is removed.
+    async fn get(&self, sha256: &str, dst: &Path) -> Result<bool, Error> {
+        let Some(entry) = self.entry(sha256) else {
+            return Ok(false);
+        };
+        if !tokio::fs::try_exists(&entry).await? {
+            return Ok(false);
+        }
+        if !sha256_file(&entry).await?.eq_ignore_ascii_case(sha256) {
+            warn!(?entry, "Removing cached download with the wrong hash");
+            tokio::fs::remove_file(&entry).await?;
+            return Ok(false);
+        }
+        tokio::fs::copy(&entry, dst).await?;
+        Ok(true)
+    }
+
+    /// Add the package at `src` whose hash is `sha256`, then evict old entries
+    async fn insert(&self, sha256: &str, src: &Path) -> Result<(), Error> {
+        let Some(entry) = self.entry(sha256) else {
+            return Ok(());
+        };
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Copy then rename so a partially copied file is never read as an entry
+        let partial = entry.with_extension("partial");
+        tokio::fs::copy(src, &partial).await?;
+        tokio::fs::rename(&partial, &entry).await?;
+        self.evict(SystemTime::now()).await
+    }
+
+    /// Remove entries older than the max age, then the oldest entries until the cache fits in the
+    /// max size
+    async fn evict(&self, now: SystemTime) -> Result<(), Error> {
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_file() {
+                entries.push((entry.path(), metadata.modified()?, metadata.len()));
+            }
+        }
+
+        // Newest first, so the entries past the size limit are the oldest
+        entries.sort_by(|a, b| b.1.cmp(&a.1));
+        let mut total_size = 0;
+        for (path, modified, len) in entries {
+            total_size += len;
+            let expired = now.duration_since(modified).unwrap_or_default() > self.max_age;
+            if expired || total_size > self.max_size {
+                debug!(?path, expired, "Evicting cached download");
+                tokio::fs::remove_file(&path).await?;
+                total_size -= len;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Download `src` to `dst` unless a package with `sha256` is already cached, returning the hex
+/// encoded sha256 of the file
+///
+/// Only downloads that match `sha256` are added to the cache, callers should still compare the
+/// returned hash.
+pub(crate) async fn download_cached(
+    cache: &DownloadCache,
+    src: impl IntoUrl,
+    dst: impl AsRef<Path>,
+    size: u64,
+    sha256: &str,
+    tx: Option<Sender<UpdateStatus>>,
+) -> Result<String, Error> {
+    let dst = dst.as_ref();
+    match cache.get(sha256, dst).await {
+        Ok(true) => {
+            debug!(?dst, "Using cached download");
+            if let Some(tx) = &tx {
+                tx.send(UpdateStatus::Percent(100.0)).await.ok();
+            }
+            return Ok(sha256.to_ascii_lowercase());
+        },
+        Ok(false) => (),
+        Err(err) => error!(%err, "Failed to read the download cache"),
+    }
+
+    let real_hash = download_file(src, dst, size, tx).await?;
+    if real_hash.eq_ignore_ascii_case(sha256) {
+        if let Err(err) = cache.insert(sha256, dst).await {
+            error!(%err, "Failed to add the download to the cache");
+        }
+    }
+    Ok(real_hash)
+}
+
+/// Download `src` to `dst`, returning the hex encoded sha256 of the file
+///
+/// Bytes already in `dst`, for example from an earlier download that failed partway, are kept
+/// and only the rest is requested with a range request. The download is started over if the
+/// server doesn't support ranges. If `size` isn't zero, the downloaded file must be exactly
+/// `size` bytes.
 #[allow(dead_code)]
 pub(crate) async fn download_file(
     src: impl IntoUrl,
     dst: impl AsRef<Path>,
     size: u64,
     tx: Option<Sender<UpdateStatus>>,
+) -> Result<String, Error> {
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&dst)
+        .await?;
+    // Can't be a prefix of the download
+    if size != 0 && file.metadata().await?.len() > size {
+        file.set_len(0).await?;
+    }
+    drop(file);
+    resume_download(src, dst, size, tx).await
+}
+
+/// Continue downloading `src` into the partially downloaded `dst`
+async fn resume_download(
+    src: impl IntoUrl,
+    dst: impl AsRef<Path>,
+    size: u64,
+    tx: Option<Sender<UpdateStatus>>,
 ) -> Result<String, Error> {
     let client = fig_request::client().expect("fig_request client must be instantiated on first request");
-    let mut response = client.get(src).timeout(Duration::from_secs(30 * 60)).send().await?;
+    let src = src.into_url()?;
+    let dst = dst.as_ref();
 
-    let mut bytes_downloaded = 0;
-    let mut file = tokio::fs::File::create(&dst).await?;
-    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut attempt = 1;
+    loop {
+        match download_remaining(client, &src, dst, size, tx.as_ref()).await {
+            Ok(()) => break,
+            Err(Error::Reqwest(err))
+                if attempt < MAX_ATTEMPTS && err.status().is_none_or(|status| status.is_server_error()) =>
+            {
+                let delay = RETRY_DELAY * 2_u32.pow(attempt - 1);
+                warn!(%err, attempt, ?delay, "Download failed, retrying");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            },
+            Err(err) => return Err(err),
+        }
+    }
+
+    let downloaded = tokio::fs::metadata(dst).await?.len();
+    if size != 0 && downloaded != size {
+        return Err(Error::UpdateFailed(format!(
+            "download size mismatch. Expected: {size} bytes, Actual: {downloaded} bytes"
+        )));
+    }
+
+    if let Some(tx) = &tx {
+        tx.send(UpdateStatus::Percent(100.0)).await.ok();
+    }
+
+    sha256_file(dst).await
+}
+
+/// Request the bytes of `src` that aren't in `dst` yet and append them
+async fn download_remaining(
+    client: &Client,
+    src: &Url,
+    dst: &Path,
+    size: u64,
+    tx: Option<&Sender<UpdateStatus>>,
+) -> Result<(), Error> {
+    let mut file = tokio::fs::OpenOptions::new().append(true).open(dst).await?;
+    let mut bytes_downloaded = file.metadata().await?.len();
+
+    let mut request = client.get(src.clone()).timeout(Duration::from_secs(30 * 60));
+    if bytes_downloaded > 0 {
+        debug!(bytes_downloaded, "Resuming download");
+        request = request.header(RANGE, format!("bytes={bytes_downloaded}-"));
+    }
+    let response = request.send().await?;
+
+    let mut response = match response.status() {
+        StatusCode::PARTIAL_CONTENT if range_start(&response) == Some(bytes_downloaded) => response,
+        // Everything was written before the connection dropped
+        StatusCode::RANGE_NOT_SATISFIABLE if bytes_downloaded == size => return Ok(()),
+        StatusCode::OK => {
+            if bytes_downloaded > 0 {
+                debug!("Server doesn't support range requests, starting over");
+                file.set_len(0).await?;
+                bytes_downloaded = 0;
+            }
+            response
+        },
+        StatusCode::PARTIAL_CONTENT | StatusCode::RANGE_NOT_SATISFIABLE => {
+            debug!(status =% response.status(), "Server can't resume the download, starting over");
+            file.set_len(0).await?;
+            bytes_downloaded = 0;
+            client
+                .get(src.clone())
+                .timeout(Duration::from_secs(30 * 60))
+                .send()
+                .await?
+        },
+        _ => response,
+    }
+    .error_for_status()?;
 
     while let Some(mut bytes) = response.chunk().await? {
         bytes_downloaded += bytes.len() as u64;
 
-        ctx.update(&bytes);
-
-        if let Some(tx) = &tx {
+        if let Some(tx) = tx {
             tx.send(UpdateStatus::Percent(bytes_downloaded as f32 / size as f32 * 100.0))
                 .await
                 .ok();
@@ -47,11 +302,190 @@ pub(crate) async fn download_file(
 
         file.write_all_buf(bytes.borrow_mut()).await?;
     }
+    file.flush().await?;
 
-    if let Some(tx) = &tx {
-        tx.send(UpdateStatus::Percent(100.0)).await.ok();
+    Ok(())
+}
+
+/// The first byte of a `206 Partial Content` response, from `Content-Range: bytes
+/// <start>-<end>/<len>`
+fn range_start(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .parse()
+        .ok()
+}
+
+async fn sha256_file(path: &Path) -> Result<String, Error> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        ctx.update(&buf[..n]);
+    }
+    Ok(encode(ctx.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use fig_test_utils::{
+        Method,
+        TestServer,
+    };
+
+    use super::*;
+
+    const CONTENTS: &str = "the first half of the update|and the second half of the update";
+
+    fn sha256(contents: &str) -> String {
+        encode(ring::digest::digest(&ring::digest::SHA256, contents.as_bytes()))
+    }
+
+    #[tokio::test]
+    async fn test_resume_requests_remaining_bytes() {
+        let server = TestServer::new()
+            .await
+            .with_ranged_response(Method::GET, "/update.tar.zst".into(), CONTENTS.into())
+            .spawn_listener();
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst = tempdir.path().join("update.tar.zst");
+
+        // A previous attempt failed after the first half
+        let (first_half, _) = CONTENTS.split_once('|').unwrap();
+        tokio::fs::write(&dst, first_half).await.unwrap();
+
+        let url = format!("http://{server}/update.tar.zst");
+        let hash = resume_download(url, &dst, CONTENTS.len() as u64, None).await.unwrap();
+        assert_eq!(hash, sha256(CONTENTS));
+        assert_eq!(tokio::fs::read_to_string(&dst).await.unwrap(), CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn test_download_file_keeps_existing_bytes() {
+        let server = TestServer::new()
+            .await
+            .with_ranged_response(Method::GET, "/update.tar.zst".into(), CONTENTS.into())
+            .spawn_listener();
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst = tempdir.path().join("update.tar.zst");
+
+        // Differs from the served bytes, so it's only in the result if the file wasn't truncated
+        let (first_half, second_half) = CONTENTS.split_once('|').unwrap();
+        let kept = format!("{}|", first_half.to_uppercase());
+        tokio::fs::write(&dst, &kept).await.unwrap();
+
+        let url = format!("http://{server}/update.tar.zst");
+        let hash = download_file(&url, &dst, CONTENTS.len() as u64, None).await.unwrap();
+        let expected = format!("{kept}{second_half}");
+        assert_eq!(hash, sha256(&expected));
+        assert_eq!(tokio::fs::read_to_string(&dst).await.unwrap(), expected);
+
+        // A file longer than the download can't be resumed
+        tokio::fs::write(&dst, format!("{CONTENTS}trailing bytes"))
+            .await
+            .unwrap();
+        let hash = download_file(&url, &dst, CONTENTS.len() as u64, None).await.unwrap();
+        assert_eq!(hash, sha256(CONTENTS));
+    }
+
+    #[tokio::test]
+    async fn test_resume_without_range_support_starts_over() {
+        let server = TestServer::new()
+            .await
+            .with_mock_response(Method::GET, "/update.tar.zst".into(), CONTENTS.into())
+            .spawn_listener();
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst = tempdir.path().join("update.tar.zst");
+        tokio::fs::write(&dst, "stale bytes").await.unwrap();
+
+        let url = format!("http://{server}/update.tar.zst");
+        let hash = resume_download(url, &dst, CONTENTS.len() as u64, None).await.unwrap();
+        assert_eq!(hash, sha256(CONTENTS));
+        assert_eq!(tokio::fs::read_to_string(&dst).await.unwrap(), CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn test_cached_download_skips_network() {
+        let server = TestServer::new()
+            .await
+            .with_mock_response(Method::GET, "/update.tar.zst".into(), CONTENTS.into())
+            .spawn_listener();
+        let tempdir = tempfile::tempdir().unwrap();
+        let cache = DownloadCache::with_dir(tempdir.path().join("cache"));
+        let url = format!("http://{server}/update.tar.zst");
+        let hash = sha256(CONTENTS);
+
+        let first = tempdir.path().join("first.tar.zst");
+        let real_hash = download_cached(&cache, &url, &first, CONTENTS.len() as u64, &hash, None)
+            .await
+            .unwrap();
+        assert_eq!(real_hash, hash);
+        assert!(tempdir.path().join("cache").join(&hash).exists());
+
+        // Stop the server, the second download must come from the cache
+        drop(server);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let second = tempdir.path().join("second.tar.zst");
+        let real_hash = download_cached(&cache, &url, &second, CONTENTS.len() as u64, &hash, None)
+            .await
+            .unwrap();
+        assert_eq!(real_hash, hash);
+        assert_eq!(tokio::fs::read_to_string(&second).await.unwrap(), CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn test_cache_eviction() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut cache = DownloadCache::with_dir(tempdir.path().to_owned());
+        cache.max_size = 10;
+
+        let now = SystemTime::now();
+        let write_entry = |name: &str, len: usize, age: Duration| {
+            let path = tempdir.path().join(name);
+            std::fs::write(&path, vec![0; len]).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - age)
+                .unwrap();
+        };
+        write_entry("new", 4, Duration::ZERO);
+        write_entry("older", 4, Duration::from_secs(60));
+        write_entry("oldest", 4, Duration::from_secs(120));
+        write_entry("expired", 1, CACHE_MAX_AGE + Duration::from_secs(1));
+
+        cache.evict(now).await.unwrap();
+        assert!(tempdir.path().join("new").exists());
+        assert!(tempdir.path().join("older").exists());
+        assert!(!tempdir.path().join("oldest").exists());
+        assert!(!tempdir.path().join("expired").exists());
     }
 
-    let hex_digest = encode(ctx.finish());
-    Ok(hex_digest)
+    #[tokio::test]
+    async fn test_size_mismatch() {
+        let server = TestServer::new()
+            .await
+            .with_ranged_response(Method::GET, "/update.tar.zst".into(), CONTENTS.into())
+            .spawn_listener();
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst = tempdir.path().join("update.tar.zst");
+
+        let url = format!("http://{server}/update.tar.zst");
+        let err = download_file(url, &dst, CONTENTS.len() as u64 + 1, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::UpdateFailed(_)));
+    }
 }
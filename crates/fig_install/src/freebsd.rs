@@ -3,6 +3,7 @@ use tokio::sync::mpsc::Sender;
 use crate::index::UpdatePackage;
 use crate::{
     Error,
+    PackageManager,
     UpdateStatus,
 };
 
@@ -12,5 +13,5 @@ pub(crate) async fn update(
     _interactive: bool,
     _relaunch_dashboard: bool,
 ) -> Result<(), Error> {
-    Err(Error::PackageManaged)
+    Err(PackageManager::Pkg.into())
 }
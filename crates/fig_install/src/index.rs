@@ -1,4 +1,5 @@
 use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
 use std::hash::{
     Hash,
     Hasher,
@@ -14,12 +15,14 @@ use fig_os_shim::Context;
 use fig_util::manifest::{
     Channel,
     FileType,
+    Manifest,
     Os,
     TargetTriple,
     Variant,
     bundle_metadata,
 };
 use fig_util::system_info::get_system_id;
+use futures::future::join_all;
 use semver::Version;
 use serde::{
     Deserialize,
@@ -95,6 +98,18 @@ impl Index {
         self.versions.iter().max_by(|a, b| a.version.cmp(&b.version))
     }
 
+    /// Whether the index ships builds for the given target and variant.
+    ///
+    /// If `file_type` is [Option::None], any file type is accepted.
+    pub fn supports(&self, target_triple: &TargetTriple, variant: &Variant, file_type: Option<&FileType>) -> bool {
+        self.supported.iter().any(|support| {
+            support.target_triple.as_ref() == Some(target_triple)
+                && support.variant == *variant
+                && (file_type.is_none()
+                    || file_type.is_some_and(|file_type| support.file_type.as_ref() == Some(file_type)))
+        })
+    }
+
     /// Determines the next package in the index to update to, given the provided parameters.
     ///
     /// If `file_type` is [Option::None], then the returned package *may have a different file type
@@ -110,12 +125,7 @@ impl Index {
         ignore_rollout: bool,
         threshold_override: Option<u8>,
     ) -> Result<Option<UpdatePackage>, Error> {
-        if !self.supported.iter().any(|support| {
-            support.target_triple.as_ref() == Some(target_triple)
-                && support.variant == *variant
-                && (file_type.is_none()
-                    || file_type.is_some_and(|file_type| support.file_type.as_ref() == Some(file_type)))
-        }) {
+        if !self.supports(target_triple, variant, file_type) {
             error!("No support found for: {} {} {:?}", target_triple, variant, file_type);
             return Err(Error::SystemNotOnChannel);
         }
@@ -367,6 +377,63 @@ pub async fn check_for_updates(
         .find_next_version(target_triple, variant, file_type, CURRENT_VERSION, ignore_rollout, None)
 }
 
+/// Checks every channel for an update concurrently, to preview what switching channels would offer
+///
+/// A channel is `None` when it has no newer version for this system, including when it doesn't
+/// support the system or can't be reached. Rollouts are respected per channel. Results are in the
+/// order of [Channel::all].
+pub async fn check_all_channels(
+    variant: &Variant,
+    target_triple: &TargetTriple,
+) -> Vec<(Channel, Option<UpdatePackage>)> {
+    const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+    check_all_channels_with(variant, target_triple, CURRENT_VERSION, |channel| async move {
+        pull(&channel).await
+    })
+    .await
+}
+
+async fn check_all_channels_with<F, Fut>(
+    variant: &Variant,
+    target_triple: &TargetTriple,
+    current_version: &str,
+    pull: F,
+) -> Vec<(Channel, Option<UpdatePackage>)>
+where
+    F: Fn(Channel) -> Fut,
+    Fut: Future<Output = Result<Index, Error>>,
+{
+    join_all(Channel::all().iter().map(|channel| {
+        let index = pull(*channel);
+        async move {
+            let update = index
+                .await
+                .and_then(|index| index.find_next_version(target_triple, variant, None, current_version, false, None));
+            match update {
+                Ok(update) => (*channel, update),
+                Err(err) => {
+                    error!(%err, %channel, "Failed to check channel for updates");
+                    (*channel, None)
+                },
+            }
+        }
+    }))
+    .await
+}
+
+/// Checks that the system described by `manifest` can switch to a channel serving `index`.
+pub fn validate_channel(index: &Index, manifest: &Manifest, file_type: Option<&FileType>) -> Result<(), Error> {
+    if index.supports(&manifest.target_triple, &manifest.variant, file_type) {
+        Ok(())
+    } else {
+        error!(
+            "Channel does not support: {} {} {:?}",
+            manifest.target_triple, manifest.variant, file_type
+        );
+        Err(Error::SystemNotOnChannel)
+    }
+}
+
 pub(crate) async fn get_file_type(ctx: &Context, variant: &Variant) -> Result<FileType, Error> {
     match ctx.platform().os() {
         fig_os_shim::Os::Mac => Ok(FileType::Dmg),
@@ -386,6 +453,7 @@ pub(crate) async fn get_file_type(ctx: &Context, variant: &Variant) -> Result<Fi
 mod tests {
     use std::str::FromStr;
 
+    use fig_util::manifest::ManagedBy;
     use fig_util::{
         OLD_CLI_BINARY_NAMES,
         OLD_PRODUCT_NAME,
@@ -440,6 +508,52 @@ mod tests {
         println!("{:#?}", *RELEASE_URL);
     }
 
+    #[test]
+    fn validate_channel_test() {
+        let index = serde_json::from_value::<Index>(serde_json::json!({
+            "supported": [
+                {
+                    "kind": "deb",
+                    "targetTriple": "x86_64-unknown-linux-gnu",
+                    "os": "linux",
+                    "architecture": "x86_64",
+                    "variant": "full",
+                    "fileType": "deb"
+                }
+            ],
+            "versions": []
+        }))
+        .unwrap();
+        let manifest = |target_triple, variant| Manifest {
+            managed_by: ManagedBy::None,
+            target_triple,
+            variant,
+            default_channel: Channel::Stable,
+            packaged_at: String::new(),
+            packaged_by: String::new(),
+        };
+
+        let linux_full = manifest(TargetTriple::X86_64UnknownLinuxGnu, Variant::Full);
+        assert!(validate_channel(&index, &linux_full, Some(&FileType::Deb)).is_ok());
+        assert!(validate_channel(&index, &linux_full, None).is_ok());
+        assert!(matches!(
+            validate_channel(&index, &linux_full, Some(&FileType::AppImage)),
+            Err(Error::SystemNotOnChannel)
+        ));
+
+        let linux_minimal = manifest(TargetTriple::X86_64UnknownLinuxGnu, Variant::Minimal);
+        assert!(matches!(
+            validate_channel(&index, &linux_minimal, None),
+            Err(Error::SystemNotOnChannel)
+        ));
+
+        let macos = manifest(TargetTriple::UniversalAppleDarwin, Variant::Full);
+        assert!(matches!(
+            validate_channel(&index, &macos, Some(&FileType::Dmg)),
+            Err(Error::SystemNotOnChannel)
+        ));
+    }
+
     #[test]
     fn index_serde_test() {
         let old_cli_name = OLD_CLI_BINARY_NAMES[0];
@@ -591,6 +705,74 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn check_all_channels_test() {
+        let index = |version: &str, rollout: serde_json::Value| {
+            serde_json::from_value::<Index>(serde_json::json!({
+                "supported": [
+                    {
+                        "kind": "deb",
+                        "targetTriple": "x86_64-unknown-linux-gnu",
+                        "os": "linux",
+                        "architecture": "x86_64",
+                        "variant": "headless",
+                        "fileType": "tarZst"
+                    }
+                ],
+                "versions": [
+                    {
+                        "version": version,
+                        "rollout": rollout,
+                        "packages": [
+                            {
+                                "kind": "deb",
+                                "targetTriple": "x86_64-unknown-linux-gnu",
+                                "os": "linux",
+                                "architecture": "x86_64",
+                                "variant": "headless",
+                                "fileType": "tarZst",
+                                "download": format!("{version}/q.tar.zst"),
+                                "sha256": "a8112",
+                                "size": 100
+                            }
+                        ]
+                    }
+                ]
+            }))
+            .unwrap()
+        };
+
+        let results = check_all_channels_with(
+            &Variant::Minimal,
+            &TargetTriple::X86_64UnknownLinuxGnu,
+            "1.0.0",
+            |channel| async move {
+                match channel {
+                    Channel::Stable => Ok(index("1.1.0", serde_json::Value::Null)),
+                    Channel::Beta => Ok(index("1.2.0-beta.1", serde_json::Value::Null)),
+                    // The rollout hasn't started yet
+                    Channel::Qa => Ok(index(
+                        "1.3.0",
+                        serde_json::json!({ "start": u64::MAX - 1, "end": u64::MAX }),
+                    )),
+                    Channel::Nightly => Err(Error::UpdateFailed("unreachable".into())),
+                }
+            },
+        )
+        .await;
+
+        let versions = results
+            .iter()
+            .map(|(channel, update)| (*channel, update.as_ref().map(|update| update.version.to_string())))
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec![
+            (Channel::Stable, Some("1.1.0".into())),
+            (Channel::Beta, Some("1.2.0-beta.1".into())),
+            (Channel::Qa, None),
+            (Channel::Nightly, None),
+        ]);
+    }
+
     fn load_test_index() -> Index {
         serde_json::from_str(include_str!("../test_files/test-index.json")).unwrap()
     }
@@ -6,19 +6,23 @@ pub mod index;
 mod linux;
 #[cfg(target_os = "macos")]
 pub mod macos;
+mod package_manager;
+mod progress;
 #[cfg(windows)]
 mod windows;
 
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::SystemTimeError;
+use std::time::{
+    Duration,
+    SystemTimeError,
+};
 
 use fig_os_shim::{
     Context,
     Os,
     PlatformProvider,
 };
-use fig_util::PRODUCT_NAME;
 use fig_util::manifest::{
     Channel,
     FileType,
@@ -26,6 +30,10 @@ use fig_util::manifest::{
     bundle_metadata,
     manifest,
 };
+use fig_util::{
+    LockFile,
+    PRODUCT_NAME,
+};
 #[cfg(target_os = "freebsd")]
 use freebsd as os;
 use index::UpdatePackage;
@@ -51,9 +59,14 @@ pub use common::{
     install,
     uninstall,
 };
+pub use package_manager::PackageManager;
+pub use progress::ProgressNormalizer;
 
 pub const UNINSTALL_URL: &str = "https://pulse.aws/survey/QYFVDA5H";
 
+/// An update lock older than this is assumed to be left over from an update that died
+const UPDATE_LOCK_TTL: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
@@ -78,8 +91,8 @@ pub enum Error {
     Strum(#[from] strum::ParseError),
     #[error("could not determine app version")]
     UnclearVersion,
-    #[error("please update from your package manager")]
-    PackageManaged,
+    #[error("please update using {manager}: `{command}`")]
+    PackageManaged { manager: PackageManager, command: String },
     #[error("failed to update: `{0}`")]
     UpdateFailed(String),
     #[error("failed to update: `{0}`")]
@@ -103,6 +116,15 @@ pub enum Error {
     UnsupportedVariant(String),
 }
 
+impl From<PackageManager> for Error {
+    fn from(manager: PackageManager) -> Self {
+        Error::PackageManaged {
+            manager,
+            command: manager.update_command(),
+        }
+    }
+}
+
 impl From<fig_util::directories::DirectoryError> for Error {
     fn from(err: fig_util::directories::DirectoryError) -> Self {
         fig_util::Error::Directory(err).into()
@@ -144,13 +166,20 @@ pub fn get_max_channel() -> Channel {
         .unwrap()
 }
 
-pub async fn check_for_updates(ignore_rollout: bool) -> Result<Option<UpdatePackage>, Error> {
+/// The file type of the current install, or [Option::None] for Linux desktop bundles without
+/// bundle metadata
+async fn current_file_type() -> Result<Option<FileType>, Error> {
     let manifest = manifest();
     let ctx = Context::new();
-    let file_type = match (&manifest.variant, ctx.platform().os()) {
+    Ok(match (&manifest.variant, ctx.platform().os()) {
         (Variant::Full, fig_os_shim::Os::Linux) => (index::get_file_type(&ctx, &manifest.variant).await).ok(),
-        _ => Some(index::get_file_type(&Context::new(), &manifest.variant).await?),
-    };
+        _ => Some(index::get_file_type(&ctx, &manifest.variant).await?),
+    })
+}
+
+pub async fn check_for_updates(ignore_rollout: bool) -> Result<Option<UpdatePackage>, Error> {
+    let manifest = manifest();
+    let file_type = current_file_type().await?;
     index::check_for_updates(
         get_channel()?,
         &manifest.target_triple,
@@ -161,9 +190,46 @@ pub async fn check_for_updates(ignore_rollout: bool) -> Result<Option<UpdatePack
     .await
 }
 
+/// The previous and newly selected channel after calling [set_channel]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelSwitch {
+    pub previous: Channel,
+    pub current: Channel,
+}
+
+impl ChannelSwitch {
+    /// Moving to a more stable channel may mean no update is offered until that channel catches up
+    /// with the installed version
+    pub fn is_downgrade(&self) -> bool {
+        self.current < self.previous
+    }
+}
+
+/// Switch the update channel, after checking that it ships builds for this system
+pub async fn set_channel(channel: Channel) -> Result<ChannelSwitch, Error> {
+    let file_type = current_file_type().await?;
+    let index = index::pull(&channel).await?;
+    index::validate_channel(&index, manifest(), file_type.as_ref())?;
+
+    let previous = get_channel()?;
+    fig_settings::state::set_value("updates.channel", channel.id())?;
+    info!("Switched update channel from {} to {}", previous.id(), channel.id());
+
+    Ok(ChannelSwitch {
+        previous,
+        current: channel,
+    })
+}
+
 #[derive(Debug, Clone)]
 pub enum UpdateStatus {
+    /// Raw percentage reported by the platform updater
     Percent(f32),
+    /// Percentage clamped to `0..=100` that never decreases, with the estimated time remaining
+    Progress {
+        percent: f32,
+        eta: Option<Duration>,
+    },
     Message(String),
     Error(String),
     Exit,
@@ -194,65 +260,41 @@ pub async fn update(
         info!("Found update: {}", update.version);
         debug!("Update info: {:?}", update);
 
-        if ctx.platform().os() == Os::Linux
-            && manifest().variant == Variant::Full
-            && bundle_metadata(&ctx)
+        if ctx.platform().os() == Os::Linux && manifest().variant == Variant::Full {
+            if let Some(packaged_as) = bundle_metadata(&ctx)
                 .await?
-                .is_some_and(|md| md.packaged_as != FileType::AppImage)
-        {
-            return Err(Error::UpdateFailed(format!(
-                "Please use your package manager to update {}",
-                PRODUCT_NAME
-            )));
+                .map(|md| md.packaged_as)
+                .filter(|packaged_as| *packaged_as != FileType::AppImage)
+            {
+                return Err(match PackageManager::from_file_type(&packaged_as) {
+                    Some(manager) => manager.into(),
+                    None => Error::UpdateFailed(format!("Please use your package manager to update {}", PRODUCT_NAME)),
+                });
+            }
         }
 
         let (tx, rx) = tokio::sync::mpsc::channel(16);
 
-        let lock_file = fig_util::directories::update_lock_path(&ctx)?;
-
-        let now_unix_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-
-        // If the lock file is older than 1hr, we can assume it's stale and remove it
-        if lock_file.exists() {
-            match std::fs::read_to_string(&lock_file) {
-                Ok(contents) => {
-                    let lock_unix_time = contents.parse::<u64>().unwrap_or(0);
-                    if now_unix_time - lock_unix_time < 3600 {
-                        return Err(Error::UpdateInProgress);
-                    } else {
-                        std::fs::remove_file(&lock_file)?;
-                    }
-                },
-                Err(err) => {
-                    error!(%err, "Failed to read lock file, but it exists");
-                },
-            }
-        }
-
-        tokio::fs::write(&lock_file, &format!("{now_unix_time}")).await?;
+        let lock_path = fig_util::directories::update_lock_path(&ctx)?;
+        let Some(lock) = LockFile::acquire(&ctx, lock_path, UPDATE_LOCK_TTL).await? else {
+            return Err(Error::UpdateInProgress);
+        };
 
         let join = tokio::spawn(async move {
+            // Released once the update finishes, whether or not it succeeded
+            let _lock = lock;
             tx.send(UpdateStatus::Message("Starting Update...".into())).await.ok();
             if let Err(err) = os::update(update, tx.clone(), interactive, relaunch_dashboard).await {
                 error!(%err, "Failed to update");
-
-                if let Err(err) = tokio::fs::remove_file(&lock_file).await {
-                    error!(%err, "Failed to remove lock file");
-                }
-
                 tx.send(UpdateStatus::Error(format!("{err}"))).await.ok();
                 return Err(err);
             }
-            tokio::fs::remove_file(&lock_file).await?;
             Ok(())
         });
 
         if let Some(on_update) = on_update {
             info!("Updating...");
-            on_update(rx);
+            on_update(progress::normalize(rx));
         } else {
             drop(rx);
         }
@@ -264,3 +306,16 @@ pub async fn update(
         Ok(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_switch_downgrade() {
+        let switch = |previous, current| ChannelSwitch { previous, current };
+        assert!(switch(Channel::Beta, Channel::Stable).is_downgrade());
+        assert!(!switch(Channel::Stable, Channel::Beta).is_downgrade());
+        assert!(!switch(Channel::Beta, Channel::Beta).is_downgrade());
+    }
+}
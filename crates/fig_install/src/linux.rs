@@ -26,7 +26,10 @@ use tracing::{
 };
 use url::Url;
 
-use crate::download::download_file;
+use crate::download::{
+    DownloadCache,
+    download_cached,
+};
 use crate::index::UpdatePackage;
 use crate::{
     Error,
@@ -167,7 +170,15 @@ pub(crate) async fn update_minimal(
     let archive_path = tempdir.path().join(archive.file_name);
 
     debug!("downloading file: {:?} to path: {:?}", download_url, archive_path);
-    let real_hash = download_file(download_url.clone(), &archive_path, size, Some(tx.clone())).await?;
+    let real_hash = download_cached(
+        &DownloadCache::new(&Context::new())?,
+        download_url.clone(),
+        &archive_path,
+        size,
+        &sha256,
+        Some(tx.clone()),
+    )
+    .await?;
     if sha256 != real_hash {
         return Err(Error::UpdateFailed(format!(
             "Hash mismatch for {}: expected {sha256}, got {real_hash}",
@@ -237,7 +248,15 @@ async fn update_full_ctx(
         .await?;
 
     debug!(?file_name, "Downloading update file");
-    let real_hash = download_file(download_url, &download_path, size, Some(tx.clone())).await?;
+    let real_hash = download_cached(
+        &DownloadCache::new(ctx)?,
+        download_url,
+        &download_path,
+        size,
+        &expected_hash,
+        Some(tx.clone()),
+    )
+    .await?;
 
     if real_hash != expected_hash {
         return Err(Error::UpdateFailed(format!(
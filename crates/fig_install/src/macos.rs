@@ -13,6 +13,7 @@ use std::path::{
     PathBuf,
 };
 
+use fig_os_shim::Context;
 use fig_util::consts::{
     APP_BUNDLE_ID,
     CLI_BINARY_NAME,
@@ -40,7 +41,10 @@ use tracing::{
     warn,
 };
 
-use crate::download::download_file;
+use crate::download::{
+    DownloadCache,
+    download_cached,
+};
 use crate::index::UpdatePackage;
 use crate::{
     Error,
@@ -74,7 +78,15 @@ pub(crate) async fn update(
 
     debug!(?dmg_path, "downloading dmg");
 
-    let real_hash = download_file(update.download_url, &dmg_path, update.size, Some(tx.clone())).await?;
+    let real_hash = download_cached(
+        &DownloadCache::new(&Context::new())?,
+        update.download_url,
+        &dmg_path,
+        update.size,
+        &update.sha256,
+        Some(tx.clone()),
+    )
+    .await?;
 
     // validate the dmg hash
     let expected_hash = update.sha256;
@@ -522,6 +534,7 @@ mod test {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::download::download_file;
 
     #[ignore]
     #[tokio::test]
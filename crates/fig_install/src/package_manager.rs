@@ -0,0 +1,70 @@
+use fig_util::consts::linux::PACKAGE_NAME;
+use fig_util::manifest::FileType;
+use strum::Display;
+
+/// System package manager that owns the installed bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Pacman,
+    Pkg,
+}
+
+impl PackageManager {
+    /// The package manager responsible for bundles packaged as `file_type`, if any
+    pub fn from_file_type(file_type: &FileType) -> Option<Self> {
+        match file_type {
+            FileType::Deb => Some(Self::Apt),
+            FileType::Rpm => Some(Self::Dnf),
+            FileType::PkgTarZst => Some(Self::Pacman),
+            _ => None,
+        }
+    }
+
+    /// Shell command the user can run to update the package
+    pub fn update_command(&self) -> String {
+        match self {
+            Self::Apt => format!("sudo apt update && sudo apt upgrade {PACKAGE_NAME}"),
+            Self::Dnf => format!("sudo dnf upgrade {PACKAGE_NAME}"),
+            Self::Pacman => format!("sudo pacman -Syu {PACKAGE_NAME}"),
+            Self::Pkg => format!("sudo pkg upgrade {PACKAGE_NAME}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fig_util::manifest::BundleMetadata;
+
+    use super::*;
+
+    fn manager_for(metadata: &str) -> Option<PackageManager> {
+        let metadata: BundleMetadata = serde_json::from_str(metadata).unwrap();
+        PackageManager::from_file_type(&metadata.packaged_as)
+    }
+
+    #[test]
+    fn test_from_bundle_metadata() {
+        assert_eq!(manager_for(r#"{"packaged_as":"deb"}"#), Some(PackageManager::Apt));
+        assert_eq!(manager_for(r#"{"packaged_as":"rpm"}"#), Some(PackageManager::Dnf));
+        assert_eq!(
+            manager_for(r#"{"packaged_as":"pkgTarZst"}"#),
+            Some(PackageManager::Pacman)
+        );
+        assert_eq!(manager_for(r#"{"packaged_as":"appImage"}"#), None);
+        assert_eq!(manager_for(r#"{"packaged_as":"tarZst"}"#), None);
+    }
+
+    #[test]
+    fn test_update_command() {
+        assert_eq!(
+            PackageManager::Apt.update_command(),
+            "sudo apt update && sudo apt upgrade amazon-q"
+        );
+        assert_eq!(PackageManager::Dnf.update_command(), "sudo dnf upgrade amazon-q");
+        assert_eq!(PackageManager::Pacman.update_command(), "sudo pacman -Syu amazon-q");
+        assert_eq!(PackageManager::Pkg.update_command(), "sudo pkg upgrade amazon-q");
+    }
+}
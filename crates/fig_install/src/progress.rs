@@ -0,0 +1,125 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use tokio::sync::mpsc::Receiver;
+
+use crate::UpdateStatus;
+
+/// Turns the raw percentages reported by the platform updaters into a clean progress stream.
+///
+/// Percentages are clamped to `0..=100` and never move backwards, and each one is paired with
+/// an estimate of the time remaining based on how long the update has taken so far.
+#[derive(Debug)]
+pub struct ProgressNormalizer {
+    started: Instant,
+    percent: f32,
+}
+
+impl ProgressNormalizer {
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    fn starting_at(started: Instant) -> Self {
+        Self { started, percent: 0.0 }
+    }
+
+    /// Converts [`UpdateStatus::Percent`] into [`UpdateStatus::Progress`], passing every other
+    /// status through unchanged
+    pub fn normalize(&mut self, status: UpdateStatus) -> UpdateStatus {
+        match status {
+            UpdateStatus::Percent(percent) => self.progress(percent, self.started.elapsed()),
+            status => status,
+        }
+    }
+
+    fn progress(&mut self, percent: f32, elapsed: Duration) -> UpdateStatus {
+        if percent.is_finite() {
+            self.percent = self.percent.max(percent.clamp(0.0, 100.0));
+        }
+
+        let eta = (self.percent > 0.0).then(|| elapsed.mul_f32((100.0 - self.percent) / self.percent));
+        UpdateStatus::Progress {
+            percent: self.percent,
+            eta,
+        }
+    }
+}
+
+impl Default for ProgressNormalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Forwards every status from `rx` through a [`ProgressNormalizer`]
+pub(crate) fn normalize(mut rx: Receiver<UpdateStatus>) -> Receiver<UpdateStatus> {
+    let (tx, normalized) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut normalizer = ProgressNormalizer::new();
+        while let Some(status) = rx.recv().await {
+            if tx.send(normalizer.normalize(status)).await.is_err() {
+                break;
+            }
+        }
+    });
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jittery_percentages_are_monotonic() {
+        let mut normalizer = ProgressNormalizer::starting_at(Instant::now());
+        let percents = [-5.0, 10.0, 8.0, f32::NAN, 35.0, 150.0, 50.0, 100.0]
+            .into_iter()
+            .map(|percent| match normalizer.progress(percent, Duration::from_secs(10)) {
+                UpdateStatus::Progress { percent, .. } => percent,
+                status => panic!("unexpected status: {status:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(percents, [0.0, 10.0, 10.0, 10.0, 35.0, 100.0, 100.0, 100.0]);
+    }
+
+    #[test]
+    fn eta_is_based_on_elapsed_time() {
+        let mut normalizer = ProgressNormalizer::new();
+        let eta = |status| match status {
+            UpdateStatus::Progress { eta, .. } => eta,
+            status => panic!("unexpected status: {status:?}"),
+        };
+
+        assert_eq!(eta(normalizer.progress(0.0, Duration::from_secs(5))), None);
+        assert_eq!(
+            eta(normalizer.progress(25.0, Duration::from_secs(10))),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            eta(normalizer.progress(100.0, Duration::from_secs(40))),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn normalize_forwards_other_statuses() {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let mut rx = normalize(rx);
+
+        tx.send(UpdateStatus::Message("Downloading".into())).await.unwrap();
+        tx.send(UpdateStatus::Percent(60.0)).await.unwrap();
+        tx.send(UpdateStatus::Percent(40.0)).await.unwrap();
+        tx.send(UpdateStatus::Exit).await.unwrap();
+        drop(tx);
+
+        assert!(matches!(rx.recv().await, Some(UpdateStatus::Message(m)) if m == "Downloading"));
+        assert!(matches!(rx.recv().await, Some(UpdateStatus::Progress { percent, .. }) if percent == 60.0));
+        assert!(matches!(rx.recv().await, Some(UpdateStatus::Progress { percent, .. }) if percent == 60.0));
+        assert!(matches!(rx.recv().await, Some(UpdateStatus::Exit)));
+        assert!(rx.recv().await.is_none());
+    }
+}
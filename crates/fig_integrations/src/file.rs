@@ -9,12 +9,15 @@ use tokio::fs::{
 use tokio::io::AsyncWriteExt;
 use tracing::debug;
 
-use crate::Integration;
 use crate::error::{
     Error,
     ErrorExt,
     Result,
 };
+use crate::{
+    Change,
+    Integration,
+};
 
 #[derive(Debug, Clone)]
 pub struct FileIntegration {
@@ -112,6 +115,34 @@ impl Integration for FileIntegration {
             Err(err) => Err(err),
         }
     }
+
+    async fn uninstall_dry_run(&self) -> Result<Vec<Change>> {
+        Ok(if self.path.exists() {
+            vec![Change::RemoveFile(self.path.clone())]
+        } else {
+            vec![]
+        })
+    }
+}
+
+/// Lines of `before` that are missing from `after`, where `after` was produced by deleting text
+/// from `before`
+///
+/// Blank lines are left out since uninstalling also trims whitespace.
+pub(crate) fn removed_lines(before: &str, after: &str) -> Vec<String> {
+    let mut remaining = after.lines().peekable();
+    before
+        .lines()
+        .filter(|line| {
+            if remaining.peek() == Some(line) {
+                remaining.next();
+                false
+            } else {
+                !line.trim().is_empty()
+            }
+        })
+        .map(str::to_owned)
+        .collect()
 }
 
 #[cfg(test)]
@@ -148,8 +179,13 @@ mod tests {
         integration.install().await.unwrap();
         assert!(integration.is_installed().await.is_ok());
 
+        assert_eq!(integration.uninstall_dry_run().await.unwrap(), vec![
+            Change::RemoveFile(integration.path.clone())
+        ]);
+
         // ensure the intgration can be uninstalled
         integration.uninstall().await.unwrap();
+        assert!(integration.uninstall_dry_run().await.unwrap().is_empty());
         assert!(matches!(
             integration.is_installed().await,
             Err(Error::FileDoesNotExist(_))
@@ -166,4 +202,12 @@ mod tests {
         integration.install().await.unwrap();
         assert!(integration.is_installed().await.is_ok());
     }
+
+    #[test]
+    fn test_removed_lines() {
+        let before = "a\n# marker\nsource x\n\nb\nc\n";
+        let after = "a\nb\nc\n";
+        assert_eq!(removed_lines(before, after), vec!["# marker", "source x"]);
+        assert!(removed_lines(after, after).is_empty());
+    }
 }
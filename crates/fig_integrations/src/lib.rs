@@ -9,9 +9,12 @@ pub mod input_method;
 pub mod intellij;
 pub mod shell;
 pub mod ssh;
+pub mod status;
 #[cfg(target_os = "macos")]
 pub mod vscode;
 
+use std::path::PathBuf;
+
 use async_trait::async_trait;
 pub use backup::backup_file;
 pub use error::{
@@ -19,6 +22,35 @@ pub use error::{
     Result,
 };
 pub use file::FileIntegration;
+pub use status::{
+    InstallStatus,
+    IntegrationStatus,
+    status_all,
+};
+
+/// A change to the filesystem that uninstalling an integration would make
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The file would be deleted
+    RemoveFile(PathBuf),
+    /// These lines would be removed from the file
+    RemoveLines { path: PathBuf, lines: Vec<String> },
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::RemoveFile(path) => write!(f, "Remove {}", path.display()),
+            Change::RemoveLines { path, lines } => {
+                write!(f, "Remove from {}:", path.display())?;
+                for line in lines {
+                    write!(f, "\n- {line}")?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
 
 #[async_trait]
 pub trait Integration {
@@ -27,6 +59,20 @@ pub trait Integration {
     async fn uninstall(&self) -> Result<()>;
     async fn is_installed(&self) -> Result<()>;
 
+    /// Describe the changes [`Integration::uninstall`] would make without making them
+    async fn uninstall_dry_run(&self) -> Result<Vec<Change>> {
+        Ok(vec![])
+    }
+
+    /// Bring a missing, partial or edited installation back to a clean install
+    ///
+    /// Unlike [`Integration::install`] this also rewrites an installation that looks installed,
+    /// so it can be called any number of times.
+    async fn repair(&self) -> Result<()> {
+        self.uninstall().await?;
+        self.install().await
+    }
+
     /// Apply any migrations, this can be called at any time so do not do anything too destructive
     async fn migrate(&self) -> Result<()> {
         Ok(())
@@ -12,6 +12,7 @@ use fig_os_shim::Env;
 use fig_util::{
     CLI_BINARY_NAME,
     PRODUCT_NAME,
+    SHELL_INTEGRATION_VERSION_PREFIX,
     Shell,
     directories,
 };
@@ -28,7 +29,9 @@ use crate::error::{
     ErrorExt,
     Result,
 };
+use crate::file::removed_lines;
 use crate::{
+    Change,
     Error,
     FileIntegration,
     Integration,
@@ -269,12 +272,38 @@ impl ShellScriptShellIntegration {
     fn get_file_integration(&self) -> FileIntegration {
         FileIntegration {
             path: self.path.clone(),
-            contents: self.get_contents(),
+            contents: self.get_versioned_contents(),
             #[cfg(unix)]
             mode: None,
         }
     }
 
+    /// [Self::get_contents] preceded by the version of the CLI writing the script, which
+    /// diagnostics compare against the running CLI
+    fn get_versioned_contents(&self) -> String {
+        format!(
+            "{SHELL_INTEGRATION_VERSION_PREFIX}{}\n{}",
+            env!("CARGO_PKG_VERSION"),
+            self.get_contents()
+        )
+    }
+
+    /// Whether the installed script matches, except for having been written by another version of
+    /// the CLI or by one from before versions were recorded
+    async fn is_other_version_installed(&self) -> bool {
+        let Ok(contents) = tokio::fs::read_to_string(&self.path).await else {
+            return false;
+        };
+        let contents = match contents
+            .strip_prefix(SHELL_INTEGRATION_VERSION_PREFIX)
+            .and_then(|rest| rest.split_once('\n'))
+        {
+            Some((_, rest)) => rest,
+            None => &contents,
+        };
+        contents == self.get_contents()
+    }
+
     fn get_name(&self) -> Option<&str> {
         self.path.file_name().and_then(|s| s.to_str())
     }
@@ -324,7 +353,11 @@ impl ShellScriptShellIntegration {
 #[async_trait]
 impl Integration for ShellScriptShellIntegration {
     async fn is_installed(&self) -> Result<()> {
-        self.get_file_integration().is_installed().await
+        match self.get_file_integration().is_installed().await {
+            // Version skew alone doesn't break the integration, it is reported by diagnostics
+            Err(Error::ImproperInstallation(_)) if self.is_other_version_installed().await => Ok(()),
+            result => result,
+        }
     }
 
     async fn install(&self) -> Result<()> {
@@ -335,6 +368,10 @@ impl Integration for ShellScriptShellIntegration {
         self.get_file_integration().uninstall().await
     }
 
+    async fn uninstall_dry_run(&self) -> Result<Vec<Change>> {
+        self.get_file_integration().uninstall_dry_run().await
+    }
+
     fn describe(&self) -> String {
         format!("{} {}", self.shell, self.when)
     }
@@ -577,20 +614,33 @@ impl DotfileShellIntegration {
         ))
     }
 
-    async fn install_inner(&self) -> Result<()> {
-        let dotfile = self.dotfile_path();
-        let mut contents = if dotfile.exists() {
-            backup_file(&dotfile, fig_util::directories::utc_backup_dir().ok())?;
-            self.uninstall().await?;
-            std::fs::read_to_string(&dotfile)?
-        } else {
-            String::new()
-        };
+    /// The dotfile contents with the integration blocks removed
+    fn uninstalled_contents(&self, contents: &str) -> Result<String> {
+        // Remove comment lines
+        let mut contents: String = Regex::new(r"(?mi)^#.*fig.*var.*$\n?")?.replace_all(contents, "").into();
 
-        let original_contents = contents.clone();
+        contents = Regex::new(r"(?mi)^#.*Please make sure this block is at the .* of this file.*$\n?")?
+            .replace_all(&contents, "")
+            .into();
+
+        if self.pre {
+            contents = self.remove_from_text(&contents, When::Pre)?;
+        }
+
+        if self.post {
+            contents = self.remove_from_text(&contents, When::Post)?;
+        }
+
+        contents = contents.trim().to_string();
+        contents.push('\n');
+        Ok(contents)
+    }
+
+    /// Add the integration blocks to dotfile contents that have none
+    fn with_blocks(&self, contents: &str) -> Result<String> {
+        let mut contents = contents.to_owned();
 
         if self.pre {
-            self.script_integration(When::Pre)?.install().await?;
             let (shebang, post_shebang) = split_shebang(&contents);
             contents = format!(
                 "{}{}\n{}\n{}",
@@ -602,7 +652,6 @@ impl DotfileShellIntegration {
         }
 
         if self.post {
-            self.script_integration(When::Post)?.install().await?;
             contents = format!(
                 "{}\n{}\n{}\n",
                 contents,
@@ -611,9 +660,83 @@ impl DotfileShellIntegration {
             );
         }
 
-        if contents.ne(&original_contents) {
+        Ok(contents)
+    }
+
+    /// Remove what is left of integration blocks after [`Self::uninstalled_contents`], such as a
+    /// block header whose source line was deleted or a source line that was cut short
+    fn remove_partial_blocks(&self, contents: &str) -> Result<String> {
+        let mut markers = vec![];
+        for (enabled, when) in [(self.pre, When::Pre), (self.post, When::Post)] {
+            if enabled {
+                markers.push(self.script_integration(when)?.file_name().to_owned());
+            }
+        }
+
+        let mut headers = vec![];
+        for (enabled, when) in [(self.pre, When::Pre), (self.post, When::Post)] {
+            if enabled {
+                headers.push(self.description(when));
+                headers.push(DotfileShellIntegration::legacy_description(when));
+            }
+        }
+
+        let mut contents = contents
+            .lines()
+            .filter(|line| {
+                let line = line.trim();
+                !headers.iter().any(|header| header == line) && !markers.iter().any(|marker| line.contains(marker))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+        contents.push('\n');
+        Ok(contents)
+    }
+
+    /// The dotfile contents with exactly one copy of each integration block, whatever state the
+    /// blocks were in before
+    fn repaired_contents(&self, contents: &str) -> Result<String> {
+        let cleaned = self.remove_partial_blocks(&self.uninstalled_contents(contents)?)?;
+        // Installing into an empty dotfile starts from an empty string rather than a newline
+        let cleaned = if cleaned.trim().is_empty() {
+            String::new()
+        } else {
+            cleaned
+        };
+        self.with_blocks(&cleaned)
+    }
+
+    /// Writes the pre and post scripts, unless they already match this version of the CLI
+    async fn install_scripts(&self) -> Result<()> {
+        if self.pre {
+            self.script_integration(When::Pre)?.install().await?;
+        }
+
+        if self.post {
+            self.script_integration(When::Post)?.install().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn install_inner(&self) -> Result<()> {
+        let dotfile = self.dotfile_path();
+        let contents = if dotfile.exists() {
+            backup_file(&dotfile, fig_util::directories::utc_backup_dir().ok())?;
+            self.uninstall().await?;
+            std::fs::read_to_string(&dotfile)?
+        } else {
+            String::new()
+        };
+
+        self.install_scripts().await?;
+
+        let new_contents = self.with_blocks(&contents)?;
+        if new_contents.ne(&contents) {
             let mut file = File::create(&dotfile).with_path(self.path())?;
-            file.write_all(contents.as_bytes())?;
+            file.write_all(new_contents.as_bytes())?;
         }
         Ok(())
     }
@@ -633,7 +756,9 @@ impl Integration for DotfileShellIntegration {
 
     async fn install(&self) -> Result<()> {
         if self.is_installed().await.is_ok() {
-            return Ok(());
+            // Scripts written by another version count as installed, but are still rewritten so
+            // their version line matches this one
+            return self.install_scripts().await;
         }
         self.install_inner().await?;
         Ok(())
@@ -642,40 +767,47 @@ impl Integration for DotfileShellIntegration {
     async fn uninstall(&self) -> Result<()> {
         let dotfile = self.dotfile_path();
         if dotfile.exists() {
-            let mut contents = std::fs::read_to_string(&dotfile)?;
+            let contents = std::fs::read_to_string(&dotfile)?;
+            let uninstalled_contents = self.uninstalled_contents(&contents)?;
 
-            // Remove comment lines
-            contents = Regex::new(r"(?mi)^#.*fig.*var.*$\n?")?
-                .replace_all(&contents, "")
-                .into();
+            if uninstalled_contents != contents {
+                backup_file(&dotfile, directories::utc_backup_dir().ok())?;
+                std::fs::write(&dotfile, uninstalled_contents.as_bytes()).with_path(self.path())?;
+            }
+        }
 
-            contents = Regex::new(r"(?mi)^#.*Please make sure this block is at the .* of this file.*$\n?")?
-                .replace_all(&contents, "")
-                .into();
+        if self.pre {
+            self.script_integration(When::Pre)?.uninstall().await?;
+        }
 
-            if self.pre {
-                contents = self.remove_from_text(&contents, When::Pre)?;
-            }
+        if self.post {
+            self.script_integration(When::Post)?.uninstall().await?;
+        }
 
-            if self.post {
-                contents = self.remove_from_text(&contents, When::Post)?;
-            }
+        Ok(())
+    }
 
-            contents = contents.trim().to_string();
-            contents.push('\n');
+    async fn uninstall_dry_run(&self) -> Result<Vec<Change>> {
+        let mut changes = vec![];
 
-            std::fs::write(&dotfile, contents.as_bytes()).with_path(self.path())?;
+        let dotfile = self.dotfile_path();
+        if dotfile.exists() {
+            let contents = std::fs::read_to_string(&dotfile)?;
+            let lines = removed_lines(&contents, &self.uninstalled_contents(&contents)?);
+            if !lines.is_empty() {
+                changes.push(Change::RemoveLines { path: dotfile, lines });
+            }
         }
 
         if self.pre {
-            self.script_integration(When::Pre)?.uninstall().await?;
+            changes.extend(self.script_integration(When::Pre)?.uninstall_dry_run().await?);
         }
 
         if self.post {
-            self.script_integration(When::Post)?.uninstall().await?;
+            changes.extend(self.script_integration(When::Post)?.uninstall_dry_run().await?);
         }
 
-        Ok(())
+        Ok(changes)
     }
 
     async fn is_installed(&self) -> Result<()> {
@@ -718,6 +850,24 @@ impl Integration for DotfileShellIntegration {
         Ok(())
     }
 
+    async fn repair(&self) -> Result<()> {
+        self.install_scripts().await?;
+
+        let dotfile = self.dotfile_path();
+        let contents = match std::fs::read_to_string(&dotfile) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let repaired_contents = self.repaired_contents(&contents)?;
+        if repaired_contents != contents {
+            backup_file(&dotfile, directories::utc_backup_dir().ok())?;
+            std::fs::write(&dotfile, repaired_contents.as_bytes()).with_path(self.path())?;
+        }
+        Ok(())
+    }
+
     async fn migrate(&self) -> Result<()> {
         match self.is_installed().await {
             Ok(_) => Ok(()),
@@ -939,4 +1089,149 @@ mod test {
             run_shellcheck(integration.get_contents());
         }
     }
+
+    #[tokio::test]
+    async fn script_integration_version() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let integration = ShellScriptShellIntegration {
+            shell: Shell::Zsh,
+            when: When::Pre,
+            path: tempdir.path().join("zshrc.pre.zsh"),
+        };
+
+        integration.install().await.unwrap();
+        let installed = std::fs::read_to_string(&integration.path).unwrap();
+        assert_eq!(
+            fig_util::shell_integration_version(&installed),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        integration.is_installed().await.unwrap();
+
+        // Scripts from other versions, or from before versions were recorded, are still installed
+        let other_version = format!(
+            "{SHELL_INTEGRATION_VERSION_PREFIX}0.0.1\n{}",
+            integration.get_contents()
+        );
+        std::fs::write(&integration.path, other_version).unwrap();
+        integration.is_installed().await.unwrap();
+        std::fs::write(&integration.path, integration.get_contents()).unwrap();
+        integration.is_installed().await.unwrap();
+
+        std::fs::write(
+            &integration.path,
+            format!("{SHELL_INTEGRATION_VERSION_PREFIX}0.0.1\necho hi\n"),
+        )
+        .unwrap();
+        assert!(integration.is_installed().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn script_install_replaces_other_version() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let integration = ShellScriptShellIntegration {
+            shell: Shell::Bash,
+            when: When::Post,
+            path: tempdir.path().join("bashrc.post.bash"),
+        };
+
+        for other_version in [
+            format!(
+                "{SHELL_INTEGRATION_VERSION_PREFIX}0.0.1\n{}",
+                integration.get_contents()
+            ),
+            integration.get_contents(),
+        ] {
+            std::fs::write(&integration.path, other_version).unwrap();
+            integration.is_installed().await.unwrap();
+            assert!(integration.get_file_integration().is_installed().await.is_err());
+
+            // Installing over the skewed script, as an update does, records this version
+            integration.install().await.unwrap();
+            let installed = std::fs::read_to_string(&integration.path).unwrap();
+            assert_eq!(installed, integration.get_versioned_contents());
+            assert_eq!(
+                fig_util::shell_integration_version(&installed),
+                Some(env!("CARGO_PKG_VERSION"))
+            );
+            integration.get_file_integration().is_installed().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn dotfile_uninstall_dry_run() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let integration = DotfileShellIntegration {
+            shell: Shell::Zsh,
+            pre: true,
+            post: true,
+            dotfile_directory: tempdir.path().into(),
+            dotfile_name: ".zshrc",
+        };
+
+        let pre_description = integration.description(When::Pre);
+        let pre_source = integration.source_text(When::Pre).unwrap();
+        let post_description = integration.description(When::Post);
+        let post_source = integration.source_text(When::Post).unwrap();
+        let contents = format!(
+            "{pre_description}\n{pre_source}\n\nexport EDITOR=vim\nalias ll='ls -l'\n\n{post_description}\n{post_source}\n"
+        );
+        std::fs::write(integration.dotfile_path(), &contents).unwrap();
+
+        let changes = integration.uninstall_dry_run().await.unwrap();
+        let dotfile_change = changes
+            .iter()
+            .find(|change| matches!(change, Change::RemoveLines { path, .. } if *path == integration.dotfile_path()))
+            .unwrap();
+        assert_eq!(dotfile_change, &Change::RemoveLines {
+            path: integration.dotfile_path(),
+            lines: vec![pre_description, pre_source, post_description, post_source],
+        });
+
+        // Nothing was changed on disk
+        assert_eq!(std::fs::read_to_string(integration.dotfile_path()).unwrap(), contents);
+
+        // A dotfile without the integration has nothing to remove
+        std::fs::write(integration.dotfile_path(), "export EDITOR=vim\n").unwrap();
+        let changes = integration.uninstall_dry_run().await.unwrap();
+        assert!(
+            !changes
+                .iter()
+                .any(|change| matches!(change, Change::RemoveLines { .. }))
+        );
+    }
+
+    #[test]
+    fn dotfile_repair_truncated_block() {
+        let integration = DotfileShellIntegration {
+            shell: Shell::Zsh,
+            pre: true,
+            post: true,
+            dotfile_directory: "".into(),
+            dotfile_name: ".zshrc",
+        };
+        let installed = integration.with_blocks("export EDITOR=vim\n").unwrap();
+
+        // The pre source line was cut short and the post block lost its source line
+        let pre_source = integration.source_text(When::Pre).unwrap();
+        let truncated = format!(
+            "{}\n{}\nexport EDITOR=vim\n\n{}\n",
+            integration.description(When::Pre),
+            &pre_source[..pre_source.find("&&").unwrap()],
+            integration.description(When::Post),
+        );
+        assert_eq!(integration.repaired_contents(&truncated).unwrap(), installed);
+
+        // A second copy of the blocks is collapsed rather than kept
+        let duplicated = integration.with_blocks(&installed).unwrap();
+        assert_eq!(integration.repaired_contents(&duplicated).unwrap(), installed);
+
+        // Repairing a clean install changes nothing
+        assert_eq!(integration.repaired_contents(&installed).unwrap(), installed);
+
+        // An empty dotfile gets a fresh install
+        assert_eq!(
+            integration.repaired_contents("").unwrap(),
+            integration.with_blocks("").unwrap()
+        );
+    }
 }
@@ -0,0 +1,258 @@
+use fig_os_shim::Context;
+use fig_util::Shell;
+use futures::future::join_all;
+use serde::Serialize;
+
+use crate::Integration;
+use crate::error::Error;
+use crate::shell::ShellExt;
+use crate::ssh::SshIntegration;
+
+/// Result of checking whether an integration is installed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "message", rename_all = "camelCase")]
+pub enum InstallStatus {
+    Installed,
+    /// Not installed, or installed in a way that needs to be reinstalled
+    NotInstalled(String),
+    /// The check itself failed, so the install state is unknown
+    Error(String),
+}
+
+impl InstallStatus {
+    pub fn is_installed(&self) -> bool {
+        matches!(self, Self::Installed)
+    }
+}
+
+impl From<Result<(), Error>> for InstallStatus {
+    fn from(result: Result<(), Error>) -> Self {
+        match result {
+            Ok(()) => Self::Installed,
+            Err(err) if is_not_installed(&err) => Self::NotInstalled(err.to_string()),
+            Err(err) => Self::Error(err.to_string()),
+        }
+    }
+}
+
+/// The install status of a single integration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrationStatus {
+    /// Description of the integration, from [`Integration::describe`]
+    pub name: String,
+    pub status: InstallStatus,
+}
+
+fn is_not_installed(err: &Error) -> bool {
+    match err {
+        Error::LegacyInstallation(_)
+        | Error::ImproperInstallation(_)
+        | Error::NotInstalled(_)
+        | Error::FileDoesNotExist(_) => true,
+        #[cfg(target_os = "macos")]
+        Error::ApplicationNotInstalled(_) => true,
+        Error::Context { error, .. } => is_not_installed(error),
+        _ => false,
+    }
+}
+
+/// Check every integration concurrently
+///
+/// Each check is independent, an integration whose check fails is reported as
+/// [`InstallStatus::Error`] and the rest are still checked. Results are in the same order as
+/// `integrations`.
+pub async fn status_of(integrations: &[&(dyn Integration + Send + Sync)]) -> Vec<IntegrationStatus> {
+    join_all(integrations.iter().map(|integration| async move {
+        IntegrationStatus {
+            name: integration.describe(),
+            status: integration.is_installed().await.into(),
+        }
+    }))
+    .await
+}
+
+/// Check the status of every integration available on this platform
+///
+/// Integrations that can't be constructed, for example because a directory can't be resolved,
+/// are reported as [`InstallStatus::Error`] rather than failing the whole call.
+pub async fn status_all(ctx: &Context) -> Vec<IntegrationStatus> {
+    let mut integrations: Vec<Box<dyn Integration + Send + Sync>> = Vec::new();
+    let mut errors = Vec::new();
+
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        match shell.get_shell_integrations(ctx.env()) {
+            Ok(shell_integrations) => integrations.extend(
+                shell_integrations
+                    .into_iter()
+                    .map(|integration| integration as Box<dyn Integration + Send + Sync>),
+            ),
+            Err(err) => errors.push(IntegrationStatus {
+                name: format!("{shell} Integrations"),
+                status: InstallStatus::Error(err.to_string()),
+            }),
+        }
+    }
+
+    match SshIntegration::new() {
+        Ok(ssh) => integrations.push(Box::new(ssh)),
+        Err(err) => errors.push(IntegrationStatus {
+            name: "SSH Integration".into(),
+            status: InstallStatus::Error(err.to_string()),
+        }),
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        integrations.push(Box::new(crate::input_method::InputMethod::default()));
+        for variant in crate::vscode::variants_installed() {
+            integrations.push(Box::new(crate::vscode::VSCodeIntegration { variant }));
+        }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    match crate::intellij::variants_installed().await {
+        Ok(variants) => {
+            for variant in variants {
+                integrations.push(Box::new(variant));
+            }
+        },
+        Err(err) => errors.push(IntegrationStatus {
+            name: "IntelliJ Integration".into(),
+            status: InstallStatus::Error(err.to_string()),
+        }),
+    }
+
+    let refs = integrations
+        .iter()
+        .map(|integration| integration.as_ref())
+        .collect::<Vec<_>>();
+    let mut statuses = status_of(&refs).await;
+    statuses.extend(errors);
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use tokio::sync::Barrier;
+
+    use super::*;
+    use crate::Result;
+
+    struct FakeIntegration {
+        name: &'static str,
+        delay: Duration,
+        result: fn() -> Result<()>,
+    }
+
+    #[async_trait]
+    impl Integration for FakeIntegration {
+        fn describe(&self) -> String {
+            self.name.into()
+        }
+
+        async fn install(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn uninstall(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn is_installed(&self) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            (self.result)()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_of_reports_each_integration() {
+        let installed = FakeIntegration {
+            name: "installed",
+            delay: Duration::from_millis(50),
+            result: || Ok(()),
+        };
+        let missing = FakeIntegration {
+            name: "missing",
+            delay: Duration::ZERO,
+            result: || Err(Error::NotInstalled(Cow::Borrowed("no file"))),
+        };
+        let broken = FakeIntegration {
+            name: "broken",
+            delay: Duration::ZERO,
+            result: || Err(Error::Io(std::io::Error::other("disk on fire"))),
+        };
+
+        let statuses = status_of(&[&installed, &broken, &missing]).await;
+        assert_eq!(statuses, vec![
+            IntegrationStatus {
+                name: "installed".into(),
+                status: InstallStatus::Installed,
+            },
+            IntegrationStatus {
+                name: "broken".into(),
+                status: InstallStatus::Error("IO Error: disk on fire".into()),
+            },
+            IntegrationStatus {
+                name: "missing".into(),
+                status: InstallStatus::NotInstalled("Integration not installed: no file".into()),
+            },
+        ]);
+    }
+
+    /// Only finishes its check once every other check sharing the barrier has started
+    struct BarrierIntegration {
+        name: &'static str,
+        barrier: Arc<Barrier>,
+    }
+
+    #[async_trait]
+    impl Integration for BarrierIntegration {
+        fn describe(&self) -> String {
+            self.name.into()
+        }
+
+        async fn install(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn uninstall(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn is_installed(&self) -> Result<()> {
+            self.barrier.wait().await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_of_runs_concurrently() {
+        // Run sequentially the first check would wait on the barrier forever, the timeout only
+        // turns that hang into a failure
+        let barrier = Arc::new(Barrier::new(3));
+        let waiting = |name| BarrierIntegration {
+            name,
+            barrier: Arc::clone(&barrier),
+        };
+        let (a, b, c) = (waiting("a"), waiting("b"), waiting("c"));
+
+        let statuses = tokio::time::timeout(Duration::from_secs(30), status_of(&[&a, &b, &c]))
+            .await
+            .expect("integration checks did not run concurrently");
+        assert!(statuses.iter().all(|status| status.status.is_installed()));
+    }
+
+    #[test]
+    fn test_context_errors_keep_not_installed() {
+        let err = Error::Context {
+            error: Box::new(Error::FileDoesNotExist(Cow::Borrowed(std::path::Path::new("/x")))),
+            context: "checking".into(),
+        };
+        assert!(matches!(InstallStatus::from(Err(err)), InstallStatus::NotInstalled(_)));
+    }
+}
@@ -22,7 +22,8 @@ pin_project! {
     pub struct BufferedReader<T> {
         #[pin]
         pub(crate) inner: T,
-        pub(crate) buffer: BytesMut
+        pub(crate) buffer: BytesMut,
+        pub(crate) max_message_size: Option<usize>,
     }
 }
 
@@ -31,9 +32,18 @@ impl<T> BufferedReader<T> {
         Self {
             inner,
             buffer: BytesMut::new(),
+            max_message_size: None,
         }
     }
 
+    /// Limits the size of received messages, larger messages fail with [`RecvError::TooLarge`]
+    ///
+    /// [`RecvError::TooLarge`]: crate::RecvError::TooLarge
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = Some(max_message_size);
+        self
+    }
+
     pub fn buffer(&self) -> &[u8] {
         self.buffer.as_ref()
     }
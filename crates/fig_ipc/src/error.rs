@@ -47,6 +47,8 @@ pub enum RecvError {
     Decode(#[from] fig_proto::FigMessageDecodeError),
     #[error("invalid message type")]
     InvalidMessageType,
+    #[error("message of {size} bytes exceeds the maximum of {max} bytes")]
+    TooLarge { size: usize, max: usize },
 }
 
 impl RecvError {
@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -39,6 +40,9 @@ use crate::{
 
 type Result<T, E = crate::Error> = std::result::Result<T, E>;
 
+/// Largest response accepted from the desktop app
+pub const MAX_RESPONSE_SIZE: usize = 8 * 1024 * 1024;
+
 pub async fn restart_settings_listener() -> Result<()> {
     let command = command::Command::RestartSettingsListener(RestartSettingsListenerCommand {});
     send_command_to_socket(command).await
@@ -52,6 +56,16 @@ pub async fn open_ui_element(element: UiElement, route: Option<String>) -> Resul
     send_command_to_socket(command).await
 }
 
+/// Like [`open_ui_element`], but fails with [`Error::Timeout`] if the desktop app doesn't accept
+/// the command within `timeout`
+pub async fn open_ui_element_with_timeout(element: UiElement, route: Option<String>, timeout: Duration) -> Result<()> {
+    let command = command::Command::OpenUiElement(OpenUiElementCommand {
+        element: element.into(),
+        route,
+    });
+    send_command_to_socket_with_timeout(command, timeout).await
+}
+
 pub async fn toggle_debug_mode() -> Result<Option<local::CommandResponse>> {
     let command = command::Command::DebugMode(DebugModeCommand {
         set_debug_mode: None,
@@ -218,11 +232,110 @@ pub async fn send_recv_command_to_socket(command: local::command::Command) -> Re
     send_recv_command_to_socket_with_timeout(command, Duration::from_secs(2)).await
 }
 
+/// Send a command without waiting for a response, failing with [`Error::Timeout`] if connecting
+/// and sending take longer than `timeout`
+pub async fn send_command_to_socket_with_timeout(command: local::command::Command, timeout: Duration) -> Result<()> {
+    let path = directories::desktop_socket_path()?;
+    send_command_to_path_with_timeout(&path, command, timeout).await
+}
+
+/// Send a command and wait up to `timeout` for the response, which may be at most
+/// [`MAX_RESPONSE_SIZE`] bytes
 pub async fn send_recv_command_to_socket_with_timeout(
     command: local::command::Command,
     timeout: Duration,
 ) -> Result<Option<local::CommandResponse>> {
     let path = directories::desktop_socket_path()?;
-    let mut conn = BufferedUnixStream::connect_timeout(&path, Duration::from_secs(3)).await?;
+    send_recv_command_to_path(&path, command, timeout, MAX_RESPONSE_SIZE).await
+}
+
+async fn send_command_to_path_with_timeout(
+    path: &Path,
+    command: local::command::Command,
+    timeout: Duration,
+) -> Result<()> {
+    tokio::time::timeout(timeout, async {
+        let mut conn = BufferedUnixStream::connect_timeout(path, Duration::from_secs(3)).await?;
+        conn.send_command(command, false).await
+    })
+    .await
+    .or(Err(Error::Timeout))?
+}
+
+async fn send_recv_command_to_path(
+    path: &Path,
+    command: local::command::Command,
+    timeout: Duration,
+    max_response_size: usize,
+) -> Result<Option<local::CommandResponse>> {
+    let conn = BufferedUnixStream::connect_timeout(path, Duration::from_secs(3)).await?;
+    let mut conn = conn.with_max_message_size(max_response_size);
     conn.send_recv_command(command, timeout).await
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::UnixListener;
+
+    use super::*;
+    use crate::SendMessage;
+
+    /// Binds a socket in a directory with the permissions required by `socket_connect`
+    async fn bind_socket() -> (tempfile::TempDir, std::path::PathBuf, UnixListener) {
+        let dir = tempfile::tempdir().unwrap();
+        #[cfg(unix)]
+        {
+            use std::fs::Permissions;
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&dir, Permissions::from_mode(0o700))
+                .await
+                .unwrap();
+        }
+        let path = dir.path().join("desktop.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        (dir, path, listener)
+    }
+
+    fn quit() -> local::command::Command {
+        command::Command::Quit(QuitCommand {})
+    }
+
+    #[tokio::test]
+    async fn send_recv_times_out_on_silent_socket() {
+        let (_dir, path, listener) = bind_socket().await;
+        // Accept the connection but never respond
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            drop(stream);
+        });
+
+        let result = send_recv_command_to_path(&path, quit(), Duration::from_millis(100), MAX_RESPONSE_SIZE).await;
+        assert!(matches!(result, Err(Error::Timeout)), "{result:?}");
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn send_recv_rejects_large_response() {
+        let (_dir, path, listener) = bind_socket().await;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufferedUnixStream::new(stream);
+            let response = local::CommandResponse {
+                id: None,
+                response: Some(command_response::Response::Success(local::SuccessResponse {
+                    message: Some("A".repeat(10_000)),
+                })),
+            };
+            stream.send_message(response).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+
+        let result = send_recv_command_to_path(&path, quit(), Duration::from_secs(5), 1000).await;
+        assert!(
+            matches!(result, Err(Error::Recv(RecvError::TooLarge { max: 1000, .. }))),
+            "{result:?}"
+        );
+        server.abort();
+    }
+}
@@ -41,7 +41,16 @@ where
                     return Ok(Some(message.decode()?));
                 },
                 // If the message is incomplete, read more into the buffer
-                Err(fig_proto::FigMessageParseError::Incomplete(_, _)) => {
+                Err(fig_proto::FigMessageParseError::Incomplete(_, needed)) => {
+                    // Once the header is read, `needed` is the rest of the message, so oversized
+                    // messages are rejected before their body is buffered
+                    if let Some(max) = self.max_message_size {
+                        let size = self.buffer.len() + needed;
+                        if size > max {
+                            return Err(RecvError::TooLarge { size, max });
+                        }
+                    }
+
                     let bytes = self.inner.read_buf(&mut self.buffer).await?;
 
                     // If the buffer is empty, we've reached EOF
@@ -137,6 +146,18 @@ mod tests {
         assert_eq!(mock.buffer.len(), 0);
     }
 
+    #[tokio::test]
+    async fn message_too_large() {
+        let mut mock = mock(vec![]);
+        mock.send_message(test_message_large()).await.unwrap();
+        mock.inner.set_position(0);
+        let mut mock = mock.with_max_message_size(1000);
+        assert!(matches!(
+            mock.recv_message::<fig_proto::local::LocalMessage>().await,
+            Err(RecvError::TooLarge { max: 1000, .. })
+        ));
+    }
+
     #[tokio::test]
     async fn invalid_header() {
         let mut mock = mock(vec![b'f', b'o', b'o']);
@@ -0,0 +1,107 @@
+use std::ffi::OsStr;
+use std::io;
+use std::process::{
+    ExitStatus,
+    Output,
+};
+use std::sync::Arc;
+
+use crate::Shim;
+
+/// Runs external programs
+#[derive(Debug, Clone, Default)]
+pub struct Command(inner::Inner);
+
+mod inner {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone, Default)]
+    pub(super) enum Inner {
+        #[default]
+        Real,
+        /// Stdout for each command line, e.g. `/bin/zsh --version`
+        Fake(Arc<HashMap<String, Vec<u8>>>),
+    }
+}
+
+impl Command {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a fake where every command fails as if the program wasn't found
+    pub fn new_fake() -> Self {
+        Self::from_slice(&[])
+    }
+
+    /// Create a fake that succeeds with the given stdout for each command line, the program and
+    /// its arguments separated by spaces. Any other command fails as if the program wasn't found.
+    pub fn from_slice(outputs: &[(&str, &str)]) -> Self {
+        let outputs = outputs
+            .iter()
+            .map(|(command, stdout)| ((*command).to_owned(), stdout.as_bytes().to_vec()))
+            .collect();
+        Self(inner::Inner::Fake(Arc::new(outputs)))
+    }
+
+    /// Runs `program` with `args` to completion, collecting its output.
+    ///
+    /// This is a proxy to [`tokio::process::Command::output`].
+    pub async fn output(&self, program: impl AsRef<OsStr>, args: &[&str]) -> io::Result<Output> {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => tokio::process::Command::new(program).args(args).output().await,
+            Inner::Fake(outputs) => {
+                let command_line = std::iter::once(program.as_ref().to_string_lossy().into_owned())
+                    .chain(args.iter().map(|arg| (*arg).to_owned()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                match outputs.get(&command_line) {
+                    Some(stdout) => Ok(Output {
+                        status: ExitStatus::default(),
+                        stdout: stdout.clone(),
+                        stderr: vec![],
+                    }),
+                    None => Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("no output faked for {command_line:?}"),
+                    )),
+                }
+            },
+        }
+    }
+}
+
+impl Shim for Command {
+    fn is_real(&self) -> bool {
+        matches!(self.0, inner::Inner::Real)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fake() {
+        let command = Command::from_slice(&[("/bin/zsh --version", "zsh 5.9 (arm-apple-darwin22.1.0)\n")]);
+        assert!(!command.is_real());
+
+        let output = command.output("/bin/zsh", &["--version"]).await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"zsh 5.9 (arm-apple-darwin22.1.0)\n");
+
+        let err = command.output("/bin/bash", &["--version"]).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_real() {
+        let command = Command::new();
+        assert!(command.is_real());
+        let output = command.output("echo", &["hello"]).await.unwrap();
+        assert_eq!(output.stdout, b"hello\n");
+    }
+}
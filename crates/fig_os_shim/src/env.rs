@@ -9,6 +9,7 @@ use std::ffi::{
 };
 use std::io;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{
     Arc,
     Mutex,
@@ -98,6 +99,19 @@ impl Env {
         }
     }
 
+    /// Parse the environment variable `key`, ignoring surrounding whitespace
+    ///
+    /// Returns `None` when the variable isn't set or its value doesn't parse as `T`.
+    pub fn get_parsed<T: FromStr>(&self, key: impl AsRef<str>) -> Option<T> {
+        self.get(key).ok()?.trim().parse().ok()
+    }
+
+    /// The value of the first of `keys` that is set and not empty, e.g. `Q_SHELL` then `SHELL`
+    pub fn get_first<K: AsRef<str>>(&self, keys: &[K]) -> Option<String> {
+        keys.iter()
+            .find_map(|key| self.get(key).ok().filter(|value| !value.is_empty()))
+    }
+
     /// Sets the environment variable `key` to the value `value` for the currently running
     /// process.
     ///
@@ -203,6 +217,37 @@ mod tests {
         assert!(env.get("NON_EXISTENT").is_err());
     }
 
+    #[test]
+    fn test_get_parsed() {
+        let env = Env::from_slice(&[("COUNT", "42"), ("PADDED", " 7\n"), ("RATIO", "0.5"), ("WORD", "many")]);
+        assert_eq!(env.get_parsed::<usize>("COUNT"), Some(42));
+        assert_eq!(env.get_parsed::<i32>("PADDED"), Some(7));
+        assert_eq!(env.get_parsed::<f64>("RATIO"), Some(0.5));
+        assert_eq!(env.get_parsed::<usize>("RATIO"), None);
+        assert_eq!(env.get_parsed::<usize>("WORD"), None);
+        assert_eq!(env.get_parsed::<usize>("NON_EXISTENT"), None);
+        assert_eq!(env.get_parsed::<String>("WORD").as_deref(), Some("many"));
+    }
+
+    #[test]
+    fn test_get_first() {
+        let env = Env::from_slice(&[("Q_SHELL", "/bin/zsh"), ("SHELL", "/bin/bash")]);
+        assert_eq!(env.get_first(&["Q_SHELL", "SHELL"]).as_deref(), Some("/bin/zsh"));
+        assert_eq!(env.get_first(&["SHELL", "Q_SHELL"]).as_deref(), Some("/bin/bash"));
+
+        let env = Env::from_slice(&[("Q_SHELL", ""), ("SHELL", "/bin/bash")]);
+        assert_eq!(env.get_first(&["Q_SHELL", "SHELL"]).as_deref(), Some("/bin/bash"));
+
+        let env = Env::from_slice(&[]);
+        assert_eq!(env.get_first(&["Q_SHELL", "SHELL"]), None);
+        assert_eq!(env.get_first::<&str>(&[]), None);
+
+        // Variables set on the fake later are honored too
+        let env = Env::new_fake();
+        unsafe { env.set_var("SHELL", "/bin/fish") };
+        assert_eq!(env.get_first(&["Q_SHELL", "SHELL"]).as_deref(), Some("/bin/fish"));
+    }
+
     #[test]
     fn test_in_envs() {
         let env = Env::from_slice(&[]);
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use std::fs::Permissions;
-use std::io;
+use std::io::{
+    self,
+    Write,
+};
 #[cfg(unix)]
 use std::os::unix::ffi::OsStrExt;
 use std::path::{
@@ -183,6 +186,82 @@ impl Fs {
         }
     }
 
+    /// Writes `contents` to `path` so that readers only ever see the previous or the new contents,
+    /// never a partially written file.
+    ///
+    /// The contents are written to a temporary sibling of `path` with the permissions `mode` (on
+    /// unix), synced to disk, and renamed over `path`. If any step fails, `path` is left as it
+    /// was. If `path` is a symlink, the file it points to is replaced and the symlink is kept.
+    pub async fn write_atomic(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>, mode: u32) -> io::Result<()> {
+        let fs = self.clone();
+        let path = path.as_ref().to_owned();
+        let contents = contents.as_ref().to_owned();
+        tokio::task::spawn_blocking(move || fs.write_atomic_sync(path, contents, mode))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// See [Fs::write_atomic], blocking the current thread.
+    pub fn write_atomic_sync(&self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>, mode: u32) -> io::Result<()> {
+        self.write_atomic_with(path, mode, true, |file| file.write_all(contents.as_ref()))
+    }
+
+    /// Like [Fs::write_atomic], except `path` must not exist yet.
+    ///
+    /// Fails with [io::ErrorKind::AlreadyExists] if something, including a symlink, is at `path`.
+    /// The file only appears once all of `contents` is written, so this can create a lock file
+    /// that other processes never see partially written.
+    pub async fn write_atomic_new(
+        &self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+        mode: u32,
+    ) -> io::Result<()> {
+        let fs = self.clone();
+        let path = path.as_ref().to_owned();
+        let contents = contents.as_ref().to_owned();
+        tokio::task::spawn_blocking(move || fs.write_atomic_new_sync(path, contents, mode))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// See [Fs::write_atomic_new], blocking the current thread.
+    pub fn write_atomic_new_sync(
+        &self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+        mode: u32,
+    ) -> io::Result<()> {
+        self.write_atomic_with(path, mode, false, |file| file.write_all(contents.as_ref()))
+    }
+
+    fn write_atomic_with(
+        &self,
+        path: impl AsRef<Path>,
+        mode: u32,
+        overwrite: bool,
+        write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+    ) -> io::Result<()> {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => write_atomic_real(path.as_ref(), mode, overwrite, write),
+            Inner::Chroot(root) => write_atomic_real(&append(root.path(), path), mode, overwrite, write),
+            Inner::Fake(map) => {
+                // Only a completed write replaces the file
+                let mut contents = Vec::new();
+                write(&mut contents)?;
+                let Ok(mut lock) = map.lock() else {
+                    return Err(io::Error::other("poisoned lock"));
+                };
+                if !overwrite && lock.contains_key(path.as_ref()) {
+                    return Err(io::Error::new(io::ErrorKind::AlreadyExists, "already exists"));
+                }
+                lock.insert(path.as_ref().to_owned(), contents);
+                Ok(())
+            },
+        }
+    }
+
     /// Removes a file from the filesystem.
     ///
     /// Note that there is no guarantee that the file is immediately deleted (e.g.
@@ -199,6 +278,18 @@ impl Fs {
         }
     }
 
+    /// Removes a file from the filesystem, blocking the current thread.
+    ///
+    /// This is a proxy to [`std::fs::remove_file`].
+    pub fn remove_file_sync(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => std::fs::remove_file(path),
+            Inner::Chroot(root) => std::fs::remove_file(append(root.path(), path)),
+            Inner::Fake(_) => panic!("unimplemented"),
+        }
+    }
+
     /// Removes a directory at this path, after removing all its contents. Use carefully!
     ///
     /// This is a proxy to [`tokio::fs::remove_dir_all`].
@@ -510,6 +601,71 @@ impl Shim for Fs {
     }
 }
 
+fn write_atomic_real(
+    path: &Path,
+    mode: u32,
+    overwrite: bool,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    // Without overwriting, a symlink at `path` is something that already exists
+    let path = if overwrite {
+        resolve_symlinks(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    // The temporary file is removed when dropped, so an error at any step cleans it up
+    let mut file = tempfile::Builder::new().prefix(".tmp").tempfile_in(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.as_file().set_permissions(Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    write(&mut file)?;
+    file.as_file().sync_all()?;
+    if overwrite {
+        file.persist(&path).map_err(|err| err.error)?;
+    } else {
+        // Hard links the file into place, which fails if `path` exists
+        file.persist_noclobber(&path).map_err(|err| err.error)?;
+    }
+    Ok(())
+}
+
+/// Follows `path` while it's a symlink, so renaming over the result replaces the linked file
+/// rather than the symlink. The target doesn't need to exist.
+fn resolve_symlinks(path: &Path) -> io::Result<PathBuf> {
+    // The same limit as Linux's, so a symlink loop is an error rather than a hang
+    const MAX_SYMLINKS: usize = 40;
+
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_SYMLINKS {
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = std::fs::read_link(&path)?;
+                // A relative target is relative to the directory containing the symlink
+                path = match path.parent() {
+                    Some(parent) => parent.join(target),
+                    None => target,
+                };
+            },
+            _ => return Ok(path),
+        }
+    }
+
+    Err(io::Error::other(format!(
+        "too many levels of symbolic links: {}",
+        path.display()
+    )))
+}
+
 /// Performs `a.join(b)`, except:
 /// - if `b` is an absolute path, then the resulting path will equal `/a/b`
 /// - if the prefix of `b` contains some `n` copies of a, then the resulting path will equal `/a/b`
@@ -637,6 +793,133 @@ mod tests {
         assert_eq!(fs.read_to_string(dir.path().join("write")).await.unwrap(), "write");
     }
 
+    #[tokio::test]
+    async fn test_write_atomic() {
+        let fake = Fs::from_slice(&[("/settings.json", "{}")]);
+        let chroot = Fs::new_chroot();
+        chroot.write("/settings.json", "{}").await.unwrap();
+
+        for fs in [fake, chroot] {
+            fs.write_atomic("/settings.json", r#"{"a":1}"#, 0o600).await.unwrap();
+            assert_eq!(fs.read_to_string("/settings.json").await.unwrap(), r#"{"a":1}"#);
+
+            fs.write_atomic_sync("/new.json", "[]", 0o600).unwrap();
+            assert_eq!(fs.read_to_string("/new.json").await.unwrap(), "[]");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_write_atomic_keeps_original() {
+        let fake = Fs::from_slice(&[("/settings.json", "{}")]);
+        let chroot = Fs::new_chroot();
+        chroot.write("/settings.json", "{}").await.unwrap();
+
+        for fs in [fake, chroot] {
+            let err = fs
+                .write_atomic_with("/settings.json", 0o600, true, |file| {
+                    file.write_all(br#"{"partially":"#)?;
+                    Err(io::Error::other("interrupted"))
+                })
+                .unwrap_err();
+            assert_eq!(err.to_string(), "interrupted");
+            assert_eq!(fs.read_to_string("/settings.json").await.unwrap(), "{}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_new() {
+        let fake = Fs::new_fake();
+        let chroot = Fs::new_chroot();
+
+        for fs in [fake, chroot] {
+            fs.write_atomic_new("/update.lock", "1", 0o644).await.unwrap();
+            assert_eq!(fs.read_to_string("/update.lock").await.unwrap(), "1");
+
+            let err = fs.write_atomic_new("/update.lock", "2", 0o644).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+            assert_eq!(fs.read_to_string("/update.lock").await.unwrap(), "1");
+
+            fs.write_atomic_with("/other.lock", 0o644, false, |file| {
+                file.write_all(b"12")?;
+                Err(io::Error::other("interrupted"))
+            })
+            .unwrap_err();
+            let err = fs.read_to_string("/other.lock").await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_atomic_new_real_keeps_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("update.lock");
+        std::os::unix::fs::symlink("missing.lock", &link).unwrap();
+        let fs = Fs::new();
+
+        let err = fs.write_atomic_new(&link, "1", 0o644).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(!dir.path().join("missing.lock").exists());
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "temporary file is removed"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_atomic_real() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        let fs = Fs::new();
+        fs.write(&path, "{}").await.unwrap();
+
+        fs.write_atomic_with(&path, 0o600, true, |_| Err(io::Error::other("interrupted")))
+            .unwrap_err();
+        assert_eq!(fs.read_to_string(&path).await.unwrap(), "{}");
+        assert_eq!(
+            std::fs::read_dir(dir.path()).unwrap().count(),
+            1,
+            "temporary file is removed"
+        );
+
+        fs.write_atomic(&path, r#"{"a":1}"#, 0o600).await.unwrap();
+        assert_eq!(fs.read_to_string(&path).await.unwrap(), r#"{"a":1}"#);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_atomic_real_keeps_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("dotfiles")).unwrap();
+        let target = dir.path().join("dotfiles").join("settings.json");
+        let link = dir.path().join("settings.json");
+        let fs = Fs::new();
+        fs.write(&target, "{}").await.unwrap();
+        std::os::unix::fs::symlink("dotfiles/settings.json", &link).unwrap();
+
+        fs.write_atomic(&link, r#"{"a":1}"#, 0o600).await.unwrap();
+        assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs.read_to_string(&target).await.unwrap(), r#"{"a":1}"#);
+        assert_eq!(std::fs::read_dir(dir.path().join("dotfiles")).unwrap().count(), 1);
+
+        // A dangling symlink creates the file it points to
+        std::fs::remove_file(&target).unwrap();
+        fs.write_atomic(&link, "{}", 0o600).await.unwrap();
+        assert!(std::fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs.read_to_string(&target).await.unwrap(), "{}");
+
+        let looped = dir.path().join("loop.json");
+        std::os::unix::fs::symlink("loop.json", &looped).unwrap();
+        fs.write_atomic(&looped, "{}", 0o600).await.unwrap_err();
+    }
+
     #[test]
     fn test_append() {
         macro_rules! assert_append {
@@ -1,15 +1,19 @@
+mod command;
 mod env;
 mod fs;
 mod platform;
 pub mod process_info;
 mod providers;
 mod sysinfo;
+mod time;
 
 use std::sync::Arc;
 
+pub use command::Command;
 pub use env::Env;
 pub use fs::Fs;
 pub use platform::{
+    Arch,
     Os,
     Platform,
 };
@@ -24,6 +28,7 @@ pub use providers::{
     SysInfoProvider,
 };
 pub use sysinfo::SysInfo;
+pub use time::Time;
 
 pub trait Shim {
     /// Returns whether or not the shim is a real implementation.
@@ -40,9 +45,11 @@ pub struct Context {
     #[allow(dead_code)]
     fs: Fs,
     env: Env,
+    command: Command,
     platform: Platform,
     process_info: ProcessInfo,
     sysinfo: SysInfo,
+    time: Time,
 }
 
 impl Context {
@@ -51,9 +58,11 @@ impl Context {
         Arc::new_cyclic(|ctx| Self {
             fs: Default::default(),
             env: Default::default(),
+            command: Default::default(),
             platform: Default::default(),
             process_info: ProcessInfo::new(ctx.clone()),
             sysinfo: SysInfo::default(),
+            time: Time::default(),
         })
     }
 
@@ -61,9 +70,11 @@ impl Context {
         Arc::new(Self {
             fs: Fs::new_fake(),
             env: Env::new_fake(),
+            command: Command::new_fake(),
             platform: Platform::new_fake(Os::current()),
             process_info: ProcessInfo::new_fake(FakePid::default()),
             sysinfo: SysInfo::new_fake(),
+            time: Time::new_fake(),
         })
     }
 
@@ -79,6 +90,10 @@ impl Context {
         &self.env
     }
 
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
     pub fn platform(&self) -> &Platform {
         &self.platform
     }
@@ -90,15 +105,21 @@ impl Context {
     pub fn sysinfo(&self) -> &SysInfo {
         &self.sysinfo
     }
+
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct ContextBuilder {
     fs: Option<Fs>,
     env: Option<Env>,
+    command: Option<Command>,
     platform: Option<Platform>,
     process_info: Option<ProcessInfo>,
     sysinfo: Option<SysInfo>,
+    time: Option<Time>,
 }
 
 impl ContextBuilder {
@@ -110,11 +131,14 @@ impl ContextBuilder {
     pub fn build(self) -> Arc<Context> {
         let fs = self.fs.unwrap_or_default();
         let env = self.env.unwrap_or_default();
+        let command = self.command.unwrap_or_default();
         let platform = self.platform.unwrap_or_default();
         let sysinfo = self.sysinfo.unwrap_or_default();
+        let time = self.time.unwrap_or_default();
         Arc::new_cyclic(|ctx| Context {
             fs,
             env,
+            command,
             platform,
             process_info: if let Some(process_info) = self.process_info {
                 process_info
@@ -122,6 +146,7 @@ impl ContextBuilder {
                 ProcessInfo::new(ctx.clone())
             },
             sysinfo,
+            time,
         })
     }
 
@@ -129,11 +154,14 @@ impl ContextBuilder {
     pub fn build_fake(self) -> Arc<Context> {
         let fs = self.fs.unwrap_or(Fs::new_fake());
         let env = self.env.unwrap_or(Env::new_fake());
+        let command = self.command.unwrap_or(Command::new_fake());
         let platform = self.platform.unwrap_or(Platform::new_fake(Os::Mac));
         let sysinfo = self.sysinfo.unwrap_or(SysInfo::new_fake());
+        let time = self.time.unwrap_or(Time::new_fake());
         Arc::new_cyclic(|ctx| Context {
             fs,
             env,
+            command,
             platform,
             process_info: if let Some(process_info) = self.process_info {
                 process_info
@@ -141,9 +169,47 @@ impl ContextBuilder {
                 ProcessInfo::new(ctx.clone())
             },
             sysinfo,
+            time,
         })
     }
 
+    /// Builds an immutable [Context] where every field is fake, for tests that must not touch the
+    /// real system.
+    ///
+    /// Unless an [Fs] was already set, this calls [ContextBuilder::with_test_home] (replacing any
+    /// [Env]) so the context has a writable chroot with `$HOME` set. Unlike
+    /// [ContextBuilder::build_fake], the default [ProcessInfo] is a fake process rather than
+    /// one backed by the context's filesystem.
+    pub async fn build_fully_fake(self) -> Result<Arc<Context>, std::io::Error> {
+        let builder = if self.fs.is_none() {
+            self.with_test_home().await?
+        } else {
+            self
+        };
+
+        Ok(Arc::new(Context {
+            fs: builder.fs.filter(|fs| !fs.is_real()).unwrap_or(Fs::new_fake()),
+            env: builder.env.filter(|env| !env.is_real()).unwrap_or(Env::new_fake()),
+            command: builder
+                .command
+                .filter(|command| !command.is_real())
+                .unwrap_or(Command::new_fake()),
+            platform: builder
+                .platform
+                .filter(|platform| !platform.is_real())
+                .unwrap_or(Platform::new_fake(Os::current())),
+            process_info: builder
+                .process_info
+                .filter(|process_info| !process_info.is_real())
+                .unwrap_or(ProcessInfo::new_fake(FakePid::default())),
+            sysinfo: builder
+                .sysinfo
+                .filter(|sysinfo| !sysinfo.is_real())
+                .unwrap_or(SysInfo::new_fake()),
+            time: builder.time.filter(|time| !time.is_real()).unwrap_or(Time::new_fake()),
+        }))
+    }
+
     pub fn with_env(mut self, env: Env) -> Self {
         self.env = Some(env);
         self
@@ -154,6 +220,11 @@ impl ContextBuilder {
         self
     }
 
+    pub fn with_command(mut self, command: Command) -> Self {
+        self.command = Some(command);
+        self
+    }
+
     pub fn with_platform(mut self, platform: Platform) -> Self {
         self.platform = Some(platform);
         self
@@ -164,6 +235,11 @@ impl ContextBuilder {
         self
     }
 
+    pub fn with_time(mut self, time: Time) -> Self {
+        self.time = Some(time);
+        self
+    }
+
     /// Creates a chroot filesystem and fake environment so that `$HOME`
     /// points to `<tempdir>/home/testuser`. Note that this replaces the
     /// [Fs] and [Env] currently set with the builder.
@@ -188,7 +264,17 @@ impl ContextBuilder {
     }
 
     pub fn with_os(mut self, os: Os) -> Self {
-        self.platform = Some(Platform::new_fake(os));
+        let platform = Platform::new_fake(os);
+        self.platform = Some(match self.platform {
+            Some(previous) if !previous.is_real() => platform.with_arch(previous.arch()),
+            _ => platform,
+        });
+        self
+    }
+
+    /// Fakes the platform's [Arch], keeping any [Os] set with [ContextBuilder::with_os].
+    pub fn with_arch(mut self, arch: Arch) -> Self {
+        self.platform = Some(self.platform.unwrap_or_default().with_arch(arch));
         self
     }
 
@@ -212,9 +298,11 @@ mod tests {
         let ctx = ContextBuilder::new().build();
         assert!(ctx.fs().is_real());
         assert!(ctx.env().is_real());
+        assert!(ctx.command().is_real());
         assert!(ctx.process_info().is_real());
         assert!(ctx.platform().is_real());
         assert!(ctx.sysinfo().is_real());
+        assert!(ctx.time().is_real());
     }
 
     #[tokio::test]
@@ -229,4 +317,50 @@ mod tests {
         assert_eq!(ctx.env().get("HOME").unwrap(), "/home/testuser");
         assert_eq!(ctx.env().get("hello").unwrap(), "world");
     }
+
+    #[test]
+    fn test_context_builder_with_arch() {
+        let ctx = ContextBuilder::new()
+            .with_os(Os::Linux)
+            .with_arch(Arch::Aarch64)
+            .build();
+        assert!(!ctx.platform().is_real());
+        assert_eq!(ctx.platform().os(), Os::Linux);
+        assert_eq!(ctx.platform().arch(), Arch::Aarch64);
+        assert_eq!(ctx.platform().target_triple(), "aarch64-unknown-linux-gnu");
+
+        let ctx = ContextBuilder::new()
+            .with_arch(Arch::X86_64)
+            .with_os(Os::Windows)
+            .build();
+        assert_eq!(ctx.platform().arch(), Arch::X86_64);
+        assert_eq!(ctx.platform().target_triple(), "x86_64-pc-windows-msvc");
+    }
+
+    #[tokio::test]
+    async fn test_context_builder_build_fully_fake() {
+        let ctx = ContextBuilder::new()
+            .with_arch(Arch::Aarch64)
+            .with_running_processes(&["zsh"])
+            .build_fully_fake()
+            .await
+            .unwrap();
+
+        assert!(!ctx.fs().is_real());
+        assert!(!ctx.env().is_real());
+        assert!(!ctx.command().is_real());
+        assert!(!ctx.platform().is_real());
+        assert!(!ctx.process_info().is_real());
+        assert!(!ctx.sysinfo().is_real());
+        assert!(!ctx.time().is_real());
+
+        let home = ctx.env().home().unwrap();
+        ctx.fs().write(home.join("file"), "contents").await.unwrap();
+        assert_eq!(ctx.fs().read_to_string(home.join("file")).await.unwrap(), "contents");
+        assert_eq!(ctx.platform().arch(), Arch::Aarch64);
+        assert_eq!(ctx.process_info().current_pid().exe(), None);
+        assert!(ctx.sysinfo().is_process_running("zsh"));
+        assert!(!ctx.sysinfo().is_process_running("bash"));
+        assert!(ctx.command().output("/bin/zsh", &["--version"]).await.is_err());
+    }
 }
@@ -1,5 +1,7 @@
+use std::borrow::Cow;
 use std::fmt;
 
+use cfg_if::cfg_if;
 use serde::Serialize;
 
 use crate::Shim;
@@ -61,6 +63,48 @@ impl fmt::Display for Os {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub enum Arch {
+    X86_64,
+    Aarch64,
+    X86,
+}
+
+impl Arch {
+    pub fn current() -> Self {
+        cfg_if! {
+            if #[cfg(target_arch = "x86_64")] {
+                Self::X86_64
+            } else if #[cfg(target_arch = "aarch64")] {
+                Self::Aarch64
+            } else if #[cfg(target_arch = "x86")] {
+                Self::X86
+            } else {
+                compile_error!("unsupported architecture")
+            }
+        }
+    }
+
+    pub fn all() -> &'static [Self] {
+        &[Self::X86_64, Self::Aarch64, Self::X86]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X86_64 => "x86_64",
+            Self::Aarch64 => "aarch64",
+            Self::X86 => "x86",
+        }
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Platform(inner::Inner);
 
@@ -71,7 +115,11 @@ mod inner {
     pub(super) enum Inner {
         #[default]
         Real,
-        Fake(Os),
+        Fake {
+            os: Os,
+            arch: Arch,
+            target_triple: Option<String>,
+        },
     }
 }
 
@@ -81,9 +129,43 @@ impl Platform {
         Self(inner::Inner::Real)
     }
 
-    /// Returns a new fake [Platform].
+    /// Returns a new fake [Platform] for `os` running on the current [Arch].
     pub fn new_fake(os: Os) -> Self {
-        Self(inner::Inner::Fake(os))
+        Self(inner::Inner::Fake {
+            os,
+            arch: Arch::current(),
+            target_triple: None,
+        })
+    }
+
+    /// Sets the [Arch] reported by a fake [Platform], turning a real one into a fake for the
+    /// current [Os].
+    pub fn with_arch(self, arch: Arch) -> Self {
+        let (os, target_triple) = self.into_fake();
+        Self(inner::Inner::Fake {
+            os,
+            arch,
+            target_triple,
+        })
+    }
+
+    /// Overrides the target triple reported by a fake [Platform], turning a real one into a fake
+    /// for the current [Os] and [Arch].
+    pub fn with_target_triple(self, target_triple: impl Into<String>) -> Self {
+        let arch = self.arch();
+        let (os, _) = self.into_fake();
+        Self(inner::Inner::Fake {
+            os,
+            arch,
+            target_triple: Some(target_triple.into()),
+        })
+    }
+
+    fn into_fake(self) -> (Os, Option<String>) {
+        match self.0 {
+            inner::Inner::Real => (Os::current(), None),
+            inner::Inner::Fake { os, target_triple, .. } => (os, target_triple),
+        }
     }
 
     /// Returns the current [Os].
@@ -91,7 +173,61 @@ impl Platform {
         use inner::Inner;
         match &self.0 {
             Inner::Real => Os::current(),
-            Inner::Fake(os) => *os,
+            Inner::Fake { os, .. } => *os,
+        }
+    }
+
+    /// Returns the current [Arch].
+    pub fn arch(&self) -> Arch {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => Arch::current(),
+            Inner::Fake { arch, .. } => *arch,
+        }
+    }
+
+    /// Returns the target triple the binary was built for, in the same format as the update
+    /// manifest (e.g. `x86_64-unknown-linux-gnu` or `universal-apple-darwin`).
+    ///
+    /// Fakes without an explicit target triple derive one from their [Os] and [Arch].
+    pub fn target_triple(&self) -> Cow<'_, str> {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => Cow::Borrowed(current_target_triple()),
+            Inner::Fake {
+                target_triple: Some(target_triple),
+                ..
+            } => Cow::Borrowed(target_triple),
+            Inner::Fake { os, arch, .. } => Cow::Owned(match (os, arch) {
+                (Os::Mac, _) => "universal-apple-darwin".into(),
+                (Os::Linux, arch) => format!("{arch}-unknown-linux-gnu"),
+                (Os::Windows, Arch::X86) => "i686-pc-windows-msvc".into(),
+                (Os::Windows, arch) => format!("{arch}-pc-windows-msvc"),
+            }),
+        }
+    }
+}
+
+const fn current_target_triple() -> &'static str {
+    cfg_if! {
+        if #[cfg(target_os = "macos")] {
+            "universal-apple-darwin"
+        } else if #[cfg(all(target_os = "linux", target_env = "musl", target_arch = "x86_64"))] {
+            "x86_64-unknown-linux-musl"
+        } else if #[cfg(all(target_os = "linux", target_env = "musl", target_arch = "aarch64"))] {
+            "aarch64-unknown-linux-musl"
+        } else if #[cfg(all(target_os = "linux", target_arch = "x86_64"))] {
+            "x86_64-unknown-linux-gnu"
+        } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
+            "aarch64-unknown-linux-gnu"
+        } else if #[cfg(all(target_os = "windows", target_arch = "x86_64"))] {
+            "x86_64-pc-windows-msvc"
+        } else if #[cfg(all(target_os = "windows", target_arch = "x86"))] {
+            "i686-pc-windows-msvc"
+        } else if #[cfg(all(target_os = "windows", target_arch = "aarch64"))] {
+            "aarch64-pc-windows-msvc"
+        } else {
+            compile_error!("unsupported target")
         }
     }
 }
@@ -120,4 +256,37 @@ mod tests {
             println!("{os:?} {os}");
         }
     }
+
+    #[test]
+    fn test_fake_arch() {
+        for arch in Arch::all() {
+            let platform = Platform::new_fake(Os::Linux).with_arch(*arch);
+            assert!(!platform.is_real());
+            assert_eq!(platform.os(), Os::Linux);
+            assert_eq!(&platform.arch(), arch);
+        }
+
+        assert_eq!(
+            Platform::new_fake(Os::Linux).with_arch(Arch::Aarch64).target_triple(),
+            "aarch64-unknown-linux-gnu"
+        );
+        assert_eq!(
+            Platform::new_fake(Os::Linux).with_arch(Arch::X86_64).target_triple(),
+            "x86_64-unknown-linux-gnu"
+        );
+        assert_eq!(
+            Platform::new_fake(Os::Mac).with_arch(Arch::Aarch64).target_triple(),
+            "universal-apple-darwin"
+        );
+
+        let platform = Platform::new_fake(Os::Linux)
+            .with_target_triple("aarch64-unknown-linux-musl")
+            .with_arch(Arch::Aarch64);
+        assert_eq!(platform.target_triple(), "aarch64-unknown-linux-musl");
+
+        let platform = Platform::new().with_arch(Arch::Aarch64);
+        assert!(!platform.is_real());
+        assert_eq!(platform.os(), Os::current());
+        assert_eq!(platform.arch(), Arch::Aarch64);
+    }
 }
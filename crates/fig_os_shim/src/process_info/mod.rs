@@ -90,6 +90,23 @@ impl ProcessInfo {
             Inner::Fake(fake) => fake.clone(),
         }
     }
+
+    /// Returns the currently running process followed by each of its ancestors, parent first.
+    ///
+    /// At most `max_depth` processes are returned, which also guards against a parent chain that
+    /// loops back on itself.
+    pub fn ancestry(&self, max_depth: usize) -> Vec<ProcessSnapshot> {
+        let mut ancestry = Vec::new();
+        let mut pid = Some(Box::new(self.current_pid()));
+        while let Some(curr) = pid {
+            if ancestry.len() >= max_depth {
+                break;
+            }
+            ancestry.push(ProcessSnapshot::new(&curr));
+            pid = curr.parent();
+        }
+        ancestry
+    }
 }
 
 impl Shim for ProcessInfo {
@@ -98,6 +115,31 @@ impl Shim for ProcessInfo {
     }
 }
 
+/// The info about a single process returned by [ProcessInfo::ancestry].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    /// The file name of [ProcessSnapshot::exe].
+    pub name: Option<String>,
+    pub exe: Option<PathBuf>,
+    pub cmdline: Option<String>,
+}
+
+impl ProcessSnapshot {
+    fn new(pid: &Pid) -> Self {
+        let exe = pid.exe();
+        Self {
+            pid: pid.as_u32(),
+            name: exe
+                .as_ref()
+                .and_then(|exe| exe.file_name())
+                .map(|name| name.to_string_lossy().into_owned()),
+            exe,
+            cmdline: pid.cmdline(),
+        }
+    }
+}
+
 /// Test helper type for creating a fake [ProcessInfo] through [ProcessInfo::from_exes].
 #[derive(Debug, Clone, Default)]
 #[allow(dead_code)]
@@ -181,6 +223,30 @@ mod tests {
         assert!(grandparent.parent().is_none());
     }
 
+    #[test]
+    fn test_ancestry() {
+        let info = ProcessInfo::from_exes(vec![
+            (Some("/usr/bin/q"), None),
+            (Some("/bin/zsh"), Some("-zsh")),
+            (Some("/Applications/Ghostty.app/Contents/MacOS/ghostty"), None),
+        ]);
+        let ancestry = info.ancestry(usize::MAX);
+        let names = ancestry.iter().map(|p| p.name.as_deref()).collect::<Vec<_>>();
+        assert_eq!(names, vec![Some("q"), Some("zsh"), Some("ghostty")]);
+        assert_eq!(ancestry[1].exe, Some(PathBuf::from("/bin/zsh")));
+        assert_eq!(ancestry[1].cmdline.as_deref(), Some("-zsh"));
+        assert_eq!(info.ancestry(2), ancestry[..2]);
+        assert!(info.ancestry(0).is_empty());
+
+        let info = ProcessInfo::from_exes(vec![(None, Some("kthreadd"))]);
+        assert_eq!(info.ancestry(usize::MAX), vec![ProcessSnapshot {
+            cmdline: Some("kthreadd".into()),
+            ..Default::default()
+        }]);
+
+        assert!(!Context::new().process_info().ancestry(1).is_empty());
+    }
+
     #[test]
     fn test_from_exe_slice_with_cmdline() {
         let info = ProcessInfo::from_exes(vec![
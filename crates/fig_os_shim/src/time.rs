@@ -0,0 +1,97 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use crate::Shim;
+
+/// Access to the current time
+#[derive(Debug, Clone, Default)]
+pub struct Time(inner::Inner);
+
+mod inner {
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+    use std::time::SystemTime;
+
+    #[derive(Debug, Clone, Default)]
+    pub enum Inner {
+        #[default]
+        Real,
+        Fake(Arc<Mutex<SystemTime>>),
+    }
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A clock frozen at the time it was created, moved with [Time::advance] and [Time::set]
+    pub fn new_fake() -> Self {
+        Self::new_fake_at(SystemTime::now())
+    }
+
+    pub fn new_fake_at(now: SystemTime) -> Self {
+        Self(inner::Inner::Fake(Arc::new(Mutex::new(now))))
+    }
+
+    /// Returns the current time.
+    ///
+    /// This is a proxy to [`SystemTime::now`].
+    pub fn now(&self) -> SystemTime {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => SystemTime::now(),
+            Inner::Fake(now) => *now.lock().unwrap(),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => panic!("unimplemented"),
+            Inner::Fake(now) => *now.lock().unwrap() += by,
+        }
+    }
+
+    pub fn set(&self, to: SystemTime) {
+        use inner::Inner;
+        match &self.0 {
+            Inner::Real => panic!("unimplemented"),
+            Inner::Fake(now) => *now.lock().unwrap() = to,
+        }
+    }
+}
+
+impl Shim for Time {
+    fn is_real(&self) -> bool {
+        matches!(self.0, inner::Inner::Real)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_time() {
+        let time = Time::new_fake_at(SystemTime::UNIX_EPOCH);
+        let clone = time.clone();
+        assert_eq!(time.now(), SystemTime::UNIX_EPOCH);
+
+        clone.advance(Duration::from_secs(60));
+        assert_eq!(time.now(), SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+
+        time.set(SystemTime::UNIX_EPOCH);
+        assert_eq!(clone.now(), SystemTime::UNIX_EPOCH);
+        assert!(!time.is_real());
+        assert!(Time::new().is_real());
+    }
+}
@@ -0,0 +1,111 @@
+//! Negotiation of the [`FigMessageType`] used between two peers
+
+use serde::de::IgnoredAny;
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+};
+
+use crate::FigMessageType;
+
+/// Message types in order of preference, compact binary encodings first and JSON last since it
+/// is only worth its size when debugging
+pub const PREFERENCE: [FigMessageType; 4] = [
+    FigMessageType::Protobuf,
+    FigMessageType::MessagePack,
+    FigMessageType::Cbor,
+    FigMessageType::Json,
+];
+
+/// Sent by each peer when connecting to advertise the message types it can read
+///
+/// The handshake itself should be sent with [`FigMessage::json`](crate::FigMessage::json) since
+/// every peer can read JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Handshake {
+    /// Message types a newer peer supports that this version doesn't know are left out
+    #[serde(deserialize_with = "deserialize_known")]
+    pub supported: Vec<FigMessageType>,
+}
+
+/// Deserializes the message types in a list, skipping any this version doesn't know
+fn deserialize_known<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<FigMessageType>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MaybeKnown {
+        Known(FigMessageType),
+        Unknown(IgnoredAny),
+    }
+
+    Ok(Vec::<MaybeKnown>::deserialize(deserializer)?
+        .into_iter()
+        .filter_map(|message_type| match message_type {
+            MaybeKnown::Known(message_type) => Some(message_type),
+            MaybeKnown::Unknown(_) => None,
+        })
+        .collect())
+}
+
+impl Handshake {
+    pub fn new(supported: impl Into<Vec<FigMessageType>>) -> Self {
+        Self {
+            supported: supported.into(),
+        }
+    }
+
+    /// Picks the message type to use with a peer that sent `other`
+    pub fn negotiate(&self, other: &Handshake) -> Option<FigMessageType> {
+        negotiate(&self.supported, &other.supported)
+    }
+}
+
+/// Picks the most preferred message type supported by both sides, or `None` if they have none
+/// in common
+pub fn negotiate(ours: &[FigMessageType], theirs: &[FigMessageType]) -> Option<FigMessageType> {
+    PREFERENCE
+        .into_iter()
+        .find(|message_type| ours.contains(message_type) && theirs.contains(message_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate_picks_highest_priority_common_type() {
+        let ours = [FigMessageType::Json, FigMessageType::Cbor, FigMessageType::MessagePack];
+        let theirs = [FigMessageType::Protobuf, FigMessageType::Cbor, FigMessageType::Json];
+        assert_eq!(negotiate(&ours, &theirs), Some(FigMessageType::Cbor));
+
+        let ours = Handshake::new([FigMessageType::Json, FigMessageType::Protobuf]);
+        let theirs = Handshake::new([FigMessageType::Protobuf, FigMessageType::Json]);
+        assert_eq!(ours.negotiate(&theirs), Some(FigMessageType::Protobuf));
+
+        // Only JSON in common falls back to JSON, nothing in common picks nothing
+        assert_eq!(
+            negotiate(&[FigMessageType::Json, FigMessageType::Cbor], &[FigMessageType::Json]),
+            Some(FigMessageType::Json)
+        );
+        assert_eq!(negotiate(&[FigMessageType::Protobuf], &[FigMessageType::Json]), None);
+    }
+
+    #[test]
+    fn handshake_round_trips_through_json() {
+        let handshake = Handshake::new([FigMessageType::MessagePack, FigMessageType::Json]);
+        let json = serde_json::to_value(&handshake).unwrap();
+        assert_eq!(json, serde_json::json!({ "supported": ["messagePack", "json"] }));
+        assert_eq!(serde_json::from_value::<Handshake>(json).unwrap(), handshake);
+    }
+
+    #[test]
+    fn handshake_skips_unknown_message_types() {
+        let json = serde_json::json!({ "supported": ["flatbuffers", "cbor", 7, "json"] });
+        let handshake = serde_json::from_value::<Handshake>(json).unwrap();
+        assert_eq!(handshake.supported, vec![FigMessageType::Cbor, FigMessageType::Json]);
+
+        let ours = Handshake::new([FigMessageType::Json, FigMessageType::Cbor]);
+        assert_eq!(ours.negotiate(&handshake), Some(FigMessageType::Cbor));
+    }
+}
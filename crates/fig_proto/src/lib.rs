@@ -3,6 +3,7 @@
 pub mod fig;
 pub mod fig_common;
 pub mod figterm;
+pub mod handshake;
 pub mod hooks;
 pub mod local;
 pub mod mux;
@@ -29,7 +30,10 @@ pub use prost_reflect::{
     DynamicMessage,
     ReflectMessage,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use thiserror::Error;
 
 pub mod remote {
@@ -42,11 +46,13 @@ static DESCRIPTOR_POOL: LazyLock<DescriptorPool> = LazyLock::new(|| {
     DescriptorPool::decode(include_bytes!(concat!(env!("OUT_DIR"), "/file_descriptor_set.bin")).as_ref()).unwrap()
 });
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum FigMessageType {
     Protobuf,
     Json,
     MessagePack,
+    Cbor,
 }
 
 impl FigMessageType {
@@ -55,6 +61,7 @@ impl FigMessageType {
             FigMessageType::Protobuf => b"fig-pbuf",
             FigMessageType::Json => b"fig-json",
             FigMessageType::MessagePack => b"fig-mpak",
+            FigMessageType::Cbor => b"fig-cbor",
         }
     }
 }
@@ -68,8 +75,9 @@ impl FigMessageType {
 ///     - `fig-pbuf` - Protocol Buffer
 ///     - `fig-json` - Json
 ///     - `fig-mpak` - MessagePack
+///     - `fig-cbor` - CBOR
 ///   - The length of the remainder of the message encoded as a big endian u64
-///   - The message, encoded as protobuf, json-protobuf, or messagepack-protobuf
+///   - The message, encoded as protobuf, json-protobuf, messagepack-protobuf, or cbor-protobuf
 #[derive(Debug, Clone)]
 pub struct FigMessage {
     pub inner: Bytes,
@@ -108,6 +116,8 @@ pub enum FigMessageDecodeError {
     JsonDecode(#[from] serde_json::Error),
     #[error(transparent)]
     RmpDecode(#[from] rmp_serde::decode::Error),
+    #[error(transparent)]
+    CborDecode(#[from] ciborium::de::Error<std::io::Error>),
 }
 
 #[derive(Debug, Error)]
@@ -119,6 +129,8 @@ pub enum FigMessageEncodeError {
     #[error(transparent)]
     RmpEncode(#[from] rmp_serde::encode::Error),
     #[error(transparent)]
+    CborEncode(#[from] ciborium::ser::Error<std::io::Error>),
+    #[error(transparent)]
     IoError(#[from] std::io::Error),
 }
 
@@ -131,6 +143,12 @@ impl FigMessage {
         FigMessage::encode(FigMessageType::MessagePack, rmp_serde::to_vec(&message_pack)?.into())
     }
 
+    pub fn cbor(cbor: impl Serialize) -> Result<Bytes, FigMessageEncodeError> {
+        let mut body = Vec::new();
+        ciborium::into_writer(&cbor, &mut body)?;
+        FigMessage::encode(FigMessageType::Cbor, body.into())
+    }
+
     pub fn encode_buf(&self, dst: &mut BytesMut) -> Result<(), FigMessageEncodeError> {
         let body = &self.inner;
         let message_type = self.message_type;
@@ -190,6 +208,7 @@ impl FigMessage {
             b"fig-pbuf" => FigMessageType::Protobuf,
             b"fig-json" => FigMessageType::Json,
             b"fig-mpak" => FigMessageType::MessagePack,
+            b"fig-cbor" => FigMessageType::Cbor,
             _ => return Err(FigMessageParseError::InvalidMessageType(message_type_buf)),
         };
 
@@ -220,6 +239,33 @@ impl FigMessage {
         }))
     }
 
+    /// Parses every complete message at the front of `src`
+    ///
+    /// Parsing stops at the first incomplete message, which is left in `src` so the rest of it can
+    /// be appended before the next call. An invalid message is skipped past the bytes the parser
+    /// read and returned as an error, once the messages before it have been returned. Each attempt
+    /// parses from a clone of `src`, so this is meant for cheaply cloneable buffers like [`Bytes`],
+    /// slices, or cursors.
+    pub fn parse_all<B: Buf + Clone>(src: &mut B) -> Result<Vec<FigMessage>, FigMessageParseError> {
+        let mut messages = Vec::new();
+        while src.has_remaining() {
+            let mut attempt = src.clone();
+            match FigMessage::parse(&mut attempt) {
+                Ok((len, message)) => {
+                    src.advance(len);
+                    messages.push(message);
+                },
+                Err(FigMessageParseError::Incomplete(..)) => break,
+                Err(_) if !messages.is_empty() => break,
+                Err(err) => {
+                    src.advance(src.remaining() - attempt.remaining());
+                    return Err(err);
+                },
+            }
+        }
+        Ok(messages)
+    }
+
     pub fn decode<T>(self) -> Result<T, FigMessageDecodeError>
     where
         T: Message + ReflectMessage + Default,
@@ -236,6 +282,13 @@ impl FigMessage {
                 &mut rmp_serde::Deserializer::from_read_ref(self.inner.as_ref()),
             )?
             .transcode_to()?),
+            // The body holds the serde mapping of the message, which has no byte strings, so it can
+            // be read into a json value and deserialized from there
+            FigMessageType::Cbor => Ok(DynamicMessage::deserialize(
+                T::default().descriptor(),
+                ciborium::from_reader::<serde_json::Value, _>(self.inner.as_ref())?,
+            )?
+            .transcode_to()?),
         }
     }
 }
@@ -363,4 +416,68 @@ mod tests {
 
         assert_eq!(message, decoded_message);
     }
+
+    #[test]
+    fn parse_all_leaves_partial_message() {
+        let message = test_message();
+        let encoded = message.encode_fig_protobuf().unwrap();
+
+        // Every possible truncation of the third message is left in the buffer untouched
+        for partial_len in 0..encoded.len() {
+            let mut buf = BytesMut::new();
+            buf.extend_from_slice(&encoded);
+            buf.extend_from_slice(&encoded);
+            buf.extend_from_slice(&encoded[..partial_len]);
+            let mut src = buf.freeze();
+
+            let messages = FigMessage::parse_all(&mut src).unwrap();
+            assert_eq!(messages.len(), 2);
+            for parsed in messages {
+                assert_eq!(parsed.message_type, FigMessageType::Protobuf);
+                assert_eq!(parsed.decode::<local::LocalMessage>().unwrap(), message);
+            }
+            assert_eq!(src.as_ref(), &encoded[..partial_len]);
+        }
+    }
+
+    #[test]
+    fn parse_all_skips_invalid_message() {
+        let message = test_message();
+        let encoded = message.encode_fig_protobuf().unwrap();
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&encoded);
+        buf.extend_from_slice(b"\x1b@fig-nope");
+        buf.extend_from_slice(&encoded);
+        let mut src = buf.freeze();
+
+        // The valid message comes first, then the invalid header is skipped and reported
+        assert_eq!(FigMessage::parse_all(&mut src).unwrap().len(), 1);
+        assert!(matches!(
+            FigMessage::parse_all(&mut src),
+            Err(FigMessageParseError::InvalidMessageType(_))
+        ));
+        let messages = FigMessage::parse_all(&mut src).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].clone().decode::<local::LocalMessage>().unwrap(), message);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn cbor_round_trip() {
+        let message = test_message();
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&message.transcode_to_dynamic(), &mut cbor).unwrap();
+
+        let msg = FigMessage {
+            inner: Bytes::from(cbor),
+            message_type: FigMessageType::Cbor,
+        };
+
+        assert_eq!(&msg.to_encoded().unwrap()[..10], b"\x1b@fig-cbor");
+
+        let decoded_message: local::LocalMessage = msg.decode().unwrap();
+
+        assert_eq!(message, decoded_message);
+    }
 }
@@ -30,6 +30,8 @@ pub enum Error {
     DbOpenError(#[from] DbOpenError),
     #[error("{}", .0)]
     PoisonError(String),
+    #[error("value of {0:?} is not an integer")]
+    NotAnInteger(String),
 }
 
 impl<T> From<PoisonError<T>> for Error {
@@ -56,6 +58,7 @@ mod tests {
             // r2d2::Error
             DbOpenError("oops".into()).into(),
             PoisonError::<()>::new(()).into(),
+            Error::NotAnInteger("key".into()),
         ]
     }
 
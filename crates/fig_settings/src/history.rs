@@ -48,13 +48,13 @@ pub struct CommandInfo {
     pub exit_code: Option<i32>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct History(inner::Inner);
 
 mod inner {
     use crate::sqlite::Db;
 
-    #[derive(Debug, Default)]
+    #[derive(Debug, Default, Clone)]
     pub enum Inner {
         #[default]
         Global,
@@ -188,6 +188,24 @@ impl History {
         Ok(rows_mapped)
     }
 
+    /// Deletes the oldest commands so that at most `max_entries` remain, returning the number of
+    /// commands deleted
+    ///
+    /// Nothing is deleted until there are more than `max_entries` commands, and then only the
+    /// excess is.
+    pub fn evict_oldest(&self, max_entries: usize) -> Result<usize> {
+        let conn = self.conn()?;
+        let count: usize = conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))?;
+        if count <= max_entries {
+            return Ok(0);
+        }
+
+        Ok(conn.execute(
+            "DELETE FROM history WHERE id IN (SELECT id FROM history ORDER BY id ASC LIMIT ?)",
+            params![count - max_entries],
+        )?)
+    }
+
     /// The Where expression is not escaped, so be careful!
     ///
     /// Ugh i should like use sqlx or something
@@ -595,4 +613,30 @@ mod tests {
             .unwrap()
         );
     }
+
+    #[test]
+    fn evict_oldest() {
+        let history = History::mock();
+        for i in 0..5 {
+            history
+                .insert_command_history(
+                    &CommandInfo {
+                        command: Some(format!("echo {i}")),
+                        ..Default::default()
+                    },
+                    false,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(history.evict_oldest(3).unwrap(), 2);
+        assert_eq!(history.evict_oldest(3).unwrap(), 0);
+        let commands = history
+            .rows(None, vec![OrderBy::new(HistoryColumn::Id, Order::Asc)], 10, 0)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.command.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(commands, vec!["echo 2", "echo 3", "echo 4"]);
+    }
 }
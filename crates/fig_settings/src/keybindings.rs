@@ -29,7 +29,7 @@ pub struct KeyBindingDescription {
     pub default_bindings: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct KeyBinding {
     pub identifier: String,
@@ -40,6 +40,13 @@ pub struct KeyBinding {
 #[serde(transparent)]
 pub struct KeyBindings(pub Vec<KeyBinding>);
 
+/// A chord that is bound to more than one action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindingConflict {
+    pub binding: String,
+    pub identifiers: Vec<String>,
+}
+
 impl KeyBindings {
     pub fn load_hardcoded() -> Self {
         let hardcoded_descriptions: Vec<KeyBindingDescription> =
@@ -84,8 +91,93 @@ impl KeyBindings {
 
     pub fn load_from_settings(product_namespace: impl Display) -> Result<Self, Error> {
         let settings = OldSettings::load()?;
-        let map = settings.map();
-        Ok(Self::load_from_json_map(&map, product_namespace))
+        Ok(Self::load_from_store(&settings, product_namespace))
+    }
+
+    pub fn load_from_store(store: &impl JsonStore, product_namespace: impl Display) -> Self {
+        Self::load_from_json_map(&store.map(), product_namespace)
+    }
+
+    /// Replaces the bindings of `product_namespace` in the settings with these ones and saves them
+    pub fn save_to_settings(&self, product_namespace: impl Display) -> Result<(), Error> {
+        let mut settings = OldSettings::load()?;
+        self.write_to_store(&mut settings, product_namespace);
+        settings.save_to_file()
+    }
+
+    /// Replaces the bindings of `product_namespace` in the store with these ones
+    pub fn write_to_store(&self, store: &mut impl JsonStore, product_namespace: impl Display) {
+        let prefix = format!("{product_namespace}.keybindings.");
+        let mut map = store.map_mut();
+        map.retain(|key, _| !key.starts_with(&prefix));
+        for key_binding in &self.0 {
+            map.insert(
+                format!("{prefix}{}", key_binding.binding),
+                key_binding.identifier.clone().into(),
+            );
+        }
+    }
+
+    /// The chords bound to an action
+    pub fn get(&self, identifier: &str) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|key_binding| key_binding.identifier == identifier)
+            .map(|key_binding| key_binding.binding.as_str())
+            .collect()
+    }
+
+    /// Binds a chord to an action, returning the binding it replaced if the chord was bound to a
+    /// different action
+    pub fn set(&mut self, identifier: impl Into<String>, binding: impl Into<String>) -> Option<KeyBinding> {
+        let identifier = identifier.into();
+        let binding = binding.into();
+
+        let mut replaced = None;
+        self.0.retain(|key_binding| {
+            if key_binding.binding != binding {
+                return true;
+            }
+            if key_binding.identifier != identifier {
+                replaced = Some(key_binding.clone());
+            }
+            false
+        });
+        self.0.push(KeyBinding { identifier, binding });
+
+        replaced
+    }
+
+    /// Unbinds every chord of an action, returning the removed bindings
+    pub fn remove(&mut self, identifier: &str) -> Vec<KeyBinding> {
+        let (removed, kept) = std::mem::take(&mut self.0)
+            .into_iter()
+            .partition(|key_binding| key_binding.identifier == identifier);
+        self.0 = kept;
+        removed
+    }
+
+    /// Chords bound to more than one action, in the order they first appear
+    pub fn conflicts(&self) -> Vec<KeyBindingConflict> {
+        let mut conflicts: Vec<KeyBindingConflict> = vec![];
+        for key_binding in &self.0 {
+            match conflicts
+                .iter_mut()
+                .find(|conflict| conflict.binding == key_binding.binding)
+            {
+                Some(conflict) => {
+                    if !conflict.identifiers.contains(&key_binding.identifier) {
+                        conflict.identifiers.push(key_binding.identifier.clone());
+                    }
+                },
+                None => conflicts.push(KeyBindingConflict {
+                    binding: key_binding.binding.clone(),
+                    identifiers: vec![key_binding.identifier.clone()],
+                }),
+            }
+        }
+        conflicts.retain(|conflict| conflict.identifiers.len() > 1);
+        conflicts
     }
 }
 
@@ -101,6 +193,17 @@ impl IntoIterator for KeyBindings {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        Backend,
+        Map,
+    };
+
+    fn key_binding(identifier: &str, binding: &str) -> KeyBinding {
+        KeyBinding {
+            identifier: identifier.into(),
+            binding: binding.into(),
+        }
+    }
 
     #[test]
     fn test_load_json() {
@@ -141,4 +244,64 @@ mod test {
         assert_eq!(json.0[3].identifier, "decreaseSize");
         assert_eq!(json.0[3].binding, "control+=");
     }
+
+    #[test]
+    fn test_set_and_persist() {
+        let mut store = OldSettings::new_from_backend(Backend::Memory(Map::new()));
+        store.set("chat.other", "kept");
+
+        let mut key_bindings = KeyBindings(vec![]);
+        assert_eq!(key_bindings.set("skimCommand", "control+s"), None);
+        assert_eq!(key_bindings.get("skimCommand"), vec!["control+s"]);
+        key_bindings.write_to_store(&mut store, "chat");
+
+        assert_eq!(
+            store.get_string("chat.keybindings.control+s").as_deref(),
+            Some("skimCommand")
+        );
+        assert_eq!(store.get_string("chat.other").as_deref(), Some("kept"));
+
+        let loaded = KeyBindings::load_from_store(&store, "chat");
+        assert_eq!(loaded.get("skimCommand"), vec!["control+s"]);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut key_bindings = KeyBindings(vec![
+            key_binding("insertSelected", "enter"),
+            key_binding("execute", "enter"),
+            key_binding("hide", "esc"),
+        ]);
+        assert_eq!(key_bindings.conflicts(), vec![KeyBindingConflict {
+            binding: "enter".into(),
+            identifiers: vec!["insertSelected".into(), "execute".into()],
+        }]);
+
+        // Binding a chord that belongs to another action replaces it
+        assert_eq!(
+            key_bindings.set("toggleDescription", "esc"),
+            Some(key_binding("hide", "esc"))
+        );
+        assert!(key_bindings.get("hide").is_empty());
+        assert_eq!(key_bindings.get("toggleDescription"), vec!["esc"]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut store = OldSettings::new_from_backend(Backend::Memory(Map::new()));
+        let mut key_bindings = KeyBindings(vec![
+            key_binding("toggleDescription", "command+i"),
+            key_binding("toggleDescription", "control+/"),
+            key_binding("hide", "esc"),
+        ]);
+        key_bindings.write_to_store(&mut store, "autocomplete");
+
+        assert_eq!(key_bindings.remove("toggleDescription").len(), 2);
+        assert!(key_bindings.get("toggleDescription").is_empty());
+        assert!(key_bindings.remove("toggleDescription").is_empty());
+        key_bindings.write_to_store(&mut store, "autocomplete");
+
+        assert!(store.get("autocomplete.keybindings.command+i").is_none());
+        assert_eq!(KeyBindings::load_from_store(&store, "autocomplete").0.len(), 1);
+    }
 }
@@ -12,17 +12,19 @@ use std::fs::{
 };
 use std::io::{
     Read,
-    Seek,
-    SeekFrom,
     Write,
 };
-use std::path::PathBuf;
+use std::path::{
+    Path,
+    PathBuf,
+};
 
 pub use error::{
     Error,
     Result,
 };
 use fd_lock::RwLock as FileRwLock;
+use fig_os_shim::Fs;
 use fig_util::directories;
 use parking_lot::{
     MappedRwLockReadGuard,
@@ -45,6 +47,12 @@ use tracing::error;
 
 pub type Map = serde_json::Map<String, Value>;
 
+/// Key holding the number of [`JsonStore::migrations`] already applied to a store
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// A step in upgrading the data of a [`JsonStore`] to a newer schema
+pub type JsonMigration = fn(&mut Map);
+
 static SETTINGS_FILE_LOCK: RwLock<()> = RwLock::new(());
 
 static SETTINGS_DATA: RwLock<Option<Map>> = RwLock::new(None);
@@ -201,6 +209,12 @@ pub trait JsonStore: Sized {
 
     fn map_mut(&mut self) -> WriteGuard<'_, Map>;
 
+    /// Migrations in the order they must be applied, a store at schema version `n` has had the
+    /// first `n` applied
+    fn migrations() -> &'static [JsonMigration] {
+        &[]
+    }
+
     fn load() -> Result<Self> {
         let is_global = Self::data_lock().read().as_ref().is_some();
         if is_global {
@@ -282,28 +296,41 @@ pub trait JsonStore: Sized {
         }
 
         let _lock_guard = Self::file_lock().write();
+        write_map_to_file(&path, &self.map())
+    }
 
-        let mut file_opts = File::options();
-        file_opts.create(true).write(true).truncate(true);
+    /// The number of migrations already applied to the data
+    fn schema_version(&self) -> u64 {
+        self.get(SCHEMA_VERSION_KEY)
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0)
+    }
 
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::OpenOptionsExt;
-            file_opts.mode(0o600);
-        }
+    /// Applies any pending [`migrations`](JsonStore::migrations) under the file lock, bumps the
+    /// schema version, and saves the result. Returns the number of migrations applied.
+    fn migrate(&mut self) -> Result<usize> {
+        let migrations = Self::migrations();
+        let version = usize::try_from(self.schema_version()).unwrap_or(usize::MAX);
+        let Some(pending) = migrations.get(version..).filter(|pending| !pending.is_empty()) else {
+            return Ok(0);
+        };
 
-        let mut file = FileRwLock::new(file_opts.open(&path)?);
-        let mut lock = file.write()?;
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?;
+            }
+        }
 
-        if let Err(_err) = serde_json::to_writer_pretty(&mut *lock, &*self.map()) {
-            // Write {} to the file if the serialization failed
-            lock.seek(SeekFrom::Start(0))?;
-            lock.set_len(0)?;
-            lock.write_all(b"{}")?;
-        };
-        lock.flush()?;
+        let _lock_guard = Self::file_lock().write();
+        let mut map = self.map_mut();
+        for migration in pending {
+            migration(&mut map);
+        }
+        map.insert(SCHEMA_VERSION_KEY.into(), migrations.len().into());
+        write_map_to_file(&path, &map)?;
 
-        Ok(())
+        Ok(pending.len())
     }
 
     fn set(&mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) {
@@ -347,6 +374,14 @@ pub trait JsonStore: Sized {
     }
 }
 
+/// Writes the map to the file at `path`, the caller must hold the store's file lock
+fn write_map_to_file(path: &Path, map: &Map) -> Result<()> {
+    // Write {} to the file if the serialization failed
+    let contents = serde_json::to_vec_pretty(map).unwrap_or_else(|_| b"{}".to_vec());
+    Fs::new().write_atomic_sync(path, contents, 0o600)?;
+    Ok(())
+}
+
 pub struct OldSettings {
     pub(crate) inner: Backend,
 }
@@ -439,3 +474,73 @@ impl JsonStore for OldSettings {
 //         test_store_type(&path, JsonType::State);
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use super::*;
+
+    static DIR: LazyLock<tempfile::TempDir> = LazyLock::new(|| tempfile::tempdir().unwrap());
+    static FILE_LOCK: RwLock<()> = RwLock::new(());
+    static DATA: RwLock<Option<Map>> = RwLock::new(None);
+
+    struct MigratingStore(Map);
+
+    impl JsonStore for MigratingStore {
+        fn path() -> Result<PathBuf> {
+            Ok(DIR.path().join("store.json"))
+        }
+
+        fn file_lock() -> &'static RwLock<()> {
+            &FILE_LOCK
+        }
+
+        fn data_lock() -> &'static RwLock<Option<Map>> {
+            &DATA
+        }
+
+        fn new_from_backend(backend: Backend) -> Self {
+            match backend {
+                Backend::Global => unreachable!("the migration tests only use an in-memory store"),
+                Backend::Memory(map) => Self(map),
+            }
+        }
+
+        fn map(&self) -> ReadGuard<'_, Map> {
+            ReadGuard::Memory(&self.0)
+        }
+
+        fn map_mut(&mut self) -> WriteGuard<'_, Map> {
+            WriteGuard::Memory(&mut self.0)
+        }
+
+        fn migrations() -> &'static [JsonMigration] {
+            &[rename_old_name]
+        }
+    }
+
+    fn rename_old_name(map: &mut Map) {
+        if let Some(value) = map.remove("oldName") {
+            map.insert("newName".into(), value);
+        }
+    }
+
+    #[test]
+    fn test_migrate_rename() {
+        let mut store = MigratingStore::new_from_backend(Backend::Memory(Map::new()));
+        store.set("oldName", "value");
+        assert_eq!(store.schema_version(), 0);
+
+        assert_eq!(store.migrate().unwrap(), 1);
+        assert_eq!(store.schema_version(), 1);
+        assert!(store.get("oldName").is_none());
+        assert_eq!(store.get_string("newName").as_deref(), Some("value"));
+
+        // The migrated data was saved, and running again is a no-op
+        let mut loaded = MigratingStore::load().unwrap();
+        assert_eq!(loaded.schema_version(), 1);
+        assert_eq!(loaded.get_string("newName").as_deref(), Some("value"));
+        assert_eq!(loaded.migrate().unwrap(), 0);
+    }
+}
@@ -13,6 +13,7 @@ use rusqlite::{
     Connection,
     Error,
     ToSql,
+    TransactionBehavior,
     params,
 };
 use serde_json::Map;
@@ -237,10 +238,11 @@ impl Db {
     fn atomic_op<T: FromSql + ToSql>(
         &self,
         key: impl AsRef<str>,
-        op: impl FnOnce(&Option<T>) -> Option<T>,
+        op: impl FnOnce(&Option<T>) -> Result<Option<T>>,
     ) -> Result<Option<T>> {
         let mut conn = self.pool.get()?;
-        let tx = conn.transaction()?;
+        // Take the write lock up front so concurrent read-modify-writes are serialized
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
 
         let value = tx.query_row::<Option<T>, _, _>(
             &format!("SELECT value FROM {STATE_TABLE_NAME} WHERE key = ?1"),
@@ -254,7 +256,7 @@ impl Db {
             Err(err) => return Err(err.into()),
         };
 
-        let value_1 = op(&value_0);
+        let value_1 = op(&value_0)?;
 
         if let Some(value) = value_1 {
             tx.execute(
@@ -276,13 +278,32 @@ impl Db {
     /// Atomically get the value of a key, then perform an or operation on it
     /// and set the new value. If the key does not exist, set it to the or value.
     pub fn atomic_bool_or(&self, key: impl AsRef<str>, or: bool) -> Result<bool> {
-        self.atomic_op::<serde_json::Value>(key, |val| match val {
-            // Some(val) => Some(serde_json::Value::Bool( || or)),
-            Some(serde_json::Value::Bool(b)) => Some(serde_json::Value::Bool(*b || or)),
-            Some(_) | None => Some(serde_json::Value::Bool(or)),
+        self.atomic_op::<serde_json::Value>(key, |val| {
+            Ok(match val {
+                // Some(val) => Some(serde_json::Value::Bool( || or)),
+                Some(serde_json::Value::Bool(b)) => Some(serde_json::Value::Bool(*b || or)),
+                Some(_) | None => Some(serde_json::Value::Bool(or)),
+            })
         })
         .map(|val| val.and_then(|val| val.as_bool()).unwrap_or(false))
     }
+
+    /// Atomically add `delta` to the integer value of a key and return the new value. If the key
+    /// does not exist it is treated as 0, if it holds anything other than an integer an error is
+    /// returned and the value is left untouched.
+    pub fn atomic_incr(&self, key: impl AsRef<str>, delta: i64) -> Result<i64> {
+        let key = key.as_ref();
+        let mut new_value = 0;
+        self.atomic_op::<serde_json::Value>(key, |val| {
+            let current = match val {
+                Some(val) => val.as_i64().ok_or_else(|| crate::Error::NotAnInteger(key.to_owned()))?,
+                None => 0,
+            };
+            new_value = current.saturating_add(delta);
+            Ok(Some(new_value.into()))
+        })?;
+        Ok(new_value)
+    }
 }
 
 fn max_migration_version<C: Deref<Target = Connection>>(conn: &C) -> Option<i64> {
@@ -466,4 +487,43 @@ mod tests {
             db.unset_state_value(key).unwrap();
         }
     }
+
+    #[test]
+    fn test_atomic_incr() {
+        let db = mock();
+
+        assert_eq!(db.atomic_incr("count", 1).unwrap(), 1);
+        assert_eq!(db.atomic_incr("count", 5).unwrap(), 6);
+        assert_eq!(db.atomic_incr("count", -2).unwrap(), 4);
+        assert_eq!(db.get_state_value("count").unwrap().unwrap(), 4);
+
+        // Non-integer values are an error and are not overwritten
+        db.set_state_value("string", "hello").unwrap();
+        assert!(matches!(
+            db.atomic_incr("string", 1),
+            Err(crate::Error::NotAnInteger(key)) if key == "string"
+        ));
+        assert_eq!(db.get_state_value("string").unwrap().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_atomic_incr_concurrent() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let db = Db::open(&tempdir.path().join("data.sqlite3")).unwrap();
+        db.migrate().unwrap();
+
+        let tasks = 8;
+        let increments = 25;
+        std::thread::scope(|scope| {
+            for _ in 0..tasks {
+                scope.spawn(|| {
+                    for _ in 0..increments {
+                        db.atomic_incr("count", 1).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(db.get_state_value("count").unwrap().unwrap(), tasks * increments);
+    }
 }
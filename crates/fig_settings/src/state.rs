@@ -1,4 +1,14 @@
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
 use serde::de::DeserializeOwned;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use serde_json::{
     Map,
     Value,
@@ -13,6 +23,36 @@ use crate::sqlite::{
 #[derive(Debug, Clone, Default)]
 pub struct State(inner::Inner);
 
+/// Wrapper stored in place of a value that was set with a TTL, values without it never expire
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpiringValue {
+    #[serde(rename = "__value")]
+    value: Value,
+    /// Expiry time in milliseconds since the unix epoch
+    #[serde(rename = "__expiresAt")]
+    expires_at: u64,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Unwraps a stored value, returning `None` if it has expired
+fn live_value(value: Value) -> Option<Value> {
+    if !value.is_object() {
+        return Some(value);
+    }
+    match serde_json::from_value::<ExpiringValue>(value.clone()) {
+        Ok(expiring) if expiring.expires_at <= now_millis() => None,
+        Ok(expiring) => Some(expiring.value),
+        Err(_) => Some(value),
+    }
+}
+
 mod inner {
     #[derive(Debug, Clone, Default)]
     pub enum Inner {
@@ -49,7 +89,12 @@ impl State {
     }
 
     pub fn all(&self) -> Result<Map<String, Value>> {
-        self.database()?.all_state_values()
+        Ok(self
+            .database()?
+            .all_state_values()?
+            .into_iter()
+            .filter_map(|(key, value)| live_value(value).map(|value| (key, value)))
+            .collect())
     }
 
     pub fn set_value(&self, key: impl AsRef<str>, value: impl Into<Value>) -> Result<()> {
@@ -57,25 +102,45 @@ impl State {
         Ok(())
     }
 
+    /// Sets a value that [`get`](Self::get) and friends treat as absent once `ttl` has elapsed
+    pub fn set_with_ttl(&self, key: impl AsRef<str>, value: impl Into<Value>, ttl: Duration) -> Result<()> {
+        let expiring = ExpiringValue {
+            value: value.into(),
+            expires_at: now_millis().saturating_add(ttl.as_millis() as u64),
+        };
+        self.set_value(key, serde_json::to_value(expiring)?)
+    }
+
     pub fn remove_value(&self, key: impl AsRef<str>) -> Result<()> {
         self.database()?.unset_state_value(key)?;
         Ok(())
     }
 
     pub fn get_value(&self, key: impl AsRef<str>) -> Result<Option<Value>> {
-        self.database()?.get_state_value(key)
+        let key = key.as_ref();
+        let db = self.database()?;
+        match db.get_state_value(key)? {
+            Some(value) => match live_value(value) {
+                Some(value) => Ok(Some(value)),
+                None => {
+                    // Lazily clean up the expired entry
+                    db.unset_state_value(key)?;
+                    Ok(None)
+                },
+            },
+            None => Ok(None),
+        }
     }
 
     pub fn get<T: DeserializeOwned>(&self, key: impl AsRef<str>) -> Result<Option<T>> {
         Ok(self
-            .database()?
-            .get_state_value(key)?
+            .get_value(key)?
             .map(|value| serde_json::from_value(value.clone()))
             .transpose()?)
     }
 
     pub fn get_bool(&self, key: impl AsRef<str>) -> Result<Option<bool>> {
-        Ok(self.database()?.get_state_value(key)?.and_then(|value| value.as_bool()))
+        Ok(self.get_value(key)?.and_then(|value| value.as_bool()))
     }
 
     pub fn get_bool_or(&self, key: impl AsRef<str>, default: bool) -> bool {
@@ -83,7 +148,7 @@ impl State {
     }
 
     pub fn get_string(&self, key: impl AsRef<str>) -> Result<Option<String>> {
-        Ok(self.database()?.get_state_value(key)?.and_then(|value| match value {
+        Ok(self.get_value(key)?.and_then(|value| match value {
             Value::String(s) => Some(s),
             _ => None,
         }))
@@ -94,7 +159,7 @@ impl State {
     }
 
     pub fn get_int(&self, key: impl AsRef<str>) -> Result<Option<i64>> {
-        Ok(self.database()?.get_state_value(key)?.and_then(|value| value.as_i64()))
+        Ok(self.get_value(key)?.and_then(|value| value.as_i64()))
     }
 
     pub fn get_int_or(&self, key: impl AsRef<str>, default: i64) -> i64 {
@@ -106,6 +171,10 @@ impl State {
     pub fn atomic_bool_or(&self, key: impl AsRef<str>, or: bool) -> Result<bool> {
         self.database()?.atomic_bool_or(key, or)
     }
+
+    pub fn atomic_incr(&self, key: impl AsRef<str>, delta: i64) -> Result<i64> {
+        self.database()?.atomic_incr(key, delta)
+    }
 }
 
 pub trait StateProvider {
@@ -126,6 +195,10 @@ pub fn set_value(key: impl AsRef<str>, value: impl Into<Value>) -> Result<()> {
     State::new().set_value(key, value)
 }
 
+pub fn set_with_ttl(key: impl AsRef<str>, value: impl Into<Value>, ttl: Duration) -> Result<()> {
+    State::new().set_with_ttl(key, value, ttl)
+}
+
 pub fn remove_value(key: impl AsRef<str>) -> Result<()> {
     State::new().remove_value(key)
 }
@@ -164,6 +237,10 @@ pub fn get_int_or(key: impl AsRef<str>, default: i64) -> i64 {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use serde_json::Value;
+
     use super::{
         Result,
         State,
@@ -197,4 +274,29 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ttl() -> Result<()> {
+        let state = State::new_fake();
+
+        state.set_with_ttl("ttl", "hello", Duration::from_millis(50))?;
+        state.set_value("plain", "world")?;
+        assert_eq!(state.get_string("ttl")?.as_deref(), Some("hello"));
+        assert_eq!(state.all()?.get("ttl"), Some(&Value::from("hello")));
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        // The expired entry is absent and removed, plain values are untouched
+        assert!(state.all()?.get("ttl").is_none());
+        assert!(state.get_value("ttl")?.is_none());
+        assert!(!state.database()?.is_state_value_set("ttl")?);
+        assert_eq!(state.get_string("plain")?.as_deref(), Some("world"));
+
+        // Objects that only look similar to the wrapper are returned as is
+        let object = serde_json::json!({ "__value": 1, "other": 2 });
+        state.set_value("object", object.clone())?;
+        assert_eq!(state.get_value("object")?, Some(object));
+
+        Ok(())
+    }
 }
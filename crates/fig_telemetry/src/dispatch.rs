@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::sync::{
     Mutex,
     OnceLock,
@@ -10,7 +11,10 @@ use fig_proto::figterm::{
     TelemetryRequest,
 };
 use fig_util::env_var::QTERM_SESSION_ID;
-use tracing::error;
+use tracing::{
+    debug,
+    error,
+};
 
 use crate::event::AppTelemetryEvent;
 
@@ -18,6 +22,8 @@ use crate::event::AppTelemetryEvent;
 pub enum DispatchMode {
     Off,
     On,
+    /// Dispatch only if the figterm socket for the current session exists, checked once per process
+    Auto,
 }
 
 static DISPATCH_MODE: Mutex<DispatchMode> = Mutex::new(DispatchMode::Off);
@@ -35,6 +41,29 @@ fn q_term_session_id() -> &'static Option<String> {
     SESSION_ID.get_or_init(|| std::env::var(QTERM_SESSION_ID).ok())
 }
 
+/// The dispatch mode to use for an event, resolving [`DispatchMode::Auto`] to `On` or `Off`
+fn effective_dispatch_mode() -> DispatchMode {
+    static AUTO_MODE: OnceLock<DispatchMode> = OnceLock::new();
+    match dispatch_mode() {
+        DispatchMode::Auto => *AUTO_MODE.get_or_init(|| {
+            let socket_path = q_term_session_id()
+                .as_ref()
+                .and_then(|session_id| fig_util::directories::figterm_socket_path(session_id).ok());
+            let mode = resolve_auto_mode(socket_path.as_deref());
+            debug!(?socket_path, ?mode, "Resolved auto dispatch mode");
+            mode
+        }),
+        mode => mode,
+    }
+}
+
+fn resolve_auto_mode(socket_path: Option<&Path>) -> DispatchMode {
+    match socket_path {
+        Some(socket_path) if socket_path.exists() => DispatchMode::On,
+        _ => DispatchMode::Off,
+    }
+}
+
 pub(crate) enum DispatchStatus {
     Failed,
     Succeeded,
@@ -48,7 +77,7 @@ impl DispatchStatus {
 }
 
 pub(crate) async fn dispatch(event: &AppTelemetryEvent) -> DispatchStatus {
-    if dispatch_mode() == DispatchMode::Off {
+    if effective_dispatch_mode() == DispatchMode::Off {
         return DispatchStatus::NotEnabled;
     };
 
@@ -107,6 +136,17 @@ mod tests {
         assert_eq!(dispatch_mode(), DispatchMode::Off);
     }
 
+    #[test]
+    fn test_resolve_auto_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("figterm.sock");
+        assert_eq!(resolve_auto_mode(None), DispatchMode::Off);
+        assert_eq!(resolve_auto_mode(Some(&socket_path)), DispatchMode::Off);
+
+        std::fs::write(&socket_path, "").unwrap();
+        assert_eq!(resolve_auto_mode(Some(&socket_path)), DispatchMode::On);
+    }
+
     #[test]
     fn test_q_term_session_id() {
         q_term_session_id();
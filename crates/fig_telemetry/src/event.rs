@@ -130,6 +130,8 @@ pub(crate) mod tests {
             terminal_version: Some("1.0".into()),
             shell: Some("bash".into()),
             shell_version: Some("4.4".into()),
+            exit_code: Some(1),
+            duration_ms: Some(250),
         })
         .await
     }
@@ -241,4 +243,18 @@ pub(crate) mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_cli_subcommand_executed_metric_datum() {
+        let metric_datum = cli_subcommand_executed().await.into_metric_datum().unwrap();
+        let metadata = metric_datum.metadata();
+        let value_of = |key: &str| {
+            metadata
+                .iter()
+                .find(|entry| entry.key() == Some(key))
+                .and_then(|entry| entry.value())
+        };
+        assert_eq!(value_of("codewhispererterminal_exitCode"), Some("1"));
+        assert_eq!(value_of("codewhispererterminal_duration"), Some("250"));
+    }
 }
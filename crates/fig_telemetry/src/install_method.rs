@@ -1,12 +1,17 @@
 use std::process::Command;
-use std::sync::LazyLock;
+use std::str::FromStr;
+use std::sync::RwLock;
 
+use fig_util::env_var::Q_INSTALL_METHOD;
 use serde::{
     Deserialize,
     Serialize,
 };
 
-static INSTALL_METHOD: LazyLock<InstallMethod> = LazyLock::new(|| {
+/// The detected install method, [None] until first detected
+static INSTALL_METHOD: RwLock<Option<InstallMethod>> = RwLock::new(None);
+
+fn detect_install_method() -> InstallMethod {
     if let Ok(output) = Command::new("brew").args(["list", "amazon-q", "-1"]).output() {
         if output.status.success() {
             return InstallMethod::Brew;
@@ -20,7 +25,7 @@ static INSTALL_METHOD: LazyLock<InstallMethod> = LazyLock::new(|| {
     }
 
     InstallMethod::Unknown
-});
+}
 
 /// The method of installation that Fig was installed with
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,6 +45,78 @@ impl std::fmt::Display for InstallMethod {
     }
 }
 
+impl FromStr for InstallMethod {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "brew" => Ok(InstallMethod::Brew),
+            "toolbox" => Ok(InstallMethod::Toolbox),
+            "unknown" => Ok(InstallMethod::Unknown),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Resolves the install method with the precedence override > detection > unknown, an override
+/// that isn't a known install method is ignored
+fn resolve_install_method(override_value: Option<&str>, detect: impl FnOnce() -> InstallMethod) -> InstallMethod {
+    override_value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(detect)
+}
+
+fn install_method_override() -> Option<String> {
+    std::env::var(Q_INSTALL_METHOD).ok()
+}
+
+/// The install method, taken from [`Q_INSTALL_METHOD`] if set, otherwise detected once and cached
 pub fn get_install_method() -> InstallMethod {
-    *INSTALL_METHOD
+    resolve_install_method(install_method_override().as_deref(), || {
+        if let Some(install_method) = *INSTALL_METHOD.read().unwrap_or_else(|err| err.into_inner()) {
+            return install_method;
+        }
+        *INSTALL_METHOD
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .get_or_insert_with(detect_install_method)
+    })
+}
+
+/// Detects the install method again, e.g. after migrating from brew to another install, and
+/// updates the cached value. Returns the install method with the same precedence as
+/// [`get_install_method`].
+pub fn refresh_install_method() -> InstallMethod {
+    let detected = detect_install_method();
+    *INSTALL_METHOD.write().unwrap_or_else(|err| err.into_inner()) = Some(detected);
+    resolve_install_method(install_method_override().as_deref(), || detected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_wins_over_detection() {
+        assert_eq!(
+            resolve_install_method(Some("toolbox"), || InstallMethod::Brew),
+            InstallMethod::Toolbox
+        );
+        assert_eq!(
+            resolve_install_method(Some(" Brew "), || InstallMethod::Unknown),
+            InstallMethod::Brew
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_detection() {
+        assert_eq!(
+            resolve_install_method(None, || InstallMethod::Brew),
+            InstallMethod::Brew
+        );
+        assert_eq!(
+            resolve_install_method(Some("dmg"), || InstallMethod::Toolbox),
+            InstallMethod::Toolbox
+        );
+    }
 }
@@ -62,15 +62,20 @@ pub use fig_telemetry_core::{
     EventType,
     SuggestionState,
 };
-use fig_util::Shell;
 use fig_util::system_info::os_version;
 use fig_util::terminal::{
     current_terminal,
     current_terminal_version,
 };
+use fig_util::{
+    RedactionPolicy,
+    Shell,
+    redact,
+};
 pub use install_method::{
     InstallMethod,
     get_install_method,
+    refresh_install_method,
 };
 use tokio::sync::{
     Mutex,
@@ -194,6 +199,13 @@ fn opt_out_preference() -> OptOutPreference {
     }
 }
 
+/// Which sub-clients of a [`Client`] initialized successfully
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientHealth {
+    pub toolkit_telemetry: bool,
+    pub codewhisperer: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Client {
     client_id: Uuid,
@@ -205,17 +217,11 @@ pub struct Client {
 impl Client {
     pub async fn new(telemetry_stage: TelemetryStage) -> Self {
         let client_id = util::get_client_id();
-        let toolkit_telemetry_client = Some(amzn_toolkit_telemetry_client::Client::from_conf(
-            Config::builder()
-                .http_client(fig_aws_common::http_client::client())
-                .behavior_version(BehaviorVersion::v2025_01_17())
-                .endpoint_resolver(StaticEndpoint(telemetry_stage.endpoint))
-                .app_name(app_name())
-                .region(telemetry_stage.region.clone())
-                .credentials_provider(SharedCredentialsProvider::new(CognitoProvider::new(telemetry_stage)))
-                .build(),
-        ));
-        let codewhisperer_client = CodewhispererClient::new().await.ok();
+        let toolkit_telemetry_client = Self::toolkit_telemetry_client(telemetry_stage);
+        let codewhisperer_client = CodewhispererClient::new()
+            .await
+            .map_err(|err| error!(%err, "Failed to create codewhisperer client, skipping codewhisperer telemetry"))
+            .ok();
         let state = State::new();
 
         Self {
@@ -226,6 +232,34 @@ impl Client {
         }
     }
 
+    fn toolkit_telemetry_client(telemetry_stage: TelemetryStage) -> Option<ToolkitTelemetryClient> {
+        match url::Url::parse(telemetry_stage.endpoint) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {},
+            Ok(url) => {
+                error!(
+                    scheme = url.scheme(),
+                    "Invalid telemetry endpoint scheme, skipping toolkit telemetry"
+                );
+                return None;
+            },
+            Err(err) => {
+                error!(%err, "Invalid telemetry endpoint, skipping toolkit telemetry");
+                return None;
+            },
+        }
+
+        Some(amzn_toolkit_telemetry_client::Client::from_conf(
+            Config::builder()
+                .http_client(fig_aws_common::http_client::client())
+                .behavior_version(BehaviorVersion::v2025_01_17())
+                .endpoint_resolver(StaticEndpoint(telemetry_stage.endpoint))
+                .app_name(app_name())
+                .region(telemetry_stage.region.clone())
+                .credentials_provider(SharedCredentialsProvider::new(CognitoProvider::new(telemetry_stage)))
+                .build(),
+        ))
+    }
+
     pub fn mock() -> Self {
         let client_id = util::get_client_id();
         let toolkit_telemetry_client = None;
@@ -240,6 +274,14 @@ impl Client {
         }
     }
 
+    /// Reports which sub-clients are available to send telemetry
+    pub fn health(&self) -> ClientHealth {
+        ClientHealth {
+            toolkit_telemetry: self.toolkit_telemetry_client.is_some(),
+            codewhisperer: self.codewhisperer_client.is_some(),
+        }
+    }
+
     async fn send_event(&self, event: AppTelemetryEvent) {
         self.send_migrate().await;
         self.send_cw_telemetry_event(&event).await;
@@ -613,7 +655,7 @@ pub async fn send_user_logged_in() {
 
 pub async fn send_completion_inserted(command: String, terminal: Option<String>, shell: Option<String>) {
     let event = AppTelemetryEvent::new(EventType::CompletionInserted {
-        command,
+        command: redact(&command, RedactionPolicy::ALL),
         terminal,
         shell,
     })
@@ -636,6 +678,15 @@ pub async fn send_translation_actioned(latency: Duration, suggestion_state: Sugg
 }
 
 pub async fn send_cli_subcommand_executed(subcommand: impl Into<String>) {
+    send_cli_subcommand_executed_with_result(subcommand, None, None).await;
+}
+
+/// Sends a subcommand executed event including how the subcommand finished
+pub async fn send_cli_subcommand_executed_with_result(
+    subcommand: impl Into<String>,
+    exit_code: Option<i32>,
+    duration: Option<Duration>,
+) {
     let (shell, shell_version) = shell().await;
     let event = AppTelemetryEvent::new(EventType::CliSubcommandExecuted {
         subcommand: subcommand.into(),
@@ -643,6 +694,8 @@ pub async fn send_cli_subcommand_executed(subcommand: impl Into<String>) {
         terminal_version: current_terminal_version().map(Into::into),
         shell: shell.map(|s| s.to_string()),
         shell_version,
+        exit_code,
+        duration_ms: duration.map(|d| d.as_millis() as u64),
     })
     .await;
     dispatch_or_send_event(event).await;
@@ -651,7 +704,7 @@ pub async fn send_cli_subcommand_executed(subcommand: impl Into<String>) {
 pub async fn send_doctor_check_failed(failed_check: impl Into<String>) {
     let (shell, shell_version) = shell().await;
     let event = AppTelemetryEvent::new(EventType::DoctorCheckFailed {
-        doctor_check: failed_check.into(),
+        doctor_check: redact(&failed_check.into(), RedactionPolicy::ALL),
         terminal: current_terminal().map(|t| t.internal_id().to_string()),
         terminal_version: current_terminal_version().map(Into::into),
         shell: shell.map(|s| s.to_string()),
@@ -766,6 +819,16 @@ mod test {
         assert_eq!(context.ide_version.as_deref(), Some(PRODUCT_VERSION));
     }
 
+    #[tokio::test]
+    async fn client_with_bad_endpoint() {
+        let client = Client::new(TelemetryStage {
+            endpoint: "not a url",
+            ..TelemetryStage::EXTERNAL_PROD
+        })
+        .await;
+        assert!(!client.health().toolkit_telemetry);
+    }
+
     #[tokio::test]
     async fn client_send_event_test() {
         let client = Client::mock();
@@ -820,6 +883,7 @@ mod test {
         send_completion_inserted(CLI_BINARY_NAME.to_owned(), None, None).await;
         send_translation_actioned(Duration::from_millis(10), SuggestionState::Accept).await;
         send_cli_subcommand_executed("doctor").await;
+        send_cli_subcommand_executed_with_result("doctor", Some(0), Some(Duration::from_millis(25))).await;
         send_doctor_check_failed("").await;
         send_dashboard_page_viewed("/").await;
         send_menu_bar_actioned(Some("Settings")).await;
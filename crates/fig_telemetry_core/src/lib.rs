@@ -31,6 +31,8 @@ use aws_toolkit_telemetry_definitions::types::{
     CodewhispererterminalCustomToolInputTokenSize,
     CodewhispererterminalCustomToolLatency,
     CodewhispererterminalCustomToolOutputTokenSize,
+    CodewhispererterminalDuration,
+    CodewhispererterminalExitCode,
     CodewhispererterminalInCloudshell,
     CodewhispererterminalIsToolValid,
     CodewhispererterminalMcpServerInitFailureReason,
@@ -212,6 +214,8 @@ impl Event {
                 terminal_version,
                 shell,
                 shell_version,
+                exit_code,
+                duration_ms,
             } => Some(
                 CodewhispererterminalCliSubcommandExecuted {
                     create_time: self.created_time,
@@ -222,6 +226,8 @@ impl Event {
                     codewhispererterminal_shell: shell.map(Into::into),
                     codewhispererterminal_shell_version: shell_version.map(Into::into),
                     codewhispererterminal_subcommand: Some(subcommand.into()),
+                    codewhispererterminal_exit_code: exit_code.map(|code| CodewhispererterminalExitCode(code.into())),
+                    codewhispererterminal_duration: duration_ms.map(|ms| CodewhispererterminalDuration(ms as i64)),
                     codewhispererterminal_in_cloudshell: in_cloudshell(),
                 }
                 .into_metric_datum(),
@@ -452,6 +458,9 @@ pub enum EventType {
         terminal_version: Option<String>,
         shell: Option<String>,
         shell_version: Option<String>,
+        /// How the subcommand finished, `None` when it wasn't measured
+        exit_code: Option<i32>,
+        duration_ms: Option<u64>,
     },
     DoctorCheckFailed {
         doctor_check: String,
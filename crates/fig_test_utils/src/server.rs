@@ -49,6 +49,7 @@ use tokio_util::sync::CancellationToken;
 pub struct TestServer {
     listener: TcpListener,
     mock_responses: HashMap<(http::Method, String), String>,
+    ranged_responses: HashMap<(http::Method, String), String>,
 }
 
 impl TestServer {
@@ -59,6 +60,7 @@ impl TestServer {
         Self {
             listener,
             mock_responses: HashMap::default(),
+            ranged_responses: HashMap::default(),
         }
     }
 
@@ -67,6 +69,13 @@ impl TestServer {
         self
     }
 
+    /// Like [TestServer::with_mock_response], except a `Range: bytes=<start>-` request is
+    /// answered with `206 Partial Content` and only the bytes from `start`.
+    pub fn with_ranged_response(mut self, method: http::Method, path: String, response: String) -> Self {
+        self.ranged_responses.insert((method, path), response);
+        self
+    }
+
     /// Spawns a new task for accepting requests, returning the address of the listening socket.
     pub fn spawn_listener(self) -> TestAddress {
         let address = self
@@ -139,11 +148,41 @@ impl Service<Request<Incoming>> for TestServer {
     fn call(&self, req: Request<Incoming>) -> Self::Future {
         let method = req.method().clone();
         let path = req.uri().path().to_string();
-        let result = self.mock_responses.get(&(method, path)).unwrap().clone();
+        let key = (method, path);
+        if let Some(body) = self.ranged_responses.get(&key) {
+            let response = ranged_response(body.as_bytes(), req.headers().get(http::header::RANGE));
+            return Box::pin(async move { Ok(response) });
+        }
+        let result = self.mock_responses.get(&key).unwrap().clone();
         Box::pin(async move { Ok(Response::builder().status(200).body(result.into()).unwrap()) })
     }
 }
 
+fn ranged_response(body: &[u8], range: Option<&http::HeaderValue>) -> ServiceResponse {
+    let start = range
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.strip_suffix('-'))
+        .and_then(|start| start.parse::<usize>().ok());
+    let len = body.len();
+    match start {
+        None => Response::builder()
+            .status(200)
+            .body(Bytes::copy_from_slice(body).into())
+            .unwrap(),
+        Some(start) if start < len => Response::builder()
+            .status(206)
+            .header(http::header::CONTENT_RANGE, format!("bytes {start}-{}/{len}", len - 1))
+            .body(Bytes::copy_from_slice(&body[start..]).into())
+            .unwrap(),
+        Some(_) => Response::builder()
+            .status(416)
+            .header(http::header::CONTENT_RANGE, format!("bytes */{len}"))
+            .body(Bytes::new().into())
+            .unwrap(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,7 +130,13 @@ pub mod env_var {
         Q_USING_ZSH_AUTOSUGGESTIONS = "Q_USING_ZSH_AUTOSUGGESTIONS",
 
         /// Overrides the path to the bundle metadata released with certain desktop builds.
-        Q_BUNDLE_METADATA_PATH = "Q_BUNDLE_METADATA_PATH"
+        Q_BUNDLE_METADATA_PATH = "Q_BUNDLE_METADATA_PATH",
+
+        /// Comma or whitespace separated names of extra env vars to include in diagnostics
+        Q_DIAGNOSTIC_EXTRA_VARS = "Q_DIAGNOSTIC_EXTRA_VARS",
+
+        /// Overrides the detected install method reported in telemetry, e.g. `brew` or `toolbox`
+        Q_INSTALL_METHOD = "Q_INSTALL_METHOD"
     }
 }
 
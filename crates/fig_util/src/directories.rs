@@ -201,6 +201,15 @@ pub fn cache_dir() -> Result<PathBuf> {
     }
 }
 
+/// Update packages that were already downloaded, named by their sha256
+///
+/// - Linux: `$XDG_CACHE_HOME/amazon-q/downloads` or `$HOME/.cache/amazon-q/downloads`
+/// - MacOS: `$HOME/Library/Caches/amazon-q/downloads`
+/// - Windows: `%LOCALAPPDATA%\AmazonQ\cache\downloads`
+pub fn downloads_cache_dir() -> Result<PathBuf> {
+    Ok(cache_dir()?.join("downloads"))
+}
+
 /// Get the macos tempdir from the `confstr` function
 ///
 /// See: <https://man7.org/linux/man-pages/man3/confstr.3.html>
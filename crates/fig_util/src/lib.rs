@@ -1,7 +1,9 @@
 pub mod directories;
+pub mod lock_file;
 pub mod manifest;
 mod open;
 pub mod process_info;
+pub mod redact;
 mod shell;
 pub mod system_info;
 pub mod terminal;
@@ -17,13 +19,22 @@ use std::path::{
 };
 
 pub use consts::*;
+pub use lock_file::LockFile;
 pub use open::{
     open_url,
     open_url_async,
 };
 pub use process_info::get_parent_process_exe;
 use rand::Rng;
-pub use shell::Shell;
+pub use redact::{
+    RedactionPolicy,
+    redact,
+};
+pub use shell::{
+    SHELL_INTEGRATION_VERSION_PREFIX,
+    Shell,
+    shell_integration_version,
+};
 pub use terminal::Terminal;
 use thiserror::Error;
 
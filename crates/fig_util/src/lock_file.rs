@@ -0,0 +1,198 @@
+//! Lock files that expire
+//!
+//! A lock file holds the unix time it was created at. A process that dies while holding one, for
+//! example during an update, leaves it behind, so a lock older than its TTL is treated as stale
+//! and taken over.
+//!
+//! The file only appears once its time is written (see [Fs::write_atomic_new]), so another
+//! process can't find it empty and mistake it for a stale lock.
+
+use std::io::ErrorKind;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::{
+    Duration,
+    UNIX_EPOCH,
+};
+
+use fig_os_shim::{
+    Context,
+    Fs,
+};
+use tracing::{
+    debug,
+    error,
+    warn,
+};
+
+use crate::Error;
+
+/// A held lock, the lock file is removed when this is dropped
+#[derive(Debug)]
+pub struct LockFile {
+    fs: Fs,
+    path: PathBuf,
+}
+
+impl LockFile {
+    /// Try to take the lock at `path`
+    ///
+    /// Returns `None` if another holder took it less than `ttl` ago. A lock file that is older,
+    /// or that can't be parsed, is removed and the lock is taken.
+    pub async fn acquire(ctx: &Context, path: impl Into<PathBuf>, ttl: Duration) -> Result<Option<Self>, Error> {
+        let path = path.into();
+        let now = ctx
+            .time()
+            .now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        for _ in 0..2 {
+            match ctx.fs().write_atomic_new(&path, now.to_string(), 0o644).await {
+                Ok(()) => {
+                    debug!(?path, "Acquired lock");
+                    return Ok(Some(Self {
+                        fs: ctx.fs().clone(),
+                        path,
+                    }));
+                },
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    if !is_stale(ctx, &path, now, ttl).await {
+                        return Ok(None);
+                    }
+                    warn!(?path, "Removing stale lock");
+                    match ctx.fs().remove_file(&path).await {
+                        Ok(()) => (),
+                        Err(err) if err.kind() == ErrorKind::NotFound => (),
+                        Err(err) => return Err(err.into()),
+                    }
+                },
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        // Another process took over the stale lock first
+        Ok(None)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        match self.fs.remove_file_sync(&self.path) {
+            Ok(()) => debug!(path =? self.path, "Released lock"),
+            Err(err) if err.kind() == ErrorKind::NotFound => (),
+            Err(err) => error!(%err, path =? self.path, "Failed to remove lock file"),
+        }
+    }
+}
+
+async fn is_stale(ctx: &Context, path: &Path, now: u64, ttl: Duration) -> bool {
+    match ctx.fs().read_to_string(path).await {
+        Ok(contents) => {
+            let locked_at = contents.trim().parse::<u64>().unwrap_or(0);
+            now.saturating_sub(locked_at) >= ttl.as_secs()
+        },
+        // Removed by the holder in the meantime
+        Err(err) if err.kind() == ErrorKind::NotFound => true,
+        Err(err) => {
+            error!(%err, ?path, "Failed to read lock file, but it exists");
+            false
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fig_os_shim::Time;
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60 * 60);
+
+    async fn context() -> std::sync::Arc<Context> {
+        Context::builder()
+            .with_time(Time::new_fake())
+            .with_test_home()
+            .await
+            .unwrap()
+            .build_fake()
+    }
+
+    #[tokio::test]
+    async fn test_acquire_and_release() {
+        let ctx = context().await;
+        let path = "/home/testuser/test.lock";
+
+        let lock = LockFile::acquire(&ctx, path, TTL).await.unwrap().unwrap();
+        assert_eq!(lock.path(), Path::new(path));
+        assert!(ctx.fs().exists(path));
+        assert!(LockFile::acquire(&ctx, path, TTL).await.unwrap().is_none());
+
+        drop(lock);
+        assert!(!ctx.fs().exists(path));
+        assert!(LockFile::acquire(&ctx, path, TTL).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stale_lock_is_taken_over() {
+        let ctx = context().await;
+        let path = "/home/testuser/test.lock";
+
+        let lock = LockFile::acquire(&ctx, path, TTL).await.unwrap().unwrap();
+        // Leave the lock file behind, as a process that died would
+        std::mem::forget(lock);
+
+        ctx.time().advance(TTL - Duration::from_secs(1));
+        assert!(LockFile::acquire(&ctx, path, TTL).await.unwrap().is_none());
+
+        ctx.time().advance(Duration::from_secs(1));
+        let lock = LockFile::acquire(&ctx, path, TTL).await.unwrap().unwrap();
+        let locked_at = ctx.fs().read_to_string(path).await.unwrap();
+        let now = ctx.time().now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(locked_at, now.to_string());
+        drop(lock);
+        assert!(!ctx.fs().exists(path));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_acquire_has_one_holder() {
+        let ctx = context().await;
+        let path = "/home/testuser/test.lock";
+
+        let tasks = (0..16)
+            .map(|_| {
+                let ctx = ctx.clone();
+                tokio::spawn(async move { LockFile::acquire(&ctx, path, TTL).await })
+            })
+            .collect::<Vec<_>>();
+        let mut locks = Vec::new();
+        for task in tasks {
+            locks.extend(task.await.unwrap().unwrap());
+        }
+        assert_eq!(locks.len(), 1);
+
+        // Every reader sees the complete time, never an empty file
+        let now = ctx.time().now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(ctx.fs().read_to_string(path).await.unwrap(), now.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_lock_is_stale() {
+        let ctx = context().await;
+        let path = "/home/testuser/test.lock";
+        // Lock files are never visible before their time is written, so one that can't be parsed
+        // was corrupted and won't be released by anyone
+        ctx.fs().write(path, "not a time").await.unwrap();
+
+        let lock = LockFile::acquire(&ctx, path, TTL).await.unwrap().unwrap();
+        let now = ctx.time().now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(ctx.fs().read_to_string(lock.path()).await.unwrap(), now.to_string());
+    }
+}
@@ -154,6 +154,9 @@ pub enum FileType {
     Zip,
     AppImage,
     Deb,
+    Rpm,
+    /// Arch Linux package (`.pkg.tar.zst`)
+    PkgTarZst,
     #[strum(default)]
     Other(String),
 }
@@ -368,6 +371,8 @@ mod tests {
         test_ser_deser!(FileType, FileType::Zip, "zip");
         test_ser_deser!(FileType, FileType::AppImage, "appImage");
         test_ser_deser!(FileType, FileType::Deb, "deb");
+        test_ser_deser!(FileType, FileType::Rpm, "rpm");
+        test_ser_deser!(FileType, FileType::PkgTarZst, "pkgTarZst");
     }
 
     #[test]
@@ -4,15 +4,19 @@ use std::path::{
     PathBuf,
 };
 use std::str::FromStr;
+use std::sync::Arc;
 
 use clap::ValueEnum;
-use fig_os_shim::Env;
+use fig_os_shim::{
+    Command,
+    Context,
+    Env,
+};
 use regex::Regex;
 use serde::{
     Deserialize,
     Serialize,
 };
-use tokio::process::Command;
 
 use crate::consts::build::SKIP_FISH_TESTS;
 use crate::env_var::Q_ZDOTDIR;
@@ -22,6 +26,19 @@ use crate::{
     directories,
 };
 
+/// Prefix of the comment line recording the version of the CLI that wrote a shell integration
+/// script
+pub const SHELL_INTEGRATION_VERSION_PREFIX: &str = "# Amazon Q shell integration version: ";
+
+/// The CLI version recorded in the contents of a shell integration script, if it has one
+pub fn shell_integration_version(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix(SHELL_INTEGRATION_VERSION_PREFIX))
+        .map(str::trim)
+        .filter(|version| !version.is_empty())
+}
+
 /// All supported shells
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
 #[serde(rename_all = "camelCase")]
@@ -95,11 +112,25 @@ impl Shell {
 
     pub async fn current_shell_version() -> Result<(Self, String), Error> {
         let parent_exe = get_parent_process_exe().ok_or(Error::NoParentProcess)?;
-        let Some(shell) = Self::try_find_shell(&parent_exe) else {
-            return Err(Error::UnknownShell(parent_exe.to_string_lossy().into()));
+        Self::exe_shell_version(&parent_exe, &Command::new()).await
+    }
+
+    /// Gets the shell of the parent process in `ctx` and its version, which is read by running the
+    /// shell through [Context::command]
+    ///
+    /// Unlike [Shell::current_shell_version], both the process tree and the shell's output can be
+    /// faked.
+    pub async fn detect_from_process(ctx: &Arc<Context>) -> Result<(Self, String), Error> {
+        let parent_exe = fig_os_shim::process_info::get_parent_process_exe(ctx).ok_or(Error::NoParentProcess)?;
+        Self::exe_shell_version(&parent_exe, ctx.command()).await
+    }
+
+    async fn exe_shell_version(exe_path: &Path, command: &Command) -> Result<(Self, String), Error> {
+        let Some(shell) = Self::try_find_shell(exe_path) else {
+            return Err(Error::UnknownShell(exe_path.to_string_lossy().into()));
         };
 
-        Ok((shell, shell_version(&shell, &parent_exe).await?))
+        Ok((shell, shell_version(&shell, exe_path, command).await?))
     }
 
     /// Get the directory for the shell that contains the dotfiles
@@ -156,29 +187,29 @@ const BASH_RE: &str = r"GNU bash, version (\d+\.\d+\.\d+)";
 const ZSH_RE: &str = r"(\d+\.\d+)";
 const FISH_RE: &str = r"(\d+\.\d+\.\d+)";
 
-async fn shell_version(shell: &Shell, exe_path: &Path) -> Result<String, Error> {
+async fn shell_version(shell: &Shell, exe_path: &Path, command: &Command) -> Result<String, Error> {
     let err = || Error::ShellVersion(*shell);
     match shell {
         Shell::Bash => {
             let re = Regex::new(BASH_RE).unwrap();
-            let version_output = Command::new(exe_path).arg("--version").output().await?;
+            let version_output = command.output(exe_path, &["--version"]).await?;
             let version_capture = re.captures(std::str::from_utf8(&version_output.stdout)?);
             Ok(version_capture.ok_or_else(err)?.get(1).ok_or_else(err)?.as_str().into())
         },
         Shell::Zsh => {
             let re = Regex::new(ZSH_RE).unwrap();
-            let version_output = Command::new(exe_path).arg("--version").output().await?;
+            let version_output = command.output(exe_path, &["--version"]).await?;
             let version_capture = re.captures(std::str::from_utf8(&version_output.stdout)?);
             Ok(version_capture.ok_or_else(err)?.get(1).ok_or_else(err)?.as_str().into())
         },
         Shell::Fish => {
             let re = Regex::new(FISH_RE).unwrap();
-            let version_output = Command::new(exe_path).arg("--version").output().await?;
+            let version_output = command.output(exe_path, &["--version"]).await?;
             let version_capture = re.captures(std::str::from_utf8(&version_output.stdout)?);
             Ok(version_capture.ok_or_else(err)?.get(1).ok_or_else(err)?.as_str().into())
         },
         Shell::Nu => {
-            let version_output = Command::new(exe_path).arg("--version").output().await?;
+            let version_output = command.output(exe_path, &["--version"]).await?;
             Ok(std::str::from_utf8(&version_output.stdout)?.trim().into())
         },
     }
@@ -186,9 +217,70 @@ async fn shell_version(shell: &Shell, exe_path: &Path) -> Result<String, Error>
 
 #[cfg(test)]
 mod tests {
+    use fig_os_shim::ProcessInfo;
+
     use super::*;
     use crate::build::SKIP_FISH_TESTS;
 
+    #[test]
+    fn test_shell_integration_version() {
+        let contents = format!("{SHELL_INTEGRATION_VERSION_PREFIX}1.17.1\neval \"$(q init zsh pre)\"\n");
+        assert_eq!(shell_integration_version(&contents), Some("1.17.1"));
+        assert_eq!(shell_integration_version("eval \"$(q init zsh pre)\"\n"), None);
+        assert_eq!(shell_integration_version(SHELL_INTEGRATION_VERSION_PREFIX), None);
+    }
+
+    #[tokio::test]
+    async fn test_detect_from_process() {
+        let ctx = Context::builder()
+            .with_process_info(ProcessInfo::from_exes(vec!["q", "/bin/zsh", "wezterm"]))
+            .with_command(Command::from_slice(&[(
+                "/bin/zsh --version",
+                "zsh 5.9 (x86_64-apple-darwin23.0)\n",
+            )]))
+            .build_fake();
+        assert_eq!(
+            Shell::detect_from_process(&ctx).await.unwrap(),
+            (Shell::Zsh, "5.9".to_owned())
+        );
+
+        let ctx = Context::builder()
+            .with_process_info(ProcessInfo::from_exes(vec!["q", "/usr/local/bin/bash"]))
+            .with_command(Command::from_slice(&[(
+                "/usr/local/bin/bash --version",
+                "GNU bash, version 5.2.26(1)-release (aarch64-apple-darwin23.2.0)\n",
+            )]))
+            .build_fake();
+        assert_eq!(
+            Shell::detect_from_process(&ctx).await.unwrap(),
+            (Shell::Bash, "5.2.26".to_owned())
+        );
+
+        // Unparsable output
+        let ctx = Context::builder()
+            .with_process_info(ProcessInfo::from_exes(vec!["q", "/usr/local/bin/bash"]))
+            .with_command(Command::from_slice(&[("/usr/local/bin/bash --version", "bash\n")]))
+            .build_fake();
+        assert!(matches!(
+            Shell::detect_from_process(&ctx).await,
+            Err(Error::ShellVersion(Shell::Bash))
+        ));
+
+        // The fake command fails without faked output
+        let ctx = Context::builder()
+            .with_process_info(ProcessInfo::from_exes(vec!["q", "/bin/zsh"]))
+            .build_fake();
+        assert!(Shell::detect_from_process(&ctx).await.is_err());
+
+        let ctx = Context::builder()
+            .with_process_info(ProcessInfo::from_exes(vec!["q", "/usr/bin/python3"]))
+            .build_fake();
+        assert!(matches!(
+            Shell::detect_from_process(&ctx).await,
+            Err(Error::UnknownShell(_))
+        ));
+    }
+
     #[cfg(not(windows))]
     #[tokio::test]
     async fn test_shell_version() {
@@ -205,7 +297,7 @@ mod tests {
             }
 
             let exe_path = Path::new(exe_path_str);
-            let version: String = shell_version(&shell, exe_path)
+            let version: String = shell_version(&shell, exe_path, &Command::new())
                 .await
                 .unwrap_or_else(|err| panic!("exe {} failed. Error: {:?}", exe_path_str, err));
             println!("{}: {version:?}\n", exe_path.display());
@@ -3,11 +3,15 @@ use std::fmt;
 use std::sync::OnceLock;
 
 use fig_os_shim::Context;
+use fig_os_shim::process_info::ProcessSnapshot;
 use serde::{
     Deserialize,
     Serialize,
 };
 
+/// How many processes up the process hierarchy to look for a terminal
+const MAX_ANCESTRY_SEARCH_DEPTH: usize = 5;
+
 /// Terminals that macOS supports
 pub const MACOS_TERMINALS: &[Terminal] = &[
     Terminal::Alacritty,
@@ -262,28 +266,23 @@ impl Terminal {
     /// Attempts to return the suspected terminal emulator for the current process according to the
     /// process hierarchy. Only the list provided in `terminals` will be searched for.
     pub fn from_process_info(ctx: &Context, terminals: &Vec<Terminal>) -> Option<Self> {
-        let mut option_pid = Some(Box::new(ctx.process_info().current_pid()));
-        let (mut curr_depth, max_depth) = (0, 5);
-        while curr_depth < max_depth {
-            if let Some(pid) = option_pid {
-                if let Some(exe) = pid.exe() {
-                    if let Some(name) = exe.file_name().and_then(|s| s.to_str()) {
-                        for terminal in terminals {
-                            if terminal.executable_names().contains(&name) {
-                                return Some(terminal.clone());
-                            }
-                        }
-                    }
+        Self::from_ancestry(&ctx.process_info().ancestry(MAX_ANCESTRY_SEARCH_DEPTH), terminals)
+    }
+
+    /// Attempts to return the suspected terminal emulator from a process `ancestry`, as returned by
+    /// [fig_os_shim::ProcessInfo::ancestry]. Only the closest few processes and the list provided
+    /// in `terminals` will be searched for.
+    pub fn from_ancestry(ancestry: &[ProcessSnapshot], terminals: &Vec<Terminal>) -> Option<Self> {
+        for process in ancestry.iter().take(MAX_ANCESTRY_SEARCH_DEPTH) {
+            if let Some(name) = &process.name {
+                if let Some(terminal) = terminals.iter().find(|t| t.executable_names().contains(&name.as_str())) {
+                    return Some(terminal.clone());
                 }
-                if let Some(cmdline) = pid.cmdline() {
-                    if let Some(terminal) = Self::try_from_cmdline(&cmdline, terminals) {
-                        return Some(terminal.clone());
-                    }
+            }
+            if let Some(cmdline) = &process.cmdline {
+                if let Some(terminal) = Self::try_from_cmdline(cmdline, terminals) {
+                    return Some(terminal);
                 }
-                option_pid = pid.parent();
-                curr_depth += 1;
-            } else {
-                break;
             }
         }
         None
@@ -856,4 +855,60 @@ mod tests {
             "should return guake"
         );
     }
+
+    fn snapshot(exe: &str, cmdline: Option<&str>) -> ProcessSnapshot {
+        ProcessSnapshot {
+            name: exe.rsplit('/').next().map(str::to_string),
+            exe: Some(exe.into()),
+            cmdline: cmdline.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_ancestry() {
+        let ancestry = [
+            snapshot("/usr/local/bin/q", None),
+            snapshot("/bin/zsh", Some("-zsh")),
+            snapshot("/usr/bin/login", None),
+            snapshot("/Applications/Ghostty.app/Contents/MacOS/ghostty", None),
+        ];
+        assert_eq!(
+            Terminal::from_ancestry(&ancestry, &MACOS_TERMINALS.to_vec()),
+            Some(Terminal::Ghostty)
+        );
+
+        let ancestry = [
+            snapshot("/usr/bin/q", None),
+            snapshot("/usr/bin/fish", None),
+            snapshot("/usr/bin/wezterm-gui", Some("/usr/bin/wezterm-gui start")),
+        ];
+        assert_eq!(
+            Terminal::from_ancestry(&ancestry, &LINUX_TERMINALS.to_vec()),
+            Some(Terminal::WezTerm)
+        );
+
+        // The closest terminal wins, e.g. tmux running inside of WezTerm.
+        let ancestry = [
+            snapshot("/usr/bin/q", None),
+            snapshot("/usr/bin/bash", None),
+            snapshot("/usr/bin/tmux", Some("tmux: server")),
+            snapshot("/usr/bin/wezterm-gui", None),
+        ];
+        assert_eq!(
+            Terminal::from_ancestry(&ancestry, &SPECIAL_TERMINALS.to_vec()),
+            Some(Terminal::Tmux)
+        );
+        assert_eq!(
+            Terminal::from_ancestry(&ancestry, &LINUX_TERMINALS.to_vec()),
+            Some(Terminal::WezTerm)
+        );
+
+        let too_deep = std::iter::repeat_n(snapshot("/usr/bin/bash", None), MAX_ANCESTRY_SEARCH_DEPTH)
+            .chain([snapshot("/usr/bin/wezterm", None)])
+            .collect::<Vec<_>>();
+        assert_eq!(Terminal::from_ancestry(&too_deep, &LINUX_TERMINALS.to_vec()), None);
+
+        assert_eq!(Terminal::from_ancestry(&[], &LINUX_TERMINALS.to_vec()), None);
+    }
 }
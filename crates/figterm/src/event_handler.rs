@@ -127,6 +127,10 @@ impl EventListener for EventHandler {
                     error!(%err, "Sender error");
                 }
             },
+            Event::ClipboardStore { selection, data } => {
+                // The sequence is also written through to the outer terminal, which owns the clipboard.
+                debug!(?selection, len = data.len(), "Program set the clipboard");
+            },
             Event::ShellChanged => {
                 // let shell = &shell_state.local_context.shell;
                 // configure_scope(|scope| {
@@ -1,9 +1,14 @@
 use fig_settings::history::{
+    History,
     HistoryColumn,
     Order,
     OrderBy,
     WhereExpression,
 };
+use fig_util::{
+    RedactionPolicy,
+    redact,
+};
 use flume::Sender;
 use tracing::{
     error,
@@ -23,18 +28,76 @@ pub enum HistoryCommand {
         HistoryQueryParams,
         Sender<Option<Vec<fig_settings::history::CommandInfo>>>,
     ),
+    /// Replies once every command queued before it has been handled
+    Flush(Sender<()>),
 }
 
 pub type HistorySender = Sender<HistoryCommand>;
 
+/// How commands are recorded, read from settings when the history task starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HistorySettings {
+    /// Oldest commands are deleted once there are more than this many, `None`, the default, keeps
+    /// every command
+    max_entries: Option<usize>,
+    /// Mask credentials, e.g. `export GITHUB_TOKEN=...`, before the command is stored
+    redact: bool,
+}
+
+impl HistorySettings {
+    fn load() -> Self {
+        let max_entries = fig_settings::settings::get_int_or("qterm.history.max-entries", 0);
+        Self {
+            // Zero or a negative value disables the limit
+            max_entries: usize::try_from(max_entries).ok().filter(|max| *max > 0),
+            redact: fig_settings::settings::get_bool_or("qterm.history.redact", true),
+        }
+    }
+}
+
+fn record_command(
+    history: &History,
+    settings: HistorySettings,
+    mut command_info: fig_settings::history::CommandInfo,
+    legacy: bool,
+) -> fig_settings::Result<()> {
+    if settings.redact {
+        command_info.command = command_info
+            .command
+            .map(|command| redact(&command, RedactionPolicy::TOKENS));
+    }
+
+    history.insert_command_history(&command_info, legacy)?;
+
+    if let Some(max_entries) = settings.max_entries {
+        history.evict_oldest(max_entries)?;
+    }
+    Ok(())
+}
+
 pub async fn spawn_history_task() -> HistorySender {
+    spawn_history_task_with(History::new(), HistorySettings::load(), true)
+}
+
+/// Waits until every command queued before this call has been written, so history isn't lost when
+/// figterm exits, see [fig_telemetry::finish_telemetry]
+pub async fn finish_history(sender: &HistorySender) {
+    let (done_tx, done_rx) = flume::bounded(1);
+    if sender.send_async(HistoryCommand::Flush(done_tx)).await.is_err() {
+        // The task has already stopped
+        return;
+    }
+    if let Err(err) = done_rx.recv_async().await {
+        error!(%err, "Failed to flush history");
+    }
+}
+
+fn spawn_history_task_with(history: History, settings: HistorySettings, legacy: bool) -> HistorySender {
     trace!("Spawning history task");
 
     let (sender, receiver) = flume::bounded::<HistoryCommand>(64);
 
     tokio::task::spawn(async move {
-        let history = fig_settings::history::History::new();
-
         while let Ok(command) = receiver.recv_async().await {
             match command {
                 HistoryCommand::Insert(command) => {
@@ -53,7 +116,7 @@ pub async fn spawn_history_task() -> HistorySender {
                         exit_code: command.exit_code,
                     };
 
-                    if let Err(err) = history.insert_command_history(&command_info, true) {
+                    if let Err(err) = record_command(&history, settings, command_info, legacy) {
                         error!(%err, "Failed to insert command into history");
                     }
                 },
@@ -77,9 +140,107 @@ pub async fn spawn_history_task() -> HistorySender {
                         },
                     }
                 },
+                HistoryCommand::Flush(done) => {
+                    // The flusher may have stopped waiting
+                    done.send(()).ok();
+                },
             }
         }
     });
 
     sender
 }
+
+#[cfg(test)]
+mod tests {
+    use fig_settings::history::CommandInfo;
+
+    use super::*;
+
+    fn command(command: &str) -> CommandInfo {
+        CommandInfo {
+            command: Some(command.into()),
+            exit_code: Some(0),
+            ..Default::default()
+        }
+    }
+
+    fn stored_commands(history: &History) -> Vec<String> {
+        history
+            .rows(None, vec![OrderBy::new(HistoryColumn::Id, Order::Asc)], 100, 0)
+            .unwrap()
+            .into_iter()
+            .filter_map(|row| row.command)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_finish_waits_for_queued_commands() {
+        let history = History::mock();
+        let sender = spawn_history_task_with(
+            history.clone(),
+            HistorySettings {
+                max_entries: None,
+                redact: false,
+            },
+            false,
+        );
+
+        let commands = (0..20).map(|i| format!("echo {i}")).collect::<Vec<_>>();
+        for cmd in &commands {
+            sender
+                .send_async(HistoryCommand::Insert(alacritty_terminal::term::CommandInfo {
+                    command: Some(cmd.clone()),
+                    shell: None,
+                    pid: None,
+                    session_id: None,
+                    cwd: None,
+                    start_time: None,
+                    end_time: None,
+                    username: None,
+                    exit_code: Some(0),
+                }))
+                .await
+                .unwrap();
+        }
+        finish_history(&sender).await;
+
+        assert_eq!(stored_commands(&history), commands);
+    }
+
+    #[test]
+    fn test_evicts_oldest_at_cap() {
+        let history = History::mock();
+        let settings = HistorySettings {
+            max_entries: Some(2),
+            redact: false,
+        };
+
+        for cmd in ["ls", "cd src", "cargo build"] {
+            record_command(&history, settings, command(cmd), false).unwrap();
+        }
+        assert_eq!(stored_commands(&history), vec!["cd src", "cargo build"]);
+    }
+
+    #[test]
+    fn test_masks_secrets() {
+        let history = History::mock();
+        let settings = HistorySettings {
+            max_entries: None,
+            redact: true,
+        };
+
+        record_command(
+            &history,
+            settings,
+            command("export GITHUB_TOKEN=ghp_abc123 && git push"),
+            false,
+        )
+        .unwrap();
+        record_command(&history, settings, command("ls ~/src"), false).unwrap();
+        assert_eq!(stored_commands(&history), vec![
+            "export GITHUB_TOKEN=<token> && git push",
+            "ls ~/src"
+        ]);
+    }
+}
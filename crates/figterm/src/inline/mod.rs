@@ -17,6 +17,7 @@ use fig_api_client::model::{
     ProgrammingLanguage,
     RecommendationsInput,
 };
+use fig_os_shim::Env;
 use fig_proto::figterm::figterm_response_message::Response as FigtermResponse;
 use fig_proto::figterm::{
     FigtermResponseMessage,
@@ -66,15 +67,13 @@ static COMPLETION_CACHE: LazyLock<Mutex<CompletionCache>> = LazyLock::new(|| Mut
 static TELEMETRY_QUEUE: Mutex<TelemetryQueue> = Mutex::const_new(TelemetryQueue::new());
 
 static HISTORY_COUNT: LazyLock<usize> = LazyLock::new(|| {
-    std::env::var("Q_INLINE_SHELL_COMPLETION_HISTORY_COUNT")
-        .ok()
-        .and_then(|s| s.parse().ok())
+    Env::new()
+        .get_parsed("Q_INLINE_SHELL_COMPLETION_HISTORY_COUNT")
         .unwrap_or(HISTORY_COUNT_DEFAULT)
 });
 static DEBOUNCE_DURATION: LazyLock<Duration> = LazyLock::new(|| {
-    std::env::var("Q_INLINE_SHELL_COMPLETION_DEBOUNCE_MS")
-        .ok()
-        .and_then(|s| s.parse().ok())
+    Env::new()
+        .get_parsed("Q_INLINE_SHELL_COMPLETION_DEBOUNCE_MS")
         .map_or(DEBOUNCE_DURATION_DEFAULT, Duration::from_millis)
 });
 
@@ -261,8 +260,10 @@ pub async fn handle_request(
 
         let response = match client.generate_recommendations(input).await {
             Err(err) if err.is_throttling_error() => {
-                warn!(%err, "Too many requests, trying again in 1 second");
-                tokio::time::sleep(Duration::from_secs(1).saturating_sub(*DEBOUNCE_DURATION)).await;
+                // Wait as long as the service asked to, if it said
+                let delay = err.retry_after().unwrap_or(Duration::from_secs(1));
+                warn!(%err, ?delay, "Too many requests, trying again");
+                tokio::time::sleep(delay.saturating_sub(*DEBOUNCE_DURATION)).await;
                 continue;
             },
             other => other,
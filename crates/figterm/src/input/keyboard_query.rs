@@ -0,0 +1,140 @@
+//! Detecting whether the host terminal supports the kitty keyboard protocol
+//!
+//! The current progressive enhancement flags are requested with `CSI ? u`, a terminal that
+//! supports the protocol replies with `CSI ? flags u`. Terminals that don't support it ignore the
+//! query, so it is followed by a primary device attributes request, `CSI c`, which every terminal
+//! answers. Once the device attributes reply arrives there will be no keyboard reply.
+
+use std::time::Duration;
+
+use super::{
+    KeyboardEncoding,
+    KittyKeyboardFlags,
+};
+
+/// Query for the keyboard flags followed by the primary device attributes request
+pub const KEYBOARD_QUERY: &[u8] = b"\x1b[?u\x1b[c";
+
+/// How long after giving up on the query a late reply is still swallowed, after that a device
+/// attributes reply is more likely to be for a program running in the shell
+pub const LATE_REPLY_WINDOW: Duration = Duration::from_secs(5);
+
+/// The replies to [KEYBOARD_QUERY] found in the input read so far
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyboardQueryReply {
+    /// The flags the terminal reported, `None` if it didn't reply to the keyboard query
+    pub flags: Option<KittyKeyboardFlags>,
+    /// Whether the device attributes reply was seen, no more replies will follow
+    pub complete: bool,
+    /// Input that isn't part of a reply, for example keys typed while waiting
+    pub other: Vec<u8>,
+}
+
+impl KeyboardQueryReply {
+    /// The encoding to use for keys while the shell is at a prompt
+    ///
+    /// Figterm pushes [KittyKeyboardFlags::DISAMBIGUATE_ESCAPE_CODES] on terminals that support
+    /// the protocol, everything else falls back to xterm style encoding.
+    pub fn encoding(&self) -> KeyboardEncoding {
+        match self.flags {
+            Some(_) => KeyboardEncoding::Kitty(KittyKeyboardFlags::DISAMBIGUATE_ESCAPE_CODES),
+            None => KeyboardEncoding::Xterm,
+        }
+    }
+}
+
+/// Scan `buf` for the replies to [KEYBOARD_QUERY]
+///
+/// The whole buffer is scanned on each call, an incomplete sequence at the end is kept in
+/// [KeyboardQueryReply::other] until more input arrives.
+pub fn parse_keyboard_query_reply(buf: &[u8]) -> KeyboardQueryReply {
+    let mut reply = KeyboardQueryReply::default();
+    let mut rest = buf;
+
+    while !rest.is_empty() {
+        let Some(params) = rest.strip_prefix(b"\x1b[?") else {
+            reply.other.push(rest[0]);
+            rest = &rest[1..];
+            continue;
+        };
+
+        let len = params
+            .iter()
+            .position(|b| !(b.is_ascii_digit() || *b == b';'))
+            .unwrap_or(params.len());
+        match params.get(len) {
+            Some(b'u') => {
+                reply.flags = std::str::from_utf8(&params[..len])
+                    .ok()
+                    .and_then(|flags| flags.parse::<u16>().ok())
+                    .map(KittyKeyboardFlags::from_bits_truncate);
+            },
+            Some(b'c') => reply.complete = true,
+            Some(_) => {
+                reply.other.extend_from_slice(&rest[..3 + len + 1]);
+            },
+            None => {
+                reply.other.extend_from_slice(rest);
+            },
+        }
+        rest = &rest[(3 + len + 1).min(rest.len())..];
+    }
+
+    reply
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kitty_reply() {
+        let reply = parse_keyboard_query_reply(b"\x1b[?1u\x1b[?62;22c");
+        assert_eq!(reply, KeyboardQueryReply {
+            flags: Some(KittyKeyboardFlags::DISAMBIGUATE_ESCAPE_CODES),
+            complete: true,
+            other: vec![],
+        });
+        assert_eq!(
+            reply.encoding(),
+            KeyboardEncoding::Kitty(KittyKeyboardFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
+
+        let reply = parse_keyboard_query_reply(b"\x1b[?0u\x1b[?1;2c");
+        assert_eq!(reply.flags, Some(KittyKeyboardFlags::NONE));
+        assert!(reply.complete);
+    }
+
+    #[test]
+    fn test_unsupported_falls_back_to_xterm() {
+        let reply = parse_keyboard_query_reply(b"\x1b[?65;1;9c");
+        assert_eq!(reply.flags, None);
+        assert!(reply.complete);
+        assert_eq!(reply.encoding(), KeyboardEncoding::Xterm);
+    }
+
+    #[test]
+    fn test_partial_reply_and_typed_input() {
+        let reply = parse_keyboard_query_reply(b"ls\x1b[?1u\x1b[?6");
+        assert_eq!(reply.flags, Some(KittyKeyboardFlags::DISAMBIGUATE_ESCAPE_CODES));
+        assert!(!reply.complete);
+        assert_eq!(reply.other, b"ls\x1b[?6");
+
+        let reply = parse_keyboard_query_reply(b"\x1b[?25hx\x1b[?62c");
+        assert_eq!(reply.flags, None);
+        assert!(reply.complete);
+        assert_eq!(reply.other, b"\x1b[?25hx");
+    }
+
+    #[test]
+    fn test_late_reply_split_across_reads() {
+        let reply = parse_keyboard_query_reply(b"\x1b[?1u");
+        assert_eq!(reply.flags, Some(KittyKeyboardFlags::DISAMBIGUATE_ESCAPE_CODES));
+        assert!(!reply.complete);
+        assert!(reply.other.is_empty());
+
+        let reply = parse_keyboard_query_reply(b"\x1b[?62;22cls\r");
+        assert!(reply.complete);
+        assert_eq!(reply.other, b"ls\r");
+    }
+}
@@ -18,6 +18,7 @@ use crate::input::keymap::{
 };
 use crate::input::readbuf::ReadBuffer;
 
+pub mod keyboard_query;
 pub mod keymap;
 pub mod readbuf;
 
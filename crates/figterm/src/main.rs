@@ -10,6 +10,7 @@ pub mod ipc;
 pub mod logger;
 mod message;
 pub mod pty;
+mod start_text;
 pub mod term;
 pub mod update;
 
@@ -19,6 +20,10 @@ use std::ffi::{
     CString,
     OsStr,
 };
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
 use std::sync::{
     LazyLock,
     Mutex,
@@ -34,6 +39,7 @@ use alacritty_terminal::ansi::Processor;
 use alacritty_terminal::event::EventListener;
 use alacritty_terminal::grid::Dimensions;
 use alacritty_terminal::term::{
+    DEFAULT_REPL_PROMPTS,
     ShellState,
     SizeInfo,
     TextBuffer,
@@ -109,6 +115,7 @@ use tracing::{
 };
 
 use crate::event_handler::EventHandler;
+use crate::input::keyboard_query::KeyboardQueryReply;
 use crate::input::{
     InputEvent,
     KeyCode,
@@ -143,6 +150,10 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 const BUFFER_SIZE: usize = 16384;
 
+/// How long to wait for the terminal to reply to the keyboard protocol query
+#[cfg(unix)]
+const KEYBOARD_QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
 static INSERT_ON_NEW_CMD: Mutex<Option<(String, bool, bool)>> = Mutex::new(None);
 static INSERTION_LOCKED_AT: RwLock<Option<SystemTime>> = RwLock::new(None);
 static EXPECTED_BUFFER: Mutex<String> = Mutex::new(String::new());
@@ -343,7 +354,13 @@ where
 
     trace!(%shell_enabled, %preexec, %insertion_locked, "can_send_edit_buffer");
 
-    shell_enabled && !insertion_locked && !preexec
+    edit_buffer_allowed(shell_enabled, preexec, insertion_locked, *REPL_BUFFER_ENABLED)
+}
+
+/// The shell's edit buffer is only sent from a supported shell at its prompt, while the REPL
+/// buffer is found without shell integration or while a command is running
+fn edit_buffer_allowed(shell_enabled: bool, preexec: bool, insertion_locked: bool, repl_buffer_enabled: bool) -> bool {
+    !insertion_locked && ((shell_enabled && !preexec) || repl_buffer_enabled)
 }
 
 const Q_DISABLE_AUTOCOMPLETE: &str = "Q_DISABLE_AUTOCOMPLETE";
@@ -354,6 +371,64 @@ fn autocomplete_enabled(env: &Env) -> bool {
 
 static AUTOCOMPLETE_ENABLED: LazyLock<bool> = LazyLock::new(|| autocomplete_enabled(&Env::new()));
 
+/// Whether to find the edit buffer of programs without shell integration, such as REPLs, it's a
+/// heuristic so it's off by default
+static REPL_BUFFER_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| fig_settings::settings::get_bool_or("qterm.repl-buffer.enabled", false));
+
+/// The edit buffer at the shell prompt, falling back to [Term::get_repl_buffer] while a command is
+/// running or without shell integration if [REPL_BUFFER_ENABLED]
+fn current_edit_buffer<T>(term: &Term<T>) -> Option<TextBuffer>
+where
+    T: EventListener,
+{
+    let shell_buffer = match term.shell_state().preexec {
+        true => None,
+        false => term.get_current_buffer(),
+    };
+    shell_buffer.or_else(|| {
+        REPL_BUFFER_ENABLED
+            .then(|| term.get_repl_buffer(DEFAULT_REPL_PROMPTS))
+            .flatten()
+            .map(TextBuffer::from)
+    })
+}
+
+const DEFAULT_MAX_EDIT_BUFFER_LEN: i64 = 4096;
+
+/// Whether the last edit buffer was over the limit, an empty edit buffer is sent once when the
+/// limit is crossed so autocomplete doesn't keep showing suggestions for the shorter buffer
+static EDIT_BUFFER_OVER_LIMIT: AtomicBool = AtomicBool::new(false);
+
+/// Edit buffers longer than the `figterm.editbuffer.maxLen` setting in bytes, like pasted scripts,
+/// aren't sent since suggestions aren't useful for them, `None` sends every edit buffer
+fn max_edit_buffer_len(setting: i64) -> Option<usize> {
+    // Zero or a negative value disables the limit
+    usize::try_from(setting).ok().filter(|max| *max > 0)
+}
+
+fn edit_buffer_too_long(buffer: &str, max_len: Option<usize>) -> bool {
+    max_len.is_some_and(|max_len| buffer.len() > max_len)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditBufferAction {
+    Send,
+    /// Send an empty edit buffer in place of one that's over the limit
+    Clear,
+    Skip,
+}
+
+/// What to do with an edit buffer given whether the previous one was over the limit, the buffer is
+/// only cleared the first time the limit is crossed
+fn edit_buffer_action(too_long: bool, was_over_limit: bool) -> EditBufferAction {
+    match (too_long, was_over_limit) {
+        (false, _) => EditBufferAction::Send,
+        (true, false) => EditBufferAction::Clear,
+        (true, true) => EditBufferAction::Skip,
+    }
+}
+
 async fn send_edit_buffer<T>(
     term: &Term<T>,
     sender: &Sender<Hostbound>,
@@ -366,8 +441,29 @@ where
         return Ok(());
     }
 
-    match term.get_current_buffer() {
+    match current_edit_buffer(term) {
         Some(edit_buffer) => {
+            let max_len = max_edit_buffer_len(fig_settings::settings::get_int_or(
+                "figterm.editbuffer.maxLen",
+                DEFAULT_MAX_EDIT_BUFFER_LEN,
+            ));
+            let too_long = edit_buffer_too_long(&edit_buffer.buffer, max_len);
+            let was_over_limit = EDIT_BUFFER_OVER_LIMIT.swap(too_long, Ordering::SeqCst);
+            let edit_buffer = match edit_buffer_action(too_long, was_over_limit) {
+                EditBufferAction::Send => edit_buffer,
+                EditBufferAction::Clear => {
+                    trace!(len = edit_buffer.buffer.len(), "edit buffer too long, clearing");
+                    TextBuffer {
+                        buffer: String::new(),
+                        cursor_idx: Some(0),
+                    }
+                },
+                EditBufferAction::Skip => {
+                    trace!(len = edit_buffer.buffer.len(), "edit buffer too long, not sending");
+                    return Ok(());
+                },
+            };
+
             if let Some(cursor_idx) = edit_buffer.cursor_idx.and_then(|i| i.try_into().ok()) {
                 debug!("edit_buffer: {edit_buffer:?}");
                 trace!("buffer bytes: {:02X?}", edit_buffer.buffer.as_bytes());
@@ -390,15 +486,9 @@ where
 }
 
 fn get_parent_shell() -> Result<String> {
-    match env::var(Q_SHELL).ok().filter(|s| !s.is_empty()) {
-        Some(v) => Ok(v),
-        None => match env::var("SHELL").ok().filter(|s| !s.is_empty()) {
-            Some(shell) => Ok(shell),
-            None => {
-                anyhow::bail!("No Q_SHELL or SHELL found");
-            },
-        },
-    }
+    Env::new()
+        .get_first(&[Q_SHELL, "SHELL"])
+        .ok_or_else(|| anyhow!("No Q_SHELL or SHELL found"))
 }
 
 fn build_shell_command(command: Option<&[String]>) -> Result<CommandBuilder> {
@@ -559,6 +649,15 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
 
         terminal.set_raw_mode()?;
 
+        #[cfg(unix)]
+        let keyboard_query = terminal.query_keyboard_protocol(KEYBOARD_QUERY_TIMEOUT).unwrap_or_else(|err| {
+            warn!(%err, "Failed to query the keyboard protocol");
+            KeyboardQueryReply::default()
+        });
+        #[cfg(windows)]
+        let keyboard_query = KeyboardQueryReply::default();
+        info!(flags = ?keyboard_query.flags, "Kitty keyboard protocol support");
+
         let (main_loop_tx, main_loop_rx) = flume::bounded::<MainLoopEvent>(16);
 
         let history_sender = history::spawn_history_task().await;
@@ -576,10 +675,18 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
         let mut stdout = io::stdout();
         let mut master = pty.master.get_async_master_pty()?;
 
+        // Keys typed while waiting on the keyboard query
+        if !keyboard_query.other.is_empty() {
+            master.write_all(&keyboard_query.other).await?;
+        }
+
         let mut processor = Processor::new();
         let size = SizeInfo::new(pty_size.rows as usize, pty_size.cols as usize);
         let event_sender = EventHandler::new(remote_sender.clone(), history_sender.clone(), main_loop_tx.clone());
-        let mut term = alacritty_terminal::Term::new(size, event_sender, 1, session_id.clone());
+        let scrollback_lines: usize = fig_settings::settings::get_int_or("qterm.scrollback.lines", 1)
+            .try_into()
+            .unwrap_or(1);
+        let mut term = alacritty_terminal::Term::new(size, event_sender, scrollback_lines, session_id.clone());
 
         #[cfg(target_os = "windows")]
         term.set_windows_delay_end_prompt(true);
@@ -604,6 +711,14 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
             newline_mode: false,
         };
 
+        // Used while the keyboard protocol is pushed, falls back to xterm if the terminal doesn't
+        // support it
+        let csi_u_encode_mode = KeyCodeEncodeModes {
+            #[cfg(unix)]
+            encoding: keyboard_query.encoding(),
+            ..key_code_encode_mode
+        };
+
         let ai_enabled = fig_settings::settings::get_bool_or("ai.terminal-hash-sub", true);
 
         if let Ok(shell) = get_parent_shell() {
@@ -618,14 +733,20 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
         let mut csi_u_set = false;
 
         let result: Result<()> = 'select_loop: loop {
-            if first_time && term.shell_state().has_seen_prompt {
+            if first_time && term.shell_state().has_seen_prompt && !term.shell_state().preexec {
                 trace!("Has seen prompt and first time");
-                let initial_command = env::var("Q_START_TEXT").ok().filter(|s| !s.is_empty());
-                if let Some(mut initial_command) = initial_command {
-                    debug!("Sending initial text: {initial_command}");
-                    initial_command.push('\n');
-                    if let Err(err) = master.write_all(initial_command.as_bytes()).await {
-                        error!("Failed to write initial command: {err}");
+                let start_text = env::var("Q_START_TEXT").ok().filter(|s| !s.is_empty());
+                if let Some(start_text) = start_text {
+                    if start_text::shell_is_interactive() {
+                        for command in start_text::parse_start_text(&start_text) {
+                            debug!("Sending start command: {command}");
+                            if let Err(err) = master.write_all(format!("{command}\n").as_bytes()).await {
+                                error!("Failed to write start command: {err}");
+                                break;
+                            }
+                        }
+                    } else {
+                        debug!("Not sending start text to a non-interactive shell");
                     }
                 }
                 first_time = false;
@@ -667,12 +788,18 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
                                         error!(%err, "Failed to set immediate mode");
                                     }
                                 },
+                                #[cfg(unix)]
+                                MainLoopEvent::SetCsiU if keyboard_query.flags.is_none() => {
+                                    debug!("Terminal doesn't support the kitty keyboard protocol, not setting CSI u");
+                                },
                                 MainLoopEvent::SetCsiU => {
                                     // Send CSI > 1 u
                                     stdout.write_all(b"\x1b[>1u").await?;
                                     stdout.flush().await?;
                                     csi_u_set = true;
                                 },
+                                #[cfg(unix)]
+                                MainLoopEvent::UnsetCsiU if !csi_u_set => {},
                                 MainLoopEvent::UnsetCsiU => {
                                     // Send CSI < u
                                     stdout.write_all(b"\x1b[<u").await?;
@@ -755,7 +882,7 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
 
                                         // if we are in CSI u mode we try to encode first, otherwise we try to send the raw bytes first
                                         let raw = if csi_u_set {
-                                            event.key.encode(event.modifiers, key_code_encode_mode, true)
+                                            event.key.encode(event.modifiers, csi_u_encode_mode, true)
                                                 .ok()
                                                 .map(|s| s.into_bytes().into()).or(raw)
                                         } else {
@@ -808,6 +935,10 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
                                             pixel_height: size.ypixel as u16,
                                         };
 
+                                        // ConPTY repaints after a resize, so apply held back prompt
+                                        // markers against the grid they were received on.
+                                        term.flush_delayed_events();
+
                                         master.resize(pty_size)?;
                                         let window_size = SizeInfo::new(size.rows, size.cols);
                                         debug!("Window size changed: {window_size:?}");
@@ -956,6 +1087,12 @@ fn figterm_main(command: Option<&[String]>) -> Result<()> {
         };
 
         let _ = stop_ipc_tx.send(());
+        if tokio::time::timeout(Duration::from_secs(1), history::finish_history(&history_sender))
+            .await
+            .is_err()
+        {
+            warn!("Timed out writing history");
+        }
         fig_telemetry::finish_telemetry().await;
 
         result
@@ -1022,4 +1159,50 @@ mod tests {
             "1"
         )])));
     }
+
+    #[test]
+    fn edit_buffer_allowed_test() {
+        // At the prompt of a supported shell
+        assert!(edit_buffer_allowed(true, false, false, false));
+        assert!(!edit_buffer_allowed(true, false, true, false));
+        // While a command is running
+        assert!(!edit_buffer_allowed(true, true, false, false));
+        assert!(edit_buffer_allowed(true, true, false, true));
+        // Without shell integration
+        assert!(!edit_buffer_allowed(false, false, false, false));
+        assert!(edit_buffer_allowed(false, false, false, true));
+        assert!(edit_buffer_allowed(false, true, false, true));
+        assert!(!edit_buffer_allowed(false, true, true, true));
+    }
+
+    #[test]
+    fn edit_buffer_too_long_test() {
+        let max_len = max_edit_buffer_len(DEFAULT_MAX_EDIT_BUFFER_LEN);
+        assert!(!edit_buffer_too_long("git status", max_len));
+        assert!(!edit_buffer_too_long(&"a".repeat(4096), max_len));
+        assert!(edit_buffer_too_long(&"a".repeat(4097), max_len));
+
+        assert_eq!(max_edit_buffer_len(0), None);
+        assert_eq!(max_edit_buffer_len(-1), None);
+        assert!(!edit_buffer_too_long(&"a".repeat(100_000), None));
+    }
+
+    #[test]
+    fn edit_buffer_action_test() {
+        let mut over_limit = false;
+        let mut next = |too_long| {
+            let action = edit_buffer_action(too_long, over_limit);
+            over_limit = too_long;
+            action
+        };
+
+        assert_eq!(next(false), EditBufferAction::Send);
+        // Crossing the limit clears the buffer once, then nothing is sent while it stays over
+        assert_eq!(next(true), EditBufferAction::Clear);
+        assert_eq!(next(true), EditBufferAction::Skip);
+        assert_eq!(next(true), EditBufferAction::Skip);
+        // Dropping back under sends again, and crossing again clears again
+        assert_eq!(next(false), EditBufferAction::Send);
+        assert_eq!(next(true), EditBufferAction::Clear);
+    }
 }
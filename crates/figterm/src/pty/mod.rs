@@ -18,6 +18,9 @@ pub mod unix;
 #[cfg(windows)]
 pub mod win;
 
+#[cfg(any(windows, test))]
+mod request_queue;
+
 #[async_trait]
 pub trait AsyncMasterPty {
     async fn read(&mut self, buff: &mut [u8]) -> io::Result<usize>;
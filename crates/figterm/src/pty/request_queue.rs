@@ -0,0 +1,186 @@
+use std::io::{
+    self,
+    Write,
+};
+
+use flume::{
+    Receiver,
+    Sender,
+};
+use portable_pty::PtySize;
+use tracing::error;
+
+/// A request handled by the pty's blocking I/O thread
+pub enum PtyRequest {
+    /// Write the whole buffer, replying on `result_tx` once it is written
+    Write {
+        bytes: Vec<u8>,
+        result_tx: Sender<io::Result<usize>>,
+    },
+    /// Resize the pty once every write queued before it has been written
+    Resize(PtySize),
+}
+
+/// Handle used by the async side of the pty to queue requests
+///
+/// Writes and resizes share one queue, so a resize can never land in the middle of a write.
+#[derive(Clone)]
+pub struct PtyRequestSender {
+    tx: Sender<PtyRequest>,
+}
+
+impl PtyRequestSender {
+    pub fn new(tx: Sender<PtyRequest>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn write(&self, bytes: &[u8]) -> io::Result<usize> {
+        // Each write gets its own reply channel, if this future is dropped before the write
+        // completes the result is discarded rather than handed to the next write.
+        let (result_tx, result_rx) = flume::bounded(1);
+        self.tx
+            .send_async(PtyRequest::Write {
+                bytes: bytes.to_vec(),
+                result_tx,
+            })
+            .await
+            .map_err(io::Error::other)?;
+        result_rx.recv_async().await.map_err(io::Error::other)?
+    }
+
+    pub fn resize(&self, size: PtySize) -> io::Result<()> {
+        self.tx.send(PtyRequest::Resize(size)).map_err(io::Error::other)
+    }
+}
+
+/// Handle requests in the order they were queued until every sender is dropped
+///
+/// Writes are written in full before the next request is looked at.
+pub fn process_requests<W, F>(requests: Receiver<PtyRequest>, mut writable: W, mut resize: F)
+where
+    W: Write,
+    F: FnMut(PtySize) -> anyhow::Result<()>,
+{
+    while let Ok(request) = requests.recv() {
+        match request {
+            PtyRequest::Write { bytes, result_tx } => {
+                let result = writable.write_all(&bytes).map(|()| bytes.len());
+                // The writer may have stopped waiting for the result
+                result_tx.send(result).ok();
+            },
+            PtyRequest::Resize(size) => {
+                if let Err(err) = resize(size) {
+                    error!("Failed to resize pty: {err}");
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Op {
+        Write(Vec<u8>),
+        Resize(u16, u16),
+    }
+
+    /// Writer that only accepts a couple of bytes per call, like a full ConPTY input pipe
+    struct ChunkedWriter {
+        ops: Arc<Mutex<Vec<Op>>>,
+    }
+
+    impl Write for ChunkedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(2);
+            self.ops.lock().unwrap().push(Op::Write(buf[..n].to_vec()));
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn size(rows: u16, cols: u16) -> PtySize {
+        PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+
+    fn spawn_worker(rx: Receiver<PtyRequest>, ops: Arc<Mutex<Vec<Op>>>) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            let writer = ChunkedWriter { ops: ops.clone() };
+            process_requests(rx, writer, |size| {
+                ops.lock().unwrap().push(Op::Resize(size.rows, size.cols));
+                Ok(())
+            });
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resize_waits_for_partial_writes() {
+        let (tx, rx) = flume::unbounded();
+        let sender = PtyRequestSender::new(tx);
+        let ops = Arc::new(Mutex::new(Vec::new()));
+
+        // Queue everything before the worker starts so the interleaving is fixed
+        let (first_tx, first_rx) = flume::bounded(1);
+        sender
+            .tx
+            .send(PtyRequest::Write {
+                bytes: b"hello".to_vec(),
+                result_tx: first_tx,
+            })
+            .unwrap();
+        sender.resize(size(24, 80)).unwrap();
+
+        let worker = spawn_worker(rx, ops.clone());
+        assert_eq!(first_rx.recv_async().await.unwrap().unwrap(), 5);
+        assert_eq!(sender.write(b"bye").await.unwrap(), 3);
+        drop(sender);
+        worker.join().unwrap();
+
+        assert_eq!(*ops.lock().unwrap(), vec![
+            Op::Write(b"he".to_vec()),
+            Op::Write(b"ll".to_vec()),
+            Op::Write(b"o".to_vec()),
+            Op::Resize(24, 80),
+            Op::Write(b"by".to_vec()),
+            Op::Write(b"e".to_vec()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_write_result_is_not_reused() {
+        let (tx, rx) = flume::unbounded();
+        let sender = PtyRequestSender::new(tx);
+        let ops = Arc::new(Mutex::new(Vec::new()));
+
+        // A write whose caller went away before it completed
+        let (abandoned_tx, abandoned_rx) = flume::bounded(1);
+        drop(abandoned_rx);
+        sender
+            .tx
+            .send(PtyRequest::Write {
+                bytes: b"abandoned".to_vec(),
+                result_tx: abandoned_tx,
+            })
+            .unwrap();
+
+        let worker = spawn_worker(rx, ops);
+        assert_eq!(sender.write(b"ok").await.unwrap(), 2);
+        drop(sender);
+        worker.join().unwrap();
+    }
+}
@@ -1,7 +1,6 @@
 use std::io::{
     self,
     Read,
-    Write,
 };
 use std::os::windows::io::{
     AsRawHandle,
@@ -29,7 +28,6 @@ use filedescriptor::{
 };
 use flume::{
     Receiver,
-    Sender,
     unbounded,
 };
 use portable_pty::{
@@ -45,6 +43,11 @@ use winapi::um::synchapi::WaitForSingleObject;
 use winapi::um::winbase::INFINITE;
 use winapi::um::wincon::COORD;
 
+use crate::pty::request_queue::{
+    PtyRequest,
+    PtyRequestSender,
+    process_requests,
+};
 use crate::pty::win::pseudocon::PseudoCon;
 use crate::pty::{
     AsyncMasterPty,
@@ -250,31 +253,29 @@ impl ConPtyMasterPty {
 }
 
 struct ConPtyAsyncMasterPty {
-    inner: Arc<Mutex<Inner>>,
-    write_request_tx: Sender<Vec<u8>>,
-    write_result_rx: Receiver<Result<usize, io::Error>>,
+    requests: PtyRequestSender,
     read_result_rx: Receiver<Result<Vec<u8>, io::Error>>,
 }
 
 impl ConPtyAsyncMasterPty {
     fn new(inner: Arc<Mutex<Inner>>) -> Result<Self> {
-        let (write_request_tx, write_request_rx) = unbounded::<Vec<u8>>();
-        let (write_result_tx, write_result_rx) = unbounded::<Result<usize, io::Error>>();
+        let (request_tx, request_rx) = unbounded::<PtyRequest>();
         let (read_result_tx, read_result_rx) = unbounded::<Result<Vec<u8>, io::Error>>();
 
         {
             // spawn threads, initialize incoming receiver and transmitter channels.
             let inner_lock = inner.lock().unwrap();
-            let mut writable = inner_lock.writable.try_clone()?;
+            let writable = inner_lock.writable.try_clone()?;
             let mut readable = inner_lock.readable.try_clone()?;
 
+            // Resizes go through the writer thread so ConPTY never sees one between the chunks of
+            // a partially written buffer.
+            let resize_inner = inner.clone();
             tokio::task::spawn_blocking(move || {
-                while let Ok(bytes) = write_request_rx.recv() {
-                    if let Err(e) = write_result_tx.send(writable.write(&bytes)) {
-                        error!("Error writing {e}");
-                        break;
-                    }
-                }
+                process_requests(request_rx, writable, |size| {
+                    let mut inner = resize_inner.lock().unwrap();
+                    inner.resize(size.rows, size.cols, size.pixel_width, size.pixel_height)
+                });
             });
 
             tokio::task::spawn_blocking(move || {
@@ -290,10 +291,8 @@ impl ConPtyAsyncMasterPty {
         }
 
         Ok(ConPtyAsyncMasterPty {
-            inner,
+            requests: PtyRequestSender::new(request_tx),
             read_result_rx,
-            write_result_rx,
-            write_request_tx,
         })
     }
 }
@@ -312,18 +311,11 @@ impl AsyncMasterPty for ConPtyAsyncMasterPty {
     }
 
     async fn write(&mut self, buff: &[u8]) -> io::Result<usize> {
-        match self.write_request_tx.send_async(buff.to_vec()).await {
-            Ok(()) => match self.write_result_rx.recv_async().await {
-                Ok(res) => res,
-                Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
-            },
-            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
-        }
+        self.requests.write(buff).await
     }
 
     fn resize(&self, size: PtySize) -> anyhow::Result<()> {
-        let mut inner = self.inner.lock().unwrap();
-        inner.resize(size.rows, size.cols, size.pixel_width, size.pixel_height)
+        Ok(self.requests.resize(size)?)
     }
 }
 
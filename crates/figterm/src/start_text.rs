@@ -0,0 +1,84 @@
+//! Commands figterm types into the shell once its first prompt is shown.
+//!
+//! `Q_START_TEXT` holds a newline separated list of commands, each sent as its own execution.
+//! Blank lines are skipped and a trailing `\r` is dropped. A line ending in an unescaped `\` is
+//! joined with the next one and the backslash-newline is kept, so the shell sees its own line
+//! continuation. The shell integration sets the variable with `printf "%b" "$INITIAL_TEXT"`, so
+//! `\n` in `INITIAL_TEXT` separates commands and `\\n` passes a literal `\n` through.
+
+use std::env;
+use std::io::IsTerminal;
+
+/// Split the start text into the commands to run, in order
+pub fn parse_start_text(text: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        current.push_str(line);
+
+        let trailing_backslashes = line.chars().rev().take_while(|c| *c == '\\').count();
+        if trailing_backslashes % 2 == 1 {
+            current.push('\n');
+            continue;
+        }
+
+        if !current.trim().is_empty() {
+            commands.push(current.trim_end_matches('\n').to_owned());
+        }
+        current.clear();
+    }
+
+    // A continuation on the last line has nothing to join, send what we have
+    if let Some(command) = current.strip_suffix('\n').filter(|c| !c.trim().is_empty()) {
+        commands.push(command.to_owned());
+    }
+
+    commands
+}
+
+/// Whether the shell is reading commands from the user, start text is never typed into a shell
+/// running `-c` or one without a terminal attached
+pub fn shell_is_interactive() -> bool {
+    let has_execution_string = env::var("Q_EXECUTION_STRING").is_ok_and(|s| !s.is_empty());
+    !has_execution_string && std::io::stdin().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_command() {
+        assert_eq!(parse_start_text("ls -la"), vec!["ls -la"]);
+    }
+
+    #[test]
+    fn test_multiple_commands() {
+        assert_eq!(parse_start_text("cd ~/src\r\n\n  \ngit status\n"), vec![
+            "cd ~/src",
+            "git status"
+        ]);
+    }
+
+    #[test]
+    fn test_line_continuation() {
+        assert_eq!(parse_start_text("echo one \\\n  two\necho three"), vec![
+            "echo one \\\n  two",
+            "echo three"
+        ]);
+        // An escaped backslash does not continue the line
+        assert_eq!(parse_start_text("echo a\\\\\necho b"), vec!["echo a\\\\", "echo b"]);
+        // A continuation with nothing after it is sent as is
+        assert_eq!(parse_start_text("bind -x '\"\\C-g\": q' \\"), vec![
+            "bind -x '\"\\C-g\": q' \\"
+        ]);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(parse_start_text("").is_empty());
+        assert!(parse_start_text("\n \n").is_empty());
+    }
+}
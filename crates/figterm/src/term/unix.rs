@@ -12,7 +12,10 @@ use std::sync::atomic::{
     AtomicBool,
     Ordering,
 };
-use std::time::Duration;
+use std::time::{
+    Duration,
+    Instant,
+};
 
 use anyhow::{
     Context,
@@ -47,12 +50,19 @@ use tokio::select;
 use tokio::signal::unix::SignalKind;
 use tokio::time::MissedTickBehavior;
 use tracing::{
+    debug,
     error,
     trace,
     warn,
 };
 
 use super::InputEventResult;
+use crate::input::keyboard_query::{
+    KEYBOARD_QUERY,
+    KeyboardQueryReply,
+    LATE_REPLY_WINDOW,
+    parse_keyboard_query_reply,
+};
 use crate::input::{
     InputEvent,
     InputParser,
@@ -187,6 +197,9 @@ impl UnixTty for TtyWriteHandle {
 pub struct UnixTerminal {
     write: TtyWriteHandle,
     saved_termios: Termios,
+    /// Set when [UnixTerminal::query_keyboard_protocol] gave up before the device attributes
+    /// reply, the replies are swallowed by [Terminal::read_input] if they arrive before this
+    late_reply_until: Option<Instant>,
 }
 
 impl UnixTerminal {
@@ -207,7 +220,11 @@ impl UnixTerminal {
         let mut write = TtyWriteHandle::new(FileDescriptor::dup(write)?);
         let saved_termios = write.get_termios()?;
 
-        Ok(UnixTerminal { write, saved_termios })
+        Ok(UnixTerminal {
+            write,
+            saved_termios,
+            late_reply_until: None,
+        })
     }
 
     /// Attempt to explicitly open a handle to the terminal device
@@ -218,6 +235,57 @@ impl UnixTerminal {
         let file = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
         Self::new_with(&file, &file)
     }
+
+    /// Ask the terminal whether it supports the kitty keyboard protocol
+    ///
+    /// Must be called in raw mode and before [Terminal::read_input], the replies are read from
+    /// stdin directly. Gives up once `timeout` passes without the device attributes reply, replies
+    /// that arrive later are dropped by [Terminal::read_input] instead of reaching the shell.
+    pub fn query_keyboard_protocol(&mut self, timeout: Duration) -> Result<KeyboardQueryReply> {
+        self.write.write_all(KEYBOARD_QUERY)?;
+        self.write.flush()?;
+
+        let fd = stdin().as_raw_fd();
+        let deadline = Instant::now() + timeout;
+        let mut buf = Vec::new();
+        let mut chunk = [0; 256];
+
+        loop {
+            let reply = parse_keyboard_query_reply(&buf);
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if reply.complete {
+                return Ok(reply);
+            }
+            if remaining.is_zero() {
+                self.late_reply_until = Some(Instant::now() + LATE_REPLY_WINDOW);
+                return Ok(reply);
+            }
+
+            let mut fds = [libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            }];
+            let timeout_ms = remaining.as_millis().try_into().unwrap_or(libc::c_int::MAX);
+            match unsafe { libc::poll(fds.as_mut_ptr(), 1, timeout_ms) } {
+                0 => continue,
+                ready if ready < 0 => {
+                    let err = IoError::last_os_error();
+                    if err.kind() == std::io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(err).context("poll failed");
+                },
+                _ => {},
+            }
+
+            // Read the fd directly, bytes left in the std buffer would never reach `read_input`
+            match nix::unistd::read(fd, &mut chunk).context("read failed")? {
+                0 => return Ok(reply),
+                n => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
 }
 
 static IMMEDIATE_MODE: AtomicBool = AtomicBool::new(true);
@@ -271,6 +339,7 @@ impl Terminal for UnixTerminal {
     fn read_input(&mut self) -> Result<Receiver<InputEventResult>> {
         let mut window_change_signal = tokio::signal::unix::signal(SignalKind::window_change())?;
         let (input_tx, input_rx) = bounded::<InputEventResult>(1);
+        let mut late_reply_until = self.late_reply_until.take();
 
         tokio::spawn(async move {
             let mut stdin = io::stdin();
@@ -299,6 +368,21 @@ impl Terminal for UnixTerminal {
                         match res {
                             Ok(n) => {
                                 trace!(buf =? &buf[0..n], "Read input");
+                                if let Some(until) = late_reply_until {
+                                    if Instant::now() < until {
+                                        let reply = parse_keyboard_query_reply(&buf);
+                                        if reply.other.len() != buf.len() {
+                                            debug!(flags =? reply.flags, "Dropping late keyboard query reply");
+                                            buf.clear();
+                                            buf.extend_from_slice(&reply.other);
+                                        }
+                                        if reply.complete {
+                                            late_reply_until = None;
+                                        }
+                                    } else {
+                                        late_reply_until = None;
+                                    }
+                                }
                                 let full = buf.capacity() == buf.len();
                                 if full {
                                     buf.reserve(buf.len());
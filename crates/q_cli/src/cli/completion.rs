@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
 use std::io::{
     Write,
     stdout,
 };
+use std::path::PathBuf;
 use std::process::ExitCode;
+use std::sync::Arc;
 
 use clap::{
     Args,
@@ -10,7 +13,12 @@ use clap::{
     ValueEnum,
 };
 use eyre::Result;
-use fig_util::CLI_BINARY_NAME;
+use fig_os_shim::Context;
+use fig_util::{
+    CLI_BINARY_NAME,
+    directories,
+};
+use indoc::indoc;
 
 use crate::cli::Cli;
 
@@ -31,17 +39,33 @@ pub struct CompletionArgs {
     /// Shell to generate the completion spec for
     #[arg(value_enum, default_value_t = Shells::Zsh)]
     shell: Shells,
+    /// Print candidates for the last word of a partial command line instead of a completion spec
+    #[arg(long, hide = true, value_name = "LINE", allow_hyphen_values = true)]
+    dynamic: Option<String>,
 }
 
 impl CompletionArgs {
     pub fn execute(&self) -> Result<ExitCode> {
-        writeln!(stdout(), "{}", match self.shell {
+        if let Some(line) = &self.dynamic {
+            let source = LiveSource::new(Context::new());
+            for candidate in dynamic_candidates(&source, line) {
+                writeln!(stdout(), "{candidate}").ok();
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        let mut completions = match self.shell {
             Shells::Bash => generation_completions(clap_complete::shells::Bash),
             Shells::Fish => generation_completions(clap_complete::shells::Fish),
             Shells::Zsh => generation_completions(clap_complete::shells::Zsh),
             Shells::Fig => generation_completions(clap_complete_fig::Fig),
-        })
-        .ok();
+        };
+        if let Some(hook) = dynamic_hook(self.shell) {
+            completions.push('\n');
+            completions.push_str(&hook);
+        }
+
+        writeln!(stdout(), "{completions}").ok();
         Ok(ExitCode::SUCCESS)
     }
 }
@@ -54,3 +78,267 @@ fn generation_completions(generator: impl clap_complete::Generator) -> String {
 
     String::from_utf8_lossy(&buffer).into()
 }
+
+/// Shell code that asks `q completion --dynamic` for candidates before falling back to the
+/// generated completions. The Fig spec has no way to call back into the binary.
+fn dynamic_hook(shell: Shells) -> Option<String> {
+    let hook = match shell {
+        // Bash splits words on `=`, so only the part after it is replaced.
+        Shells::Bash => indoc! {r#"
+            _{bin}_dynamic() {
+                local candidate candidates=()
+                while IFS= read -r candidate; do
+                    candidates+=("${candidate#*=}")
+                done < <({bin} completion --dynamic "${COMP_LINE:0:COMP_POINT}" 2>/dev/null)
+                if (( ${#candidates[@]} )); then
+                    COMPREPLY=("${candidates[@]}")
+                    return 0
+                fi
+                _{bin} "$@"
+            }
+            complete -F _{bin}_dynamic -o nosort -o bashdefault -o default {bin}
+        "#},
+        Shells::Zsh => indoc! {r#"
+            _{bin}_dynamic() {
+                local -a candidates
+                candidates=("${(@f)$({bin} completion --dynamic "${BUFFER[1,CURSOR]}" 2>/dev/null)}")
+                if [[ -n ${candidates[1]} ]]; then
+                    compadd -- "${candidates[@]}"
+                else
+                    _{bin} "$@"
+                fi
+            }
+            compdef _{bin}_dynamic {bin}
+        "#},
+        Shells::Fish => indoc! {r#"
+            complete -c {bin} -n '__fish_seen_subcommand_from chat' -f -a '({bin} completion --dynamic (commandline -cp))'
+        "#},
+        Shells::Fig => return None,
+    };
+    Some(hook.replace("{bin}", CLI_BINARY_NAME))
+}
+
+/// The tool schema `q chat` loads its built-in tools from.
+const TOOL_INDEX: &str = include_str!("../../../chat-cli/src/cli/chat/tools/tool_index.json");
+
+/// Placeholder tool in the schema that is never offered to the model.
+const DUMMY_TOOL: &str = "dummy";
+
+/// Separator between the server and tool name of MCP tools, e.g. `server___tool`.
+const MCP_NAMESPACE_DELIMITER: &str = "___";
+
+/// Values that can only be known at completion time.
+pub trait CompletionSource {
+    /// Names of the chat context profiles.
+    fn profiles(&self) -> Vec<String>;
+
+    /// Names of the tools that can be trusted in chat.
+    fn tools(&self) -> Vec<String>;
+}
+
+/// Reads completion values from the user's chat configuration.
+struct LiveSource {
+    ctx: Arc<Context>,
+}
+
+impl LiveSource {
+    fn new(ctx: Arc<Context>) -> Self {
+        Self { ctx }
+    }
+
+    /// The global and workspace MCP configurations, in the order chat merges them.
+    fn mcp_config_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Ok(home) = directories::home_dir_ctx(&self.ctx) {
+            paths.push(home.join(".aws").join("amazonq").join("mcp.json"));
+        }
+        if let Ok(cwd) = self.ctx.env().current_dir() {
+            paths.push(cwd.join(".amazonq").join("mcp.json"));
+        }
+        paths
+    }
+}
+
+impl CompletionSource for LiveSource {
+    fn profiles(&self) -> Vec<String> {
+        // The default profile exists even before its directory is created.
+        let mut profiles = vec!["default".to_owned()];
+        let Ok(dir) = directories::chat_profiles_dir(&self.ctx) else {
+            return profiles;
+        };
+        if let Ok(entries) = std::fs::read_dir(self.ctx.fs().chroot_path(dir)) {
+            let mut names = entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| name != "default")
+                .collect::<Vec<_>>();
+            names.sort();
+            profiles.extend(names);
+        }
+        profiles
+    }
+
+    fn tools(&self) -> Vec<String> {
+        let mut tools = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(TOOL_INDEX)
+            .map(|schema| schema.into_iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .unwrap_or_default();
+        tools.retain(|name| name != DUMMY_TOOL);
+        // Chat swaps `execute_bash` for `execute_cmd` on Windows.
+        if cfg!(windows) {
+            for name in &mut tools {
+                if name == "execute_bash" {
+                    "execute_cmd".clone_into(name);
+                }
+            }
+        }
+        tools.sort();
+
+        // MCP tools are only known once their server is running, so offer a pattern trusting
+        // every tool of each configured server instead. Workspace servers replace global ones.
+        let servers = self
+            .mcp_config_paths()
+            .into_iter()
+            .filter_map(|path| self.ctx.fs().read_to_string_sync(path).ok())
+            .filter_map(|config| serde_json::from_str::<serde_json::Value>(&config).ok())
+            .filter_map(|config| config.get("mcpServers")?.as_object().cloned())
+            .flatten()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .filter(|(_, server)| !server.get("disabled").and_then(|d| d.as_bool()).unwrap_or(false))
+            .map(|(name, _)| format!("{name}{MCP_NAMESPACE_DELIMITER}*"));
+        tools.extend(servers);
+        tools
+    }
+}
+
+/// Completion candidates for the last word of `line`, a partial `q` command line.
+///
+/// Only values that clap can't know statically are completed: `q chat --profile` and
+/// `q chat --trust-tools`. Everything else returns no candidates so the static spec applies.
+pub fn dynamic_candidates(source: &impl CompletionSource, line: &str) -> Vec<String> {
+    let mut words = line.split_whitespace().collect::<Vec<_>>();
+    let current = if line.is_empty() || line.ends_with(char::is_whitespace) {
+        ""
+    } else {
+        words.pop().unwrap_or_default()
+    };
+
+    // Skip the binary name, then only `q chat` has dynamic values.
+    if words.get(1) != Some(&"chat") {
+        return Vec::new();
+    }
+
+    let (flag, value) = match current.split_once('=') {
+        Some((flag, value)) if flag.starts_with("--") => (flag, value),
+        _ => (words.last().copied().unwrap_or_default(), current),
+    };
+    let prefix = current.strip_suffix(value).unwrap_or_default();
+
+    match flag {
+        "--profile" => source
+            .profiles()
+            .into_iter()
+            .filter(|profile| profile.starts_with(value))
+            .map(|profile| format!("{prefix}{profile}"))
+            .collect(),
+        "--trust-tools" => {
+            // Tools are comma separated, so only the last one is being completed.
+            let (done, partial) = value.rsplit_once(',').unwrap_or(("", value));
+            let done = done.split(',').filter(|tool| !tool.is_empty()).collect::<Vec<_>>();
+            let head = value.strip_suffix(partial).unwrap_or_default();
+            source
+                .tools()
+                .into_iter()
+                .filter(|tool| tool.starts_with(partial) && !done.contains(&tool.as_str()))
+                .map(|tool| format!("{prefix}{head}{tool}"))
+                .collect()
+        },
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeSource;
+
+    impl CompletionSource for FakeSource {
+        fn profiles(&self) -> Vec<String> {
+            vec!["default".to_owned(), "backend".to_owned(), "frontend".to_owned()]
+        }
+
+        fn tools(&self) -> Vec<String> {
+            vec!["fs_read".to_owned(), "fs_write".to_owned(), "use_aws".to_owned()]
+        }
+    }
+
+    #[test]
+    fn test_profile_candidates() {
+        assert_eq!(dynamic_candidates(&FakeSource, "q chat --profile "), vec![
+            "default", "backend", "frontend"
+        ]);
+        assert_eq!(dynamic_candidates(&FakeSource, "q chat --profile b"), vec!["backend"]);
+        assert_eq!(dynamic_candidates(&FakeSource, "q chat --profile=f"), vec![
+            "--profile=frontend"
+        ]);
+    }
+
+    #[test]
+    fn test_tool_candidates() {
+        assert_eq!(dynamic_candidates(&FakeSource, "q chat --trust-tools fs"), vec![
+            "fs_read", "fs_write"
+        ]);
+        assert_eq!(dynamic_candidates(&FakeSource, "q chat --trust-tools=fs_read,"), vec![
+            "--trust-tools=fs_read,fs_write",
+            "--trust-tools=fs_read,use_aws",
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_live_tools_from_schema_and_mcp_config() {
+        let ctx = Context::builder().with_test_home().await.unwrap().build_fake();
+        let fs = ctx.fs();
+        fs.create_dir_all("/home/testuser/.aws/amazonq").await.unwrap();
+        fs.write(
+            "/home/testuser/.aws/amazonq/mcp.json",
+            r#"{"mcpServers":{"git":{"command":"git-mcp"},"slow":{"command":"slow-mcp"}}}"#,
+        )
+        .await
+        .unwrap();
+        let workspace = ctx.env().current_dir().unwrap().join(".amazonq");
+        fs.create_dir_all(&workspace).await.unwrap();
+        fs.write(
+            workspace.join("mcp.json"),
+            r#"{"mcpServers":{"slow":{"command":"slow-mcp","disabled":true}}}"#,
+        )
+        .await
+        .unwrap();
+
+        let tools = LiveSource::new(ctx).tools();
+        assert!(tools.contains(&"fs_read".to_owned()));
+        assert!(tools.contains(&"git___*".to_owned()));
+        assert!(!tools.contains(&"slow___*".to_owned()));
+        assert!(!tools.contains(&DUMMY_TOOL.to_owned()));
+    }
+
+    #[test]
+    fn test_dynamic_hook_calls_back() {
+        for shell in [Shells::Bash, Shells::Zsh, Shells::Fish] {
+            let hook = dynamic_hook(shell).unwrap();
+            assert!(
+                hook.contains(&format!("{CLI_BINARY_NAME} completion --dynamic")),
+                "{hook}"
+            );
+        }
+        assert!(dynamic_hook(Shells::Fig).is_none());
+    }
+
+    #[test]
+    fn test_no_dynamic_candidates() {
+        assert!(dynamic_candidates(&FakeSource, "q chat ").is_empty());
+        assert!(dynamic_candidates(&FakeSource, "q settings --profile ").is_empty());
+        assert!(dynamic_candidates(&FakeSource, "").is_empty());
+    }
+}
@@ -99,12 +99,14 @@ use futures::future::BoxFuture;
 use owo_colors::OwoColorize;
 use regex::Regex;
 use semver::Version;
+use serde::Serialize;
 use spinners::{
     Spinner,
     Spinners,
 };
 use tokio::io::AsyncBufReadExt;
 
+use super::OutputFormat;
 use super::app::restart_fig;
 use super::diagnostics::verify_integration;
 use crate::util::desktop::{
@@ -127,11 +129,14 @@ pub struct DoctorArgs {
     /// Error on warnings
     #[arg(long, short = 's')]
     pub strict: bool,
+    /// The format of the output, anything but plain runs every check and prints a single report
+    #[arg(long, short, value_enum, default_value_t)]
+    pub format: OutputFormat,
 }
 
 impl DoctorArgs {
     pub async fn execute(self) -> Result<ExitCode> {
-        doctor_cli(self.all, self.strict).await
+        doctor_cli(self.all, self.strict, self.format).await
     }
 }
 
@@ -345,6 +350,75 @@ fn print_status_result(name: impl Display, status: &Result<(), DoctorError>, ver
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Warning,
+    Fail,
+}
+
+/// The outcome of a single check, as reported by `doctor --format json`
+#[derive(Debug, Clone, Serialize)]
+struct CheckResult {
+    section: String,
+    name: String,
+    /// Stable id of the check, the same one sent with failed check telemetry
+    id: String,
+    status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    info: Vec<String>,
+}
+
+impl CheckResult {
+    fn new(section: &str, name: &str, id: String, status: &Result<(), DoctorError>) -> Self {
+        let (status, message, info) = match status {
+            Ok(()) => (CheckStatus::Pass, None, vec![]),
+            Err(DoctorError::Warning(msg)) => (CheckStatus::Warning, Some(msg.to_string()), vec![]),
+            Err(DoctorError::Error { reason, info, .. }) => (
+                CheckStatus::Fail,
+                Some(reason.to_string()),
+                info.iter().map(|line| line.to_string()).collect(),
+            ),
+        };
+        Self {
+            section: section.to_owned(),
+            name: name.to_owned(),
+            id,
+            status,
+            message,
+            info,
+        }
+    }
+}
+
+/// Everything `doctor --format json` prints, the diagnostics alongside every check result
+fn doctor_report(diagnostics: impl Serialize, checks: &[CheckResult]) -> serde_json::Value {
+    let failed_checks = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .map(|check| check.id.as_str())
+        .collect::<Vec<_>>();
+
+    serde_json::json!({
+        "diagnostics": diagnostics,
+        "checks": checks,
+        "failedChecks": failed_checks,
+    })
+}
+
+/// Fails when any check in a report failed. Soft checks only fail with `--strict`, otherwise
+/// they were already downgraded to warnings.
+fn report_exit_code(checks: &[CheckResult]) -> ExitCode {
+    if checks.iter().any(|check| check.status == CheckStatus::Fail) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::enum_variant_names)]
 enum DoctorCheckType {
@@ -1946,16 +2020,30 @@ async fn run_checks_with_context<T, Fut>(
     get_context: impl Fn() -> Fut,
     config: CheckConfiguration,
     spinner: &mut Option<Spinner>,
+    results: &mut Vec<CheckResult>,
 ) -> Result<()>
 where
     T: Sync + Send,
     Fut: Future<Output = Result<T>>,
 {
-    if config.all {
-        println!("{}", header.as_ref().dark_grey());
+    let header = header.as_ref();
+    if config.all && !config.report() {
+        println!("{}", header.dark_grey());
     }
     let mut context = match get_context().await {
         Ok(c) => c,
+        Err(e) if config.report() => {
+            let error = Err(doctor_error!("Failed to get context: {e}"));
+            for check in checks {
+                results.push(CheckResult::new(
+                    header,
+                    &check.name(),
+                    check.analytics_event_name(),
+                    &error,
+                ));
+            }
+            return Ok(());
+        },
         Err(e) => {
             println!("Failed to get context: {e:?}");
             eyre::bail!(e);
@@ -1977,6 +2065,12 @@ where
             }
         }
 
+        // Reports run every check without fixing anything, so all failures are recorded
+        if config.report() {
+            results.push(CheckResult::new(header, &name, check.analytics_event_name(), &result));
+            continue;
+        }
+
         if config.all || result.is_err() {
             stop_spinner(spinner.take())?;
             print_status_result(&name, &result, config.all);
@@ -2023,7 +2117,7 @@ where
         }
     }
 
-    if config.all {
+    if config.all && !config.report() {
         println!();
     }
 
@@ -2054,8 +2148,9 @@ async fn run_checks(
     checks: Vec<&dyn DoctorCheck>,
     config: CheckConfiguration,
     spinner: &mut Option<Spinner>,
+    results: &mut Vec<CheckResult>,
 ) -> Result<()> {
-    run_checks_with_context(header, checks, get_null_context, config, spinner).await
+    run_checks_with_context(header, checks, get_null_context, config, spinner, results).await
 }
 
 fn stop_spinner(spinner: Option<Spinner>) -> Result<()> {
@@ -2072,10 +2167,18 @@ fn stop_spinner(spinner: Option<Spinner>) -> Result<()> {
 struct CheckConfiguration {
     all: bool,
     strict: bool,
+    format: OutputFormat,
+}
+
+impl CheckConfiguration {
+    /// Whether results are collected into a structured report instead of printed as they run
+    fn report(&self) -> bool {
+        self.format != OutputFormat::Plain
+    }
 }
 
 // Doctor
-pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
+pub async fn doctor_cli(all: bool, strict: bool, format: OutputFormat) -> Result<ExitCode> {
     #[cfg(unix)]
     {
         use nix::unistd::geteuid;
@@ -2091,10 +2194,11 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
         }
     }
 
-    let config = CheckConfiguration { all, strict };
+    let config = CheckConfiguration { all, strict, format };
+    let mut results = vec![];
 
     let mut spinner: Option<Spinner> = None;
-    if !config.all {
+    if !config.all && !config.report() {
         spinner = Some(Spinner::new(Spinners::Dots, "Running checks...".into()));
         execute!(std::io::stdout(), cursor::Hide)?;
 
@@ -2117,6 +2221,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
         vec![&LoginStatusCheck {}],
         config,
         &mut spinner,
+        &mut results,
     )
     .await?;
 
@@ -2148,6 +2253,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
             get_shell_context,
             config,
             &mut spinner,
+            &mut results,
         )
         .await?;
 
@@ -2166,6 +2272,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
             ],
             config,
             &mut spinner,
+            &mut results,
         )
         .await?;
 
@@ -2175,6 +2282,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
                 vec![&AppRunningCheck, &DesktopSocketCheck],
                 config,
                 &mut spinner,
+                &mut results,
             )
             .await?;
         }
@@ -2193,6 +2301,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
             ],
             config,
             &mut spinner,
+            &mut results,
         )
         .await?;
 
@@ -2207,6 +2316,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
             ],
             config,
             &mut spinner,
+            &mut results,
         )
         .await
         .ok();
@@ -2230,6 +2340,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
                 super::diagnostics::get_diagnostics,
                 config,
                 &mut spinner,
+                &mut results,
             )
             .await?;
         }
@@ -2261,6 +2372,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
                     get_linux_context,
                     config,
                     &mut spinner,
+                    &mut results,
                 )
                 .await?;
             }
@@ -2275,6 +2387,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
                     super::diagnostics::get_diagnostics,
                     config,
                     &mut spinner,
+                    &mut results,
                 )
                 .await?;
             }
@@ -2297,6 +2410,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
             get_terminal_context,
             config,
             &mut spinner,
+            &mut results,
         )
         .await?;
 
@@ -2308,6 +2422,22 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
 
     stop_spinner(spinner)?;
 
+    if config.report() {
+        for check in results.iter().filter(|check| check.status == CheckStatus::Fail) {
+            fig_telemetry::send_doctor_check_failed(&check.id).await;
+        }
+
+        let diagnostics = fig_diagnostic::Diagnostics::new().await;
+        let report = doctor_report(&diagnostics, &results);
+        format.print(|| "", || report);
+        // Only structured reports fail the process, plain `q doctor` keeps exiting successfully
+        return Ok(if is_error {
+            ExitCode::FAILURE
+        } else {
+            report_exit_code(&results)
+        });
+    }
+
     if is_error {
         println!();
         println!("{} Doctor found errors. Please fix them and try again.", CROSS.red());
@@ -2342,3 +2472,156 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
 
     Ok(ExitCode::SUCCESS)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingCheck;
+
+    #[async_trait]
+    impl DoctorCheck for FailingCheck {
+        fn name(&self) -> Cow<'static, str> {
+            "Always Fails".into()
+        }
+
+        async fn check(&self, _: &()) -> Result<(), DoctorError> {
+            Err(DoctorError::Error {
+                reason: "forced failure".into(),
+                info: vec!["some detail".into()],
+                fix: None,
+                error: None,
+            })
+        }
+    }
+
+    struct PassingCheck;
+
+    #[async_trait]
+    impl DoctorCheck for PassingCheck {
+        fn name(&self) -> Cow<'static, str> {
+            "Passes".into()
+        }
+
+        async fn check(&self, _: &()) -> Result<(), DoctorError> {
+            Ok(())
+        }
+    }
+
+    struct WarningCheck;
+
+    #[async_trait]
+    impl DoctorCheck for WarningCheck {
+        fn name(&self) -> Cow<'static, str> {
+            "Warns".into()
+        }
+
+        async fn check(&self, _: &()) -> Result<(), DoctorError> {
+            Err(doctor_warning!("just a warning"))
+        }
+    }
+
+    struct SoftFailingCheck;
+
+    #[async_trait]
+    impl DoctorCheck for SoftFailingCheck {
+        fn name(&self) -> Cow<'static, str> {
+            "Soft Fails".into()
+        }
+
+        async fn get_type(&self, _: &(), _platform: Platform) -> DoctorCheckType {
+            DoctorCheckType::SoftCheck
+        }
+
+        async fn check(&self, _: &()) -> Result<(), DoctorError> {
+            Err(doctor_error!("soft failure"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_check_in_json_report() {
+        let config = CheckConfiguration {
+            all: false,
+            strict: false,
+            format: OutputFormat::Json,
+        };
+        let mut results = vec![];
+
+        // Without a report the first failure would stop the run, here every check still runs
+        run_checks(
+            "Section".into(),
+            vec![&FailingCheck, &WarningCheck, &PassingCheck],
+            config,
+            &mut None,
+            &mut results,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 3);
+
+        let report = doctor_report(serde_json::json!({ "q-details": {} }), &results);
+        assert_eq!(report["failedChecks"], serde_json::json!(["always_fails"]));
+        assert_eq!(
+            report["checks"][0],
+            serde_json::json!({
+                "section": "Section",
+                "name": "Always Fails",
+                "id": "always_fails",
+                "status": "fail",
+                "message": "forced failure",
+                "info": ["some detail"],
+            })
+        );
+        assert_eq!(report["checks"][1]["status"], "warning");
+        assert_eq!(report["checks"][2]["status"], "pass");
+        assert!(report["diagnostics"].get("q-details").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_report_exit_code() {
+        let config = CheckConfiguration {
+            all: false,
+            strict: false,
+            format: OutputFormat::Json,
+        };
+
+        let mut results = vec![];
+        run_checks(
+            "Section".into(),
+            vec![&WarningCheck, &PassingCheck],
+            config,
+            &mut None,
+            &mut results,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report_exit_code(&results), ExitCode::SUCCESS);
+
+        run_checks("Section".into(), vec![&FailingCheck], config, &mut None, &mut results)
+            .await
+            .unwrap();
+        assert_eq!(report_exit_code(&results), ExitCode::FAILURE);
+    }
+
+    #[tokio::test]
+    async fn test_report_exit_code_strict() {
+        for (strict, expected) in [(false, ExitCode::SUCCESS), (true, ExitCode::FAILURE)] {
+            let config = CheckConfiguration {
+                all: false,
+                strict,
+                format: OutputFormat::Json,
+            };
+            let mut results = vec![];
+            run_checks(
+                "Section".into(),
+                vec![&SoftFailingCheck],
+                config,
+                &mut None,
+                &mut results,
+            )
+            .await
+            .unwrap();
+            assert_eq!(report_exit_code(&results), expected, "strict: {strict}");
+        }
+    }
+}
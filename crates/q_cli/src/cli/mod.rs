@@ -27,6 +27,7 @@ use std::io::{
 };
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::time::Duration;
 
 use anstream::{
     eprintln,
@@ -54,7 +55,7 @@ use fig_auth::consts::OIDC_BUILDER_ID_REGION;
 use fig_auth::is_logged_in;
 use fig_auth::pkce::Region;
 use fig_auth::secret_store::SecretStore;
-use fig_ipc::local::open_ui_element;
+use fig_ipc::local::open_ui_element_with_timeout;
 use fig_log::{
     LogArgs,
     initialize_logging,
@@ -243,7 +244,7 @@ impl CliRootCommands {
             CliRootCommands::RootUser(RootUserSubcommand::Login(_)) => "login",
             CliRootCommands::RootUser(RootUserSubcommand::Logout) => "logout",
             CliRootCommands::RootUser(RootUserSubcommand::Whoami { .. }) => "whoami",
-            CliRootCommands::RootUser(RootUserSubcommand::Profile) => "profile",
+            CliRootCommands::RootUser(RootUserSubcommand::Profile { .. }) => "profile",
             CliRootCommands::User(_) => "user",
             CliRootCommands::Doctor(_) => "doctor",
             CliRootCommands::Completion(_) => "completion",
@@ -327,8 +328,6 @@ impl Cli {
 
         debug!(command =? std::env::args().collect::<Vec<_>>(), "Command ran");
 
-        self.send_telemetry().await;
-
         if self.help_all {
             return self.print_help_all();
         }
@@ -463,7 +462,8 @@ impl Cli {
         Ok(exit_code)
     }
 
-    async fn send_telemetry(&self) {
+    /// The subcommand name to report in telemetry, `None` for commands that are not tracked
+    pub fn telemetry_subcommand(&self) -> Option<&'static str> {
         match &self.subcommand {
             None
             | Some(
@@ -471,10 +471,8 @@ impl Cli {
                 | CliRootCommands::Internal(_)
                 | CliRootCommands::Completion(_)
                 | CliRootCommands::Hook(_),
-            ) => {},
-            Some(subcommand) => {
-                fig_telemetry::send_cli_subcommand_executed(subcommand.name()).await;
-            },
+            ) => None,
+            Some(subcommand) => Some(subcommand.name()),
         }
     }
 
@@ -592,7 +590,7 @@ async fn launch_dashboard(help_fallback: bool) -> Result<ExitCode> {
 
     println!("Opening {PRODUCT_NAME} dashboard");
 
-    open_ui_element(UiElement::MissionControl, route)
+    open_ui_element_with_timeout(UiElement::MissionControl, route, Duration::from_secs(5))
         .await
         .context("Failed to open dashboard")?;
 
@@ -708,6 +706,18 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_profile() {
+        assert_parse!(
+            ["profile"],
+            CliRootCommands::RootUser(RootUserSubcommand::Profile { refresh: false })
+        );
+        assert_parse!(
+            ["profile", "--refresh"],
+            CliRootCommands::RootUser(RootUserSubcommand::Profile { refresh: true })
+        );
+    }
+
     #[test]
     fn test_inline_shell_completion() {
         use internal::InternalSubcommand;
@@ -768,6 +778,7 @@ mod test {
             CliRootCommands::Doctor(doctor::DoctorArgs {
                 all: false,
                 strict: false,
+                format: OutputFormat::Plain,
             })
         );
         assert_parse!(
@@ -775,6 +786,7 @@ mod test {
             CliRootCommands::Doctor(doctor::DoctorArgs {
                 all: true,
                 strict: false,
+                format: OutputFormat::Plain,
             })
         );
         assert_parse!(
@@ -782,6 +794,7 @@ mod test {
             CliRootCommands::Doctor(doctor::DoctorArgs {
                 all: false,
                 strict: true,
+                format: OutputFormat::Plain,
             })
         );
         assert_parse!(
@@ -789,6 +802,15 @@ mod test {
             CliRootCommands::Doctor(doctor::DoctorArgs {
                 all: true,
                 strict: true,
+                format: OutputFormat::Plain,
+            })
+        );
+        assert_parse!(
+            ["doctor", "--format", "json"],
+            CliRootCommands::Doctor(doctor::DoctorArgs {
+                all: false,
+                strict: false,
+                format: OutputFormat::Json,
             })
         );
     }
@@ -54,8 +54,8 @@ const SEEN_ONBOARDING_KEY: &str = "ai.seen-onboarding";
 #[derive(Debug, Args, Default, PartialEq, Eq)]
 pub struct TranslateArgs {
     input: Vec<String>,
-    /// Number of completions to generate (must be <=5)
-    #[arg(short, long, hide = true)]
+    /// Number of candidate commands to generate, more than one shows them ranked (must be <=5)
+    #[arg(short, long)]
     n: Option<i32>,
 }
 
@@ -80,8 +80,64 @@ struct CompleteResponse {
     choices: Vec<Choice>,
 }
 
+/// A generated command, ranked by how many of the generated completions agree on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TranslateCandidate {
+    rank: usize,
+    command: String,
+    /// Number of completions that produced this command
+    votes: usize,
+    /// Number of valid completions
+    total: usize,
+}
+
+impl Display for TranslateCandidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}. {}", self.rank, highlighter(&self.command))?;
+        if self.total > 1 {
+            write!(
+                f,
+                " {}",
+                format!("({} of {} suggestions)", self.votes, self.total).dark_grey()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Group identical completions into candidates, the most agreed upon first
+///
+/// Candidates with the same number of votes keep the order the service returned them in.
+fn rank_candidates(completions: &[String]) -> Vec<TranslateCandidate> {
+    let mut commands: Vec<(&str, usize)> = Vec::new();
+    for completion in completions {
+        let command = completion.trim();
+        if command.is_empty() || command.starts_with("# UNIMPLEMENTED: ") {
+            continue;
+        }
+        match commands.iter_mut().find(|(existing, _)| *existing == command) {
+            Some((_, votes)) => *votes += 1,
+            None => commands.push((command, 1)),
+        }
+    }
+
+    let total = commands.iter().map(|(_, votes)| votes).sum();
+    commands.sort_by(|a, b| b.1.cmp(&a.1));
+    commands
+        .into_iter()
+        .enumerate()
+        .map(|(i, (command, votes))| TranslateCandidate {
+            rank: i + 1,
+            command: command.to_string(),
+            votes,
+            total,
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 enum DialogActions {
+    Pick(TranslateCandidate),
     Execute {
         command: String,
         display: bool,
@@ -103,6 +159,7 @@ enum DialogActions {
 impl Display for DialogActions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            DialogActions::Pick(candidate) => write!(f, "{candidate}"),
             DialogActions::Execute { command, display } => {
                 if *display {
                     write!(f, "⚡ Execute {}", command.bright_magenta())
@@ -354,7 +411,7 @@ impl TranslateArgs {
         let mut input = if input.is_empty() { None } else { Some(input.join(" ")) };
 
         let n = match n {
-            Some(n) if n >= 0 || n > 5 => {
+            Some(n) if !(1..=5).contains(&n) => {
                 eyre::bail!("n must be 0 < n <= 5");
             },
             Some(n) => n,
@@ -374,13 +431,15 @@ impl TranslateArgs {
                 },
             };
 
-            match &generate_response(&question, 1).await?.completions[..] {
-                [] => eyre::bail!("no valid completions were generated"),
-                [res, ..] => {
-                    println!("{res}");
-                    return Ok(ExitCode::SUCCESS);
-                },
-            };
+            let candidates = rank_candidates(&generate_response(&question, n).await?.completions);
+            if candidates.is_empty() {
+                eyre::bail!("no valid completions were generated");
+            }
+            // One command per line, best first
+            for candidate in &candidates {
+                println!("{}", candidate.command);
+            }
+            return Ok(ExitCode::SUCCESS);
         }
 
         // hack to show cursor which dialoguer eats
@@ -428,137 +487,152 @@ impl TranslateArgs {
                 #[cfg(unix)]
                 clear_stdin()?;
 
-                match &res.completions[..] {
+                if let Some(error_reason) = res
+                    .completions
+                    .first()
+                    .and_then(|choice| choice.strip_prefix("# UNIMPLEMENTED: "))
+                {
+                    spinner.stop_with_message(format!("{spinner_text}❌"));
+                    eyre::bail!("{}", error_reason);
+                }
+
+                let candidates = rank_candidates(&res.completions);
+                let choice = match &candidates[..] {
                     [] => {
                         spinner.stop_with_message(format!("{spinner_text}❌"));
                         eyre::bail!("no valid completions were generated");
                     },
-                    [choice, ..] => {
-                        if let Some(error_reason) = choice.strip_prefix("# UNIMPLEMENTED: ") {
-                            spinner.stop_with_message(format!("{spinner_text}❌"));
-                            eyre::bail!("{}", error_reason);
-                        }
-
-                        spinner.stop_with_message(format!("{spinner_text}{}", highlighter(choice)));
+                    [candidate] => {
+                        spinner.stop_with_message(format!("{spinner_text}{}", highlighter(&candidate.command)));
+                        println!();
+                        candidate.command.clone()
+                    },
+                    candidates => {
+                        spinner.stop_with_message(format!("{spinner_text}{}", "<multiple options>".dark_grey()));
                         println!();
-                        warning_message(choice);
-
-                        let actions: Vec<DialogActions> = fig_settings::settings::get("ai.menu-actions")
-                            .ok()
-                            .flatten()
-                            .unwrap_or_else(|| {
-                                ["execute", "edit", "regenerate", "ask", "cancel"]
-                                    .map(String::from)
-                                    .to_vec()
-                            })
-                            .into_iter()
-                            .filter_map(|action| match action.as_str() {
-                                "execute" => Some(DialogActions::Execute {
-                                    command: choice.clone(),
-                                    display: false,
-                                }),
-                                "edit" => Some(DialogActions::Edit {
-                                    command: choice.clone(),
-                                    display: false,
-                                }),
-                                "copy" => Some(DialogActions::Copy {
-                                    command: choice.clone(),
-                                    display: false,
-                                }),
-                                "regenerate" => Some(DialogActions::Regenerate),
-                                "ask" => Some(DialogActions::Ask),
-                                "cancel" => Some(DialogActions::Cancel),
-                                _ => None,
-                            })
-                            .collect();
+
+                        let mut actions: Vec<_> = candidates.iter().cloned().map(DialogActions::Pick).collect();
+                        actions.extend_from_slice(&[
+                            DialogActions::Regenerate,
+                            DialogActions::Ask,
+                            DialogActions::Cancel,
+                        ]);
 
                         let selected = dialoguer::Select::with_theme(&crate::util::dialoguer_theme())
                             .default(0)
                             .items(&actions)
                             .interact_opt()?;
 
-                        let action = selected.and_then(|i| actions.get(i));
-
-                        fig_telemetry::send_translation_actioned(response_latency, match action {
-                            Some(DialogActions::Execute { .. }) => SuggestionState::Accept,
-                            _ => SuggestionState::Reject,
-                        })
-                        .await;
-
-                        match action {
-                            Some(DialogActions::Execute { command, .. }) => {
-                                // let command = PARAM_REGEX
-                                //     .replace_all(command, |a: &Captures<'_>| {
-                                //         let env = a[0].strip_prefix("$").unwrap();
-                                //         if std::env::var_os(env).is_some() {
-                                //             a[0].to_string()
-                                //         } else {
-                                //             dialoguer::Input::with_theme(&theme())
-                                //                 .with_prompt(env)
-                                //                 .with_prompt(format!("{env}"))
-                                //                 .interact_text()
-                                //                 .unwrap_or_else(|_| std::process::exit(0))
-                                //         }
-                                //     })
-                                //     .to_string();
-
-                                if send_figterm(command.clone(), true).await.is_err() {
-                                    let mut child =
-                                        tokio::process::Command::new("bash").arg("-c").arg(command).spawn()?;
-                                    child.wait().await?;
-                                }
-                                break 'ask_loop;
-                            },
-                            Some(DialogActions::Edit { command, .. }) => {
-                                if let Err(err) = send_figterm(command.to_owned(), false).await {
-                                    println!("{} {err}", "Failed to insert command:".bright_red().bold());
-                                    println!();
-                                    println!("Command: {command}");
-                                }
-                                break 'ask_loop;
-                            },
-                            Some(DialogActions::Copy { command, .. }) => {
-                                if let Ok(mut clipboard) = Clipboard::new() {
-                                    match clipboard.set_text(command.clone()) {
-                                        Ok(_) => println!("Copied!"),
-                                        Err(err) => eyre::bail!(err),
-                                    }
-                                }
-                                break 'ask_loop;
+                        match selected.and_then(|i| actions.get(i)) {
+                            Some(DialogActions::Pick(candidate)) => {
+                                println!();
+                                candidate.command.clone()
                             },
-                            Some(DialogActions::Regenerate) => (),
+                            Some(DialogActions::Regenerate) => continue,
                             Some(DialogActions::Ask) => {
                                 input = None;
                                 continue 'ask_loop;
                             },
-                            _ => break 'ask_loop,
+                            _ => {
+                                fig_telemetry::send_translation_actioned(response_latency, SuggestionState::Reject)
+                                    .await;
+                                break 'ask_loop;
+                            },
+                        }
+                    },
+                };
+                let choice = &choice;
+
+                warning_message(choice);
+
+                let actions: Vec<DialogActions> = fig_settings::settings::get("ai.menu-actions")
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| {
+                        ["execute", "edit", "regenerate", "ask", "cancel"]
+                            .map(String::from)
+                            .to_vec()
+                    })
+                    .into_iter()
+                    .filter_map(|action| match action.as_str() {
+                        "execute" => Some(DialogActions::Execute {
+                            command: choice.clone(),
+                            display: false,
+                        }),
+                        "edit" => Some(DialogActions::Edit {
+                            command: choice.clone(),
+                            display: false,
+                        }),
+                        "copy" => Some(DialogActions::Copy {
+                            command: choice.clone(),
+                            display: false,
+                        }),
+                        "regenerate" => Some(DialogActions::Regenerate),
+                        "ask" => Some(DialogActions::Ask),
+                        "cancel" => Some(DialogActions::Cancel),
+                        _ => None,
+                    })
+                    .collect();
+
+                let selected = dialoguer::Select::with_theme(&crate::util::dialoguer_theme())
+                    .default(0)
+                    .items(&actions)
+                    .interact_opt()?;
+
+                let action = selected.and_then(|i| actions.get(i));
+
+                fig_telemetry::send_translation_actioned(response_latency, match action {
+                    Some(DialogActions::Execute { .. }) => SuggestionState::Accept,
+                    _ => SuggestionState::Reject,
+                })
+                .await;
+
+                match action {
+                    Some(DialogActions::Execute { command, .. }) => {
+                        // let command = PARAM_REGEX
+                        //     .replace_all(command, |a: &Captures<'_>| {
+                        //         let env = a[0].strip_prefix("$").unwrap();
+                        //         if std::env::var_os(env).is_some() {
+                        //             a[0].to_string()
+                        //         } else {
+                        //             dialoguer::Input::with_theme(&theme())
+                        //                 .with_prompt(env)
+                        //                 .with_prompt(format!("{env}"))
+                        //                 .interact_text()
+                        //                 .unwrap_or_else(|_| std::process::exit(0))
+                        //         }
+                        //     })
+                        //     .to_string();
+
+                        if send_figterm(command.clone(), true).await.is_err() {
+                            let mut child = tokio::process::Command::new("bash").arg("-c").arg(command).spawn()?;
+                            child.wait().await?;
                         }
+                        break 'ask_loop;
                     },
-                    // choices => {
-                    //     spinner.stop_with_message(format!("{spinner_text}{}", "<multiple options>".dark_grey()));
-                    //     println!();
-
-                    //     let mut actions: Vec<_> = choices
-                    //         .iter()
-                    //         .map(|choice| DialogActions::Execute {
-                    //             command: choice.to_string(),
-                    //             display: true,
-                    //         })
-                    //         .collect();
-
-                    //     actions.extend_from_slice(&[
-                    //         DialogActions::Regenerate,
-                    //         DialogActions::Ask,
-                    //         DialogActions::Cancel,
-                    //     ]);
-
-                    //     let selected = dialoguer::Select::with_theme(&crate::util::dialoguer_theme())
-                    //         .default(0)
-                    //         .items(&actions)
-                    //         .interact_opt()?;
-
-                    //     handle_action!(selected.and_then(|i| actions.get(i)));
-                    // },
+                    Some(DialogActions::Edit { command, .. }) => {
+                        if let Err(err) = send_figterm(command.to_owned(), false).await {
+                            println!("{} {err}", "Failed to insert command:".bright_red().bold());
+                            println!();
+                            println!("Command: {command}");
+                        }
+                        break 'ask_loop;
+                    },
+                    Some(DialogActions::Copy { command, .. }) => {
+                        if let Ok(mut clipboard) = Clipboard::new() {
+                            match clipboard.set_text(command.clone()) {
+                                Ok(_) => println!("Copied!"),
+                                Err(err) => eyre::bail!(err),
+                            }
+                        }
+                        break 'ask_loop;
+                    },
+                    Some(DialogActions::Regenerate) => (),
+                    Some(DialogActions::Ask) => {
+                        input = None;
+                        continue 'ask_loop;
+                    },
+                    _ => break 'ask_loop,
                 }
             }
         }
@@ -642,6 +716,42 @@ mod test {
         warning_message("sudo dd if=/dev/sda");
     }
 
+    #[test]
+    fn test_rank_candidates() {
+        let completions = [
+            "ls -la",
+            "  find . -maxdepth 1  ",
+            "ls -la",
+            "",
+            "# UNIMPLEMENTED: not a shell task",
+        ]
+        .map(String::from);
+
+        let candidates = rank_candidates(&completions);
+        assert_eq!(candidates, vec![
+            TranslateCandidate {
+                rank: 1,
+                command: "ls -la".into(),
+                votes: 2,
+                total: 3,
+            },
+            TranslateCandidate {
+                rank: 2,
+                command: "find . -maxdepth 1".into(),
+                votes: 1,
+                total: 3,
+            },
+        ]);
+
+        let rendered = DialogActions::Pick(candidates[0].clone()).to_string();
+        assert!(rendered.starts_with("1. ls -la"), "{rendered}");
+        assert!(rendered.contains("(2 of 3 suggestions)"), "{rendered}");
+
+        let single = rank_candidates(&["git status".to_string()]);
+        assert_eq!(single[0].to_string(), "1. git status");
+        assert!(rank_candidates(&["# UNIMPLEMENTED: no".to_string()]).is_empty());
+    }
+
     #[test]
     fn test_highlighter() {
         std::println!("{}", highlighter("echo $PATH $ABC $USER $HOME $DEF"));
@@ -2,7 +2,10 @@ use std::io::stdout;
 use std::process::ExitCode;
 
 use anstream::println;
-use clap::Args;
+use clap::{
+    Args,
+    ValueEnum,
+};
 use crossterm::style::Stylize;
 use eyre::Result;
 use fig_install::index::UpdatePackage;
@@ -26,6 +29,7 @@ use fig_proto::local::{
 use fig_settings::keys::UPDATE_AVAILABLE_KEY;
 use fig_util::manifest::{
     BundleMetadata,
+    Channel,
     FileType,
     Variant,
     manifest,
@@ -57,10 +61,33 @@ pub struct UpdateArgs {
     /// Uses rollout
     #[arg(long)]
     rollout: bool,
+    /// Switch to this update channel and check what it has available
+    #[arg(long, value_enum)]
+    channel: Option<UpdateChannel>,
+}
+
+/// Channels users can switch to with `update --channel`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UpdateChannel {
+    Stable,
+    Beta,
+}
+
+impl From<UpdateChannel> for Channel {
+    fn from(channel: UpdateChannel) -> Self {
+        match channel {
+            UpdateChannel::Stable => Channel::Stable,
+            UpdateChannel::Beta => Channel::Beta,
+        }
+    }
 }
 
 impl UpdateArgs {
     pub async fn execute(&self) -> Result<ExitCode> {
+        if let Some(channel) = self.channel {
+            return switch_channel(channel.into()).await;
+        }
+
         let ctx = Context::new();
         if ctx.platform().os() == Os::Linux && manifest().variant == Variant::Full {
             return try_linux_update().await;
@@ -70,6 +97,7 @@ impl UpdateArgs {
             non_interactive,
             relaunch_dashboard,
             rollout,
+            ..
         } = &self;
 
         let res = fig_install::update(
@@ -82,6 +110,9 @@ impl UpdateArgs {
                             Some(UpdateStatus::Percent(p)) => {
                                 progress_bar.set_position(p as u64);
                             },
+                            Some(UpdateStatus::Progress { percent, .. }) => {
+                                progress_bar.set_position(percent as u64);
+                            },
                             Some(UpdateStatus::Message(m)) => {
                                 progress_bar.set_message(m);
                             },
@@ -128,6 +159,42 @@ impl UpdateArgs {
     }
 }
 
+async fn switch_channel(channel: Channel) -> Result<ExitCode> {
+    let switch = match fig_install::set_channel(channel).await {
+        Ok(switch) => switch,
+        Err(fig_install::Error::SystemNotOnChannel) => {
+            eyre::bail!("The {} channel does not offer builds for this system", channel.name())
+        },
+        Err(err) => eyre::bail!(
+            "{err}\n\nFailed to switch update channels. If this is unexpected, try running {} and then try again.\n",
+            format!("{CLI_BINARY_NAME} doctor").bold()
+        ),
+    };
+
+    if switch.previous == switch.current {
+        println!("Already on the {} channel", switch.current.name().bold());
+    } else {
+        println!(
+            "Switched update channel from {} to {}",
+            switch.previous.name(),
+            switch.current.name().bold()
+        );
+    }
+
+    if switch.is_downgrade() {
+        println!(
+            "{}",
+            format!(
+                "The installed version may be newer than anything on the {} channel, so no downgrade will be offered until it catches up.",
+                switch.current.name()
+            )
+            .yellow()
+        );
+    }
+
+    display_update_check_result(&fig_install::check_for_updates(true).await)
+}
+
 async fn try_linux_update() -> Result<ExitCode> {
     match (fig_install::check_for_updates(true).await, bundle_metadata().await) {
         (ref update_result @ Ok(Some(ref pkg)), Some(file_type)) => {
@@ -264,3 +331,38 @@ fn display_update_check_result(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::cli::{
+        Cli,
+        CliRootCommands,
+    };
+
+    fn parse_update(args: &[&str]) -> UpdateArgs {
+        match Cli::parse_from([CLI_BINARY_NAME, "update"].iter().chain(args)).subcommand {
+            Some(CliRootCommands::Update(args)) => args,
+            other => panic!("expected update subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_channel_flag() {
+        assert_eq!(parse_update(&[]).channel, None);
+        assert_eq!(
+            parse_update(&["--channel", "stable"]).channel,
+            Some(UpdateChannel::Stable)
+        );
+        assert_eq!(parse_update(&["--channel", "beta"]).channel, Some(UpdateChannel::Beta));
+        assert!(Cli::try_parse_from([CLI_BINARY_NAME, "update", "--channel", "nightly"]).is_err());
+    }
+
+    #[test]
+    fn test_update_channel_maps_to_manifest_channel() {
+        assert_eq!(Channel::from(UpdateChannel::Stable), Channel::Stable);
+        assert_eq!(Channel::from(UpdateChannel::Beta), Channel::Beta);
+    }
+}
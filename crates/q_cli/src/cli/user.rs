@@ -21,7 +21,10 @@ use eyre::{
     bail,
 };
 use fig_api_client::list_available_profiles;
-use fig_api_client::profile::Profile;
+use fig_api_client::profile::{
+    Profile,
+    refresh_available_profiles,
+};
 use fig_auth::builder_id::{
     PollCreateToken,
     TokenType,
@@ -77,7 +80,11 @@ pub enum RootUserSubcommand {
         format: OutputFormat,
     },
     /// Show the profile associated with this idc user
-    Profile,
+    Profile {
+        /// Fetch the available profiles again instead of using the recently fetched list
+        #[arg(long)]
+        refresh: bool,
+    },
 }
 
 #[derive(Args, Debug, PartialEq, Eq, Clone, Default)]
@@ -198,7 +205,7 @@ impl RootUserSubcommand {
                     },
                 }
             },
-            Self::Profile => {
+            Self::Profile { refresh } => {
                 assert_logged_in().await?;
 
                 if let Ok(Some(token)) = fig_auth::builder_id_token().await {
@@ -207,7 +214,7 @@ impl RootUserSubcommand {
                     }
                 }
 
-                select_profile_interactive(false).await?;
+                select_profile_interactive(false, refresh).await?;
 
                 Ok(ExitCode::SUCCESS)
             },
@@ -316,7 +323,7 @@ pub async fn login_interactive(args: LoginArgs) -> Result<()> {
     }
 
     if login_method == AuthMethod::IdentityCenter {
-        select_profile_interactive(true).await?;
+        select_profile_interactive(true, false).await?;
     }
 
     eprintln!("Logged in successfully");
@@ -382,12 +389,16 @@ async fn try_device_authorization(
     Ok(())
 }
 
-async fn select_profile_interactive(whoami: bool) -> Result<()> {
+async fn select_profile_interactive(whoami: bool, refresh: bool) -> Result<()> {
     let mut spinner = Spinner::new(vec![
         SpinnerComponent::Spinner,
         SpinnerComponent::Text(" Fetching profiles...".into()),
     ]);
-    let profiles = list_available_profiles().await;
+    let profiles = if refresh {
+        refresh_available_profiles().await
+    } else {
+        list_available_profiles().await
+    };
     if profiles.is_empty() {
         info!("Available profiles was empty");
         return Ok(());
@@ -2,6 +2,7 @@ pub mod cli;
 pub mod util;
 
 use std::process::ExitCode;
+use std::time::Instant;
 
 use anstream::eprintln;
 use clap::Parser;
@@ -23,7 +24,7 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 fn main() -> Result<ExitCode> {
     color_eyre::install()?;
-    fig_telemetry::set_dispatch_mode(fig_telemetry::DispatchMode::On);
+    fig_telemetry::set_dispatch_mode(fig_telemetry::DispatchMode::Auto);
     fig_telemetry::init_global_telemetry_emitter();
 
     let mut args = std::env::args();
@@ -68,8 +69,19 @@ fn main() -> Result<ExitCode> {
     .enable_all()
     .build()?;
 
+    let telemetry_subcommand = parsed.telemetry_subcommand();
+
     let result = runtime.block_on(async {
+        let start = Instant::now();
         let result = parsed.execute().await;
+        if let Some(subcommand) = telemetry_subcommand {
+            let exit_code = match &result {
+                Ok(exit_code) if *exit_code == ExitCode::SUCCESS => 0,
+                _ => 1,
+            };
+            fig_telemetry::send_cli_subcommand_executed_with_result(subcommand, Some(exit_code), Some(start.elapsed()))
+                .await;
+        }
         fig_telemetry::finish_telemetry().await;
         result
     });
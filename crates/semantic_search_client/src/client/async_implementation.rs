@@ -29,6 +29,10 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::processing::{
+    BatchEmbedder,
+    walk_files,
+};
 use crate::types::{
     ContextId,
     DataPoint,
@@ -73,7 +77,7 @@ struct BackgroundWorker {
     contexts: Arc<RwLock<HashMap<ContextId, KnowledgeContext>>>,
     volatile_contexts: Arc<RwLock<HashMap<ContextId, Arc<Mutex<SemanticContext>>>>>,
     active_operations: Arc<RwLock<HashMap<Uuid, OperationHandle>>>,
-    embedder: Box<dyn TextEmbedderTrait>,
+    embedder: Arc<dyn TextEmbedderTrait>,
     config: SemanticSearchConfig,
     base_dir: PathBuf,
     indexing_semaphore: Arc<Semaphore>,
@@ -141,7 +145,7 @@ impl AsyncSemanticSearchClient {
             contexts: contexts.clone(),
             volatile_contexts: volatile_contexts.clone(),
             active_operations: active_operations.clone(),
-            embedder: worker_embedder,
+            embedder: worker_embedder.into(),
             config: config.clone(),
             base_dir: base_dir.clone(),
             indexing_semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_OPERATIONS)),
@@ -266,6 +270,10 @@ impl AsyncSemanticSearchClient {
                 match context_guard.search(&query_vector, effective_limit) {
                     Ok(results) => {
                         if !results.is_empty() {
+                            let results: SearchResults = results
+                                .into_iter()
+                                .map(|result| result.with_context_id(context_id.as_str()))
+                                .collect();
                             all_results.push((context_id.clone(), results));
                         }
                     },
@@ -903,8 +911,8 @@ impl BackgroundWorker {
         }
 
         // Create semantic context
-        let semantic_context = self
-            .create_semantic_context_impl(&context_dir, &items, &**embedder, operation_id, &cancel_token_clone)
+        let mut semantic_context = self
+            .create_semantic_context_impl(&context_dir, &items, embedder, operation_id, &cancel_token_clone)
             .await?;
 
         // Final cancellation check
@@ -1218,19 +1226,7 @@ impl BackgroundWorker {
             let mut count = 0;
             let mut checked = 0;
 
-            for _entry in walkdir::WalkDir::new(&dir_path)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
-                .filter(|e| {
-                    // Skip hidden files
-                    !e.path()
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .is_some_and(|s| s.starts_with('.'))
-                })
-            {
+            for _path in walk_files(&dir_path) {
                 count += 1;
                 checked += 1;
 
@@ -1283,30 +1279,14 @@ impl BackgroundWorker {
         let mut processed_files = 0;
         let mut items = Vec::new();
 
-        for entry in walkdir::WalkDir::new(dir_path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
+        for path in walk_files(dir_path) {
             // Check for cancellation frequently
             if cancel_token.is_cancelled() {
                 return Err("Operation was cancelled during file processing".to_string());
             }
 
-            let path = entry.path();
-
-            // Skip hidden files
-            if path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .is_some_and(|s| s.starts_with('.'))
-            {
-                continue;
-            }
-
             // Process the file
-            match process_file(path) {
+            match process_file(&path) {
                 Ok(mut file_items) => items.append(&mut file_items),
                 Err(_) => continue, // Skip files that fail to process
             }
@@ -1332,7 +1312,7 @@ impl BackgroundWorker {
         &self,
         context_dir: &Path,
         items: &[serde_json::Value],
-        embedder: &dyn TextEmbedderTrait,
+        embedder: &Arc<dyn TextEmbedderTrait>,
         operation_id: Uuid,
         cancel_token: &CancellationToken,
     ) -> std::result::Result<SemanticContext, String> {
@@ -1347,32 +1327,40 @@ impl BackgroundWorker {
         let mut semantic_context = SemanticContext::new(context_dir.join("data.json"))
             .map_err(|e| format!("Failed to create semantic context: {}", e))?;
 
-        // Process items to data points with cancellation checks
-        let mut data_points = Vec::new();
+        // Embed items in bounded batches so large directories don't spike memory
         let total_items = items.len();
-
-        for (i, item) in items.iter().enumerate() {
-            // Check for cancellation frequently
-            if cancel_token.is_cancelled() {
-                return Err("Operation was cancelled during embedding generation".to_string());
-            }
-
-            // Update progress for embedding generation
-            if i % 10 == 0 {
+        let texts = items
+            .iter()
+            .map(|item| item.get("text").and_then(|v| v.as_str()).unwrap_or("").to_string());
+        let batch_embedder = BatchEmbedder::new(
+            embedder.clone(),
+            self.config.embedding_batch_size,
+            self.config.embedding_concurrency,
+        );
+        let vectors = batch_embedder
+            .embed_all(texts, cancel_token, |done, total| {
                 self.update_operation_progress(
                     operation_id,
-                    i as u64,
-                    total_items as u64,
-                    format!("Generating embeddings ({}/{})", i, total_items),
+                    done as u64,
+                    total as u64,
+                    format!("Generating embeddings ({}/{})", done, total_items),
                 )
-                .await;
-            }
+            })
+            .await
+            .map_err(|e| {
+                if cancel_token.is_cancelled() {
+                    "Operation was cancelled during embedding generation".to_string()
+                } else {
+                    format!("Failed to create data point: {}", e)
+                }
+            })?;
 
-            // Create a data point from the item
-            let data_point = Self::create_data_point_from_item(item, i, embedder)
-                .map_err(|e| format!("Failed to create data point: {}", e))?;
-            data_points.push(data_point);
-        }
+        let data_points = items
+            .iter()
+            .zip(vectors)
+            .enumerate()
+            .map(|(i, (item, vector))| Self::create_data_point_from_item(item, i, vector))
+            .collect::<Vec<_>>();
 
         // Check for cancellation before building index
         if cancel_token.is_cancelled() {
@@ -1390,19 +1378,9 @@ impl BackgroundWorker {
         Ok(semantic_context)
     }
 
-    fn create_data_point_from_item(
-        item: &serde_json::Value,
-        id: usize,
-        embedder: &dyn TextEmbedderTrait,
-    ) -> Result<DataPoint> {
+    fn create_data_point_from_item(item: &serde_json::Value, id: usize, vector: Vec<f32>) -> DataPoint {
         use crate::types::DataPoint;
 
-        // Extract the text from the item
-        let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
-
-        // Generate an embedding for the text
-        let vector = embedder.embed(text)?;
-
         // Convert Value to HashMap
         let payload: HashMap<String, serde_json::Value> = if let serde_json::Value::Object(map) = item {
             map.clone().into_iter().collect()
@@ -1412,7 +1390,7 @@ impl BackgroundWorker {
             map
         };
 
-        Ok(DataPoint { id, payload, vector })
+        DataPoint { id, payload, vector }
     }
 
     async fn save_contexts_metadata(&self) -> std::result::Result<(), String> {
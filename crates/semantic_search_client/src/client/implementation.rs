@@ -8,6 +8,7 @@ use std::sync::{
     Arc,
     Mutex,
 };
+use std::time::Instant;
 
 use serde_json::Value;
 
@@ -25,13 +26,22 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
-use crate::processing::process_file;
+use crate::observer::{
+    NoopObserver,
+    Observer,
+};
+use crate::processing::{
+    BatchEmbedder,
+    process_file,
+    walk_files,
+};
 use crate::types::{
     ContextId,
     ContextMap,
     DataPoint,
     KnowledgeContext,
     ProgressStatus,
+    ScoredResult,
     SearchResults,
 };
 
@@ -66,12 +76,14 @@ pub struct SemanticSearchClient {
     persistent_contexts: HashMap<ContextId, KnowledgeContext>,
     /// Text embedder for generating embeddings
     #[cfg(any(target_os = "macos", target_os = "windows"))]
-    embedder: Box<dyn TextEmbedderTrait>,
+    embedder: Arc<dyn TextEmbedderTrait>,
     /// Text embedder for generating embeddings (Linux only)
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    embedder: Box<dyn TextEmbedderTrait>,
+    embedder: Arc<dyn TextEmbedderTrait>,
     /// Configuration for the client
     config: crate::config::SemanticSearchConfig,
+    /// Receives timing and failure events
+    observer: Arc<dyn Observer>,
 }
 impl SemanticSearchClient {
     /// Create a new semantic search client
@@ -129,7 +141,7 @@ impl SemanticSearchClient {
             // Continue with default config if initialization fails
         }
 
-        let embedder = embedder_factory::create_embedder(embedding_type)?;
+        let embedder = Arc::from(embedder_factory::create_embedder(embedding_type)?);
 
         // Load metadata for persistent contexts
         let contexts_file = base_dir.join("contexts.json");
@@ -142,6 +154,7 @@ impl SemanticSearchClient {
             persistent_contexts,
             embedder,
             config,
+            observer: Arc::new(NoopObserver),
         };
 
         // Now load all persistent contexts
@@ -238,6 +251,15 @@ impl SemanticSearchClient {
         config::update_config(&self.base_dir, new_config)
     }
 
+    /// Set the observer notified of searches, builds and failures
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to notify, replacing the current one
+    pub fn set_observer(&mut self, observer: Arc<dyn Observer>) {
+        self.observer = observer;
+    }
+
     /// Validate inputs
     fn validate_input(name: &str) -> Result<()> {
         if name.is_empty() {
@@ -289,7 +311,9 @@ impl SemanticSearchClient {
             self.add_context_from_directory(path, name, description, persistent, progress_callback)
         } else if path.is_file() {
             // Handle file
-            self.add_context_from_file(path, name, description, persistent, progress_callback)
+            let started = Instant::now();
+            let result = self.add_context_from_file(path, name, description, persistent, progress_callback);
+            self.observe_build(started, result)
         } else {
             Err(SemanticSearchError::InvalidPath(format!(
                 "Path is not a file or directory: {}",
@@ -398,8 +422,23 @@ impl SemanticSearchClient {
     where
         F: Fn(ProgressStatus) + Send + 'static,
     {
-        let dir_path = dir_path.as_ref();
+        let started = Instant::now();
+        let result = self.index_directory(dir_path.as_ref(), name, description, persistent, progress_callback);
+        self.observe_build(started, result)
+    }
 
+    /// Index a directory into a new context without notifying the observer
+    fn index_directory<F>(
+        &mut self,
+        dir_path: &Path,
+        name: &str,
+        description: &str,
+        persistent: bool,
+        progress_callback: Option<F>,
+    ) -> Result<ContextId>
+    where
+        F: Fn(ProgressStatus) + Send + 'static,
+    {
         // Generate a unique ID for this context
         let id = utils::generate_context_id();
 
@@ -467,25 +506,9 @@ impl SemanticSearchClient {
         let mut processed_files = 0;
         let mut items = Vec::new();
 
-        for entry in walkdir::WalkDir::new(dir_path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
-            let path = entry.path();
-
-            // Skip hidden files
-            if path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .is_some_and(|s| s.starts_with('.'))
-            {
-                continue;
-            }
-
+        for path in walk_files(dir_path) {
             // Process the file
-            match process_file(path) {
+            match process_file(&path) {
                 Ok(mut file_items) => items.append(&mut file_items),
                 Err(_) => continue, // Skip files that fail to process
             }
@@ -537,24 +560,27 @@ impl SemanticSearchClient {
     where
         F: Fn(ProgressStatus) + Send + 'static,
     {
-        let mut data_points = Vec::new();
-        let total_items = items.len();
-
-        // Process items with progress updates for embedding generation
-        for (i, item) in items.iter().enumerate() {
-            // Update progress for embedding generation
+        // Embed items in bounded batches so large directories don't spike memory
+        let texts = items
+            .iter()
+            .map(|item| item.get("text").and_then(|v| v.as_str()).unwrap_or("").to_string());
+        let batch_embedder = BatchEmbedder::new(
+            self.embedder.clone(),
+            self.config.embedding_batch_size,
+            self.config.embedding_concurrency,
+        );
+        let vectors = batch_embedder.embed_all_blocking(texts, |done, total| {
             if let Some(callback) = progress_callback {
-                if i % 10 == 0 {
-                    callback(ProgressStatus::GeneratingEmbeddings(i, total_items));
-                }
+                callback(ProgressStatus::GeneratingEmbeddings(done, total));
             }
+        })?;
 
-            // Create a data point from the item
-            let data_point = self.create_data_point_from_item(item, i)?;
-            data_points.push(data_point);
-        }
-
-        Ok(data_points)
+        Ok(items
+            .iter()
+            .zip(vectors)
+            .enumerate()
+            .map(|(i, (item, vector))| Self::create_data_point_from_item(item, i, vector))
+            .collect())
     }
 
     /// Save and store context
@@ -565,7 +591,7 @@ impl SemanticSearchClient {
         description: &str,
         persistent: bool,
         source_path: Option<String>,
-        semantic_context: SemanticContext,
+        mut semantic_context: SemanticContext,
     ) -> Result<()> {
         // Notify progress: Finalizing (90% progress point)
         let item_count = semantic_context.get_data_points().len();
@@ -618,17 +644,12 @@ impl SemanticSearchClient {
     ///
     /// * `item` - The JSON item to create a data point from
     /// * `id` - The ID for the data point
+    /// * `vector` - The embedding of the item's text
     ///
     /// # Returns
     ///
     /// A new DataPoint
-    fn create_data_point_from_item(&self, item: &Value, id: usize) -> Result<DataPoint> {
-        // Extract the text from the item
-        let text = item.get("text").and_then(|v| v.as_str()).unwrap_or("");
-
-        // Generate an embedding for the text
-        let vector = self.embedder.embed(text)?;
-
+    fn create_data_point_from_item(item: &Value, id: usize, vector: Vec<f32>) -> DataPoint {
         // Convert Value to HashMap
         let payload: HashMap<String, Value> = if let Value::Object(map) = item {
             map.clone().into_iter().collect()
@@ -638,7 +659,7 @@ impl SemanticSearchClient {
             map
         };
 
-        Ok(DataPoint { id, payload, vector })
+        DataPoint { id, payload, vector }
     }
 
     /// Add a context from text
@@ -659,6 +680,19 @@ impl SemanticSearchClient {
         context_name: &str,
         context_description: &str,
         is_persistent: bool,
+    ) -> Result<String> {
+        let started = Instant::now();
+        let result = self.index_text(text, context_name, context_description, is_persistent);
+        self.observe_build(started, result)
+    }
+
+    /// Index text into a new context without notifying the observer
+    fn index_text(
+        &mut self,
+        text: &str,
+        context_name: &str,
+        context_description: &str,
+        is_persistent: bool,
     ) -> Result<String> {
         // Validate inputs
         if text.is_empty() {
@@ -750,6 +784,19 @@ impl SemanticSearchClient {
     ///
     /// A vector of (context_id, results) pairs
     pub fn search_all(&self, query_text: &str, result_limit: Option<usize>) -> Result<Vec<(ContextId, SearchResults)>> {
+        let started = Instant::now();
+        let result = self.search_all_contexts(query_text, result_limit);
+        self.observe_search(started, None, result, |results| {
+            results.iter().map(|(_, results)| results.len()).sum()
+        })
+    }
+
+    /// Search across all contexts without notifying the observer
+    fn search_all_contexts(
+        &self,
+        query_text: &str,
+        result_limit: Option<usize>,
+    ) -> Result<Vec<(ContextId, SearchResults)>> {
         // Validate inputs
         if query_text.is_empty() {
             return Err(SemanticSearchError::InvalidArgument(
@@ -760,8 +807,7 @@ impl SemanticSearchClient {
         // Use the configured default_results if limit is None
         let effective_limit = result_limit.unwrap_or_else(|| config::get_config().default_results);
 
-        // Generate an embedding for the query
-        let query_vector = self.embedder.embed(query_text)?;
+        let query_vectors = self.embed_query(query_text)?;
 
         let mut all_results = Vec::new();
 
@@ -771,9 +817,13 @@ impl SemanticSearchClient {
                 SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e))
             })?;
 
-            match context_guard.search(&query_vector, effective_limit) {
+            match context_guard.search_pooled(&query_vectors, effective_limit) {
                 Ok(results) => {
                     if !results.is_empty() {
+                        let results: SearchResults = results
+                            .into_iter()
+                            .map(|result| result.with_context_id(context_id.as_str()))
+                            .collect();
                         all_results.push((context_id.clone(), results));
                     }
                 },
@@ -783,8 +833,9 @@ impl SemanticSearchClient {
             }
         }
 
-        // Sort contexts by best match
-        all_results.sort_by(|(_, a), (_, b)| {
+        // Sort contexts by best match, ties are ordered by ID so the output doesn't depend on the
+        // order of the map
+        all_results.sort_by(|(a_id, a), (b_id, b)| {
             if a.is_empty() {
                 return std::cmp::Ordering::Greater;
             }
@@ -794,11 +845,22 @@ impl SemanticSearchClient {
             a[0].distance
                 .partial_cmp(&b[0].distance)
                 .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a_id.cmp(b_id))
         });
 
         Ok(all_results)
     }
 
+    /// Embed a search query, after expanding it when query expansion is configured
+    fn embed_query(&self, query_text: &str) -> Result<Vec<Vec<f32>>> {
+        self.config
+            .query_expansion
+            .expand(query_text)
+            .iter()
+            .map(|query| self.embedder.embed(query))
+            .collect()
+    }
+
     /// Search in a specific context
     ///
     /// # Arguments
@@ -816,6 +878,18 @@ impl SemanticSearchClient {
         context_id: &str,
         query_text: &str,
         result_limit: Option<usize>,
+    ) -> Result<SearchResults> {
+        let started = Instant::now();
+        let result = self.search_single_context(context_id, query_text, result_limit);
+        self.observe_search(started, Some(context_id), result, Vec::len)
+    }
+
+    /// Search in a specific context without notifying the observer
+    fn search_single_context(
+        &self,
+        context_id: &str,
+        query_text: &str,
+        result_limit: Option<usize>,
     ) -> Result<SearchResults> {
         // Validate inputs
         if context_id.is_empty() {
@@ -833,8 +907,7 @@ impl SemanticSearchClient {
         // Use the configured default_results if limit is None
         let effective_limit = result_limit.unwrap_or_else(|| config::get_config().default_results);
 
-        // Generate an embedding for the query
-        let query_vector = self.embedder.embed(query_text)?;
+        let query_vectors = self.embed_query(query_text)?;
 
         let context = self
             .volatile_contexts
@@ -845,7 +918,115 @@ impl SemanticSearchClient {
             .lock()
             .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?;
 
-        context_guard.search(&query_vector, effective_limit)
+        let results = context_guard.search_pooled(&query_vectors, effective_limit)?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.with_context_id(context_id))
+            .collect())
+    }
+
+    /// Search a context with several queries at once
+    ///
+    /// All queries are embedded in a single call to the embedding backend, which saves round
+    /// trips when the backend is remote.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context to search in
+    /// * `queries` - Search queries
+    /// * `top_k` - Maximum number of results to return per query
+    ///
+    /// # Returns
+    ///
+    /// The results of each query, in the same order as `queries`
+    pub fn search_batch(&self, context_id: &str, queries: &[String], top_k: usize) -> Result<Vec<SearchResults>> {
+        let started = Instant::now();
+        let result = self.search_context_batch(context_id, queries, top_k);
+        self.observe_search(started, Some(context_id), result, |results| {
+            results.iter().map(Vec::len).sum()
+        })
+    }
+
+    /// Search a context with several queries without notifying the observer
+    fn search_context_batch(&self, context_id: &str, queries: &[String], top_k: usize) -> Result<Vec<SearchResults>> {
+        if queries.iter().any(String::is_empty) {
+            return Err(SemanticSearchError::InvalidArgument(
+                "Query text cannot be empty".to_string(),
+            ));
+        }
+
+        let context = self
+            .volatile_contexts
+            .get(context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+
+        if queries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Expanded queries are embedded in the same batch, then split up again per query
+        let expanded = queries
+            .iter()
+            .map(|query| self.config.query_expansion.expand(query))
+            .collect::<Vec<_>>();
+        let mut vectors = self.embedder.embed_batch(&expanded.concat())?.into_iter();
+
+        let context_guard = context
+            .lock()
+            .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?;
+
+        expanded
+            .iter()
+            .map(|queries| {
+                let query_vectors = vectors.by_ref().take(queries.len()).collect::<Vec<_>>();
+                Ok(context_guard
+                    .search_pooled(&query_vectors, top_k)?
+                    .into_iter()
+                    .map(|result| result.with_context_id(context_id))
+                    .collect())
+            })
+            .collect()
+    }
+
+    /// Search in a specific context and explain how each result was scored
+    ///
+    /// This is slower than [`Self::search_context`] and meant for tuning relevance.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context to search in
+    /// * `query_text` - Search query
+    /// * `top_k` - Maximum number of results to return
+    ///
+    /// # Returns
+    ///
+    /// The results with their score breakdown, best first
+    pub fn search_explain(&self, context_id: &str, query_text: &str, top_k: usize) -> Result<Vec<ScoredResult>> {
+        if query_text.is_empty() {
+            return Err(SemanticSearchError::InvalidArgument(
+                "Query text cannot be empty".to_string(),
+            ));
+        }
+
+        let context = self
+            .volatile_contexts
+            .get(context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+
+        let query_vector = self.embedder.embed(query_text)?;
+
+        let context_guard = context
+            .lock()
+            .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?;
+
+        Ok(context_guard
+            .search_explain(&query_vector, top_k)?
+            .into_iter()
+            .map(|mut scored| {
+                scored.result = scored.result.with_context_id(context_id);
+                scored
+            })
+            .collect())
     }
 
     /// Get all contexts
@@ -857,6 +1038,46 @@ impl SemanticSearchClient {
         self.persistent_contexts.values().cloned().collect()
     }
 
+    /// Render all contexts as a `tree`-style listing with item counts
+    ///
+    /// # Returns
+    ///
+    /// The rendered tree, with sub-contexts nested under their parent
+    pub fn render_tree(&self) -> String {
+        utils::render_context_tree(&self.get_all_contexts())
+    }
+
+    /// Nest a persistent context under another context
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context to nest
+    /// * `parent_id` - ID of the new parent context, or `None` to move it to the top level
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure
+    pub fn set_context_parent(&mut self, context_id: &str, parent_id: Option<&str>) -> Result<()> {
+        if parent_id == Some(context_id) {
+            return Err(SemanticSearchError::InvalidArgument(
+                "A context cannot be its own parent".to_string(),
+            ));
+        }
+
+        if let Some(parent_id) = parent_id {
+            if !self.persistent_contexts.contains_key(parent_id) && !self.volatile_contexts.contains_key(parent_id) {
+                return Err(SemanticSearchError::ContextNotFound(parent_id.to_string()));
+            }
+        }
+
+        let context = self
+            .persistent_contexts
+            .get_mut(context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+        context.parent_id = parent_id.map(str::to_string);
+        self.save_contexts_metadata()
+    }
+
     /// Make a context persistent
     ///
     /// # Arguments
@@ -899,7 +1120,7 @@ impl SemanticSearchClient {
 
         // Save the data to the persistent directory
         let data_path = persistent_dir.join("data.json");
-        utils::save_json_to_file(&data_path, context_guard.get_data_points())?;
+        utils::save_json_to_file(&data_path, &context_guard.saved_data_points()?)?;
 
         // Create the context metadata
         let context_meta = KnowledgeContext::new(
@@ -1041,6 +1262,133 @@ impl SemanticSearchClient {
         self.remove_context_by_name(context_id_or_name, delete_persistent)
     }
 
+    /// Rename a persistent context
+    ///
+    /// The context files are stored under the context ID, so only the metadata changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context to rename
+    /// * `new_name` - New name for the context, must not be used by another context
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure
+    pub fn rename_context(&mut self, context_id: &str, new_name: &str) -> Result<()> {
+        Self::validate_input(new_name)?;
+        self.ensure_not_busy(context_id)?;
+
+        if self
+            .persistent_contexts
+            .values()
+            .any(|ctx| ctx.id != context_id && ctx.name == new_name)
+        {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "A context named '{}' already exists",
+                new_name
+            )));
+        }
+
+        let context = self
+            .persistent_contexts
+            .get_mut(context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+        context.name = new_name.to_string();
+        context.updated_at = chrono::Utc::now();
+        self.save_contexts_metadata()
+    }
+
+    /// Mark a persistent context as read-only, or make it editable again
+    ///
+    /// Used for curated contexts shared with a team, adding data points to a read-only context
+    /// fails with [`SemanticSearchError::ReadOnly`].
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context
+    /// * `read_only` - Whether to refuse changes to the data points
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure
+    pub fn set_context_read_only(&mut self, context_id: &str, read_only: bool) -> Result<()> {
+        self.ensure_not_busy(context_id)?;
+
+        let context = self
+            .persistent_contexts
+            .get_mut(context_id)
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+        context.read_only = read_only;
+        context.updated_at = chrono::Utc::now();
+
+        if let Some(semantic_context) = self.volatile_contexts.get(context_id) {
+            semantic_context
+                .lock()
+                .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?
+                .set_read_only(read_only);
+        }
+
+        self.save_contexts_metadata()
+    }
+
+    /// Delete a context and every file stored for it
+    ///
+    /// Deleting a context that doesn't exist succeeds without touching any files. The files are
+    /// removed before the context is forgotten, so a delete that was interrupted can be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `context_id` - ID of the context to delete
+    ///
+    /// # Returns
+    ///
+    /// Result indicating success or failure, fails if the context is in use by another operation
+    /// or the ID isn't a plain directory name
+    pub fn delete_context(&mut self, context_id: &str) -> Result<()> {
+        if !is_valid_context_id(context_id) {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "Invalid context ID: {:?}",
+                context_id
+            )));
+        }
+        if !self.volatile_contexts.contains_key(context_id) && !self.persistent_contexts.contains_key(context_id) {
+            return Ok(());
+        }
+        self.ensure_not_busy(context_id)?;
+
+        for dir in [
+            self.base_dir.join(context_id),
+            utils::volatile_context_directory(context_id),
+        ] {
+            match fs::remove_dir_all(&dir) {
+                Ok(()) => (),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        self.volatile_contexts.remove(context_id);
+        if self.persistent_contexts.remove(context_id).is_some() {
+            self.save_contexts_metadata()?;
+        }
+
+        Ok(())
+    }
+
+    /// Fail if another operation holds the lock on the context's index
+    fn ensure_not_busy(&self, context_id: &str) -> Result<()> {
+        let Some(context) = self.volatile_contexts.get(context_id) else {
+            return Ok(());
+        };
+        match context.try_lock() {
+            Ok(_) | Err(std::sync::TryLockError::Poisoned(_)) => Ok(()),
+            Err(std::sync::TryLockError::WouldBlock) => Err(SemanticSearchError::OperationFailed(format!(
+                "Context {} is in use by another operation",
+                context_id
+            ))),
+        }
+    }
+
     /// Load a persistent context
     ///
     /// # Arguments
@@ -1071,7 +1419,8 @@ impl SemanticSearchClient {
         }
 
         // Create a new semantic context
-        let semantic_context = SemanticContext::new(context_dir.join("data.json"))?;
+        let mut semantic_context = SemanticContext::new(context_dir.join("data.json"))?;
+        semantic_context.set_read_only(self.persistent_contexts[context_id].read_only);
 
         // Store the semantic context
         self.volatile_contexts
@@ -1080,9 +1429,367 @@ impl SemanticSearchClient {
         Ok(())
     }
 
+    /// Report a finished search to the observer
+    fn observe_search<T>(
+        &self,
+        started: Instant,
+        context_id: Option<&str>,
+        result: Result<T>,
+        result_count: impl FnOnce(&T) -> usize,
+    ) -> Result<T> {
+        match &result {
+            Ok(results) => self
+                .observer
+                .on_search(started.elapsed(), context_id, result_count(results)),
+            Err(err) => self.observer.on_error("search", err),
+        }
+        result
+    }
+
+    /// Report a finished build to the observer
+    fn observe_build(&self, started: Instant, result: Result<ContextId>) -> Result<ContextId> {
+        match &result {
+            Ok(context_id) => {
+                let item_count = self
+                    .volatile_contexts
+                    .get(context_id)
+                    .and_then(|context| context.lock().ok().map(|context| context.get_data_points().len()))
+                    .unwrap_or_default();
+                self.observer.on_build(started.elapsed(), context_id, item_count);
+            },
+            Err(err) => self.observer.on_error("build", err),
+        }
+        result
+    }
+
     /// Save contexts metadata to disk
     fn save_contexts_metadata(&self) -> Result<()> {
         let contexts_file = self.base_dir.join("contexts.json");
         utils::save_json_to_file(&contexts_file, &self.persistent_contexts)
     }
 }
+
+/// Whether `context_id` names a single directory, so joining it to a path can't escape that path
+fn is_valid_context_id(context_id: &str) -> bool {
+    let mut components = Path::new(context_id).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(_)), None)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+    use std::time::Duration;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        searches: Mutex<Vec<(Option<String>, usize)>>,
+        builds: Mutex<Vec<(String, usize)>>,
+        errors: Mutex<Vec<String>>,
+    }
+
+    impl Observer for RecordingObserver {
+        fn on_search(&self, _duration: Duration, context_id: Option<&str>, result_count: usize) {
+            self.searches
+                .lock()
+                .unwrap()
+                .push((context_id.map(str::to_string), result_count));
+        }
+
+        fn on_build(&self, _duration: Duration, context_id: &str, item_count: usize) {
+            self.builds.lock().unwrap().push((context_id.to_string(), item_count));
+        }
+
+        fn on_error(&self, operation: &str, _error: &SemanticSearchError) {
+            self.errors.lock().unwrap().push(operation.to_string());
+        }
+    }
+
+    #[test]
+    fn test_observer_receives_search_and_build_events() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut client = SemanticSearchClient::with_config_and_embedding_type(
+            temp_dir.path(),
+            config::SemanticSearchConfig::default(),
+            EmbeddingType::Mock,
+        )
+        .unwrap();
+
+        let observer = Arc::new(RecordingObserver::default());
+        client.set_observer(observer.clone());
+
+        let context_id = client
+            .add_context_from_text("Observers see every search", "Notes", "Test notes", false)
+            .unwrap();
+        assert_eq!(*observer.builds.lock().unwrap(), vec![(context_id.clone(), 1)]);
+
+        let results = client.search_context(&context_id, "search", Some(5)).unwrap();
+        assert_eq!(*observer.searches.lock().unwrap(), vec![(
+            Some(context_id),
+            results.len()
+        )]);
+
+        assert!(client.search_context("missing", "search", None).is_err());
+        assert_eq!(observer.searches.lock().unwrap().len(), 1);
+        assert_eq!(*observer.errors.lock().unwrap(), vec!["search".to_string()]);
+    }
+
+    fn mock_client(base_dir: &Path) -> SemanticSearchClient {
+        SemanticSearchClient::with_config_and_embedding_type(
+            base_dir,
+            config::SemanticSearchConfig::default(),
+            EmbeddingType::Mock,
+        )
+        .unwrap()
+    }
+
+    fn saved_context_names(base_dir: &Path) -> HashMap<ContextId, String> {
+        let contexts: HashMap<ContextId, KnowledgeContext> =
+            serde_json::from_str(&fs::read_to_string(base_dir.join("contexts.json")).unwrap()).unwrap();
+        contexts.into_iter().map(|(id, ctx)| (id, ctx.name)).collect()
+    }
+
+    #[test]
+    fn test_rename_and_delete_context() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_dir = temp_dir.path();
+        let mut client = mock_client(base_dir);
+
+        let id = client
+            .add_context_from_text("Notes to rename", "Old name", "Test notes", true)
+            .unwrap();
+        let other_id = client
+            .add_context_from_text("Notes to keep", "Other", "Test notes", true)
+            .unwrap();
+        assert!(base_dir.join(&id).join("data.json").exists());
+
+        client.rename_context(&id, "New name").unwrap();
+        assert_eq!(saved_context_names(base_dir)[&id], "New name");
+        let renamed = client.get_contexts().into_iter().find(|ctx| ctx.id == id).unwrap();
+        assert_eq!(renamed.name, "New name");
+        assert!(base_dir.join(&id).join("data.json").exists());
+        assert!(matches!(
+            client.rename_context(&id, "Other"),
+            Err(SemanticSearchError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            client.rename_context("missing", "Name"),
+            Err(SemanticSearchError::ContextNotFound(_))
+        ));
+
+        client.delete_context(&id).unwrap();
+        assert!(!base_dir.join(&id).exists());
+        assert!(!saved_context_names(base_dir).contains_key(&id));
+        assert!(client.search_context(&id, "notes", None).is_err());
+        assert_eq!(client.get_contexts().len(), 1);
+
+        // Deleting again is a no-op
+        client.delete_context(&id).unwrap();
+        assert!(base_dir.join(&other_id).join("data.json").exists());
+        assert_eq!(saved_context_names(base_dir).len(), 1);
+    }
+
+    #[test]
+    fn test_delete_context_rejects_paths() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_dir = temp_dir.path().join("knowledge");
+        let mut client = mock_client(&base_dir);
+        let id = client
+            .add_context_from_text("Notes to keep", "Keep", "Test notes", true)
+            .unwrap();
+
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        for context_id in ["", "..", ".", "../outside", outside.to_str().unwrap(), "a/b"] {
+            assert!(
+                matches!(
+                    client.delete_context(context_id),
+                    Err(SemanticSearchError::InvalidArgument(_))
+                ),
+                "{context_id:?} should be rejected"
+            );
+        }
+        assert!(outside.exists());
+        assert!(base_dir.join(&id).join("data.json").exists());
+
+        // An unknown ID doesn't touch the filesystem
+        let unknown = base_dir.join("unknown");
+        fs::create_dir(&unknown).unwrap();
+        client.delete_context("unknown").unwrap();
+        assert!(unknown.exists());
+    }
+
+    #[test]
+    fn test_delete_volatile_context_removes_temp_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut client = mock_client(temp_dir.path());
+
+        let id = client
+            .add_context_from_text("Short lived notes", "Volatile", "Test notes", false)
+            .unwrap();
+        assert!(utils::volatile_context_directory(&id).exists());
+
+        client.delete_context(&id).unwrap();
+        assert!(!utils::volatile_context_directory(&id).exists());
+        assert!(client.get_all_contexts().is_empty());
+    }
+
+    #[test]
+    fn test_busy_context_is_not_changed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_dir = temp_dir.path();
+        let mut client = mock_client(base_dir);
+
+        let id = client
+            .add_context_from_text("Notes being indexed", "Busy", "Test notes", true)
+            .unwrap();
+        let context = Arc::clone(&client.volatile_contexts[&id]);
+        let guard = context.lock().unwrap();
+
+        assert!(matches!(
+            client.delete_context(&id),
+            Err(SemanticSearchError::OperationFailed(_))
+        ));
+        assert!(matches!(
+            client.rename_context(&id, "Renamed"),
+            Err(SemanticSearchError::OperationFailed(_))
+        ));
+        assert!(base_dir.join(&id).join("data.json").exists());
+        assert_eq!(saved_context_names(base_dir)[&id], "Busy");
+
+        drop(guard);
+        client.delete_context(&id).unwrap();
+        assert!(!base_dir.join(&id).exists());
+    }
+
+    #[test]
+    fn test_read_only_context_refuses_changes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base_dir = temp_dir.path();
+        let mut client = mock_client(base_dir);
+
+        let id = client
+            .add_context_from_text("Curated team notes", "Shared", "Test notes", true)
+            .unwrap();
+        client.set_context_read_only(&id, true).unwrap();
+
+        let add_point = |client: &SemanticSearchClient| {
+            let point = client.create_data_point_from_text("Local edit", 1).unwrap();
+            client.volatile_contexts[&id]
+                .lock()
+                .unwrap()
+                .add_data_points(vec![point])
+        };
+        assert!(matches!(add_point(&client), Err(SemanticSearchError::ReadOnly(_))));
+        assert_eq!(client.volatile_contexts[&id].lock().unwrap().get_data_points().len(), 1);
+
+        // The flag survives reloading the contexts from disk
+        let mut client = mock_client(base_dir);
+        assert!(client.get_contexts()[0].read_only);
+        assert!(matches!(add_point(&client), Err(SemanticSearchError::ReadOnly(_))));
+
+        client.set_context_read_only(&id, false).unwrap();
+        assert_eq!(add_point(&client).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_query_expansion_finds_expanded_form() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut config = config::SemanticSearchConfig::default();
+        config.query_expansion = config::QueryExpansionConfig {
+            enabled: true,
+            synonyms: HashMap::from([("k8s".to_string(), vec!["kubernetes".to_string()])]),
+            max_pool: true,
+        };
+        let mut client =
+            SemanticSearchClient::with_config_and_embedding_type(temp_dir.path(), config, EmbeddingType::Mock).unwrap();
+
+        let kubernetes_id = client
+            .add_context_from_text("kubernetes", "Cluster", "Test notes", false)
+            .unwrap();
+        client
+            .add_context_from_text("terraform", "Infra", "Test notes", false)
+            .unwrap();
+
+        let results = client.search_context(&kubernetes_id, "k8s", Some(1)).unwrap();
+        assert_eq!(results[0].text(), Some("kubernetes"));
+        assert!(results[0].distance < 1e-4);
+
+        let (best_context, results) = client.search_all("k8s", Some(1)).unwrap().remove(0);
+        assert_eq!(best_context, kubernetes_id);
+        assert_eq!(results[0].text(), Some("kubernetes"));
+
+        // Without expansion only the literal query is embedded
+        client.config.query_expansion.enabled = false;
+        let results = client.search_context(&kubernetes_id, "k8s", Some(1)).unwrap();
+        assert!(results[0].distance > 1e-4);
+    }
+
+    struct CountingEmbedder {
+        inner: crate::embedding::MockTextEmbedder,
+        embed_calls: Arc<AtomicUsize>,
+        batch_calls: Arc<AtomicUsize>,
+    }
+
+    impl TextEmbedderTrait for CountingEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            self.embed_calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.embed(text)
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.batch_calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.embed_batch(texts)
+        }
+    }
+
+    #[test]
+    fn test_search_batch_embeds_queries_once() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut client = mock_client(temp_dir.path());
+
+        let id = client
+            .add_context_from_text("alpha", "Notes", "Test notes", false)
+            .unwrap();
+        let points = ["beta", "gamma"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| client.create_data_point_from_text(text, i + 1).unwrap())
+            .collect();
+        client.volatile_contexts[&id]
+            .lock()
+            .unwrap()
+            .add_data_points(points)
+            .unwrap();
+
+        let embed_calls = Arc::new(AtomicUsize::new(0));
+        let batch_calls = Arc::new(AtomicUsize::new(0));
+        client.embedder = Arc::new(CountingEmbedder {
+            inner: crate::embedding::MockTextEmbedder::new(384),
+            embed_calls: Arc::clone(&embed_calls),
+            batch_calls: Arc::clone(&batch_calls),
+        });
+
+        let queries = ["gamma", "alpha", "beta"].map(str::to_string);
+        let results = client.search_batch(&id, &queries, 2).unwrap();
+        assert_eq!(embed_calls.load(Ordering::SeqCst), 0);
+        assert_eq!(batch_calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(results.len(), queries.len());
+        for (query, results) in queries.iter().zip(&results) {
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].text(), Some(query.as_str()));
+            assert_eq!(results[0].context_id.as_deref(), Some(id.as_str()));
+        }
+
+        assert!(client.search_batch(&id, &[], 2).unwrap().is_empty());
+        assert_eq!(batch_calls.load(Ordering::SeqCst), 1);
+    }
+}
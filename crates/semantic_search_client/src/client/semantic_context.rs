@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::{
     self,
     File,
@@ -7,11 +8,21 @@ use std::io::{
     BufWriter,
 };
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
-use crate::error::Result;
-use crate::index::VectorIndex;
+use crate::config;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::index::{
+    IndexOptions,
+    VectorIndex,
+    VectorStorage,
+};
 use crate::types::{
     DataPoint,
+    ScoredResult,
     SearchResult,
 };
 
@@ -23,11 +34,30 @@ pub struct SemanticContext {
     index: Option<VectorIndex>,
     /// Path to save/load the data points
     data_path: PathBuf,
+    /// Whether changes to the data points are refused
+    read_only: bool,
+    /// Options the index is built with
+    options: IndexOptions,
+    /// Number of leading data points whose vectors were released from memory
+    ///
+    /// With [`VectorStorage::Int8`] the index holds its own quantized copy, so the full precision
+    /// vectors of saved data points are only kept on disk and read back when needed.
+    vectors_on_disk: usize,
+    /// The released vectors, read from disk the first time they're needed and kept from then on
+    released_vectors: OnceLock<Vec<Vec<f32>>>,
 }
 
 impl SemanticContext {
-    /// Create a new semantic context
+    /// Create a new semantic context using the index options from the global configuration
     pub fn new(data_path: PathBuf) -> Result<Self> {
+        let options = config::try_get_config()
+            .map(|config| config.index_options())
+            .unwrap_or_default();
+        Self::with_index_options(data_path, options)
+    }
+
+    /// Create a new semantic context whose index is built with `options`
+    pub fn with_index_options(data_path: PathBuf, options: IndexOptions) -> Result<Self> {
         // Create the directory if it doesn't exist
         if let Some(parent) = data_path.parent() {
             fs::create_dir_all(parent)?;
@@ -38,6 +68,10 @@ impl SemanticContext {
             data_points: Vec::new(),
             index: None,
             data_path: data_path.clone(),
+            read_only: false,
+            options,
+            vectors_on_disk: 0,
+            released_vectors: OnceLock::new(),
         };
 
         // Load data points if the file exists
@@ -50,29 +84,51 @@ impl SemanticContext {
         // If we have data points, rebuild the index
         if !context.data_points.is_empty() {
             context.rebuild_index()?;
+            context.release_saved_vectors();
         }
 
         Ok(context)
     }
 
     /// Save data points to disk
-    pub fn save(&self) -> Result<()> {
+    pub fn save(&mut self) -> Result<()> {
+        let data_points = self.saved_data_points()?;
+
         // Save the data points as JSON
         let file = File::create(&self.data_path)?;
         let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, &self.data_points)?;
+        serde_json::to_writer(writer, &data_points)?;
 
+        self.release_saved_vectors();
         Ok(())
     }
 
+    /// The data points with every vector, as they are saved to disk
+    pub fn saved_data_points(&self) -> Result<Vec<DataPoint>> {
+        // Released vectors are read back from the previous save
+        let mut released = self.load_released_vectors()?.iter().cloned();
+        Ok(self
+            .data_points
+            .iter()
+            .map(|point| match released.next() {
+                Some(vector) => DataPoint {
+                    vector,
+                    ..point.clone()
+                },
+                None => point.clone(),
+            })
+            .collect())
+    }
+
     /// Rebuild the index from the current data points
     pub fn rebuild_index(&mut self) -> Result<()> {
         // Create a new index with the current data points
-        let index = VectorIndex::new(self.data_points.len().max(100));
+        let index = VectorIndex::with_options(self.data_points.len().max(100), self.options);
 
         // Add all data points to the index
+        let released = self.load_released_vectors()?;
         for (i, point) in self.data_points.iter().enumerate() {
-            index.insert(&point.vector, i);
+            index.insert(released.get(i).unwrap_or(&point.vector), i);
         }
 
         // Set the new index
@@ -81,8 +137,73 @@ impl SemanticContext {
         Ok(())
     }
 
-    /// Add data points to the context
+    /// Drop the in-memory vectors of every saved data point when the index quantizes them
+    fn release_saved_vectors(&mut self) {
+        if self.options.storage != VectorStorage::Int8 {
+            return;
+        }
+
+        // Once read, the cache is kept in step with the file, otherwise it's read on first use
+        let mut released = self.released_vectors.get_mut();
+        for point in &mut self.data_points[self.vectors_on_disk..] {
+            let vector = std::mem::take(&mut point.vector);
+            if let Some(released) = &mut released {
+                released.push(vector);
+            }
+        }
+        self.vectors_on_disk = self.data_points.len();
+    }
+
+    /// The vectors released by [`Self::release_saved_vectors`], in order
+    ///
+    /// The saved data points are parsed at most once, later calls reuse the vectors read then.
+    fn load_released_vectors(&self) -> Result<&[Vec<f32>]> {
+        /// A saved data point without its payload
+        #[derive(serde::Deserialize)]
+        struct SavedVector {
+            vector: Vec<f32>,
+        }
+
+        if self.vectors_on_disk == 0 {
+            return Ok(&[]);
+        }
+        if let Some(released) = self.released_vectors.get() {
+            return Ok(released);
+        }
+
+        let reader = BufReader::new(File::open(&self.data_path)?);
+        let mut vectors = serde_json::from_reader::<_, Vec<SavedVector>>(reader)?
+            .into_iter()
+            .map(|saved| saved.vector)
+            .collect::<Vec<_>>();
+        if vectors.len() < self.vectors_on_disk {
+            return Err(SemanticSearchError::OperationFailed(format!(
+                "{} has {} vectors, expected at least {}",
+                self.data_path.display(),
+                vectors.len(),
+                self.vectors_on_disk
+            )));
+        }
+
+        vectors.truncate(self.vectors_on_disk);
+        Ok(self.released_vectors.get_or_init(|| vectors))
+    }
+
+    /// Refuse or allow changes to the data points
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Add data points to the context, fails if the context is read-only
     pub fn add_data_points(&mut self, data_points: Vec<DataPoint>) -> Result<usize> {
+        if self.read_only {
+            return Err(SemanticSearchError::ReadOnly(self.data_path.display().to_string()));
+        }
+
         // Store the count before extending the data points
         let count = data_points.len();
 
@@ -122,6 +243,10 @@ impl SemanticContext {
     }
 
     /// Search for similar items to the given vector
+    ///
+    /// With [`VectorStorage::Int8`] the vectors of saved data points aren't kept in memory, so
+    /// [`SearchResult::point`] has an empty vector for them. Use [`Self::search_explain`] or
+    /// [`Self::saved_data_points`] when the vectors are needed.
     pub fn search(&self, query_vector: &[f32], limit: usize) -> Result<Vec<SearchResult>> {
         let index = match &self.index {
             Some(idx) => idx,
@@ -129,7 +254,7 @@ impl SemanticContext {
         };
 
         // Search for the nearest neighbors
-        let results = index.search(query_vector, limit, 100);
+        let results = index.search_default(query_vector, limit);
 
         // Convert the results to our SearchResult type
         let search_results = results
@@ -143,8 +268,75 @@ impl SemanticContext {
         Ok(search_results)
     }
 
+    /// Search with several query vectors, keeping the best distance of each data point
+    pub fn search_pooled(&self, query_vectors: &[Vec<f32>], limit: usize) -> Result<Vec<SearchResult>> {
+        if let [query_vector] = query_vectors {
+            return self.search(query_vector, limit);
+        }
+
+        let mut best: HashMap<usize, SearchResult> = HashMap::new();
+        for query_vector in query_vectors {
+            for result in self.search(query_vector, limit)? {
+                match best.get(&result.point.id) {
+                    Some(existing) if existing.distance <= result.distance => (),
+                    _ => {
+                        best.insert(result.point.id, result);
+                    },
+                }
+            }
+        }
+
+        let mut results = best.into_values().collect::<Vec<_>>();
+        results.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.point.id.cmp(&b.point.id)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    /// Search for similar items and explain how each result was scored
+    ///
+    /// Candidates come from the index as in [`Self::search`], but the similarity is recomputed
+    /// exactly on the stored vectors and results are ordered by their final score.
+    pub fn search_explain(&self, query_vector: &[f32], limit: usize) -> Result<Vec<ScoredResult>> {
+        let index = match &self.index {
+            Some(idx) => idx,
+            None => return Ok(Vec::new()),
+        };
+        let released = self.load_released_vectors()?;
+
+        let mut scored = index
+            .search_default(query_vector, limit)
+            .into_iter()
+            .map(|(id, distance)| {
+                let mut point = self.data_points[id].clone();
+                if let Some(vector) = released.get(id) {
+                    point.vector.clone_from(vector);
+                }
+                let similarity = cosine_similarity(query_vector, &point.vector);
+                ScoredResult::new(SearchResult::new(point, distance), similarity, None, Vec::new())
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(scored)
+    }
+
     /// Get the data points for serialization
+    ///
+    /// Vectors released to disk with [`VectorStorage::Int8`] are empty, use
+    /// [`Self::saved_data_points`] to get them with their vectors.
     pub fn get_data_points(&self) -> &Vec<DataPoint> {
         &self.data_points
     }
 }
+
+/// Cosine similarity of two vectors, `0.0` if either has no magnitude
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
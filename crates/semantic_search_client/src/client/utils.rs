@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::fs;
 use std::path::{
     Path,
@@ -7,7 +8,11 @@ use std::path::{
 use uuid::Uuid;
 
 use crate::error::Result;
-use crate::types::ProgressStatus;
+use crate::processing::walk_files;
+use crate::types::{
+    KnowledgeContext,
+    ProgressStatus,
+};
 
 /// Create a context directory based on persistence setting
 ///
@@ -27,7 +32,7 @@ pub fn create_context_directory(base_dir: &Path, id: &str, persistent: bool) ->
         context_dir
     } else {
         // For volatile contexts, use a temporary directory
-        let temp_dir = std::env::temp_dir().join("memory_bank").join(id);
+        let temp_dir = volatile_context_directory(id);
         fs::create_dir_all(&temp_dir)?;
         temp_dir
     };
@@ -35,6 +40,11 @@ pub fn create_context_directory(base_dir: &Path, id: &str, persistent: bool) ->
     Ok(context_dir)
 }
 
+/// The temporary directory holding the files of a volatile context
+pub fn volatile_context_directory(id: &str) -> PathBuf {
+    std::env::temp_dir().join("memory_bank").join(id)
+}
+
 /// Generate a unique context ID
 ///
 /// # Returns
@@ -64,28 +74,7 @@ where
     }
 
     // Count files first to provide progress information
-    let mut file_count = 0;
-    for entry in walkdir::WalkDir::new(dir_path)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Skip hidden files
-        if path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .is_some_and(|s| s.starts_with('.'))
-        {
-            continue;
-        }
-
-        file_count += 1;
-    }
-
-    Ok(file_count)
+    Ok(walk_files(dir_path).count())
 }
 
 /// Save JSON data to a file
@@ -121,3 +110,80 @@ pub fn load_json_from_file<T: serde::de::DeserializeOwned + Default>(path: &Path
         Ok(T::default())
     }
 }
+
+/// Render contexts as a `tree`-style listing, nesting each context under its parent
+///
+/// Children are sorted by name. Contexts whose parent is unknown are shown at the top level.
+///
+/// # Arguments
+///
+/// * `contexts` - The contexts to render
+///
+/// # Returns
+///
+/// The rendered tree, one context per line
+pub fn render_context_tree(contexts: &[KnowledgeContext]) -> String {
+    let mut sorted = contexts.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+
+    let is_root = |context: &KnowledgeContext| {
+        context
+            .parent_id
+            .as_ref()
+            .is_none_or(|parent_id| parent_id == &context.id || !contexts.iter().any(|c| &c.id == parent_id))
+    };
+
+    let mut output = String::from(".\n");
+    let mut visited = HashSet::new();
+    let roots = sorted
+        .iter()
+        .copied()
+        .filter(|context| is_root(context))
+        .collect::<Vec<_>>();
+    render_context_children(&roots, &sorted, "", &mut visited, &mut output);
+
+    // Contexts that only reference each other have no root, list them at the top level
+    let unvisited = sorted
+        .iter()
+        .copied()
+        .filter(|context| !visited.contains(context.id.as_str()))
+        .collect::<Vec<_>>();
+    render_context_children(&unvisited, &sorted, "", &mut visited, &mut output);
+
+    output
+}
+
+fn render_context_children<'a>(
+    children: &[&'a KnowledgeContext],
+    contexts: &[&'a KnowledgeContext],
+    prefix: &str,
+    visited: &mut HashSet<&'a str>,
+    output: &mut String,
+) {
+    let children = children
+        .iter()
+        .filter(|context| !visited.contains(context.id.as_str()))
+        .collect::<Vec<_>>();
+    for (i, context) in children.iter().enumerate() {
+        if !visited.insert(context.id.as_str()) {
+            continue;
+        }
+
+        let last = i + 1 == children.len();
+        let items = if context.item_count == 1 { "item" } else { "items" };
+        output.push_str(&format!(
+            "{prefix}{} {} ({} {items})\n",
+            if last { "└──" } else { "├──" },
+            context.name,
+            context.item_count
+        ));
+
+        let grandchildren = contexts
+            .iter()
+            .copied()
+            .filter(|c| c.id != context.id && c.parent_id.as_deref() == Some(context.id.as_str()))
+            .collect::<Vec<_>>();
+        let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+        render_context_children(&grandchildren, contexts, &prefix, visited, output);
+    }
+}
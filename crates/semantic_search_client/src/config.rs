@@ -4,6 +4,7 @@
 //! It supports loading configuration from a JSON file and provides default values.
 //! It also manages model paths and directory structure.
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::{
     Path,
@@ -16,6 +17,14 @@ use serde::{
     Serialize,
 };
 
+use crate::index::{
+    HnswParams,
+    IndexOptions,
+    IndexType,
+    VectorStorage,
+};
+use crate::processing::TokenizerConfig;
+
 /// Main configuration structure for the semantic search client.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticSearchConfig {
@@ -39,6 +48,112 @@ pub struct SemanticSearchConfig {
 
     /// Maximum number of files allowed for indexing (default: 5000)
     pub max_files: usize,
+
+    /// How vectors are stored in the search index (default: f32)
+    #[serde(default)]
+    pub vector_storage: VectorStorage,
+
+    /// Search structure used by the index (default: brute_force)
+    #[serde(default)]
+    pub index_type: IndexType,
+
+    /// HNSW build and query parameters, used when `index_type` is hnsw
+    #[serde(default)]
+    pub hnsw: HnswParams,
+
+    /// Number of texts embedded per batch while indexing (default: 32)
+    #[serde(default = "default_embedding_batch_size")]
+    pub embedding_batch_size: usize,
+
+    /// Maximum number of embedding batches in flight while indexing (default: 2)
+    #[serde(default = "default_embedding_concurrency")]
+    pub embedding_concurrency: usize,
+
+    /// Stop-word and stemming settings for keyword (BM25) embeddings (default: none)
+    #[serde(default)]
+    pub tokenizer: TokenizerConfig,
+
+    /// Synonyms and acronyms added to search queries (default: disabled)
+    #[serde(default)]
+    pub query_expansion: QueryExpansionConfig,
+
+    /// Build indexes that give identical search output for the same context, for tests and
+    /// reproducing a ranking (default: false)
+    ///
+    /// The HNSW graph can't be built deterministically, so this searches with an exact scan
+    /// instead, whatever `index_type` is.
+    #[serde(default)]
+    pub deterministic: bool,
+}
+
+/// Expansion of search queries with user supplied synonyms and acronyms
+///
+/// Short queries like "k8s" miss documents that only use the expanded form, so each term found in
+/// `synonyms` is expanded before the query is embedded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueryExpansionConfig {
+    /// Whether queries are expanded (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Expansions of a lowercase term, for example `"k8s": ["kubernetes"]`
+    #[serde(default)]
+    pub synonyms: HashMap<String, Vec<String>>,
+
+    /// Embed a query per expansion and keep the best score of each result, instead of embedding
+    /// one query with the expansions appended (default: false)
+    #[serde(default)]
+    pub max_pool: bool,
+}
+
+impl QueryExpansionConfig {
+    /// The queries to embed for `query`, just `query` when expansion is disabled or no term has
+    /// an expansion
+    pub fn expand(&self, query: &str) -> Vec<String> {
+        if !self.enabled {
+            return vec![query.to_string()];
+        }
+
+        let terms = query.split_whitespace().collect::<Vec<_>>();
+        let expansions = terms
+            .iter()
+            .enumerate()
+            .flat_map(|(i, term)| {
+                let key = term.trim_matches(|c: char| c.is_ascii_punctuation()).to_lowercase();
+                self.synonyms
+                    .get(&key)
+                    .into_iter()
+                    .flatten()
+                    .map(move |expansion| (i, expansion.as_str()))
+            })
+            .filter(|(_, expansion)| !terms.iter().any(|term| term.eq_ignore_ascii_case(expansion)))
+            .collect::<Vec<_>>();
+
+        if expansions.is_empty() {
+            return vec![query.to_string()];
+        }
+
+        if self.max_pool {
+            let mut queries = vec![query.to_string()];
+            queries.extend(expansions.iter().map(|(i, expansion)| {
+                let mut terms = terms.clone();
+                terms[*i] = *expansion;
+                terms.join(" ")
+            }));
+            queries
+        } else {
+            let appended = expansions.iter().map(|(_, expansion)| *expansion).collect::<Vec<_>>();
+            vec![format!("{} {}", query, appended.join(" "))]
+        }
+    }
+}
+
+fn default_embedding_batch_size() -> usize {
+    32
+}
+
+fn default_embedding_concurrency() -> usize {
+    2
 }
 
 impl SemanticSearchConfig {
@@ -71,6 +186,16 @@ impl SemanticSearchConfig {
         self.max_files = max_files;
         self
     }
+
+    /// Options for creating a vector index from this configuration
+    pub fn index_options(&self) -> IndexOptions {
+        IndexOptions {
+            index_type: self.index_type,
+            storage: self.vector_storage,
+            hnsw: self.hnsw,
+            deterministic: self.deterministic,
+        }
+    }
 }
 
 impl Default for SemanticSearchConfig {
@@ -83,6 +208,14 @@ impl Default for SemanticSearchConfig {
             timeout: 30000, // 30 seconds
             base_dir: get_default_base_dir(),
             max_files: 5000, // Default limit of 5000 files
+            vector_storage: VectorStorage::default(),
+            index_type: IndexType::default(),
+            hnsw: HnswParams::default(),
+            embedding_batch_size: default_embedding_batch_size(),
+            embedding_concurrency: default_embedding_concurrency(),
+            tokenizer: TokenizerConfig::default(),
+            query_expansion: QueryExpansionConfig::default(),
+            deterministic: false,
         }
     }
 }
@@ -192,6 +325,15 @@ pub fn get_config() -> &'static SemanticSearchConfig {
     CONFIG.get().expect("Semantic search configuration not initialized")
 }
 
+/// Gets a reference to the global configuration, if it has been initialized.
+///
+/// # Returns
+///
+/// `None` when [`init_config`] has not been called yet
+pub fn try_get_config() -> Option<&'static SemanticSearchConfig> {
+    CONFIG.get()
+}
+
 /// Loads the configuration from a file or creates a new one with default values.
 ///
 /// # Arguments
@@ -333,6 +475,17 @@ mod tests {
             timeout: 30000,
             base_dir: temp_dir.path().to_path_buf(),
             max_files: 10000,
+            vector_storage: VectorStorage::Int8,
+            index_type: IndexType::BruteForce,
+            hnsw: HnswParams {
+                ef_search: 64,
+                ..Default::default()
+            },
+            embedding_batch_size: 8,
+            embedding_concurrency: 4,
+            tokenizer: Default::default(),
+            query_expansion: Default::default(),
+            deterministic: false,
         };
 
         // Update the config
@@ -347,6 +500,11 @@ mod tests {
         assert_eq!(loaded_config.chunk_overlap, 256);
         assert_eq!(loaded_config.default_results, 10);
         assert_eq!(loaded_config.model_name, "different-model");
+        assert_eq!(loaded_config.vector_storage, VectorStorage::Int8);
+        assert_eq!(loaded_config.index_type, IndexType::BruteForce);
+        assert_eq!(loaded_config.hnsw.ef_search, 64);
+        assert_eq!(loaded_config.embedding_batch_size, 8);
+        assert_eq!(loaded_config.embedding_concurrency, 4);
     }
 
     #[test]
@@ -380,4 +538,34 @@ mod tests {
         assert!(models_dir.exists());
         assert!(models_dir.is_dir());
     }
+
+    #[test]
+    fn test_query_expansion() {
+        let mut expansion = QueryExpansionConfig {
+            enabled: false,
+            synonyms: HashMap::from([
+                ("k8s".to_string(), vec!["kubernetes".to_string()]),
+                ("auth".to_string(), vec![
+                    "authentication".to_string(),
+                    "authorization".to_string(),
+                ]),
+            ]),
+            max_pool: false,
+        };
+        assert_eq!(expansion.expand("k8s auth"), vec!["k8s auth"]);
+
+        expansion.enabled = true;
+        assert_eq!(expansion.expand("K8s auth?"), vec![
+            "K8s auth? kubernetes authentication authorization"
+        ]);
+        assert_eq!(expansion.expand("deploy pods"), vec!["deploy pods"]);
+        assert_eq!(expansion.expand("k8s kubernetes"), vec!["k8s kubernetes"]);
+
+        expansion.max_pool = true;
+        assert_eq!(expansion.expand("auth setup"), vec![
+            "auth setup",
+            "authentication setup",
+            "authorization setup"
+        ]);
+    }
 }
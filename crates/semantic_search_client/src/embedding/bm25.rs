@@ -10,8 +10,13 @@ use tracing::{
     info,
 };
 
+use crate::config;
 use crate::embedding::benchmark_utils::BenchmarkableEmbedder;
 use crate::error::Result;
+use crate::processing::{
+    Tokenizer,
+    TokenizerConfig,
+};
 
 /// BM25 Text Embedder implementation
 ///
@@ -27,11 +32,21 @@ pub struct BM25TextEmbedder {
     embedder: Arc<Embedder>,
     /// Vector dimension (fixed size for compatibility with other embedders)
     dimension: usize,
+    /// Tokenizer applied to both indexed text and queries before embedding
+    tokenizer: Tokenizer,
 }
 
 impl BM25TextEmbedder {
-    /// Create a new BM25 text embedder
+    /// Create a new BM25 text embedder, using the configured tokenizer settings if available
     pub fn new() -> Result<Self> {
+        let tokenizer = config::try_get_config()
+            .map(|config| config.tokenizer)
+            .unwrap_or_default();
+        Self::with_tokenizer(tokenizer)
+    }
+
+    /// Create a new BM25 text embedder with explicit tokenizer settings
+    pub fn with_tokenizer(tokenizer: TokenizerConfig) -> Result<Self> {
         info!("Initializing BM25TextEmbedder with language detection");
 
         // Initialize with a small sample corpus to build the embedder
@@ -47,6 +62,7 @@ impl BM25TextEmbedder {
         Ok(Self {
             embedder: Arc::new(embedder),
             dimension: 384, // Match dimension of other embedders for compatibility
+            tokenizer: Tokenizer::new(tokenizer),
         })
     }
 
@@ -75,8 +91,8 @@ impl BM25TextEmbedder {
 
     /// Embed a text using BM25 algorithm
     pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        // Generate BM25 embedding
-        let embedding = self.embedder.embed(text);
+        // Generate BM25 embedding from the normalized tokens
+        let embedding = self.embedder.embed(&self.tokenizer.normalize(text));
 
         // Convert to dense vector
         let dense = self.sparse_to_dense(embedding);
@@ -209,4 +225,18 @@ mod tests {
         assert!((norm_es - 1.0).abs() < 1e-5 || norm_es == 0.0);
         assert!((norm_fr - 1.0).abs() < 1e-5 || norm_fr == 0.0);
     }
+
+    #[test]
+    fn test_bm25_tokenizer_applies_to_queries_and_documents() {
+        let embedder = BM25TextEmbedder::with_tokenizer(TokenizerConfig {
+            language: crate::processing::TokenizerLanguage::English,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // Stemming and stop-word removal make both texts embed to the same terms
+        let document = embedder.embed("the dog is running").unwrap();
+        let query = embedder.embed("dog run").unwrap();
+        assert_eq!(document, query);
+    }
 }
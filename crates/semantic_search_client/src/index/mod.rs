@@ -1,3 +1,9 @@
 mod vector_index;
 
-pub use vector_index::VectorIndex;
+pub use vector_index::{
+    HnswParams,
+    IndexOptions,
+    IndexType,
+    VectorIndex,
+    VectorStorage,
+};
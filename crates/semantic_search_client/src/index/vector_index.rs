@@ -1,14 +1,99 @@
+use std::sync::RwLock;
+
 use hnsw_rs::hnsw::Hnsw;
-use hnsw_rs::prelude::DistCosine;
+use hnsw_rs::prelude::{
+    DistCosine,
+    Distance,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use tracing::{
     debug,
     info,
 };
 
-/// Vector index for fast approximate nearest neighbor search
+/// How vectors are stored inside a [`VectorIndex`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorStorage {
+    /// Full precision vectors
+    #[default]
+    F32,
+    /// Vectors quantized to signed bytes, using roughly a quarter of the memory at a small cost
+    /// in recall
+    Int8,
+}
+
+/// Search structure backing a [`VectorIndex`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexType {
+    /// Approximate nearest neighbor search over an HNSW graph
+    Hnsw,
+    /// Exact linear scan over every stored vector, best suited to small contexts
+    #[default]
+    BruteForce,
+}
+
+/// Build and query parameters for the HNSW graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HnswParams {
+    /// Maximum number of connections per node and layer (M)
+    pub max_connections: usize,
+    /// Size of the candidate list while building the graph
+    pub ef_construction: usize,
+    /// Size of the candidate list while searching
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            max_connections: 16,
+            ef_construction: 100,
+            ef_search: 100,
+        }
+    }
+}
+
+/// Options used to create a [`VectorIndex`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexOptions {
+    /// Search structure to use
+    pub index_type: IndexType,
+    /// How vectors are stored
+    pub storage: VectorStorage,
+    /// Parameters for [`IndexType::Hnsw`], ignored by brute force
+    pub hnsw: HnswParams,
+    /// Whether search output must be reproducible, meant for tests
+    ///
+    /// The HNSW graph draws node levels from its own unseeded generator, so a deterministic index
+    /// is searched with an exact scan instead.
+    pub deterministic: bool,
+}
+
+/// Vectors kept by the brute-force backend, in the chosen [`VectorStorage`]
+enum StoredVectors {
+    F32(Vec<(usize, Vec<f32>)>),
+    Int8(Vec<(usize, Vec<i8>)>),
+}
+
+/// Search structure specialised for the chosen [`IndexType`] and [`VectorStorage`]
+enum Backend {
+    HnswF32(Hnsw<'static, f32, DistCosine>),
+    HnswInt8(Hnsw<'static, i8, DistCosineInt8>),
+    BruteForce(RwLock<StoredVectors>),
+}
+
+/// Vector index for nearest neighbor search
 pub struct VectorIndex {
-    /// The HNSW index
-    index: Hnsw<'static, f32, DistCosine>,
+    /// The search structure
+    index: Backend,
+    /// Options the index was created with
+    options: IndexOptions,
     /// Counter to track the number of elements
     count: std::sync::atomic::AtomicUsize,
 }
@@ -24,23 +109,95 @@ impl VectorIndex {
     ///
     /// A new VectorIndex instance
     pub fn new(max_elements: usize) -> Self {
-        info!("Creating new vector index with max_elements: {}", max_elements);
-
-        let index = Hnsw::new(
-            16,                    // Max number of connections per layer
-            max_elements.max(100), // Maximum elements
-            16,                    // Max layer
-            100,                   // ef_construction (size of the dynamic candidate list)
-            DistCosine {},
+        Self::with_options(max_elements, IndexOptions::default())
+    }
+
+    /// Create a new empty vector index using the given storage format
+    ///
+    /// # Arguments
+    ///
+    /// * `max_elements` - Maximum number of elements the index can hold
+    /// * `storage` - How vectors are stored in the index
+    ///
+    /// # Returns
+    ///
+    /// A new VectorIndex instance
+    pub fn with_storage(max_elements: usize, storage: VectorStorage) -> Self {
+        Self::with_options(max_elements, IndexOptions {
+            storage,
+            ..Default::default()
+        })
+    }
+
+    /// Create a new empty vector index with explicit options
+    ///
+    /// # Arguments
+    ///
+    /// * `max_elements` - Maximum number of elements the index can hold
+    /// * `options` - Index type, storage format and HNSW parameters
+    ///
+    /// # Returns
+    ///
+    /// A new VectorIndex instance
+    pub fn with_options(max_elements: usize, options: IndexOptions) -> Self {
+        info!(
+            "Creating new vector index with max_elements: {}, options: {:?}",
+            max_elements, options
         );
 
+        let max_elements = max_elements.max(100);
+        let HnswParams {
+            max_connections,
+            ef_construction,
+            ..
+        } = options.hnsw;
+        let index_type = if options.deterministic && options.index_type != IndexType::BruteForce {
+            info!("Deterministic index requested, using an exact scan instead of approximate search");
+            IndexType::BruteForce
+        } else {
+            options.index_type
+        };
+        let index = match (index_type, options.storage) {
+            (IndexType::Hnsw, VectorStorage::F32) => Backend::HnswF32(Hnsw::new(
+                max_connections,
+                max_elements,
+                16, // Max layer
+                ef_construction,
+                DistCosine {},
+            )),
+            (IndexType::Hnsw, VectorStorage::Int8) => Backend::HnswInt8(Hnsw::new(
+                max_connections,
+                max_elements,
+                16, // Max layer
+                ef_construction,
+                DistCosineInt8,
+            )),
+            (IndexType::BruteForce, VectorStorage::F32) => {
+                Backend::BruteForce(RwLock::new(StoredVectors::F32(Vec::with_capacity(max_elements))))
+            },
+            (IndexType::BruteForce, VectorStorage::Int8) => {
+                Backend::BruteForce(RwLock::new(StoredVectors::Int8(Vec::with_capacity(max_elements))))
+            },
+        };
+
         debug!("Vector index created successfully");
         Self {
             index,
+            options,
             count: std::sync::atomic::AtomicUsize::new(0),
         }
     }
 
+    /// Get the storage format of the index
+    pub fn storage(&self) -> VectorStorage {
+        self.options.storage
+    }
+
+    /// Get the options the index was created with
+    pub fn options(&self) -> IndexOptions {
+        self.options
+    }
+
     /// Insert a vector into the index
     ///
     /// # Arguments
@@ -48,7 +205,14 @@ impl VectorIndex {
     /// * `vector` - The vector to insert
     /// * `id` - The ID associated with the vector
     pub fn insert(&self, vector: &[f32], id: usize) {
-        self.index.insert((vector, id));
+        match &self.index {
+            Backend::HnswF32(index) => index.insert((vector, id)),
+            Backend::HnswInt8(index) => index.insert((&quantize(vector), id)),
+            Backend::BruteForce(vectors) => match &mut *vectors.write().unwrap_or_else(|e| e.into_inner()) {
+                StoredVectors::F32(vectors) => vectors.push((id, vector.to_vec())),
+                StoredVectors::Int8(vectors) => vectors.push((id, quantize(vector))),
+            },
+        }
         self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
     }
 
@@ -58,13 +222,34 @@ impl VectorIndex {
     ///
     /// * `query` - The query vector
     /// * `limit` - Maximum number of results to return
-    /// * `ef_search` - Size of the dynamic candidate list for search
+    /// * `ef_search` - Size of the dynamic candidate list for search, ignored by brute force
     ///
     /// # Returns
     ///
-    /// A vector of (id, distance) pairs
+    /// A vector of (id, distance) pairs, closest first
     pub fn search(&self, query: &[f32], limit: usize, ef_search: usize) -> Vec<(usize, f32)> {
-        let results = self.index.search(query, limit, ef_search);
+        let results = match &self.index {
+            Backend::HnswF32(index) => index.search(query, limit, ef_search),
+            Backend::HnswInt8(index) => index.search(&quantize(query), limit, ef_search),
+            Backend::BruteForce(vectors) => {
+                let mut scored = match &*vectors.read().unwrap_or_else(|e| e.into_inner()) {
+                    StoredVectors::F32(vectors) => vectors
+                        .iter()
+                        .map(|(id, vector)| (*id, DistCosine {}.eval(query, vector)))
+                        .collect::<Vec<_>>(),
+                    StoredVectors::Int8(vectors) => {
+                        let query = quantize(query);
+                        vectors
+                            .iter()
+                            .map(|(id, vector)| (*id, DistCosineInt8.eval(&query, vector)))
+                            .collect::<Vec<_>>()
+                    },
+                };
+                scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+                scored.truncate(limit);
+                return scored;
+            },
+        };
 
         results
             .into_iter()
@@ -72,6 +257,20 @@ impl VectorIndex {
             .collect()
     }
 
+    /// Search for nearest neighbors using the configured HNSW `ef_search`
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query vector
+    /// * `limit` - Maximum number of results to return
+    ///
+    /// # Returns
+    ///
+    /// A vector of (id, distance) pairs, closest first
+    pub fn search_default(&self, query: &[f32], limit: usize) -> Vec<(usize, f32)> {
+        self.search(query, limit, self.options.hnsw.ef_search)
+    }
+
     /// Get the number of elements in the index
     ///
     /// # Returns
@@ -90,3 +289,35 @@ impl VectorIndex {
         self.len() == 0
     }
 }
+
+/// Scale a vector so its largest component maps to ±127 and round to signed bytes.
+///
+/// Each vector gets its own scale, which is fine because cosine distance ignores magnitude.
+fn quantize(vector: &[f32]) -> Vec<i8> {
+    let max = vector.iter().fold(0.0f32, |max, value| max.max(value.abs()));
+    if max == 0.0 {
+        return vec![0; vector.len()];
+    }
+
+    let scale = i8::MAX as f32 / max;
+    vector.iter().map(|value| (value * scale).round() as i8).collect()
+}
+
+/// Cosine distance over quantized vectors, accumulated in integers to avoid overflow
+struct DistCosineInt8;
+
+impl Distance<i8> for DistCosineInt8 {
+    fn eval(&self, va: &[i8], vb: &[i8]) -> f32 {
+        let (dot, norm_a, norm_b) = va.iter().zip(vb).fold((0i64, 0i64, 0i64), |acc, (&a, &b)| {
+            let (a, b) = (a as i64, b as i64);
+            (acc.0 + a * b, acc.1 + a * a, acc.2 + b * b)
+        });
+
+        // Match DistCosine, which treats zero vectors as distance 0
+        if norm_a == 0 || norm_b == 0 {
+            return 0.0;
+        }
+
+        (1.0 - dot as f64 / ((norm_a as f64) * (norm_b as f64)).sqrt()).max(0.0) as f32
+    }
+}
@@ -14,6 +14,8 @@ pub mod config;
 pub mod error;
 /// Vector index implementation
 pub mod index;
+/// Hooks for observing client operations
+pub mod observer;
 /// File processing utilities
 pub mod processing;
 /// Data types for semantic search operations
@@ -28,6 +30,10 @@ pub use error::{
     Result,
     SemanticSearchError,
 };
+pub use observer::{
+    NoopObserver,
+    Observer,
+};
 pub use types::{
     DataPoint,
     FileType,
@@ -36,6 +42,7 @@ pub use types::{
     OperationType,
     ProgressInfo,
     ProgressStatus,
+    ScoredResult,
     SearchResult,
     SystemStatus,
 };
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use crate::error::SemanticSearchError;
+
+/// Receives timing and failure events from client operations
+///
+/// All methods default to doing nothing, so implementors only override the events they care
+/// about. This lets callers forward metrics to their own telemetry without this crate depending
+/// on it.
+pub trait Observer: Send + Sync {
+    /// Called after a search completes
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Time spent embedding the query and searching
+    /// * `context_id` - The searched context, `None` when searching all contexts
+    /// * `result_count` - Number of results returned
+    fn on_search(&self, duration: Duration, context_id: Option<&str>, result_count: usize) {
+        let _ = (duration, context_id, result_count);
+    }
+
+    /// Called after a context is built and stored
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Time spent processing, embedding and indexing the content
+    /// * `context_id` - The new context
+    /// * `item_count` - Number of items indexed
+    fn on_build(&self, duration: Duration, context_id: &str, item_count: usize) {
+        let _ = (duration, context_id, item_count);
+    }
+
+    /// Called when an observed operation fails
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Name of the failed operation, `search` or `build`
+    /// * `error` - The error returned to the caller
+    fn on_error(&self, operation: &str, error: &SemanticSearchError) {
+        let _ = (operation, error);
+    }
+}
+
+/// Observer that ignores every event, used when none is set
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopObserver;
+
+impl Observer for NoopObserver {}
@@ -0,0 +1,298 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use crate::embedding::TextEmbedderTrait;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Embeds texts in fixed-size batches with a bounded number of batches in flight.
+///
+/// A new batch is only handed to the embedder once one of the running batches has finished, so
+/// large directories never have more than `max_concurrent_batches * batch_size` texts being
+/// embedded at the same time.
+pub struct BatchEmbedder {
+    embedder: Arc<dyn TextEmbedderTrait>,
+    batch_size: usize,
+    max_concurrent_batches: usize,
+}
+
+impl BatchEmbedder {
+    /// Create a new batch embedder
+    ///
+    /// # Arguments
+    ///
+    /// * `embedder` - Backend used to embed each batch
+    /// * `batch_size` - Number of texts per batch (at least 1)
+    /// * `max_concurrent_batches` - Maximum number of batches embedded at once (at least 1)
+    pub fn new(embedder: Arc<dyn TextEmbedderTrait>, batch_size: usize, max_concurrent_batches: usize) -> Self {
+        Self {
+            embedder,
+            batch_size: batch_size.max(1),
+            max_concurrent_batches: max_concurrent_batches.max(1),
+        }
+    }
+
+    /// Embed all texts, returning the vectors in the same order as the input
+    ///
+    /// Texts are pulled from `texts` one batch at a time, so only the batches in flight are held
+    /// in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `texts` - Texts to embed
+    /// * `cancel_token` - Checked before every batch is started
+    /// * `on_progress` - Called with `(embedded, total)` whenever a batch finishes
+    pub async fn embed_all<I, F, Fut>(
+        &self,
+        texts: I,
+        cancel_token: &CancellationToken,
+        mut on_progress: F,
+    ) -> Result<Vec<Vec<f32>>>
+    where
+        I: IntoIterator<Item = String>,
+        I::IntoIter: ExactSizeIterator,
+        F: FnMut(usize, usize) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let mut texts = texts.into_iter();
+        let total = texts.len();
+        let mut batches: Vec<Option<Vec<Vec<f32>>>> = Vec::with_capacity(total.div_ceil(self.batch_size));
+        let mut in_flight = JoinSet::new();
+        let mut embedded = 0;
+
+        loop {
+            // Backpressure: wait for a slot before taking the next batch
+            while in_flight.len() >= self.max_concurrent_batches {
+                embedded += Self::collect_next(&mut in_flight, &mut batches).await?;
+                on_progress(embedded, total).await;
+            }
+
+            let batch = texts.by_ref().take(self.batch_size).collect::<Vec<_>>();
+            if batch.is_empty() {
+                break;
+            }
+
+            if cancel_token.is_cancelled() {
+                in_flight.abort_all();
+                return Err(SemanticSearchError::OperationFailed(
+                    "Embedding was cancelled".to_string(),
+                ));
+            }
+
+            let embedder = self.embedder.clone();
+            let batch_index = batches.len();
+            batches.push(None);
+            in_flight.spawn_blocking(move || (batch_index, check_batch(&batch, embedder.embed_batch(&batch))));
+        }
+
+        while !in_flight.is_empty() {
+            embedded += Self::collect_next(&mut in_flight, &mut batches).await?;
+            on_progress(embedded, total).await;
+        }
+
+        Ok(batches.into_iter().flatten().flatten().collect())
+    }
+
+    /// Embed all texts without an async runtime, returning the vectors in the same order as the
+    /// input
+    ///
+    /// Up to `max_concurrent_batches` batches are taken from `texts` and embedded on scoped
+    /// threads, and the next batches are only taken once all of them have finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `texts` - Texts to embed
+    /// * `on_progress` - Called with `(embedded, total)` whenever a group of batches finishes
+    pub fn embed_all_blocking<I, F>(&self, texts: I, mut on_progress: F) -> Result<Vec<Vec<f32>>>
+    where
+        I: IntoIterator<Item = String>,
+        I::IntoIter: ExactSizeIterator,
+        F: FnMut(usize, usize),
+    {
+        let mut texts = texts.into_iter();
+        let total = texts.len();
+        let mut vectors = Vec::with_capacity(total);
+
+        loop {
+            let batches = (0..self.max_concurrent_batches)
+                .map(|_| texts.by_ref().take(self.batch_size).collect::<Vec<_>>())
+                .filter(|batch| !batch.is_empty())
+                .collect::<Vec<_>>();
+            if batches.is_empty() {
+                break;
+            }
+
+            let results = std::thread::scope(|scope| {
+                let handles = batches
+                    .iter()
+                    .map(|batch| scope.spawn(|| check_batch(batch, self.embedder.embed_batch(batch))))
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle.join().unwrap_or_else(|_| {
+                            Err(SemanticSearchError::EmbeddingError(
+                                "Embedding thread panicked".to_string(),
+                            ))
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            });
+            for result in results {
+                vectors.extend(result?);
+            }
+            on_progress(vectors.len(), total);
+        }
+
+        Ok(vectors)
+    }
+
+    /// Wait for the next batch to finish and store its vectors, returning how many it embedded
+    async fn collect_next(
+        in_flight: &mut JoinSet<(usize, Result<Vec<Vec<f32>>>)>,
+        batches: &mut [Option<Vec<Vec<f32>>>],
+    ) -> Result<usize> {
+        let Some(joined) = in_flight.join_next().await else {
+            return Ok(0);
+        };
+        let (batch_index, vectors) =
+            joined.map_err(|e| SemanticSearchError::EmbeddingError(format!("Embedding task failed: {}", e)))?;
+        let vectors = vectors?;
+        let count = vectors.len();
+        batches[batch_index] = Some(vectors);
+        Ok(count)
+    }
+}
+
+/// Fail when the embedder returned a different number of vectors than texts in the batch, which
+/// would otherwise shift every following vector onto the wrong text
+fn check_batch(batch: &[String], vectors: Result<Vec<Vec<f32>>>) -> Result<Vec<Vec<f32>>> {
+    let vectors = vectors?;
+    if vectors.len() != batch.len() {
+        return Err(SemanticSearchError::EmbeddingError(format!(
+            "Embedder returned {} vectors for a batch of {} texts",
+            vectors.len(),
+            batch.len()
+        )));
+    }
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Backend that records how many `embed_batch` calls overlap
+    #[derive(Default)]
+    struct CountingEmbedder {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        calls: AtomicUsize,
+    }
+
+    impl TextEmbedderTrait for CountingEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![text.len() as f32])
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            texts.iter().map(|text| self.embed(text)).collect()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrency_limit_is_respected() {
+        let backend = Arc::new(CountingEmbedder::default());
+        let embedder = BatchEmbedder::new(backend.clone(), 4, 2);
+        let texts = (0..50).map(|i| "x".repeat(i)).collect::<Vec<_>>();
+
+        let mut progress = Vec::new();
+        let vectors = embedder
+            .embed_all(texts, &CancellationToken::new(), |done, total| {
+                progress.push((done, total));
+                async {}
+            })
+            .await
+            .unwrap();
+
+        // Order is preserved even though batches finish out of order
+        assert_eq!(vectors, (0..50).map(|i| vec![i as f32]).collect::<Vec<_>>());
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 13);
+        assert_eq!(backend.max_running.load(Ordering::SeqCst), 2);
+        assert_eq!(progress.last(), Some(&(50, 50)));
+    }
+
+    /// Backend that drops the last vector of every batch
+    struct ShortEmbedder;
+
+    impl TextEmbedderTrait for ShortEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![text.len() as f32])
+        }
+
+        fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            texts.iter().skip(1).map(|text| self.embed(text)).collect()
+        }
+    }
+
+    #[test]
+    fn test_blocking_matches_async_batching() {
+        let backend = Arc::new(CountingEmbedder::default());
+        let embedder = BatchEmbedder::new(backend.clone(), 4, 2);
+        let texts = (0..50).map(|i| "x".repeat(i));
+
+        let mut progress = Vec::new();
+        let vectors = embedder
+            .embed_all_blocking(texts, |done, total| progress.push((done, total)))
+            .unwrap();
+
+        assert_eq!(vectors, (0..50).map(|i| vec![i as f32]).collect::<Vec<_>>());
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 13);
+        assert!(backend.max_running.load(Ordering::SeqCst) <= 2);
+        assert_eq!(progress.first(), Some(&(8, 50)));
+        assert_eq!(progress.last(), Some(&(50, 50)));
+    }
+
+    #[tokio::test]
+    async fn test_vector_count_mismatch_is_an_error() {
+        let embedder = BatchEmbedder::new(Arc::new(ShortEmbedder), 4, 2);
+        let texts = (0..6).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        let result = embedder
+            .embed_all(texts.clone(), &CancellationToken::new(), |_, _| async {})
+            .await;
+        assert!(matches!(result, Err(SemanticSearchError::EmbeddingError(_))));
+        assert!(matches!(
+            embedder.embed_all_blocking(texts, |_, _| ()),
+            Err(SemanticSearchError::EmbeddingError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_before_start() {
+        let embedder = BatchEmbedder::new(Arc::new(CountingEmbedder::default()), 4, 2);
+        let cancel_token = CancellationToken::new();
+        cancel_token.cancel();
+
+        let result = embedder
+            .embed_all(["text".to_string()], &cancel_token, |_, _| async {})
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::client::semantic_context::cosine_similarity;
+use crate::embedding::TextEmbedderTrait;
+use crate::error::Result;
+
+/// Two texts whose embeddings are nearly identical
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DuplicatePair {
+    /// Index of the earlier text
+    pub first: usize,
+    /// Index of the later text, which duplicates `first`
+    pub second: usize,
+    /// Cosine similarity of the two embeddings
+    pub similarity: f32,
+}
+
+/// Find pairs of texts that are near-duplicates of each other
+///
+/// # Arguments
+///
+/// * `embedder` - Embedder used to compare the texts
+/// * `texts` - The texts to compare
+/// * `threshold` - Minimum cosine similarity for two texts to count as duplicates
+///
+/// # Returns
+///
+/// Every pair at or above `threshold`, ordered by `first` then `second`
+pub fn find_near_duplicates(
+    embedder: &dyn TextEmbedderTrait,
+    texts: &[String],
+    threshold: f32,
+) -> Result<Vec<DuplicatePair>> {
+    let embeddings = embedder.embed_batch(texts)?;
+
+    let mut pairs = Vec::new();
+    for (first, a) in embeddings.iter().enumerate() {
+        for (second, b) in embeddings.iter().enumerate().skip(first + 1) {
+            let similarity = cosine_similarity(a, b);
+            if similarity >= threshold {
+                pairs.push(DuplicatePair {
+                    first,
+                    second,
+                    similarity,
+                });
+            }
+        }
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::BM25TextEmbedder;
+
+    #[test]
+    fn test_find_near_duplicates() {
+        let embedder = BM25TextEmbedder::new().unwrap();
+        let texts = vec![
+            "Database migrations are applied with the migrate command".to_string(),
+            "Frontend components are styled with tailwind".to_string(),
+            "Database migrations are applied with the migrate command".to_string(),
+        ];
+
+        let pairs = find_near_duplicates(&embedder, &texts, 0.95).unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].first, pairs[0].second), (0, 2));
+    }
+}
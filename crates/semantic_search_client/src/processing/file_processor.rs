@@ -7,6 +7,7 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::processing::ignore::walk_files;
 use crate::processing::text_chunker::chunk_text;
 use crate::types::FileType;
 
@@ -151,25 +152,9 @@ pub fn process_file(path: &Path) -> Result<Vec<Value>> {
 pub fn process_directory(dir_path: &Path) -> Result<Vec<Value>> {
     let mut results = Vec::new();
 
-    for entry in walkdir::WalkDir::new(dir_path)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Skip hidden files
-        if path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .is_some_and(|s| s.starts_with('.'))
-        {
-            continue;
-        }
-
+    for path in walk_files(dir_path) {
         // Process the file
-        if let Ok(mut items) = process_file(path) {
+        if let Ok(mut items) = process_file(&path) {
             results.append(&mut items);
         }
     }
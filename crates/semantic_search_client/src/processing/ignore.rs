@@ -0,0 +1,189 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use globset::{
+    GlobBuilder,
+    GlobMatcher,
+};
+use tracing::warn;
+
+/// Name of the file listing paths to leave out of indexing and chat context
+pub const IGNORE_FILE_NAME: &str = ".amazonqignore";
+
+/// Patterns read from an ignore file, using gitignore syntax
+///
+/// Patterns apply to paths below the directory the file is in. Like gitignore, the last pattern
+/// matching a path decides whether it is ignored, `!` re-includes a path, a trailing `/` only
+/// matches directories, and a pattern with a `/` before its end is anchored to that directory.
+/// Everything below an ignored directory is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    matcher: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRules {
+    /// Parse the gitignore style `contents` of an ignore file in `root`
+    pub fn parse(root: impl Into<PathBuf>, contents: &str) -> Self {
+        let rules = contents.lines().filter_map(parse_rule).collect();
+        Self {
+            root: root.into(),
+            rules,
+        }
+    }
+
+    /// Load the [IGNORE_FILE_NAME] file in `root`, with no rules if there isn't one
+    pub fn load(root: &Path) -> Self {
+        match std::fs::read_to_string(root.join(IGNORE_FILE_NAME)) {
+            Ok(contents) => Self::parse(root, &contents),
+            Err(_) => Self {
+                root: root.to_path_buf(),
+                rules: Vec::new(),
+            },
+        }
+    }
+
+    /// Whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path` is ignored, `is_dir` tells if it is a directory
+    ///
+    /// Paths outside the root are never ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        let components = relative.components().collect::<Vec<_>>();
+        let mut current = PathBuf::new();
+        for (i, component) in components.iter().enumerate() {
+            current.push(component);
+            let is_last = i + 1 == components.len();
+            if self.matches(&current, !is_last || is_dir) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (!rule.dir_only || is_dir) && rule.matcher.is_match(relative))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(pattern) => (true, pattern),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let glob = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{pattern}"),
+    };
+
+    match GlobBuilder::new(&glob).literal_separator(true).build() {
+        Ok(glob) => Some(Rule {
+            matcher: glob.compile_matcher(),
+            negated,
+            dir_only,
+        }),
+        Err(err) => {
+            warn!(pattern = line, ?err, "Skipping invalid ignore pattern");
+            None
+        },
+    }
+}
+
+/// Files below `dir_path` to index, leaving out hidden files and paths ignored by the directory's
+/// [IGNORE_FILE_NAME]
+pub fn walk_files(dir_path: &Path) -> impl Iterator<Item = PathBuf> {
+    let rules = IgnoreRules::load(dir_path);
+    walkdir::WalkDir::new(dir_path)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(move |entry| !rules.is_ignored(entry.path(), entry.file_type().is_dir()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            // Skip hidden files
+            !e.file_name().to_str().is_some_and(|s| s.starts_with('.'))
+        })
+        .map(|e| e.into_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignore_rules() {
+        let rules = IgnoreRules::parse(
+            "/repo",
+            "# secrets\n.env\n*.pem\n/build/\ndocs/generated/\n!keep.pem\n\n\\#notes\n",
+        );
+
+        let ignored = |path: &str, is_dir: bool| rules.is_ignored(Path::new(path), is_dir);
+        assert!(ignored("/repo/.env", false));
+        assert!(ignored("/repo/config/.env", false));
+        assert!(ignored("/repo/certs/server.pem", false));
+        assert!(!ignored("/repo/certs/keep.pem", false));
+        assert!(ignored("/repo/build", true));
+        assert!(ignored("/repo/build/out.txt", false));
+        assert!(
+            !ignored("/repo/src/build", true),
+            "anchored patterns only match at the root"
+        );
+        assert!(!ignored("/repo/build", false), "directory patterns don't match files");
+        assert!(ignored("/repo/docs/generated/api.md", false));
+        assert!(ignored("/repo/#notes", false));
+        assert!(!ignored("/repo/src/main.rs", false));
+        assert!(!ignored("/elsewhere/.env", false));
+    }
+
+    #[test]
+    fn test_walk_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(IGNORE_FILE_NAME), "secrets/\n*.log\n").unwrap();
+        std::fs::create_dir(dir.path().join("secrets")).unwrap();
+        std::fs::write(dir.path().join("secrets/token.txt"), "token").unwrap();
+        std::fs::write(dir.path().join("debug.log"), "log").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "notes").unwrap();
+
+        let files = walk_files(dir.path()).collect::<Vec<_>>();
+        assert_eq!(files, vec![dir.path().join("notes.md")]);
+    }
+}
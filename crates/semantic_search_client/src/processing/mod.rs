@@ -1,11 +1,34 @@
+/// Bounded, batched embedding of large numbers of texts
+pub mod batch_embedder;
+/// Detection of near-duplicate texts
+pub mod dedup;
 /// File processing utilities for handling different file types and extracting content
 pub mod file_processor;
+/// `.amazonqignore` support for leaving files out of indexing and chat context
+pub mod ignore;
 /// Text chunking utilities for breaking down text into manageable pieces for embedding
 pub mod text_chunker;
+/// Configurable tokenization for keyword search
+pub mod tokenizer;
 
+pub use batch_embedder::BatchEmbedder;
+pub use dedup::{
+    DuplicatePair,
+    find_near_duplicates,
+};
 pub use file_processor::{
     get_file_type,
     process_directory,
     process_file,
 };
+pub use ignore::{
+    IGNORE_FILE_NAME,
+    IgnoreRules,
+    walk_files,
+};
 pub use text_chunker::chunk_text;
+pub use tokenizer::{
+    Tokenizer,
+    TokenizerConfig,
+    TokenizerLanguage,
+};
@@ -13,9 +13,8 @@ use crate::config;
 /// A vector of string chunks
 pub fn chunk_text(text: &str, chunk_size: Option<usize>, overlap: Option<usize>) -> Vec<String> {
     // Get configuration values or use provided values
-    let config = config::get_config();
-    let chunk_size = chunk_size.unwrap_or(config.chunk_size);
-    let overlap = overlap.unwrap_or(config.chunk_overlap);
+    let chunk_size = chunk_size.unwrap_or_else(|| config::get_config().chunk_size);
+    let overlap = overlap.unwrap_or_else(|| config::get_config().chunk_overlap);
 
     let mut chunks = Vec::new();
     let words: Vec<&str> = text.split_whitespace().collect();
@@ -60,6 +59,14 @@ mod tests {
                     timeout: 30000,
                     base_dir: std::path::PathBuf::from("."),
                     max_files: 1000, // Add missing max_files field
+                    vector_storage: Default::default(),
+                    index_type: Default::default(),
+                    hnsw: Default::default(),
+                    embedding_batch_size: 32,
+                    embedding_concurrency: 2,
+                    tokenizer: Default::default(),
+                    query_expansion: Default::default(),
+                    deterministic: false,
                 };
                 // Use a different approach that doesn't access private static
                 let _ = crate::config::init_config(&std::env::temp_dir());
@@ -0,0 +1,276 @@
+use rust_stemmers::{
+    Algorithm,
+    Stemmer,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Language used for stop-word removal and stemming
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenizerLanguage {
+    /// Only lowercase and split the text
+    #[default]
+    None,
+    /// English stop words and Porter stemming
+    English,
+}
+
+/// Settings for the keyword tokenizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenizerConfig {
+    /// Language of the indexed text
+    pub language: TokenizerLanguage,
+    /// Whether to drop stop words, ignored for [`TokenizerLanguage::None`]
+    pub stop_words: bool,
+    /// Whether to stem tokens, ignored for [`TokenizerLanguage::None`]
+    pub stemming: bool,
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            language: TokenizerLanguage::None,
+            stop_words: true,
+            stemming: true,
+        }
+    }
+}
+
+/// Tokenizer for the keyword side of search
+///
+/// The same tokenizer must be used for indexed text and queries so their terms line up.
+pub struct Tokenizer {
+    config: TokenizerConfig,
+    stemmer: Option<Stemmer>,
+}
+
+impl Tokenizer {
+    /// Create a new tokenizer
+    pub fn new(config: TokenizerConfig) -> Self {
+        let stemmer = match config.language {
+            TokenizerLanguage::None => None,
+            TokenizerLanguage::English if config.stemming => Some(Stemmer::create(Algorithm::English)),
+            TokenizerLanguage::English => None,
+        };
+        Self { config, stemmer }
+    }
+
+    /// Get the settings the tokenizer was created with
+    pub fn config(&self) -> TokenizerConfig {
+        self.config
+    }
+
+    /// Split text into lowercase tokens, dropping stop words and stemming as configured
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to tokenize
+    ///
+    /// # Returns
+    ///
+    /// The tokens in the order they appear in the text
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .filter(|word| !self.is_stop_word(word))
+            .map(|word| match &self.stemmer {
+                Some(stemmer) => stemmer.stem(&word).into_owned(),
+                None => word,
+            })
+            .collect()
+    }
+
+    /// Tokenize text and join the tokens back into a single string
+    pub fn normalize(&self, text: &str) -> String {
+        self.tokenize(text).join(" ")
+    }
+
+    fn is_stop_word(&self, word: &str) -> bool {
+        match self.config.language {
+            TokenizerLanguage::None => false,
+            TokenizerLanguage::English => self.config.stop_words && ENGLISH_STOP_WORDS.contains(&word),
+        }
+    }
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new(TokenizerConfig::default())
+    }
+}
+
+/// Common English words that carry little meaning for keyword matching
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a",
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "am",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "by",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "doing",
+    "down",
+    "during",
+    "each",
+    "few",
+    "for",
+    "from",
+    "further",
+    "had",
+    "has",
+    "have",
+    "having",
+    "he",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "i",
+    "if",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "itself",
+    "just",
+    "me",
+    "more",
+    "most",
+    "my",
+    "myself",
+    "no",
+    "nor",
+    "not",
+    "now",
+    "of",
+    "off",
+    "on",
+    "once",
+    "only",
+    "or",
+    "other",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "same",
+    "she",
+    "should",
+    "so",
+    "some",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "to",
+    "too",
+    "under",
+    "until",
+    "up",
+    "very",
+    "was",
+    "we",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "why",
+    "will",
+    "with",
+    "would",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn english() -> Tokenizer {
+        Tokenizer::new(TokenizerConfig {
+            language: TokenizerLanguage::English,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_stemming_matches_word_forms() {
+        let tokenizer = english();
+        assert_eq!(tokenizer.tokenize("running"), tokenizer.tokenize("run"));
+        assert_eq!(tokenizer.tokenize("running"), vec!["run"]);
+    }
+
+    #[test]
+    fn test_stop_words_dropped() {
+        let tokenizer = english();
+        assert_eq!(tokenizer.tokenize("The cat is on the mat"), vec!["cat", "mat"]);
+    }
+
+    #[test]
+    fn test_language_none_only_splits() {
+        let tokenizer = Tokenizer::default();
+        assert_eq!(tokenizer.tokenize("The dogs, running!"), vec!["the", "dogs", "running"]);
+    }
+
+    #[test]
+    fn test_options_can_be_disabled() {
+        let tokenizer = Tokenizer::new(TokenizerConfig {
+            language: TokenizerLanguage::English,
+            stop_words: false,
+            stemming: false,
+        });
+        assert_eq!(tokenizer.normalize("The dogs running"), "the dogs running");
+    }
+}
@@ -54,6 +54,14 @@ pub struct KnowledgeContext {
 
     /// Number of items in the context
     pub item_count: usize,
+
+    /// ID of the context this one is nested under, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<ContextId>,
+
+    /// Whether the data points are protected from changes, for contexts shared with a team
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
 }
 
 impl KnowledgeContext {
@@ -76,6 +84,8 @@ impl KnowledgeContext {
             source_path,
             persistent,
             item_count,
+            parent_id: None,
+            read_only: false,
         }
     }
 }
@@ -101,12 +111,32 @@ pub struct SearchResult {
 
     /// Distance/similarity score (lower is better)
     pub distance: f32,
+
+    /// ID of the context the data point belongs to, if the search went through the client
+    #[serde(default)]
+    pub context_id: Option<ContextId>,
+
+    /// Path of the file the data point was extracted from, taken from the `path` metadata
+    #[serde(default)]
+    pub source_path: Option<String>,
 }
 
 impl SearchResult {
     /// Create a new search result
     pub fn new(point: DataPoint, distance: f32) -> Self {
-        Self { point, distance }
+        let source_path = point.payload.get("path").and_then(|v| v.as_str()).map(str::to_string);
+        Self {
+            point,
+            distance,
+            context_id: None,
+            source_path,
+        }
+    }
+
+    /// Attach the ID of the context this result was found in
+    pub fn with_context_id(mut self, context_id: impl Into<ContextId>) -> Self {
+        self.context_id = Some(context_id.into());
+        self
     }
 
     /// Get the text content of this result
@@ -115,6 +145,40 @@ impl SearchResult {
     }
 }
 
+/// A search result together with the components that produced its score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredResult {
+    /// The result being explained
+    pub result: SearchResult,
+
+    /// Cosine similarity between the query and the data point, computed on the full precision
+    /// vectors
+    pub similarity: f32,
+
+    /// Keyword match component, `None` when the search had no keyword side
+    pub keyword_score: Option<f32>,
+
+    /// Named boosts added on top of the similarity
+    pub boosts: Vec<(String, f32)>,
+
+    /// Final score (higher is better)
+    pub score: f32,
+}
+
+impl ScoredResult {
+    /// Create a new scored result, combining the components into the final score
+    pub fn new(result: SearchResult, similarity: f32, keyword_score: Option<f32>, boosts: Vec<(String, f32)>) -> Self {
+        let score = similarity + keyword_score.unwrap_or(0.0) + boosts.iter().map(|(_, boost)| boost).sum::<f32>();
+        Self {
+            result,
+            similarity,
+            keyword_score,
+            boosts,
+            score,
+        }
+    }
+}
+
 /// File type for processing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileType {
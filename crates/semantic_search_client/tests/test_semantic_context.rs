@@ -5,6 +5,11 @@ use std::{
 };
 
 use semantic_search_client::client::SemanticContext;
+use semantic_search_client::index::{
+    IndexOptions,
+    IndexType,
+    VectorStorage,
+};
 use semantic_search_client::types::DataPoint;
 use serde_json::Value;
 
@@ -98,3 +103,93 @@ fn test_add_data_points() {
     // Clean up
     fs::remove_dir_all(temp_dir).unwrap_or(());
 }
+
+#[test]
+fn test_search_explain() {
+    let temp_dir = env::temp_dir().join("memory_bank_test_search_explain");
+    fs::create_dir_all(&temp_dir).unwrap();
+
+    let mut semantic_context = SemanticContext::new(temp_dir.join("data.json")).unwrap();
+
+    let data_points = [vec![1.0, 0.0, 0.0], vec![1.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]]
+        .into_iter()
+        .enumerate()
+        .map(|(id, vector)| DataPoint {
+            id,
+            payload: HashMap::from([("text".to_string(), Value::String(format!("point {id}")))]),
+            vector,
+        })
+        .collect::<Vec<_>>();
+    semantic_context.add_data_points(data_points).unwrap();
+
+    let query_vector = vec![1.0, 0.0, 0.0];
+    let explained = semantic_context.search_explain(&query_vector, 3).unwrap();
+    assert_eq!(explained.len(), 3);
+
+    // Best match first, with the exact cosine similarity
+    assert_eq!(explained[0].result.point.id, 0);
+    assert!((explained[0].similarity - 1.0).abs() < 1e-5);
+    assert!((explained[1].similarity - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-5);
+
+    for scored in &explained {
+        let boosts = scored.boosts.iter().map(|(_, boost)| boost).sum::<f32>();
+        let combined = scored.similarity + scored.keyword_score.unwrap_or(0.0) + boosts;
+        assert!((combined - scored.score).abs() < 1e-6);
+    }
+    assert!(explained.windows(2).all(|w| w[0].score >= w[1].score));
+
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
+
+#[test]
+fn test_int8_context_keeps_vectors_only_on_disk() {
+    let temp_dir = env::temp_dir().join("memory_bank_test_int8_vectors");
+    fs::create_dir_all(&temp_dir).unwrap();
+    let data_path = temp_dir.join("data.json");
+    let options = IndexOptions {
+        index_type: IndexType::BruteForce,
+        storage: VectorStorage::Int8,
+        ..Default::default()
+    };
+    let point = |id: usize, vector: Vec<f32>| DataPoint {
+        id,
+        payload: HashMap::from([("text".to_string(), Value::String(format!("point {id}")))]),
+        vector,
+    };
+
+    let mut semantic_context = SemanticContext::with_index_options(data_path.clone(), options).unwrap();
+    semantic_context
+        .add_data_points(vec![point(0, vec![1.0, 0.0, 0.0]), point(1, vec![1.0, 1.0, 0.0])])
+        .unwrap();
+    semantic_context.save().unwrap();
+    assert!(semantic_context.get_data_points().iter().all(|p| p.vector.is_empty()));
+
+    // Points added after a save keep their vectors until the next one
+    semantic_context
+        .add_data_points(vec![point(2, vec![0.0, 0.0, 1.0])])
+        .unwrap();
+    assert_eq!(semantic_context.get_data_points()[2].vector, vec![0.0, 0.0, 1.0]);
+    semantic_context.save().unwrap();
+
+    let saved = semantic_context.saved_data_points().unwrap();
+    assert_eq!(saved[1].vector, vec![1.0, 1.0, 0.0]);
+    assert_eq!(saved[2].vector, vec![0.0, 0.0, 1.0]);
+
+    // Explained similarities are exact even though the index only holds quantized vectors
+    let loaded = SemanticContext::with_index_options(data_path.clone(), options).unwrap();
+    assert!(loaded.get_data_points().iter().all(|p| p.vector.is_empty()));
+    let explained = loaded.search_explain(&[1.0, 0.0, 0.0], 3).unwrap();
+    assert_eq!(explained[0].result.point.id, 0);
+    assert!((explained[1].similarity - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+    // The saved vectors are only read once, later explanations reuse them
+    fs::remove_file(&data_path).unwrap();
+    let explained = loaded.search_explain(&[1.0, 0.0, 0.0], 3).unwrap();
+    assert!((explained[1].similarity - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+
+    // Plain search results don't carry the released vectors
+    let results = loaded.search(&[1.0, 0.0, 0.0], 3).unwrap();
+    assert!(results.iter().all(|result| result.point.vector.is_empty()));
+
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
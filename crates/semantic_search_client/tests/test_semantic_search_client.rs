@@ -4,6 +4,12 @@ use std::{
 };
 
 use semantic_search_client::SemanticSearchClient;
+use semantic_search_client::client::utils::render_context_tree;
+use semantic_search_client::embedding::EmbeddingType;
+use semantic_search_client::types::{
+    KnowledgeContext,
+    ProgressStatus,
+};
 
 #[test]
 fn test_client_initialization() {
@@ -205,3 +211,78 @@ fn test_remove_context() {
     // Clean up
     fs::remove_dir_all(temp_dir).unwrap_or(());
 }
+
+#[test]
+fn test_search_results_carry_provenance() {
+    // Create a temporary directory for the test
+    let temp_dir = env::temp_dir().join("semantic_search_test_provenance");
+    let base_dir = temp_dir.join("semantic_search");
+    let docs_dir = temp_dir.join("docs");
+    fs::create_dir_all(&base_dir).unwrap();
+    fs::create_dir_all(&docs_dir).unwrap();
+
+    // Create a few test files
+    let files = [
+        ("lambda.md", "AWS Lambda runs serverless functions on demand"),
+        ("s3.md", "Amazon S3 is a scalable object storage service"),
+        ("ec2.md", "Amazon EC2 provides resizable virtual machines"),
+    ];
+    for (name, content) in files {
+        fs::write(docs_dir.join(name), content).unwrap();
+    }
+    let expected_paths = files
+        .iter()
+        .map(|(name, _)| docs_dir.join(name).to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    // BM25 does not need a model download, so this test can always run
+    let mut client = SemanticSearchClient::with_embedding_type(base_dir, EmbeddingType::BM25).unwrap();
+    let context_id = client
+        .add_context_from_path(&docs_dir, "Docs", "AWS notes", false, None::<fn(ProgressStatus)>)
+        .unwrap();
+
+    // Single-context search
+    let results = client
+        .search_context(&context_id, "serverless lambda", Some(5))
+        .unwrap();
+    assert!(!results.is_empty());
+    for result in &results {
+        assert_eq!(result.context_id.as_deref(), Some(context_id.as_str()));
+        assert!(expected_paths.contains(result.source_path.as_ref().unwrap()));
+    }
+
+    // Cross-context search keeps the provenance once results are flattened
+    let merged = client
+        .search_all("serverless lambda", Some(5))
+        .unwrap()
+        .into_iter()
+        .flat_map(|(_, results)| results)
+        .collect::<Vec<_>>();
+    assert_eq!(merged.len(), results.len());
+    for (merged, single) in merged.iter().zip(&results) {
+        assert_eq!(merged.context_id, single.context_id);
+        assert_eq!(merged.source_path, single.source_path);
+    }
+
+    // Clean up
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
+
+#[test]
+fn test_render_context_tree() {
+    let parent = KnowledgeContext::new("a".to_string(), "Docs", "All docs", true, None, 3);
+    let mut child = KnowledgeContext::new("b".to_string(), "Guides", "How-to guides", true, None, 1);
+    child.parent_id = Some(parent.id.clone());
+
+    // Input order must not affect the output
+    let expected = ".\n└── Docs (3 items)\n    └── Guides (1 item)\n";
+    assert_eq!(render_context_tree(&[parent.clone(), child.clone()]), expected);
+    assert_eq!(render_context_tree(&[child.clone(), parent.clone()]), expected);
+
+    // Without a parent relationship both contexts are listed at the top level
+    child.parent_id = None;
+    assert_eq!(
+        render_context_tree(&[child, parent]),
+        ".\n├── Docs (3 items)\n└── Guides (1 item)\n"
+    );
+}
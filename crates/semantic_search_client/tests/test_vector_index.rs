@@ -1,4 +1,17 @@
-use semantic_search_client::index::VectorIndex;
+use std::collections::HashSet;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use semantic_search_client::config::SemanticSearchConfig;
+use semantic_search_client::index::{
+    HnswParams,
+    IndexOptions,
+    IndexType,
+    VectorIndex,
+    VectorStorage,
+};
 
 #[test]
 fn test_vector_index_creation() {
@@ -53,3 +66,176 @@ fn test_search() {
         assert!(results[0].0 <= 2);
     }
 }
+
+/// Deterministic pseudo-random vectors in [-1, 1] so the test does not depend on a RNG crate
+fn synthetic_vectors(count: usize, dim: usize, mut seed: u64) -> Vec<Vec<f32>> {
+    (0..count)
+        .map(|_| {
+            (0..dim)
+                .map(|_| {
+                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    ((seed >> 33) as f32 / (1u64 << 31) as f32) * 2.0 - 1.0
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    1.0 - dot / (norm_a * norm_b)
+}
+
+fn recall_at_10(storage: VectorStorage, data: &[Vec<f32>], queries: &[Vec<f32>]) -> f32 {
+    let index = VectorIndex::with_storage(data.len(), storage);
+    assert_eq!(index.storage(), storage);
+    for (id, vector) in data.iter().enumerate() {
+        index.insert(vector, id);
+    }
+
+    let mut hits = 0;
+    for query in queries {
+        let mut exact = (0..data.len()).collect::<Vec<_>>();
+        exact.sort_by(|&a, &b| cosine_distance(query, &data[a]).total_cmp(&cosine_distance(query, &data[b])));
+        let exact = exact.into_iter().take(10).collect::<HashSet<_>>();
+
+        hits += index
+            .search(query, 10, 100)
+            .into_iter()
+            .filter(|(id, _)| exact.contains(id))
+            .count();
+    }
+
+    hits as f32 / (queries.len() * 10) as f32
+}
+
+#[test]
+fn test_int8_recall_close_to_f32() {
+    let data = synthetic_vectors(1000, 64, 42);
+    let queries = synthetic_vectors(50, 64, 7);
+
+    let f32_recall = recall_at_10(VectorStorage::F32, &data, &queries);
+    let int8_recall = recall_at_10(VectorStorage::Int8, &data, &queries);
+
+    assert!(
+        int8_recall >= f32_recall - 0.05,
+        "int8 recall@10 {int8_recall} fell too far below f32 recall@10 {f32_recall}"
+    );
+}
+
+fn build_index(options: IndexOptions, data: &[Vec<f32>]) -> VectorIndex {
+    let index = VectorIndex::with_options(data.len(), options);
+    for (id, vector) in data.iter().enumerate() {
+        index.insert(vector, id);
+    }
+    index
+}
+
+fn search_ids(index: &VectorIndex, queries: &[Vec<f32>]) -> Vec<HashSet<usize>> {
+    queries
+        .iter()
+        .map(|query| index.search_default(query, 10).into_iter().map(|(id, _)| id).collect())
+        .collect()
+}
+
+/// The fastest of a few runs searching every query, to keep scheduling noise out of comparisons
+fn best_search_time(index: &VectorIndex, queries: &[Vec<f32>]) -> Duration {
+    (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            search_ids(index, queries);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn test_brute_force_search_is_exact() {
+    let data = synthetic_vectors(200, 16, 3);
+    let index = build_index(
+        IndexOptions {
+            index_type: IndexType::BruteForce,
+            ..Default::default()
+        },
+        &data,
+    );
+
+    let results = index.search_default(&data[17], 3);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].0, 17);
+    assert!(results.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+}
+
+#[test]
+fn test_hnsw_recall_and_speed_against_brute_force() {
+    let data = synthetic_vectors(10_000, 16, 11);
+    let queries = synthetic_vectors(200, 16, 5);
+
+    let exact = build_index(
+        IndexOptions {
+            index_type: IndexType::BruteForce,
+            ..Default::default()
+        },
+        &data,
+    );
+    let approximate = build_index(
+        IndexOptions {
+            index_type: IndexType::Hnsw,
+            hnsw: HnswParams {
+                max_connections: 16,
+                ef_construction: 64,
+                ef_search: 32,
+            },
+            ..Default::default()
+        },
+        &data,
+    );
+
+    let expected = search_ids(&exact, &queries);
+    let actual = search_ids(&approximate, &queries);
+
+    let hits: usize = expected
+        .iter()
+        .zip(&actual)
+        .map(|(expected, actual)| expected.intersection(actual).count())
+        .sum();
+    let recall = hits as f32 / (queries.len() * 10) as f32;
+
+    assert!(recall >= 0.9, "HNSW recall@10 {recall} is below 0.9");
+
+    // On this many vectors a graph search visits a small fraction of them, so it should win by a
+    // wide margin rather than by timing noise
+    let exact_time = best_search_time(&exact, &queries);
+    let approximate_time = best_search_time(&approximate, &queries);
+    assert!(
+        approximate_time * 2 < exact_time,
+        "HNSW search took {approximate_time:?}, brute force took {exact_time:?}"
+    );
+}
+
+#[test]
+fn test_deterministic_index_is_reproducible() {
+    // Deterministic indexes ignore the requested HNSW graph
+    let config = SemanticSearchConfig {
+        deterministic: true,
+        index_type: IndexType::Hnsw,
+        ..Default::default()
+    };
+
+    let data = synthetic_vectors(2000, 16, 13);
+    let queries = synthetic_vectors(50, 16, 17);
+    let search = |index: &VectorIndex| {
+        queries
+            .iter()
+            .map(|query| index.search_default(query, 10))
+            .collect::<Vec<_>>()
+    };
+
+    let first = search(&build_index(config.index_options(), &data));
+    let second = search(&build_index(config.index_options(), &data));
+    assert_eq!(first, second);
+    assert!(first.iter().all(|results| results.len() == 10));
+}